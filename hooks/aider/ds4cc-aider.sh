@@ -0,0 +1,89 @@
+#!/bin/bash
+# DS4CC wrapper for Aider
+# Run `ds4cc-aider` instead of `aider` (all arguments are passed through).
+# Each wrapped session writes its own state file: ds4cc_agent_aider_<pid>
+#
+# Aider has no prompt-submit hook, so the wrapper follows the chat history
+# file: every user prompt is logged there as a "#### " line → working.
+# Aider runs --notifications-command when it's waiting for input again →
+# done (or idle if the reply took less than the threshold).
+#
+# Set DS4CC_DONE_THRESHOLD_S to override the done threshold (default: 600).
+
+DONE_THRESHOLD_S="${DS4CC_DONE_THRESHOLD_S:-600}"
+
+# Resolve state directory (same temp dir the daemon watches)
+if [ -n "${DS4CC_STATE_DIR:-}" ]; then
+    STATE_DIR="$DS4CC_STATE_DIR"
+fi
+
+if [ -z "${STATE_DIR:-}" ] && [ -d "/mnt/c" ] && [ -f /proc/version ] && grep -qi microsoft /proc/version 2>/dev/null; then
+    for _dir in /mnt/c/Users/*/AppData/Local/Temp/DS4CC; do
+        if [ -d "$_dir" ] && [ -w "$_dir" ]; then
+            STATE_DIR="$_dir"
+            break
+        fi
+    done
+    if [ -z "${STATE_DIR:-}" ]; then
+        for _parent in /mnt/c/Users/*/AppData/Local/Temp; do
+            if [ -d "$_parent" ] && [ -w "$_parent" ]; then
+                STATE_DIR="$_parent/DS4CC"
+                break
+            fi
+        done
+    fi
+fi
+
+if [ -z "${STATE_DIR:-}" ] && [ -n "${TEMP:-}" ]; then
+    STATE_DIR="$TEMP/DS4CC"
+fi
+
+if [ -z "${STATE_DIR:-}" ]; then
+    STATE_DIR="/tmp/DS4CC"
+fi
+
+mkdir -p "$STATE_DIR"
+
+# Event mode: invoked by Aider's --notifications-command
+if [ "${1:-}" = "--ds4cc-done" ]; then
+    AGENT_FILE="${STATE_DIR}/ds4cc_agent_${DS4CC_AIDER_SESSION:-aider_unknown}"
+    TIMESTAMP_FILE="${AGENT_FILE}_start"
+    if [ -f "$TIMESTAMP_FILE" ]; then
+        START=$(cat "$TIMESTAMP_FILE")
+        NOW=$(date +%s)
+        rm -f "$TIMESTAMP_FILE"
+        if [ $((NOW - START)) -ge "$DONE_THRESHOLD_S" ]; then
+            printf '%s' "done" > "$AGENT_FILE"
+            exit 0
+        fi
+    fi
+    printf '%s' "idle" > "$AGENT_FILE"
+    exit 0
+fi
+
+# Wrapper mode
+export DS4CC_AIDER_SESSION="aider_$$"
+AGENT_FILE="${STATE_DIR}/ds4cc_agent_${DS4CC_AIDER_SESSION}"
+TIMESTAMP_FILE="${AGENT_FILE}_start"
+HISTORY_FILE="${AIDER_CHAT_HISTORY_FILE:-.aider.chat.history.md}"
+touch "$HISTORY_FILE"
+
+tail -n 0 -F "$HISTORY_FILE" 2>/dev/null > >(
+    while IFS= read -r line; do
+        case "$line" in
+            "#### "*)
+                date +%s > "$TIMESTAMP_FILE"
+                printf '%s' "working" > "$AGENT_FILE"
+                ;;
+        esac
+    done
+) &
+TAIL_PID=$!
+
+aider --notifications --notifications-command "'$0' --ds4cc-done" "$@"
+STATUS=$?
+
+# The reader exits on its own once tail is gone
+kill "$TAIL_PID" 2>/dev/null
+rm -f "$AGENT_FILE" "$TIMESTAMP_FILE"
+exit "$STATUS"
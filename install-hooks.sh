@@ -130,5 +130,26 @@ else
     echo "  To install manually later: cp hooks/opencode/ds4cc-opencode.js ~/.config/opencode/plugins/"
 fi
 
+# ── Aider wrapper (optional) ─────────────────────────────────────────
+
+AIDER_SRC="$SCRIPT_DIR/hooks/aider/ds4cc-aider.sh"
+if command -v aider &>/dev/null; then
+    echo ""
+    echo "Aider detected — installing wrapper..."
+    if [ -f "$AIDER_SRC" ]; then
+        mkdir -p "$HOME/.local/bin"
+        cp "$AIDER_SRC" "$HOME/.local/bin/ds4cc-aider"
+        sed -i 's/\r$//' "$HOME/.local/bin/ds4cc-aider" 2>/dev/null || true
+        chmod +x "$HOME/.local/bin/ds4cc-aider"
+        echo "Installed Aider wrapper to $HOME/.local/bin/ds4cc-aider"
+        echo "Run ds4cc-aider instead of aider to track its state."
+    else
+        echo "Warning: hooks/aider/ds4cc-aider.sh not found in repo. Skipping Aider setup."
+    fi
+else
+    echo ""
+    echo "(Aider not detected — skipping Aider wrapper installation)"
+fi
+
 echo ""
 echo "Done. Restart Claude Code for hooks to take effect."
@@ -0,0 +1,149 @@
+/// Low-battery warning: fires once when the controller battery drops to
+/// `[battery] warn_pct` while discharging.
+///
+/// The warning is a one-shot rumble plus a short red lightbar flash that
+/// overrides the agent color. It re-arms only after the battery climbs back
+/// above the threshold or the controller is plugged in, so a battery sitting
+/// at the threshold doesn't nag on every report.
+///
+/// With several controllers connected, the warning and the tray follow the
+/// weakest battery ([`BatteryBoard`]).
+
+use crate::input::BatteryStatus;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How long the lightbar flashes red after the warning fires.
+pub const FLASH_DURATION: Duration = Duration::from_millis(3000);
+/// On/off half-period of the flash.
+const FLASH_BLINK_MS: u64 = 250;
+/// Flash color.
+const FLASH_COLOR: (u8, u8, u8) = (255, 0, 0);
+
+/// One-shot latch over battery readings.
+pub struct LowBatteryLatch {
+    warn_pct: u8,
+    /// True until the warning fires; reset by recovering above the threshold.
+    armed: bool,
+}
+
+impl LowBatteryLatch {
+    pub fn new(warn_pct: u8) -> Self {
+        Self { warn_pct, armed: true }
+    }
+
+    /// Feed the latest battery reading. Returns true on the reading that
+    /// crosses into low battery. Unknown readings (None) change nothing.
+    pub fn update(&mut self, battery: Option<BatteryStatus>) -> bool {
+        let Some(battery) = battery else {
+            return false;
+        };
+        let low = !battery.charging && battery.percent <= self.warn_pct;
+        if !low {
+            self.armed = true;
+            return false;
+        }
+        std::mem::replace(&mut self.armed, false)
+    }
+}
+
+/// The reading that matters most: the lowest discharging battery, else the
+/// lowest charging one. None if there are no readings.
+pub fn weakest(readings: impl IntoIterator<Item = BatteryStatus>) -> Option<BatteryStatus> {
+    readings.into_iter().min_by_key(|b| (b.charging, b.percent))
+}
+
+/// Battery readings of every connected controller (keyed by HID path),
+/// publishing the [`weakest`] one.
+#[derive(Clone)]
+pub struct BatteryBoard {
+    readings: Arc<Mutex<HashMap<String, BatteryStatus>>>,
+    tx: Arc<watch::Sender<Option<BatteryStatus>>>,
+}
+
+impl BatteryBoard {
+    pub fn new(tx: watch::Sender<Option<BatteryStatus>>) -> Self {
+        Self { readings: Arc::default(), tx: Arc::new(tx) }
+    }
+
+    /// Record the latest reading for `path` (None = unknown or disconnected).
+    pub fn report(&self, path: &str, reading: Option<BatteryStatus>) {
+        let mut readings = self.readings.lock().unwrap();
+        let changed = match reading {
+            Some(b) => readings.insert(path.to_string(), b) != Some(b),
+            None => readings.remove(path).is_some(),
+        };
+        if changed {
+            let weakest = weakest(readings.values().copied());
+            self.tx.send_if_modified(|published| std::mem::replace(published, weakest) != weakest);
+        }
+    }
+}
+
+/// Lightbar color `since_ms` into the flash: red/off blinking, or None once
+/// the flash is over.
+pub fn flash_color(since_ms: u64) -> Option<(u8, u8, u8)> {
+    if since_ms >= FLASH_DURATION.as_millis() as u64 {
+        return None;
+    }
+    Some(if (since_ms / FLASH_BLINK_MS).is_multiple_of(2) { FLASH_COLOR } else { (0, 0, 0) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on_battery(percent: u8) -> Option<BatteryStatus> {
+        Some(BatteryStatus { percent, charging: false })
+    }
+
+    #[test]
+    fn fires_once_per_crossing() {
+        let mut latch = LowBatteryLatch::new(15);
+        assert!(!latch.update(on_battery(25)));
+        assert!(latch.update(on_battery(15)));
+        // Still low: no repeat, even across unknown readings
+        assert!(!latch.update(on_battery(15)));
+        assert!(!latch.update(None));
+        assert!(!latch.update(on_battery(5)));
+        // Recovers, then drops again → fires again
+        assert!(!latch.update(on_battery(25)));
+        assert!(latch.update(on_battery(15)));
+    }
+
+    #[test]
+    fn charging_never_fires_and_rearms() {
+        let mut latch = LowBatteryLatch::new(15);
+        assert!(!latch.update(Some(BatteryStatus { percent: 5, charging: true })));
+        assert!(latch.update(on_battery(5)));
+        assert!(!latch.update(Some(BatteryStatus { percent: 5, charging: true })));
+        // Unplugged while still low → warns again
+        assert!(latch.update(on_battery(5)));
+    }
+
+    #[test]
+    fn board_publishes_weakest_battery() {
+        let (tx, rx) = watch::channel(None);
+        let board = BatteryBoard::new(tx);
+        board.report("a", on_battery(75));
+        board.report("b", on_battery(35));
+        assert_eq!(*rx.borrow(), on_battery(35));
+        // A charging controller only counts when nothing is discharging
+        board.report("b", Some(BatteryStatus { percent: 15, charging: true }));
+        assert_eq!(*rx.borrow(), on_battery(75));
+        board.report("a", None);
+        assert_eq!(*rx.borrow(), Some(BatteryStatus { percent: 15, charging: true }));
+        board.report("b", None);
+        assert_eq!(*rx.borrow(), None);
+    }
+
+    #[test]
+    fn flash_blinks_then_ends() {
+        assert_eq!(flash_color(0), Some(FLASH_COLOR));
+        assert_eq!(flash_color(FLASH_BLINK_MS), Some((0, 0, 0)));
+        assert_eq!(flash_color(2 * FLASH_BLINK_MS), Some(FLASH_COLOR));
+        assert_eq!(flash_color(FLASH_DURATION.as_millis() as u64), None);
+    }
+}
@@ -0,0 +1,110 @@
+/// Stick calibration: "Calibrate sticks" in the tray menu samples the resting
+/// sticks for [`SAMPLE_DURATION`] and averages them into `[calibration]`
+/// centers.
+///
+/// The sticks must be left alone while sampling. If either stick moves more
+/// than [`MAX_SPREAD`] the run is rejected instead of saving a skewed center.
+
+use crate::config::CalibrationConfig;
+use crate::input::UnifiedInput;
+use std::time::{Duration, Instant};
+
+/// How long the resting sticks are sampled.
+pub const SAMPLE_DURATION: Duration = Duration::from_millis(1000);
+/// Largest min-to-max swing on any axis that still counts as resting.
+const MAX_SPREAD: u8 = 12;
+
+/// Why a calibration run produced no centers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationError {
+    /// No input reports arrived while sampling.
+    NoSamples,
+    /// A stick moved while sampling.
+    Moved,
+}
+
+impl std::fmt::Display for CalibrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSamples => write!(f, "no input while sampling"),
+            Self::Moved => write!(f, "a stick moved while sampling (leave both sticks at rest)"),
+        }
+    }
+}
+
+/// Running per-axis statistics over one calibration run.
+/// Axes are ordered left x, left y, right x, right y.
+pub struct StickSampler {
+    started: Instant,
+    count: u32,
+    sums: [u32; 4],
+    min: [u8; 4],
+    max: [u8; 4],
+}
+
+impl StickSampler {
+    pub fn new(now: Instant) -> Self {
+        Self { started: now, count: 0, sums: [0; 4], min: [u8::MAX; 4], max: [0; 4] }
+    }
+
+    /// Record one report's stick positions.
+    pub fn add(&mut self, input: &UnifiedInput) {
+        let axes = [input.left_stick.0, input.left_stick.1, input.right_stick.0, input.right_stick.1];
+        for (i, value) in axes.into_iter().enumerate() {
+            self.sums[i] += value as u32;
+            self.min[i] = self.min[i].min(value);
+            self.max[i] = self.max[i].max(value);
+        }
+        self.count += 1;
+    }
+
+    /// True once [`SAMPLE_DURATION`] has passed since the run started.
+    pub fn is_done(&self, now: Instant) -> bool {
+        now.duration_since(self.started) >= SAMPLE_DURATION
+    }
+
+    /// The averaged (rounded) resting centers.
+    pub fn finish(&self) -> Result<CalibrationConfig, CalibrationError> {
+        if self.count == 0 {
+            return Err(CalibrationError::NoSamples);
+        }
+        if (0..4).any(|i| self.max[i] - self.min[i] > MAX_SPREAD) {
+            return Err(CalibrationError::Moved);
+        }
+        let center = |i: usize| ((self.sums[i] + self.count / 2) / self.count) as u8;
+        Ok(CalibrationConfig { left_x: center(0), left_y: center(1), right_x: center(2), right_y: center(3) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sticks(left: (u8, u8), right: (u8, u8)) -> UnifiedInput {
+        UnifiedInput { left_stick: left, right_stick: right, ..UnifiedInput::default() }
+    }
+
+    #[test]
+    fn averages_resting_sticks() {
+        let start = Instant::now();
+        let mut sampler = StickSampler::new(start);
+        sampler.add(&sticks((133, 126), (128, 120)));
+        sampler.add(&sticks((134, 126), (129, 121)));
+        sampler.add(&sticks((134, 127), (128, 121)));
+        assert!(!sampler.is_done(start + Duration::from_millis(500)));
+        assert!(sampler.is_done(start + SAMPLE_DURATION));
+        assert_eq!(
+            sampler.finish(),
+            Ok(CalibrationConfig { left_x: 134, left_y: 126, right_x: 128, right_y: 121 })
+        );
+    }
+
+    #[test]
+    fn rejects_moving_stick_and_empty_runs() {
+        let mut sampler = StickSampler::new(Instant::now());
+        assert_eq!(sampler.finish(), Err(CalibrationError::NoSamples));
+        sampler.add(&sticks((128, 128), (128, 128)));
+        sampler.add(&sticks((128, 128), (200, 128)));
+        assert_eq!(sampler.finish(), Err(CalibrationError::Moved));
+    }
+}
@@ -9,9 +9,11 @@
 
 use crate::wsl::run_wsl;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek, SeekFrom};
+use std::panic::AssertUnwindSafe;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::SystemTime;
 use tokio::time::{interval, Duration};
 
@@ -38,39 +40,52 @@ pub fn resolve_sessions_dir() -> Option<PathBuf> {
 
 /// Run the Codex JSONL poller loop. Scans for session files, reads new
 /// JSONL records, and writes state files to `state_dir`.
-pub async fn run(sessions_dir: PathBuf, state_dir: PathBuf, done_threshold_s: u64, poll_ms: u64) {
-    let mut poller = CodexPoller::new(sessions_dir, state_dir, done_threshold_s);
+pub async fn run(
+    sessions_dir: PathBuf,
+    state_dir: PathBuf,
+    done_threshold_s: u64,
+    done_thresholds: HashMap<String, u64>,
+    token_sidecar: bool,
+    poll_ms: u64,
+) {
+    let poller = Arc::new(Mutex::new(
+        CodexPoller::new(sessions_dir, state_dir, done_threshold_s, token_sidecar)
+            .with_done_thresholds(done_thresholds),
+    ));
     let mut ticker = interval(Duration::from_millis(poll_ms));
 
     loop {
         ticker.tick().await;
         // spawn_blocking because file I/O on UNC paths can block
-        let mut poller_moved = poller;
-        poller_moved = tokio::task::spawn_blocking(move || {
-            poller_moved.poll();
-            poller_moved
-        })
-        .await
-        .unwrap_or_else(|_| {
-            // If the blocking task panicked, create a fresh poller.
-            // This should never happen, but prevents the task from dying.
-            log::error!("Codex poller task panicked, resetting state");
-            CodexPoller::new(
-                PathBuf::new(), // will be replaced next iteration
-                PathBuf::new(),
-                done_threshold_s,
-            )
-        });
-        poller = poller_moved;
+        let shared = Arc::clone(&poller);
+        if tokio::task::spawn_blocking(move || poll_shared(&shared)).await.is_err() {
+            // Should never happen (poll catches per-file panics); the state survives it
+            log::error!("Codex poller task panicked; keeping its offsets for the next poll");
+        }
     }
 }
 
+/// One poll of the shared poller. A panic in an earlier poll poisons the
+/// lock but leaves the offsets and session ids intact, so keep using them —
+/// starting over would replay or skip every session file.
+fn poll_shared(poller: &Mutex<CodexPoller>) {
+    poller.lock().unwrap_or_else(PoisonError::into_inner).poll();
+}
+
 // ── Poller state ────────────────────────────────────────────────────
 
+/// How far into a new session file `session_meta` is looked for.
+const META_SCAN_BYTES: u64 = 16 * 1024;
+
 struct CodexPoller {
     sessions_dir: PathBuf,
     state_dir: PathBuf,
     done_threshold_s: u64,
+    /// Per-tool overrides of `done_threshold_s`, keyed by tool name
+    /// ("default" replaces the global value).
+    done_thresholds: HashMap<String, u64>,
+    /// Write `ds4cc_agent_<id>_tokens` sidecars on `token_count` events.
+    token_sidecar: bool,
 
     /// Per-file read offset (bytes already processed).
     offsets: HashMap<PathBuf, u64>,
@@ -80,8 +95,16 @@ struct CodexPoller {
     session_ids: HashMap<PathBuf, String>,
     /// When each session entered "working" state (for done-threshold logic).
     working_since: HashMap<String, SystemTime>,
-    /// Tracks function call_id → tool name for error attribution.
-    call_names: HashMap<String, String>,
+    /// Sessions currently reasoning (state file says "thinking").
+    thinking: HashSet<String>,
+    /// Sessions whose last tool failed (state file says "error").
+    errored: HashSet<String>,
+    /// Last tool each session called in its current task (picks the done threshold).
+    last_tool: HashMap<String, String>,
+    /// Tracks function call_id → (session, tool name) for error attribution.
+    call_names: HashMap<String, (String, String)>,
+    /// Cumulative token usage per session (latest `token_count` total).
+    token_totals: HashMap<String, u64>,
     /// Whether the initial scan has completed. Files discovered during the
     /// first poll jump to EOF (old sessions). Files discovered later are
     /// processed from line 2 (new sessions started after daemon).
@@ -89,20 +112,32 @@ struct CodexPoller {
 }
 
 impl CodexPoller {
-    fn new(sessions_dir: PathBuf, state_dir: PathBuf, done_threshold_s: u64) -> Self {
+    fn new(sessions_dir: PathBuf, state_dir: PathBuf, done_threshold_s: u64, token_sidecar: bool) -> Self {
         Self {
             sessions_dir,
             state_dir,
             done_threshold_s,
+            done_thresholds: HashMap::new(),
+            token_sidecar,
             offsets: HashMap::new(),
             trailing: HashMap::new(),
             session_ids: HashMap::new(),
             working_since: HashMap::new(),
+            thinking: HashSet::new(),
+            errored: HashSet::new(),
+            last_tool: HashMap::new(),
             call_names: HashMap::new(),
+            token_totals: HashMap::new(),
             initial_scan_done: false,
         }
     }
 
+    /// Per-tool done thresholds (see `[codex.done_thresholds]`).
+    fn with_done_thresholds(mut self, done_thresholds: HashMap<String, u64>) -> Self {
+        self.done_thresholds = done_thresholds;
+        self
+    }
+
     /// One poll cycle: scan for JSONL files, read new data, process records.
     fn poll(&mut self) {
         let jsonl_files = match collect_jsonl_files(&self.sessions_dir) {
@@ -110,12 +145,53 @@ impl CodexPoller {
             Err(_) => return, // sessions dir not accessible (WSL may be down)
         };
 
+        self.prune_missing(&jsonl_files);
         for file_path in jsonl_files {
-            self.poll_file(&file_path);
+            // One bad file mustn't cost the other sessions their progress
+            let polled = std::panic::catch_unwind(AssertUnwindSafe(|| self.poll_file(&file_path)));
+            if polled.is_err() {
+                log::error!("Codex poller failed on {}; skipping its new data", file_path.display());
+                self.skip_to_end(&file_path);
+            }
         }
         self.initial_scan_done = true;
     }
 
+    /// Forget files that were deleted or rotated away since the last poll
+    /// (`files` is the current listing), and the per-session state of
+    /// sessions no remaining file belongs to.
+    fn prune_missing(&mut self, files: &[PathBuf]) {
+        let current: HashSet<&PathBuf> = files.iter().collect();
+        let gone: Vec<PathBuf> = self.offsets.keys().filter(|path| !current.contains(path)).cloned().collect();
+        if gone.is_empty() {
+            return;
+        }
+        for path in &gone {
+            log::debug!("Codex session file gone: {}", path.display());
+            self.offsets.remove(path);
+            self.trailing.remove(path);
+        }
+        let gone_sessions: HashSet<String> = gone.iter().filter_map(|path| self.session_ids.remove(path)).collect();
+        let live_sessions: HashSet<&String> = self.session_ids.values().collect();
+        let ended: HashSet<String> =
+            gone_sessions.into_iter().filter(|id| !live_sessions.contains(id)).collect();
+        self.working_since.retain(|id, _| !ended.contains(id));
+        self.thinking.retain(|id| !ended.contains(id));
+        self.errored.retain(|id| !ended.contains(id));
+        self.last_tool.retain(|id, _| !ended.contains(id));
+        self.token_totals.retain(|id, _| !ended.contains(id));
+        self.call_names.retain(|_, (id, _)| !ended.contains(id));
+    }
+
+    /// Treat everything currently in `file_path` as read, so a record that
+    /// broke `poll_file` isn't retried on every poll.
+    fn skip_to_end(&mut self, file_path: &Path) {
+        if let Ok(metadata) = std::fs::metadata(file_path) {
+            self.offsets.insert(file_path.to_path_buf(), metadata.len());
+            self.trailing.insert(file_path.to_path_buf(), Vec::new());
+        }
+    }
+
     fn poll_file(&mut self, file_path: &Path) {
         let size = match std::fs::metadata(file_path) {
             Ok(m) => m.len(),
@@ -123,8 +199,8 @@ impl CodexPoller {
         };
 
         if !self.offsets.contains_key(file_path) {
-            // First time seeing this file. Read line 1 for session_id.
-            let first_line_end = self.extract_session_id(file_path);
+            // First time seeing this file. Find session_meta for the session_id.
+            let meta_end = self.extract_session_id(file_path);
             self.trailing.insert(file_path.to_path_buf(), Vec::new());
 
             if !self.initial_scan_done {
@@ -135,7 +211,7 @@ impl CodexPoller {
 
             // New session appeared after daemon started — process from
             // after session_meta so we catch the first user_message.
-            let start_offset = first_line_end.unwrap_or(0);
+            let start_offset = meta_end.unwrap_or(0);
             self.offsets.insert(file_path.to_path_buf(), start_offset);
             if size <= start_offset {
                 return; // only session_meta so far, nothing else to read
@@ -165,38 +241,41 @@ impl CodexPoller {
         self.process_chunk(file_path, &chunk);
     }
 
-    /// Read the first line of a JSONL file to extract the session_id from
-    /// the `session_meta` record. Returns the byte offset just past the
-    /// first newline (i.e., where line 2 starts).
+    /// Find the `session_meta` record among the lines starting in the first
+    /// [`META_SCAN_BYTES`] of a JSONL file (usually line 1, but blank lines may
+    /// come first) and record its session_id. Returns the byte offset just
+    /// past that record, or None if there is none yet — the file is then read
+    /// from the start and `handle_record` picks the meta up when it appears.
     ///
     /// Uses `BufReader::read_line` so lines of any length are handled correctly
     /// (Codex session files with large payloads can exceed naive fixed-buffer limits).
     fn extract_session_id(&mut self, file_path: &Path) -> Option<u64> {
         use std::io::BufRead;
-        let file = match std::fs::File::open(file_path) {
-            Ok(f) => f,
-            Err(_) => return None,
-        };
+        let file = std::fs::File::open(file_path).ok()?;
         let mut reader = std::io::BufReader::new(file);
-        let mut first_line = String::new();
-        let bytes_read = match reader.read_line(&mut first_line) {
-            Ok(n) => n as u64,
-            Err(_) => return None,
-        };
-        if let Ok(record) = serde_json::from_str::<serde_json::Value>(first_line.trim_end()) {
-            if record.get("type").and_then(|v| v.as_str()) == Some("session_meta") {
-                if let Some(id) = record
-                    .get("payload")
-                    .and_then(|p| p.get("id"))
-                    .and_then(|v| v.as_str())
-                {
-                    self.session_ids
-                        .insert(file_path.to_path_buf(), id.to_string());
-                }
+        let mut offset = 0u64;
+        let mut line = Vec::new();
+        while offset < META_SCAN_BYTES {
+            line.clear();
+            let bytes_read = reader.read_until(b'\n', &mut line).ok()? as u64;
+            // EOF, or a last line that's still being written
+            if bytes_read == 0 || line.last() != Some(&b'\n') {
+                return None;
+            }
+            offset += bytes_read;
+            let Ok(record) = serde_json::from_slice::<serde_json::Value>(&line) else {
+                continue;
+            };
+            if record.get("type").and_then(|v| v.as_str()) != Some("session_meta") {
+                continue;
+            }
+            if let Some(id) = record.get("payload").and_then(|p| p.get("id")).and_then(|v| v.as_str()) {
+                self.session_ids.insert(file_path.to_path_buf(), id.to_string());
             }
+            // `offset` includes the record's '\n': the next record starts here
+            return Some(offset);
         }
-        // bytes_read includes the trailing '\n', so this is already the start of line 2.
-        if bytes_read > 0 { Some(bytes_read) } else { None }
+        None
     }
 
     /// Process a chunk of bytes: split on newlines, parse complete JSON lines.
@@ -206,24 +285,11 @@ impl CodexPoller {
             .remove(&file_path.to_path_buf())
             .unwrap_or_default();
 
-        let mut data = trailing;
-        data.extend_from_slice(chunk);
-
-        let mut lines: Vec<&[u8]> = data.split(|&b| b == b'\n').collect();
+        let (records, remainder) = split_jsonl_records(trailing, chunk);
+        self.trailing.insert(file_path.to_path_buf(), remainder);
 
-        // Last element is either empty (line ended with \n) or incomplete
-        let remainder = lines.pop().unwrap_or(&[]);
-        self.trailing
-            .insert(file_path.to_path_buf(), remainder.to_vec());
-
-        for raw_line in lines {
-            if raw_line.is_empty() {
-                continue;
-            }
-            let line_str = String::from_utf8_lossy(raw_line);
-            if let Ok(record) = serde_json::from_str::<serde_json::Value>(&line_str) {
-                self.handle_record(file_path, &record);
-            }
+        for record in records {
+            self.handle_record(file_path, &record);
         }
     }
 
@@ -248,18 +314,31 @@ impl CodexPoller {
             None => return,
         };
 
-        let payload_type = match payload.get("type").and_then(|v| v.as_str()) {
-            Some(t) => t,
-            None => return,
-        };
-
         let session_id = match self.session_ids.get(&file_path.to_path_buf()) {
             Some(id) => id.clone(),
             None => return, // no session_meta seen yet
         };
 
+        // Context compaction happens mid-task: keep the session working and
+        // keep working_since, so the done threshold still measures the whole task.
+        if is_compaction(top_type, payload) {
+            if self.working_since.contains_key(&session_id) {
+                log::debug!("Codex session {session_id} compacted context — still working");
+                self.write_state(&session_id, "working");
+            }
+            return;
+        }
+
+        let payload_type = match payload.get("type").and_then(|v| v.as_str()) {
+            Some(t) => t,
+            None => return,
+        };
+
         match payload_type {
             "user_message" => {
+                self.thinking.remove(&session_id);
+                self.errored.remove(&session_id);
+                self.last_tool.remove(&session_id);
                 self.working_since
                     .insert(session_id.clone(), SystemTime::now());
                 self.write_state(&session_id, "working");
@@ -269,16 +348,31 @@ impl CodexPoller {
                 let state = self.compute_done_state(&session_id);
                 self.write_state(&session_id, state);
                 self.working_since.remove(&session_id);
+                self.thinking.remove(&session_id);
+                self.errored.remove(&session_id);
+                self.last_tool.remove(&session_id);
                 self.remove_start_timestamp(&session_id);
             }
+            // Extended thinking; repeated records don't rewrite the file
+            "reasoning" | "agent_reasoning" if self.thinking.insert(session_id.clone()) => {
+                self.errored.remove(&session_id);
+                self.write_state(&session_id, "thinking");
+            }
             "function_call" => {
+                // Reasoning is over once the model starts running tools
+                if self.thinking.remove(&session_id) {
+                    self.write_state(&session_id, "working");
+                }
                 // Track call_id → tool name for error attribution
                 if let (Some(call_id), Some(name)) = (
                     payload.get("call_id").and_then(|v| v.as_str()),
                     payload.get("name").and_then(|v| v.as_str()),
                 ) {
                     self.call_names
-                        .insert(call_id.to_string(), name.to_string());
+                        .insert(call_id.to_string(), (session_id.clone(), name.to_string()));
+                }
+                if let Some(name) = payload.get("name").and_then(|v| v.as_str()) {
+                    self.last_tool.insert(session_id.clone(), name.to_string());
                 }
             }
             "function_call_output" => {
@@ -286,18 +380,34 @@ impl CodexPoller {
                 let tool_name = payload
                     .get("call_id")
                     .and_then(|v| v.as_str())
-                    .and_then(|id| self.call_names.remove(id));
-                // Non-zero exit codes transition the session to "error" state.
-                if let Some(output) = payload.get("output").and_then(|v| v.as_str()) {
-                    if has_nonzero_exit(output) {
+                    .and_then(|id| self.call_names.remove(id))
+                    .map(|(_, name)| name);
+                // Non-zero exit codes transition the session to "error" state;
+                // the next zero exit (the fix) brings it back to "working".
+                match payload.get("output").and_then(|v| v.as_str()).and_then(exit_code) {
+                    Some(0) if self.errored.remove(&session_id) => {
+                        log::debug!("Codex session {session_id} recovered from error → working");
+                        self.write_state(&session_id, "working");
+                    }
+                    Some(0) | None => {}
+                    Some(_) => {
                         log::debug!(
                             "Tool '{}' exited with non-zero code → error",
                             tool_name.as_deref().unwrap_or("unknown")
                         );
+                        self.errored.insert(session_id.clone());
                         self.write_state(&session_id, "error");
                     }
                 }
             }
+            "token_count" => {
+                if let Some(total) = payload.get("info").and_then(token_total) {
+                    self.token_totals.insert(session_id.clone(), total);
+                    if self.token_sidecar {
+                        self.write_token_sidecar(&session_id, total);
+                    }
+                }
+            }
             _ => {}
         }
     }
@@ -305,9 +415,10 @@ impl CodexPoller {
     /// Decide whether a completed task should be "done" or "idle" based on
     /// how long it was "working".
     fn compute_done_state(&self, session_id: &str) -> &'static str {
+        let threshold = self.done_threshold_for(session_id);
         if let Some(start) = self.working_since.get(session_id) {
             if let Ok(elapsed) = start.elapsed() {
-                if elapsed.as_secs() >= self.done_threshold_s {
+                if elapsed.as_secs() >= threshold {
                     return "done";
                 }
             }
@@ -315,6 +426,17 @@ impl CodexPoller {
         "idle"
     }
 
+    /// Done threshold for a session: the override for the last tool it
+    /// called, else the "default" override, else `done_threshold_s`.
+    fn done_threshold_for(&self, session_id: &str) -> u64 {
+        self.last_tool
+            .get(session_id)
+            .and_then(|tool| self.done_thresholds.get(tool))
+            .or_else(|| self.done_thresholds.get("default"))
+            .copied()
+            .unwrap_or(self.done_threshold_s)
+    }
+
     fn write_state(&self, session_id: &str, state: &str) {
         let path = self.state_dir.join(format!("ds4cc_agent_{session_id}"));
         if let Err(e) = std::fs::write(&path, state) {
@@ -333,6 +455,15 @@ impl CodexPoller {
         let _ = std::fs::write(&path, ts);
     }
 
+    fn write_token_sidecar(&self, session_id: &str, total: u64) {
+        let path = self
+            .state_dir
+            .join(format!("ds4cc_agent_{session_id}_tokens"));
+        if let Err(e) = std::fs::write(&path, total.to_string()) {
+            log::debug!("Failed to write token sidecar {}: {e}", path.display());
+        }
+    }
+
     fn remove_start_timestamp(&self, session_id: &str) {
         let path = self
             .state_dir
@@ -341,6 +472,39 @@ impl CodexPoller {
     }
 }
 
+// ── Token meter ─────────────────────────────────────────────────────
+
+/// Number of player-indicator LEDs used for the token meter.
+const METER_LEDS: u32 = 5;
+
+/// Player-LED bitmask for a context-fill bar: 1–5 dots lit from the left,
+/// rounded up so any usage shows at least one dot and a full window all five.
+pub fn token_meter_leds(tokens: u64, context_window: u64) -> u8 {
+    let window = context_window.max(1);
+    let dots = (tokens.saturating_mul(METER_LEDS as u64)).div_ceil(window).clamp(1, METER_LEDS as u64);
+    ((1u32 << dots) - 1) as u8
+}
+
+/// Token total of the most recently updated session that is still active
+/// (its state file exists). None if no session has reported tokens.
+pub fn latest_token_total(state_dir: &Path) -> Option<u64> {
+    let entries = std::fs::read_dir(state_dir).ok()?;
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let agent = name.strip_prefix("ds4cc_agent_")?.strip_suffix("_tokens")?;
+            if !state_dir.join(format!("ds4cc_agent_{agent}")).exists() {
+                return None;
+            }
+            let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+            let total = std::fs::read_to_string(entry.path()).ok()?.trim().parse().ok()?;
+            Some((modified, total))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, total)| total)
+}
+
 // ── Helpers ─────────────────────────────────────────────────────────
 
 /// Recursively collect all `.jsonl` files under a directory.
@@ -364,17 +528,68 @@ fn collect_jsonl_recursive(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Resul
     Ok(())
 }
 
-/// Returns true if the tool output string contains a non-zero process exit code,
-/// e.g. "Process exited with code 1" or "Process exited with code 127".
-fn has_nonzero_exit(output: &str) -> bool {
-    for line in output.lines() {
-        if let Some(rest) = line.strip_prefix("Process exited with code ") {
-            if let Ok(code) = rest.trim().parse::<i32>() {
-                return code != 0;
-            }
-        }
+/// True for records produced by context compaction rather than a finished
+/// turn: the top-level `compacted` item, a `context_compacted` event, or a
+/// `task_complete`/`turn_aborted` whose `reason` mentions compaction.
+fn is_compaction(top_type: &str, payload: &serde_json::Map<String, serde_json::Value>) -> bool {
+    if top_type == "compacted" {
+        return true;
+    }
+    match payload.get("type").and_then(|v| v.as_str()) {
+        Some("context_compacted") => true,
+        Some("task_complete" | "turn_aborted") => payload
+            .get("reason")
+            .and_then(|v| v.as_str())
+            .is_some_and(|r| r.to_ascii_lowercase().contains("compact")),
+        _ => false,
     }
-    false
+}
+
+/// The process exit code a tool output string reports, e.g.
+/// "Process exited with code 1" or "Process exited with code 127".
+fn exit_code(output: &str) -> Option<i32> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("Process exited with code ")?.trim().parse().ok())
+}
+
+/// Cumulative token count from a `token_count` event's `info` object.
+///
+/// Prefers `total_token_usage.total_tokens`; older Codex builds only report the
+/// input/output split, so fall back to their sum.
+fn token_total(info: &serde_json::Value) -> Option<u64> {
+    let usage = info.get("total_token_usage")?;
+    if let Some(total) = usage.get("total_tokens").and_then(|v| v.as_u64()) {
+        return Some(total);
+    }
+    let input = usage.get("input_tokens").and_then(|v| v.as_u64());
+    let output = usage.get("output_tokens").and_then(|v| v.as_u64());
+    match (input, output) {
+        (None, None) => None,
+        (i, o) => Some(i.unwrap_or(0) + o.unwrap_or(0)),
+    }
+}
+
+/// Parse the complete JSON lines in `trailing + chunk`. Returns the records
+/// (unparseable lines skipped) and the incomplete tail to carry over.
+fn split_jsonl_records(
+    trailing: Vec<u8>,
+    chunk: &[u8],
+) -> (Vec<serde_json::Value>, Vec<u8>) {
+    let mut data = trailing;
+    data.extend_from_slice(chunk);
+
+    let mut lines: Vec<&[u8]> = data.split(|&b| b == b'\n').collect();
+
+    // Last element is either empty (line ended with \n) or incomplete
+    let remainder = lines.pop().unwrap_or(&[]).to_vec();
+
+    let records = lines
+        .into_iter()
+        .filter(|raw_line| !raw_line.is_empty())
+        .filter_map(|raw_line| serde_json::from_str(&String::from_utf8_lossy(raw_line)).ok())
+        .collect();
+    (records, remainder)
 }
 
 /// Read bytes from `offset` to `size` in a file.
@@ -394,11 +609,11 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_has_nonzero_exit() {
-        assert!(has_nonzero_exit("Process exited with code 1"));
-        assert!(has_nonzero_exit("some output\nProcess exited with code 127\n"));
-        assert!(!has_nonzero_exit("Process exited with code 0"));
-        assert!(!has_nonzero_exit("no exit code here"));
+    fn test_exit_code() {
+        assert_eq!(exit_code("Process exited with code 1"), Some(1));
+        assert_eq!(exit_code("some output\nProcess exited with code 127\n"), Some(127));
+        assert_eq!(exit_code("Process exited with code 0"), Some(0));
+        assert_eq!(exit_code("no exit code here"), None);
     }
 
     #[test]
@@ -415,7 +630,7 @@ mod tests {
         let _ = std::fs::create_dir_all(&sessions_dir);
         let _ = std::fs::create_dir_all(&state_dir);
 
-        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600);
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, false);
 
         // Create a JSONL session file
         let session_file = sessions_dir.join("test-session.jsonl");
@@ -513,7 +728,7 @@ mod tests {
         let _ = std::fs::create_dir_all(&state_dir);
 
         // Use the top-level sessions dir (recursive scan should find the file)
-        let mut poller = CodexPoller::new(test_dir.join("sessions"), state_dir.clone(), 600);
+        let mut poller = CodexPoller::new(test_dir.join("sessions"), state_dir.clone(), 600, false);
 
         let session_file = sessions_dir.join("rollout-2026-02-22T08-16-51-test.jsonl");
 
@@ -560,12 +775,429 @@ mod tests {
         let _ = std::fs::remove_dir_all(&test_dir);
     }
 
+    #[test]
+    fn test_token_count_updates_session_total() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_tokens_test");
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, true);
+        let session_file = sessions_dir.join("tokens.jsonl");
+        std::fs::write(
+            &session_file,
+            "{\"type\":\"session_meta\",\"payload\":{\"id\":\"tok-1\"}}\n",
+        )
+        .unwrap();
+        poller.poll();
+
+        use std::io::Write;
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":8338,"output_tokens":120,"total_tokens":8458}}}}}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(poller.token_totals.get("tok-1"), Some(&8458));
+
+        // Later events replace the running total; input/output-only payloads are summed
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":12000,"output_tokens":500}}}}}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"token_count","info":null}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(poller.token_totals.get("tok-1"), Some(&12500));
+        assert_eq!(
+            std::fs::read_to_string(state_dir.join("ds4cc_agent_tok-1_tokens")).unwrap(),
+            "12500"
+        );
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn removed_session_file_is_pruned() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_prune_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, false);
+        poller.poll();
+        let (gone, kept) = (sessions_dir.join("gone.jsonl"), sessions_dir.join("kept.jsonl"));
+        for (path, id) in [(&gone, "gone-1"), (&kept, "kept-1")] {
+            std::fs::write(
+                path,
+                format!(
+                    "{{\"type\":\"session_meta\",\"payload\":{{\"id\":\"{id}\"}}}}\n\
+                     {{\"type\":\"event_msg\",\"payload\":{{\"type\":\"user_message\",\"message\":\"go\"}}}}\n\
+                     {{\"type\":\"response_item\",\"payload\":{{\"type\":\"function_call\",\"name\":\"shell\",\"call_id\":\"{id}-c1\"}}}}\n\
+                     {{\"type\":\"event_msg\",\"payload\":{{\"type\":\"agent_reasoning\",\"text\":\"hm\"}}}}"
+                ),
+            )
+            .unwrap();
+        }
+        poller.poll();
+        assert!(poller.working_since.contains_key("gone-1"));
+        assert!(poller.call_names.contains_key("gone-1-c1"));
+        assert!(poller.trailing.contains_key(&gone));
+
+        std::fs::remove_file(&gone).unwrap();
+        poller.poll();
+        assert!(!poller.offsets.contains_key(&gone));
+        assert!(!poller.trailing.contains_key(&gone));
+        assert!(!poller.session_ids.contains_key(&gone));
+        assert!(!poller.working_since.contains_key("gone-1"));
+        assert!(!poller.last_tool.contains_key("gone-1"));
+        assert!(!poller.call_names.contains_key("gone-1-c1"));
+        // The other session is untouched
+        assert!(poller.offsets.contains_key(&kept));
+        assert!(poller.working_since.contains_key("kept-1"));
+        assert!(poller.call_names.contains_key("kept-1-c1"));
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn session_meta_after_a_blank_line() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_meta_scan_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, false);
+        poller.poll(); // initial scan of the empty dir
+
+        // A new session whose meta comes after a blank line, with a big payload
+        let meta = format!(
+            "{{\"type\":\"session_meta\",\"payload\":{{\"id\":\"late-meta\",\"instructions\":\"{}\"}}}}\n",
+            "x".repeat(8000)
+        );
+        let session_file = sessions_dir.join("late.jsonl");
+        std::fs::write(&session_file, format!("\n{meta}")).unwrap();
+        assert_eq!(poller.extract_session_id(&session_file), Some(1 + meta.len() as u64));
+        assert_eq!(poller.session_ids.get(&session_file).map(String::as_str), Some("late-meta"));
+
+        poller.session_ids.clear();
+        use std::io::Write;
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"user_message","message":"hi"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(std::fs::read_to_string(state_dir.join("ds4cc_agent_late-meta")).unwrap(), "working");
+
+        // No meta in the scanned lines (or an unfinished line) → none found
+        let no_meta = sessions_dir.join("none.jsonl");
+        std::fs::write(&no_meta, "\n{\"type\":\"event_msg\"}\n{\"type\":\"session_meta\"").unwrap();
+        assert_eq!(poller.extract_session_id(&no_meta), None);
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn malformed_file_and_panics_keep_offsets() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_malformed_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        let poller = Mutex::new(CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, false));
+        let good = sessions_dir.join("good.jsonl");
+        let bad = sessions_dir.join("bad.jsonl");
+        std::fs::write(&good, "{\"type\":\"session_meta\",\"payload\":{\"id\":\"good-1\"}}\n").unwrap();
+        std::fs::write(&bad, b"\xff\xfe not even text\n").unwrap();
+        poll_shared(&poller);
+        let read_state = || std::fs::read_to_string(state_dir.join("ds4cc_agent_good-1")).unwrap();
+        let append = |path: &Path, bytes: &[u8]| {
+            use std::io::Write;
+            std::fs::OpenOptions::new().append(true).open(path).unwrap().write_all(bytes).unwrap();
+        };
+        let len = |path: &Path| std::fs::metadata(path).unwrap().len();
+
+        // Garbage in one file while the other session starts working
+        append(&bad, b"{not json\n[1,2]\n{\"type\":\"event_msg\",\"payload\":\"text\"}\n\xc3\x28\n{\"payload\":{\"type\":7}}\n{\"type\":");
+        append(&good, br#"{"type":"event_msg","payload":{"type":"user_message","message":"go"}}"#);
+        append(&good, b"\n");
+        poll_shared(&poller);
+        assert_eq!(read_state(), "working");
+        {
+            let poller = poller.lock().unwrap();
+            assert_eq!(poller.offsets.get(&good), Some(&len(&good)));
+            assert_eq!(poller.offsets.get(&bad), Some(&len(&bad)));
+        }
+
+        // A poll that panics poisons the lock but not the poller's state
+        let _ = std::panic::catch_unwind(|| {
+            let _guard = poller.lock().unwrap();
+            panic!("simulated poll panic");
+        });
+        assert!(poller.is_poisoned());
+        append(&good, br#"{"type":"event_msg","payload":{"type":"task_complete","turn_id":"t1"}}"#);
+        append(&good, b"\n");
+        poll_shared(&poller);
+        // Still tailing the session from where it was (a fresh poller would jump to EOF)
+        assert_eq!(read_state(), "idle");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_reasoning_sets_thinking_until_tool_call() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_thinking_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, false);
+        let session_file = sessions_dir.join("think.jsonl");
+        std::fs::write(&session_file, "{\"type\":\"session_meta\",\"payload\":{\"id\":\"think-1\"}}\n").unwrap();
+        poller.poll();
+        let read_state = || std::fs::read_to_string(state_dir.join("ds4cc_agent_think-1")).unwrap();
+
+        use std::io::Write;
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"user_message","message":"plan it"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"agent_reasoning","text":"**Planning**"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"reasoning","summary":[]}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "thinking");
+
+        // Running a tool ends the reasoning stretch
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call","name":"shell","call_id":"c1"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "working");
+
+        // Error handling is unchanged: a later tool call doesn't clear it
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call_output","call_id":"c1","output":"Process exited with code 2"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call","name":"shell","call_id":"c2"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "error");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_successful_tool_output_recovers_from_error() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_recover_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, false);
+        let session_file = sessions_dir.join("fix.jsonl");
+        std::fs::write(&session_file, "{\"type\":\"session_meta\",\"payload\":{\"id\":\"fix-1\"}}\n").unwrap();
+        poller.poll();
+        let read_state = || std::fs::read_to_string(state_dir.join("ds4cc_agent_fix-1")).unwrap();
+
+        use std::io::Write;
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"user_message","message":"run the tests"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call","name":"shell","call_id":"c1"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call_output","call_id":"c1","output":"Process exited with code 1"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "error");
+
+        // Output without an exit code says nothing about the failure
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call","name":"apply_patch","call_id":"c2"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call_output","call_id":"c2","output":"Done!"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "error");
+
+        // The fixed command succeeds
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call","name":"shell","call_id":"c3"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call_output","call_id":"c3","output":"ok\nProcess exited with code 0"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "working");
+        assert!(poller.working_since.contains_key("fix-1"));
+
+        // So does a new prompt after another failure
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"response_item","payload":{{"type":"function_call_output","call_id":"c4","output":"Process exited with code 2"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "error");
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"user_message","message":"try again"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "working");
+        assert!(!poller.errored.contains("fix-1"));
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_compaction_keeps_session_working() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_compaction_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        // Zero threshold: a real task_complete always ends in "done"
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 0, false);
+        let session_file = sessions_dir.join("compact.jsonl");
+        std::fs::write(&session_file, "{\"type\":\"session_meta\",\"payload\":{\"id\":\"cmp-1\"}}\n").unwrap();
+        poller.poll();
+        let read_state = || std::fs::read_to_string(state_dir.join("ds4cc_agent_cmp-1")).unwrap();
+
+        use std::io::Write;
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"user_message","message":"big refactor"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        let started = poller.working_since.get("cmp-1").copied();
+        assert!(started.is_some());
+
+        // Compaction markers in all three shapes: none of them end the task
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"compacted","payload":{{"message":"summary of the conversation so far"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"context_compacted"}}}}"#).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"turn_aborted","reason":"auto_compact"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "working");
+        assert_eq!(poller.working_since.get("cmp-1").copied(), started);
+
+        // The genuine completion still lands
+        let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+        writeln!(f, r#"{{"type":"event_msg","payload":{{"type":"task_complete","turn_id":"t1"}}}}"#).unwrap();
+        drop(f);
+        poller.poll();
+        assert_eq!(read_state(), "done");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_per_tool_done_threshold() {
+        let test_dir = std::env::temp_dir().join("ds4cc_codex_tool_threshold_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let sessions_dir = test_dir.join("sessions");
+        let state_dir = test_dir.join("state");
+        let _ = std::fs::create_dir_all(&sessions_dir);
+        let _ = std::fs::create_dir_all(&state_dir);
+
+        // Global 600s; shell tasks count as done right away
+        let thresholds = HashMap::from([("shell".to_string(), 0)]);
+        let mut poller = CodexPoller::new(sessions_dir.clone(), state_dir.clone(), 600, false)
+            .with_done_thresholds(thresholds);
+        let session_file = sessions_dir.join("tools.jsonl");
+        std::fs::write(&session_file, "{\"type\":\"session_meta\",\"payload\":{\"id\":\"tool-1\"}}\n").unwrap();
+        poller.poll();
+        let read_state = || std::fs::read_to_string(state_dir.join("ds4cc_agent_tool-1")).unwrap();
+        let append = |lines: &[&str]| {
+            use std::io::Write;
+            let mut f = std::fs::OpenOptions::new().append(true).open(&session_file).unwrap();
+            for line in lines {
+                writeln!(f, "{line}").unwrap();
+            }
+        };
+
+        // Last tool is shell → its 0s threshold applies → done
+        append(&[
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"build"}}"#,
+            r#"{"type":"response_item","payload":{"type":"function_call","name":"shell","call_id":"c1"}}"#,
+            r#"{"type":"event_msg","payload":{"type":"task_complete"}}"#,
+        ]);
+        poller.poll();
+        assert_eq!(read_state(), "done");
+
+        // Another tool with no override → global 600s → idle
+        append(&[
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"patch"}}"#,
+            r#"{"type":"response_item","payload":{"type":"function_call","name":"apply_patch","call_id":"c2"}}"#,
+            r#"{"type":"event_msg","payload":{"type":"task_complete"}}"#,
+        ]);
+        poller.poll();
+        assert_eq!(read_state(), "idle");
+
+        // A new task forgets the previous task's tool
+        append(&[
+            r#"{"type":"event_msg","payload":{"type":"user_message","message":"chat"}}"#,
+            r#"{"type":"event_msg","payload":{"type":"task_complete"}}"#,
+        ]);
+        poller.poll();
+        assert_eq!(read_state(), "idle");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_default_override_replaces_global_threshold() {
+        let poller = CodexPoller::new(PathBuf::new(), PathBuf::new(), 600, false)
+            .with_done_thresholds(HashMap::from([("default".to_string(), 30), ("shell".to_string(), 120)]));
+        assert_eq!(poller.done_threshold_for("no-tool"), 30);
+        let mut poller = poller;
+        poller.last_tool.insert("s".into(), "shell".into());
+        assert_eq!(poller.done_threshold_for("s"), 120);
+        poller.last_tool.insert("s".into(), "web_search".into());
+        assert_eq!(poller.done_threshold_for("s"), 30);
+    }
+
+    #[test]
+    fn test_token_meter_leds_fill_levels() {
+        let window = 200_000;
+        assert_eq!(token_meter_leds(0, window), 0b00001);
+        assert_eq!(token_meter_leds(1, window), 0b00001);
+        assert_eq!(token_meter_leds(40_000, window), 0b00001);
+        assert_eq!(token_meter_leds(40_001, window), 0b00011);
+        assert_eq!(token_meter_leds(100_000, window), 0b00111);
+        assert_eq!(token_meter_leds(150_000, window), 0b01111);
+        assert_eq!(token_meter_leds(199_999, window), 0b11111);
+        // Overflowing the window stays at five dots
+        assert_eq!(token_meter_leds(500_000, window), 0b11111);
+        assert_eq!(token_meter_leds(10, 0), 0b11111);
+    }
+
+    #[test]
+    fn test_latest_token_total_ignores_finished_sessions() {
+        let dir = std::env::temp_dir().join("ds4cc_codex_meter_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(latest_token_total(&dir), None);
+
+        std::fs::write(dir.join("ds4cc_agent_a"), "working").unwrap();
+        std::fs::write(dir.join("ds4cc_agent_a_tokens"), "1200").unwrap();
+        // Sidecar left behind by a session whose state file is gone
+        std::fs::write(dir.join("ds4cc_agent_gone_tokens"), "99999").unwrap();
+        assert_eq!(latest_token_total(&dir), Some(1200));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// The live tests' sessions dir, in `DS4CC_WSL_DISTRO` (default "Ubuntu").
+    fn live_sessions_dir() -> PathBuf {
+        let distro = std::env::var("DS4CC_WSL_DISTRO").unwrap_or_else(|_| "Ubuntu".into());
+        PathBuf::from(format!(r"\\wsl.localhost\{distro}\home\vhpnk\.codex\sessions"))
+    }
+
     /// Live integration test: simulate a new Codex session appearing after
     /// daemon startup. Verifies the poller processes events from the start
     /// (not jumping to EOF like it does for pre-existing sessions).
     #[test]
     fn test_live_unc_new_session_detection() {
-        let unc = PathBuf::from(r"\\wsl.localhost\Ubuntu\home\vhpnk\.codex\sessions");
+        let unc = live_sessions_dir();
         if !unc.exists() {
             eprintln!("Skipping live UNC test: WSL path not accessible");
             return;
@@ -581,7 +1213,7 @@ mod tests {
         let _ = std::fs::remove_file(state_dir.join("ds4cc_agent_new-sess-001"));
         let _ = std::fs::remove_file(state_dir.join("ds4cc_agent_new-sess-001_start"));
 
-        let mut poller = CodexPoller::new(unc.clone(), state_dir.clone(), 600);
+        let mut poller = CodexPoller::new(unc.clone(), state_dir.clone(), 600, false);
 
         // First poll: initial scan, discovers existing files, jumps to EOF
         poller.poll();
@@ -640,7 +1272,7 @@ mod tests {
     /// from the WSL UNC path (only runs if WSL is available).
     #[test]
     fn test_read_real_codex_sessions_via_unc() {
-        let unc = PathBuf::from(r"\\wsl.localhost\Ubuntu\home\vhpnk\.codex\sessions");
+        let unc = live_sessions_dir();
         if !unc.exists() {
             eprintln!("Skipping UNC test: WSL path not accessible");
             return;
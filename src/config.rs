@@ -1,21 +1,46 @@
 /// TOML configuration with sensible defaults.
 /// No config file is required to run — defaults work out of the box.
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Top-level configuration.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Config {
     pub lightbar: LightbarConfig,
     pub buttons: ButtonConfig,
     pub scroll: ScrollConfig,
     pub stick_mouse: StickMouseConfig,
+    pub calibration: CalibrationConfig,
     pub touchpad: TouchpadConfig,
     pub tmux: TmuxConfig,
     pub codex: CodexConfig,
+    pub gemini: GeminiConfig,
     pub opencode: OpenCodeConfig,
     pub wt: WtConfig,
+    pub wsl: WslConfig,
+    pub focus: FocusConfig,
+    pub rumble: RumbleConfig,
+    pub mute_led: MuteLedConfig,
+    pub mic: MicConfig,
+    pub triggers: TriggerConfig,
+    pub battery: BatteryConfig,
+    pub layer: LayerConfig,
+    pub tap: TapConfig,
+    pub turbo: TurboConfig,
+    pub macros: MacroConfig,
+    pub gyro: GyroConfig,
+    pub notifications: NotificationsConfig,
+    pub pipe: PipeConfig,
+    pub http: HttpConfig,
+    pub hid: HidConfig,
+    pub controllers: ControllersConfig,
+    pub idle_reminder: IdleReminderConfig,
+    pub gestures: GesturesConfig,
+    pub update: UpdateConfig,
+    pub tray: TrayConfig,
+    pub logging: LoggingConfig,
     /// Directory where agent state files are written (ds4cc_agent_*)
     pub state_dir: String,
     pub poll_interval_ms: u64,
@@ -28,22 +53,103 @@ pub struct Config {
     /// Seconds an agent must have been working before it's eligible for idle reminders.
     /// Agents that worked less than this are treated as subagents and silently pruned.
     pub subagent_filter_s: u64,
+    /// Agent id the lightbar follows at startup ("" = all agents). Changed at
+    /// runtime over the HTTP endpoint (`PUT /focus/<id>`, `DELETE /focus`).
+    pub focus_agent: String,
 }
 
 /// Lightbar color configuration per agent state.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct LightbarConfig {
     pub idle: ColorConfig,
     pub working: ColorConfig,
+    /// Extended reasoning (pulses like working, in its own color)
+    pub thinking: ColorConfig,
     pub done: ColorConfig,
     pub error: ColorConfig,
-    /// Pulse speed for working state (full cycle in ms)
+    /// Pulse speed for working/thinking states (full cycle in ms)
     pub pulse_period_ms: u64,
+    /// Brightness curve of the working/thinking pulse
+    pub pulse_waveform: PulseWaveform,
+    /// Scales every lightbar color (0.0 = off, 1.0 = full)
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub brightness: f32,
+    /// Extra scale for the idle color, on top of `brightness` (0.0–1.0)
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub idle_brightness: f32,
+    /// DualSense LED brightness byte: coarse hardware dimming (high/medium/low)
+    pub hardware_brightness: HardwareBrightness,
+    /// Done color: solid, or blink/fade a few times before settling solid
+    pub done_animation: DoneAnimation,
+    /// Blink/fade cycles of the done animation before it holds solid
+    pub done_cycles: u32,
+    /// Idle color: the configured solid, or a slow hue cycle
+    pub idle_mode: IdleMode,
+    /// Full hue cycle of the rainbow idle mode (ms)
+    pub rainbow_period_ms: u64,
+    /// Mix the colors of all agents' states (weighted by count) instead of
+    /// showing only the highest-priority state
+    pub blend: bool,
+    /// Turn the lightbar off after this many seconds of Idle (0 = never).
+    /// Any state change or button press wakes it.
+    pub sleep_after_s: u64,
+    /// Show the last color right away after DS4CC relaunches itself (update/restart)
+    pub restore_last_color: bool,
+}
+
+/// Shape of the working/thinking brightness pulse over one period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PulseWaveform {
+    /// Smooth breathing.
+    #[default]
+    Sine,
+    /// Slow ramp up, then an instant drop.
+    Sawtooth,
+    /// Linear ramp up to mid-period and back down.
+    Triangle,
+    /// Two quick beats, then a rest.
+    Heartbeat,
+}
+
+/// How the Done state enters on the lightbar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DoneAnimation {
+    /// The `done` color right away.
+    #[default]
+    Solid,
+    /// Done color / off, `done_cycles` times, then solid.
+    Blink,
+    /// Fade out and back in, `done_cycles` times, then solid.
+    Fade,
+}
+
+/// How the Idle state is shown on the lightbar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleMode {
+    /// The `idle` color.
+    #[default]
+    Solid,
+    /// Cycle through the hue wheel every `rainbow_period_ms`.
+    Rainbow,
+}
+
+/// DualSense hardware LED brightness level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HardwareBrightness {
+    #[default]
+    High,
+    #[serde(alias = "med")]
+    Medium,
+    Low,
 }
 
 /// RGB color.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ColorConfig {
     pub r: u8,
     pub g: u8,
@@ -51,15 +157,30 @@ pub struct ColorConfig {
 }
 
 /// Right stick scroll configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct ScrollConfig {
     /// Dead zone radius around center (0-127). Values within this range are ignored.
     pub dead_zone: u8,
     /// Scroll speed multiplier. 1.0 = normal, 2.0 = double speed.
+    #[serde(serialize_with = "serialize_short_f32")]
     pub sensitivity: f32,
     /// Enable horizontal scrolling (X axis).
     pub horizontal: bool,
+    /// Reverse vertical direction ("natural" scrolling: stick up scrolls down).
+    pub invert_vertical: bool,
+    /// Reverse horizontal direction (stick right scrolls left).
+    pub invert_horizontal: bool,
+    /// "wheel" (mouse wheel events) or "arrows" (Up/Down/Left/Right key presses,
+    /// for TUIs that ignore the wheel). Default: wheel.
+    pub mode: ScrollMode,
+    /// Lines per wheel event at full deflection (Windows scrolls 3 lines per
+    /// notch by default, so 3.0 = one notch). Default: 3.0.
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub lines_per_event: f32,
+    /// Send a quarter of the wheel delta four times as often instead of
+    /// whole events, for apps that scroll smoothly. Wheel mode only. Default: false.
+    pub smooth: bool,
 }
 
 impl Default for ScrollConfig {
@@ -68,10 +189,26 @@ impl Default for ScrollConfig {
             dead_zone: 20,
             sensitivity: 1.0,
             horizontal: true,
+            invert_vertical: false,
+            invert_horizontal: false,
+            mode: ScrollMode::Wheel,
+            lines_per_event: 3.0,
+            smooth: false,
         }
     }
 }
 
+/// How right stick scrolling is delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrollMode {
+    /// Mouse wheel events (vertical + horizontal).
+    #[default]
+    Wheel,
+    /// Arrow key presses at the same rate-limited cadence.
+    Arrows,
+}
+
 /// Tmux integration configuration.
 ///
 /// Button values are **tmux action names** (e.g., "previous-window") by default.
@@ -82,7 +219,7 @@ impl Default for ScrollConfig {
 /// the action-name resolution — useful for custom overrides.
 ///
 /// Empty strings mean "unmapped" — the button does nothing in Tmux profile.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct TmuxConfig {
     /// Enable tmux profile (PS button cycles to it).
@@ -92,6 +229,13 @@ pub struct TmuxConfig {
     pub auto_detect: bool,
     /// Tmux prefix key combo (e.g., "Ctrl+B"). Used as fallback if auto-detect fails.
     pub prefix: String,
+    /// Extra key table to read bindings from (e.g., "apps"; empty = prefix table only).
+    /// It must be reachable via a key bound to `switch-client -T <table>`; its
+    /// bindings take precedence over the prefix table.
+    pub key_table: String,
+    /// Send the root-table key (`bind -n`, no prefix) for actions bound there,
+    /// instead of prefix + key. Default: false (prefix table only).
+    pub root_bindings: bool,
     // Button → tmux action names or direct key combos (empty = unmapped)
     pub l1: String,
     pub r1: String,
@@ -111,6 +255,8 @@ impl Default for TmuxConfig {
             enabled: true,
             auto_detect: true,
             prefix: "Ctrl+B".into(),         // tmux default, overridden by auto-detect
+            key_table: "".into(),
+            root_bindings: false,
             l1: "previous-window".into(),
             r1: "next-window".into(),
             l2: "".into(),                    // unmapped
@@ -118,7 +264,7 @@ impl Default for TmuxConfig {
             l3: "".into(),                    // unmapped
             r3: "".into(),                    // unmapped (R3 = Ctrl+P direct)
             square: "new-window".into(),
-            share: "".into(),                 // unmapped
+            share: "copy-mode".into(),
             options: "".into(),               // unmapped
             touchpad: "".into(),              // unmapped
         }
@@ -130,34 +276,120 @@ impl Default for TmuxConfig {
 /// When enabled, deflecting the left analog stick moves the mouse cursor.
 /// Speed is proportional to deflection; a sub-pixel accumulator ensures smooth
 /// movement even at low sensitivity values.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct StickMouseConfig {
     /// Enable left stick cursor control.
     pub enabled: bool,
     /// Max pixels per input frame at full deflection. Default: 8.0.
+    #[serde(serialize_with = "serialize_short_f32")]
     pub sensitivity: f32,
     /// Dead zone radius around center (0-127). Default: 15.
     pub dead_zone: u8,
+    /// Whether `dead_zone` applies to each axis or to the stick's distance
+    /// from center. Default: axial.
+    pub dead_zone_shape: DeadZoneShape,
+    /// Snap to the dominant axis when the minor axis is below this fraction of
+    /// the major one (e.g. 0.25 ≈ 14°). 0.0 disables snapping. Default: 0.0.
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub axis_snap_ratio: f32,
+    /// Response curve exponent applied to each axis' deflection. 1.0 = linear;
+    /// higher values give finer control near center and full speed at the edge
+    /// (2.0 = half deflection moves at quarter speed). Default: 1.0.
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub accel_curve: f32,
+    /// Move the cursor with the right stick and scroll with the left one.
+    /// The tray's stick mode toggle then switches the right stick. Default: false.
+    pub swap_sticks: bool,
+    /// Button (e.g. "l3") that turns stick mouse on only while held, instead
+    /// of the tray's stick mode toggle, which is then ignored. The button's
+    /// own binding is dropped. Empty = use the toggle. Default: "".
+    pub momentary_button: String,
 }
 
 impl Default for StickMouseConfig {
     fn default() -> Self {
-        Self { enabled: true, sensitivity: 8.0, dead_zone: 15 }
+        Self {
+            enabled: true,
+            sensitivity: 8.0,
+            dead_zone: 15,
+            dead_zone_shape: DeadZoneShape::Axial,
+            axis_snap_ratio: 0.0,
+            accel_curve: 1.0,
+            swap_sticks: false,
+            momentary_button: String::new(),
+        }
+    }
+}
+
+/// Shape of the stick mouse dead zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeadZoneShape {
+    /// Each axis is zeroed on its own below the dead zone (a square).
+    /// Small diagonal pushes stay dead.
+    #[default]
+    Axial,
+    /// The stick is dead while its distance from center is below the dead
+    /// zone (a circle); past it, speed ramps up from zero at the edge.
+    Radial,
+}
+
+/// Resting stick centers (0-255 per axis), for worn pads whose sticks don't
+/// settle at 128 and creep past the dead zone.
+///
+/// Written by "Calibrate sticks" in the tray menu; stick mouse and scrolling
+/// measure deflection (and their dead zones) from these centers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CalibrationConfig {
+    pub left_x: u8,
+    pub left_y: u8,
+    pub right_x: u8,
+    pub right_y: u8,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self { left_x: 128, left_y: 128, right_x: 128, right_y: 128 }
+    }
+}
+
+/// Gyro-as-mouse configuration (DualSense only — needs the IMU).
+///
+/// While `button` is held, turning the controller left/right (yaw) and tilting
+/// it up/down (pitch) moves the cursor. The button's normal action is
+/// suppressed while gyro mouse is enabled.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GyroConfig {
+    pub enabled: bool,
+    /// Button that activates gyro aiming while held. Empty = always active.
+    pub button: String,
+    /// Pixels per raw gyro unit per report. Default: 0.002.
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub sensitivity: f32,
+    /// Raw angular velocity ignored per axis (hand tremor). Default: 30.
+    pub dead_zone: i16,
+}
+
+impl Default for GyroConfig {
+    fn default() -> Self {
+        Self { enabled: false, button: "l1".into(), sensitivity: 0.002, dead_zone: 30 }
     }
 }
 
 /// Touchpad-as-mouse configuration.
 ///
-/// When enabled, sliding a finger on the DualSense touchpad moves the cursor,
-/// and pressing (clicking) the touchpad sends a left mouse button click.
-/// DS4 touchpad coordinates are not yet supported.
-#[derive(Debug, Clone, Deserialize)]
+/// When enabled, sliding a finger on the touchpad (DualSense or DS4) moves the
+/// cursor, and pressing (clicking) the touchpad sends a left mouse button click.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct TouchpadConfig {
     /// Enable touchpad cursor control. Set false to use touchpad button in tmux/opencode mappings.
     pub enabled: bool,
     /// Cursor speed multiplier. 1.0 = raw touchpad units → pixels 1:1. Default 1.5.
+    #[serde(serialize_with = "serialize_short_f32")]
     pub sensitivity: f32,
 }
 
@@ -168,13 +400,25 @@ impl Default for TouchpadConfig {
 }
 
 /// Codex JSONL poller configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct CodexConfig {
     /// Enable native Codex JSONL polling via WSL UNC paths.
     pub enabled: bool,
     /// Seconds the task must run before "done" fires (shorter tasks go straight to idle).
     pub done_threshold_s: u64,
+    /// Per-tool overrides of `done_threshold_s`, keyed by the last tool the task
+    /// called (e.g. `shell = 120`). A `default` entry replaces the global value.
+    pub done_thresholds: BTreeMap<String, u64>,
+    /// Write each session's cumulative token count (from `token_count` events)
+    /// to a `ds4cc_agent_<id>_tokens` sidecar next to its state file.
+    pub token_sidecar: bool,
+    /// Show the active session's token usage on the player LEDs as a 1–5 dot
+    /// context-fill bar (replaces the profile indicator while a session is
+    /// active). Implies the token sidecar.
+    pub token_meter: bool,
+    /// Context window size the token meter fills up to. Default: 200000.
+    pub context_window: u64,
 }
 
 impl Default for CodexConfig {
@@ -182,6 +426,29 @@ impl Default for CodexConfig {
         Self {
             enabled: true,
             done_threshold_s: 600, // 10 minutes
+            done_thresholds: BTreeMap::new(),
+            token_sidecar: false,
+            token_meter: false,
+            context_window: 200_000,
+        }
+    }
+}
+
+/// Gemini CLI session poller configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GeminiConfig {
+    /// Enable Gemini CLI session polling via WSL UNC paths. Opt-in.
+    pub enabled: bool,
+    /// Seconds the turn must run before "done" fires (shorter turns go straight to idle).
+    pub done_threshold_s: u64,
+}
+
+impl Default for GeminiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            done_threshold_s: 600, // 10 minutes
         }
     }
 }
@@ -203,7 +470,7 @@ impl Default for CodexConfig {
 /// the action-name resolution — useful for custom overrides.
 ///
 /// Empty strings mean "unmapped" — the button does nothing in OpenCode profile.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct OpenCodeConfig {
     /// Enable OpenCode profile (PS button cycles to it).
@@ -215,17 +482,14 @@ pub struct OpenCodeConfig {
     pub done_threshold_s: u64,
     /// Leader key combo fallback (e.g., "ctrl+x"). Used when auto-detect finds no leader.
     pub leader: String,
-    // Button → OpenCode action names or direct key combos (empty = unmapped)
+    // Button → OpenCode action names or direct key combos (empty = unmapped).
+    // The Agent profile keeps L2 (Ctrl+Win), R2 (tmux window modifier), L3/R3
+    // and the touchpad click for itself, so only these are configurable.
     pub l1: String,
     pub r1: String,
-    pub l2: String,
-    pub r2: String,
-    pub l3: String,
-    pub r3: String,
     pub square: String,
     pub share: String,
     pub options: String,
-    pub touchpad: String,
 }
 
 impl Default for OpenCodeConfig {
@@ -237,14 +501,9 @@ impl Default for OpenCodeConfig {
             leader: "ctrl+x".into(),      // OpenCode default leader
             l1: "session:prev".into(),
             r1: "session:next".into(),
-            l2: "".into(),                // unmapped (L2 = Ctrl+Win hold in all profiles)
-            r2: "".into(),                // unmapped
-            l3: "".into(),                // unmapped (L3 = Ctrl+T in all profiles)
-            r3: "".into(),                // unmapped (R3 = Ctrl+P in all profiles)
             square: "app:new-session".into(),
             share: "".into(),
             options: "".into(),
-            touchpad: "".into(),
         }
     }
 }
@@ -256,7 +515,7 @@ impl Default for OpenCodeConfig {
 ///   1. Auto-detected from settings.json
 ///   2. Hardcoded default for well-known WT actions
 ///   3. Direct key combo parse (backward compatible)
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct WtConfig {
     /// Enable Windows Terminal shortcut detection and mapping.
@@ -295,8 +554,529 @@ impl Default for WtConfig {
     }
 }
 
+/// How the integrations (tmux / OpenCode detection, Codex and Gemini
+/// pollers, hook setup) run WSL commands.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WslConfig {
+    /// Distro name as listed by `wsl -l`, e.g. "Ubuntu-24.04".
+    /// Empty = the default distro. Default: "".
+    pub distro: String,
+    /// A WSL command still running after this long is killed and treated as
+    /// failed, so a distro that hangs while starting can't stall startup
+    /// (at least 100). Default: 10000.
+    pub command_timeout_ms: u64,
+}
+
+impl Default for WslConfig {
+    fn default() -> Self {
+        Self { distro: String::new(), command_timeout_ms: 10_000 }
+    }
+}
+
+impl WslConfig {
+    /// Shortest allowed `command_timeout_ms`: WSL needs a moment even when warm.
+    const MIN_COMMAND_TIMEOUT_MS: u64 = 100;
+
+    /// `command_timeout_ms`, raised to its minimum.
+    pub fn command_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.command_timeout_ms.max(Self::MIN_COMMAND_TIMEOUT_MS))
+    }
+}
+
+/// Foreground-app passthrough.
+///
+/// While a listed app is focused, the controller's buttons and sticks are not
+/// mapped to keystrokes/mouse (lightbar and rumble keep working). Entries are
+/// executable names, case-insensitive, ".exe" optional — e.g. `["eldenring", "vlc.exe"]`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FocusConfig {
+    pub passthrough_apps: Vec<String>,
+}
+
+/// Rumble feedback configuration.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RumbleConfig {
+    /// Master switch: false silences every rumble pattern.
+    pub enabled: bool,
+    /// Scales every pattern's motor values (0.0–1.0).
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub intensity: f32,
+    /// Distinct "all clear" rumble when every agent of a parallel run has settled.
+    pub all_clear: bool,
+    /// Minimum number of agents that must have been active at once for the all-clear.
+    pub all_clear_min_agents: usize,
+    /// Two light bumps when the PS button switches profile.
+    pub profile_feedback: bool,
+    /// Short buzz and red lightbar flash when an agent enters Error.
+    pub on_error: bool,
+    /// Custom patterns replacing the built-in ones, keyed by name
+    /// (`working_done`, `idle_reminder`, `all_clear`, `profile_switch`, `low_battery`, `error`).
+    pub patterns: BTreeMap<String, Vec<RumbleStepConfig>>,
+}
+
+/// Longest allowed step of a config-defined rumble pattern.
+pub const MAX_RUMBLE_STEP_MS: u64 = 5000;
+
+/// One step of a config-defined rumble pattern: `{ left, right, ms }`.
+/// Motor values are 0–255; `ms` must be 1..=`MAX_RUMBLE_STEP_MS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "RawRumbleStep")]
+pub struct RumbleStepConfig {
+    pub left: u8,
+    pub right: u8,
+    pub ms: u64,
+}
+
+#[derive(Deserialize)]
+struct RawRumbleStep {
+    left: u8,
+    right: u8,
+    ms: u64,
+}
+
+impl TryFrom<RawRumbleStep> for RumbleStepConfig {
+    type Error = String;
+
+    fn try_from(raw: RawRumbleStep) -> Result<Self, String> {
+        if raw.ms == 0 || raw.ms > MAX_RUMBLE_STEP_MS {
+            return Err(format!("rumble step ms must be 1..={MAX_RUMBLE_STEP_MS}, got {}", raw.ms));
+        }
+        Ok(Self { left: raw.left, right: raw.right, ms: raw.ms })
+    }
+}
+
+impl Default for RumbleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intensity: 1.0,
+            all_clear: false,
+            all_clear_min_agents: 2,
+            profile_feedback: true,
+            on_error: false,
+            patterns: BTreeMap::new(),
+        }
+    }
+}
+
+/// What the DualSense mute button LED shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MuteLedMode {
+    /// System microphone mute state (lit = muted).
+    #[default]
+    Mic,
+    /// DS4CC agent status: lit while working, pulsing when done, off when idle.
+    Status,
+    /// Always off.
+    Off,
+}
+
+/// Mute button LED configuration (DualSense only — DS4 has no mute LED).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MuteLedConfig {
+    pub mode: MuteLedMode,
+}
+
+/// Which microphone the mute button toggles.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MicConfig {
+    /// With a headset in the DualSense audio jack, toggle the controller's
+    /// capture device instead of the system default mic.
+    pub prefer_controller: bool,
+}
+
+/// Adaptive trigger resistance (DualSense only — DS4 triggers have no motors).
+///
+/// Constant resistance from `*_start` (0-255 along the trigger's travel) with
+/// strength `*_force` (0-255), so L2/R2 feel more like buttons. A force of 0
+/// leaves that trigger free.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TriggerConfig {
+    pub enabled: bool,
+    pub left_start: u8,
+    pub left_force: u8,
+    pub right_start: u8,
+    pub right_force: u8,
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            left_start: 64,
+            left_force: 160,
+            right_start: 64,
+            right_force: 160,
+        }
+    }
+}
+
+/// Low-battery warning: one rumble + red lightbar flash when the controller
+/// battery drops to `warn_pct` while discharging (once per crossing).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BatteryConfig {
+    pub enabled: bool,
+    /// Warn at or below this charge (%). The controller reports 10% steps
+    /// as midpoints (5, 15, 25 …). Default: 15.
+    pub warn_pct: u8,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self { enabled: true, warn_pct: 15 }
+    }
+}
+
+/// Desktop notifications.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Show Windows toasts at all. Opt-in; the fields below pick which.
+    pub toast: bool,
+    /// Toast when an agent finishes ("Agent … done after 12m"). Default: true.
+    pub done: bool,
+    /// Toast when an agent errors. Default: true.
+    pub error: bool,
+    /// Toast alongside the idle reminder rumble. Default: false.
+    pub idle_reminder: bool,
+    /// Minimum seconds between two toasts of the same kind. Default: 10.
+    pub debounce_s: u64,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self { toast: false, done: true, error: true, idle_reminder: false, debounce_s: 10 }
+    }
+}
+
+/// Named-pipe state input (`\\.\pipe\ds4cc`).
+///
+/// Hooks write `<agent_id> <state>` lines to the pipe instead of state files;
+/// pushed states are aggregated together with the files in `state_dir`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PipeConfig {
+    /// Run the pipe server. Opt-in.
+    pub enabled: bool,
+}
+
+/// Local HTTP status endpoint (`GET /state` on `127.0.0.1:<port>`).
+///
+/// Serves the agent state, profile, per-agent states and battery as JSON for
+/// external dashboards. Binds to loopback only.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Run the endpoint. Opt-in.
+    pub enabled: bool,
+    /// Port on 127.0.0.1. Default: 7878.
+    pub port: u16,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self { enabled: false, port: 7878 }
+    }
+}
+
+/// HID device handling.
+///
+/// When a controller is found but can't be opened (typically because Steam or
+/// DS4Windows holds it exclusively), retries back off exponentially from
+/// `open_retry_initial_ms` up to `open_retry_max_ms`.
+///
+/// `read_timeout_ms` and `reconnect_scan_interval_ms` trade CPU for latency;
+/// out-of-range values are clamped (see [`HidConfig::read_timeout_ms`] and
+/// [`HidConfig::reconnect_scan_interval`]).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HidConfig {
+    /// Delay after the first failed open. Default: 500.
+    pub open_retry_initial_ms: u64,
+    /// Upper bound for the retry delay. Default: 30000.
+    pub open_retry_max_ms: u64,
+    /// Failed opens of the same device before warning that it's claimed
+    /// by another app (logged once, plus a tray warning). 0 = never. Default: 5.
+    pub open_give_up_after: u32,
+    /// How long one input report read waits for data (1-10). Default: 5.
+    pub read_timeout_ms: u64,
+    /// How often connected/removed controllers are looked for, including a
+    /// Bluetooth controller that was plugged in over USB (250-60000). Default: 2000.
+    pub reconnect_scan_interval_ms: u64,
+}
+
+impl Default for HidConfig {
+    fn default() -> Self {
+        Self {
+            open_retry_initial_ms: 500,
+            open_retry_max_ms: 30_000,
+            open_give_up_after: 5,
+            read_timeout_ms: 5,
+            reconnect_scan_interval_ms: 2000,
+        }
+    }
+}
+
+impl HidConfig {
+    /// Allowed `read_timeout_ms`: 0 would spin the input loop, and the read
+    /// holds the device lock, so a long wait would hold up lightbar and rumble
+    /// writes to the same controller.
+    const READ_TIMEOUT_RANGE: (u64, u64) = (1, 10);
+    /// Allowed `reconnect_scan_interval_ms`: enumerating HID devices isn't free.
+    const SCAN_INTERVAL_RANGE: (u64, u64) = (250, 60_000);
+
+    /// `read_timeout_ms` clamped to its allowed range, as hidapi takes it.
+    pub fn read_timeout_ms(&self) -> i32 {
+        let (min, max) = Self::READ_TIMEOUT_RANGE;
+        self.read_timeout_ms.clamp(min, max) as i32
+    }
+
+    /// `reconnect_scan_interval_ms` clamped to its allowed range.
+    pub fn reconnect_scan_interval(&self) -> std::time::Duration {
+        let (min, max) = Self::SCAN_INTERVAL_RANGE;
+        std::time::Duration::from_millis(self.reconnect_scan_interval_ms.clamp(min, max))
+    }
+}
+
+/// Adaptive per-agent idle reminder (`idle_reminder_s` is the base threshold).
+///
+/// A quick session going idle is less urgent than a long one: when enabled, an
+/// agent that worked less than `reference_work_s` waits proportionally longer
+/// (base × reference / worked, capped at `max_scale`) before its reminder.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IdleReminderConfig {
+    /// Scale the reminder threshold by the agent's last working duration. Default: false.
+    pub adaptive: bool,
+    /// Working time (seconds) at which the base threshold applies. Default: 600.
+    pub reference_work_s: u64,
+    /// Maximum threshold multiplier for very short work. Default: 4.0.
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub max_scale: f32,
+}
+
+impl Default for IdleReminderConfig {
+    fn default() -> Self {
+        Self { adaptive: false, reference_work_s: 600, max_scale: 4.0 }
+    }
+}
+
+/// Self-updater (tray "Check for Update").
+///
+/// Forks and mirrors can point `repo` at their own GitHub repository, or at a
+/// full repository API URL (e.g. a GitHub Enterprise `…/api/v3/repos/owner/name`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    /// Allow update checks at all. Default: true.
+    pub enabled: bool,
+    /// GitHub repository as "owner/name", or a repository API URL. Default: "VeigaPunk/DS4CC".
+    pub repo: String,
+    /// Release asset to download and run. Default: "DS4CC-Setup.exe".
+    pub installer_name: String,
+    /// "stable" (latest release only) or "prerelease" (newest release including betas).
+    pub channel: UpdateChannel,
+    /// Verify the installer's SHA-256 (from a "<installer>.sha256" asset or the
+    /// release notes) before running it; refuse to run it on a mismatch. A
+    /// release that publishes no checksum is run unverified, with a warning.
+    pub verify_checksum: bool,
+    /// Quietly check once at startup; only speaks up if an update is available.
+    pub check_on_startup: bool,
+}
+
+/// Tray icon behavior.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TrayConfig {
+    /// Ask "Quit DS4CC?" before Exit. Default: false.
+    pub confirm_exit: bool,
+}
+
+/// Log file (`%APPDATA%\ds4cc\ds4cc.log`) in addition to the console.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Also write the log to the file. Default: false.
+    pub file: bool,
+    /// Size at which the file rolls over to `ds4cc.log.1`. Default: 5.
+    pub max_size_mb: u64,
+    /// Rolled-over files to keep (`ds4cc.log.1` … `.N`). Default: 3.
+    pub keep: u32,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self { file: false, max_size_mb: 5, keep: 3 }
+    }
+}
+
+impl LoggingConfig {
+    /// `max_size_mb` in bytes, at least 1 MB.
+    pub fn max_size_bytes(&self) -> u64 {
+        self.max_size_mb.max(1) * 1024 * 1024
+    }
+}
+
+/// Which releases the updater considers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    /// GitHub's "latest" release (never a prerelease).
+    #[default]
+    Stable,
+    /// The newest release by version, prereleases included.
+    Prerelease,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            repo: "VeigaPunk/DS4CC".into(),
+            installer_name: "DS4CC-Setup.exe".into(),
+            channel: UpdateChannel::Stable,
+            verify_checksum: true,
+            check_on_startup: false,
+        }
+    }
+}
+
+/// Motion gestures (DualSense only — needs the accelerometer).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GesturesConfig {
+    /// Shake the controller to acknowledge a Done state (lightbar back to idle). Default: false.
+    pub shake_to_ack: bool,
+    /// Acceleration a shake must exceed, in g (1.0 = gravity at rest). Default: 2.0.
+    #[serde(serialize_with = "serialize_short_f32")]
+    pub shake_threshold_g: f32,
+    /// Spikes above the threshold needed within `shake_window_ms`. Default: 3.
+    pub shake_peaks: usize,
+    /// Window for counting spikes. Default: 1000.
+    pub shake_window_ms: u64,
+}
+
+impl Default for GesturesConfig {
+    fn default() -> Self {
+        Self { shake_to_ack: false, shake_threshold_g: 2.0, shake_peaks: 3, shake_window_ms: 1000 }
+    }
+}
+
+/// Controller roles ("two controllers = split responsibilities").
+///
+/// One controller drives keystrokes/mouse; any other is status-only — its
+/// lightbar and rumble still follow agent state, but its buttons are ignored.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ControllersConfig {
+    /// The input controller: a serial number (Bluetooth MAC, e.g.
+    /// "a0:ab:51:12:34:56") or a 1-based detection order ("1", "2"; USB first).
+    /// Empty = every controller maps input. Default: "".
+    pub input: String,
+}
+
+/// Hold layer (like a keyboard Fn key).
+///
+/// While `button` is held, every other button sends its binding from this table
+/// instead of its normal action, in all profiles. The layer button itself does
+/// nothing on its own. Values are key combos (e.g. "ctrl+s"); empty = the button
+/// does nothing while the layer is held. Sticks, touchpad and PS are unaffected.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LayerConfig {
+    /// Button that activates the layer while held (e.g. "r1", "l2"). Empty = disabled.
+    /// When it is L2, the usual Ctrl+Win hold is not sent.
+    pub button: String,
+    pub cross: String,
+    pub circle: String,
+    pub square: String,
+    pub triangle: String,
+    pub l1: String,
+    pub r1: String,
+    pub l2: String,
+    pub r2: String,
+    pub l3: String,
+    pub r3: String,
+    pub share: String,
+    pub options: String,
+    pub dpad_up: String,
+    pub dpad_down: String,
+    pub dpad_left: String,
+    pub dpad_right: String,
+}
+
+/// Double-tap bindings for face buttons.
+///
+/// The single-press action always fires immediately; a second press within
+/// `double_tap_ms` sends the double-tap combo *instead of* a second single
+/// press (e.g. Triangle = Tab, Triangle-Triangle = Shift+Tab). A third press
+/// starts over. Values are key combos; empty = no double-tap action.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TapConfig {
+    /// Max gap between the two presses. Default: 250.
+    pub double_tap_ms: u64,
+    pub cross: String,
+    pub circle: String,
+    pub triangle: String,
+}
+
+impl Default for TapConfig {
+    fn default() -> Self {
+        Self {
+            double_tap_ms: 250,
+            cross: "".into(),
+            circle: "".into(),
+            triangle: "".into(),
+        }
+    }
+}
+
+/// Turbo (rapid-fire): listed buttons repeat `key` every `rate_ms` while held,
+/// replacing their normal action in every profile. D-pad directions already
+/// repeat and can't be listed; L2 in the list disables its Ctrl+Win hold.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TurboConfig {
+    pub enabled: bool,
+    /// Interval between repeats. Default: 100.
+    pub rate_ms: u64,
+    /// Button names (e.g. ["r2"]).
+    pub buttons: Vec<String>,
+    /// Key combo repeated while a turbo button is held. Default: "Enter".
+    pub key: String,
+}
+
+impl Default for TurboConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rate_ms: 100,
+            buttons: vec!["r2".into()],
+            key: "Enter".into(),
+        }
+    }
+}
+
+/// Per-button macros: button name → ordered list of steps.
+///
+/// A step is a key combo ("ctrl+a") or a pause ("delay:50", in ms). A button
+/// with a macro plays it on press in every profile, replacing its normal
+/// action. Invalid steps are skipped with a warning at startup.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct MacroConfig(pub BTreeMap<String, Vec<String>>);
+
 /// Button mapping configuration.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 pub struct ButtonConfig {
     pub cross: String,
@@ -309,6 +1089,15 @@ pub struct ButtonConfig {
     pub dpad_down: String,
     pub dpad_left: String,
     pub dpad_right: String,
+    /// Create + PS chord (PS5 screenshot gesture). Empty = disabled.
+    pub screenshot: String,
+    /// Options in the Default profile: combo for a quick tap. Empty = nothing.
+    pub options_tap: String,
+    /// Options in the Default profile: combo when held past `options_hold_ms`.
+    /// Empty = a long press sends the tap combo too.
+    pub options_hold: String,
+    /// Hold threshold for `options_hold`. Default: 500.
+    pub options_hold_ms: u64,
 }
 
 impl Default for Config {
@@ -318,17 +1107,42 @@ impl Default for Config {
             buttons: ButtonConfig::default(),
             scroll: ScrollConfig::default(),
             stick_mouse: StickMouseConfig::default(),
+            calibration: CalibrationConfig::default(),
             touchpad: TouchpadConfig::default(),
             tmux: TmuxConfig::default(),
             codex: CodexConfig::default(),
+            gemini: GeminiConfig::default(),
             opencode: OpenCodeConfig::default(),
             wt: WtConfig::default(),
+            wsl: WslConfig::default(),
+            focus: FocusConfig::default(),
+            rumble: RumbleConfig::default(),
+            mute_led: MuteLedConfig::default(),
+            mic: MicConfig::default(),
+            triggers: TriggerConfig::default(),
+            battery: BatteryConfig::default(),
+            layer: LayerConfig::default(),
+            tap: TapConfig::default(),
+            turbo: TurboConfig::default(),
+            macros: MacroConfig::default(),
+            gyro: GyroConfig::default(),
+            notifications: NotificationsConfig::default(),
+            pipe: PipeConfig::default(),
+            http: HttpConfig::default(),
+            hid: HidConfig::default(),
+            controllers: ControllersConfig::default(),
+            idle_reminder: IdleReminderConfig::default(),
+            gestures: GesturesConfig::default(),
+            update: UpdateConfig::default(),
+            tray: TrayConfig::default(),
+            logging: LoggingConfig::default(),
             state_dir: default_state_dir(),
             poll_interval_ms: 500, // 2Hz
             idle_timeout_s: 60,
             stale_timeout_s: 600, // 10 minutes
             idle_reminder_s: 480, // 8 minutes per-agent
             subagent_filter_s: 40,
+            focus_agent: String::new(),
         }
     }
 }
@@ -338,9 +1152,21 @@ impl Default for LightbarConfig {
         Self {
             idle: ColorConfig { r: 255, g: 140, b: 0 },   // orange
             working: ColorConfig { r: 0, g: 100, b: 255 }, // blue
+            thinking: ColorConfig { r: 160, g: 0, b: 255 }, // purple
             done: ColorConfig { r: 0, g: 255, b: 0 },     // green
             error: ColorConfig { r: 0, g: 0, b: 0 },       // off (configurable)
             pulse_period_ms: 2000,
+            pulse_waveform: PulseWaveform::Sine,
+            brightness: 1.0,
+            idle_brightness: 1.0,
+            hardware_brightness: HardwareBrightness::High,
+            done_animation: DoneAnimation::Solid,
+            done_cycles: 3,
+            idle_mode: IdleMode::Solid,
+            rainbow_period_ms: 10_000,
+            blend: false,
+            sleep_after_s: 0,
+            restore_last_color: true,
         }
     }
 }
@@ -358,6 +1184,10 @@ impl Default for ButtonConfig {
             dpad_down: "Down".into(),
             dpad_left: "Left".into(),
             dpad_right: "Right".into(),
+            screenshot: "Win+Shift+S".into(),
+            options_tap: "Escape".into(),
+            options_hold: "Ctrl+Shift+P".into(),
+            options_hold_ms: 500,
         }
     }
 }
@@ -373,27 +1203,37 @@ fn default_state_dir() -> String {
 impl Config {
     /// Load config from the default config file path, or return defaults if not found.
     pub fn load() -> Self {
-        let config_path = config_file_path();
-        match std::fs::read_to_string(&config_path) {
-            Ok(contents) => match toml::from_str(&contents) {
-                Ok(config) => {
-                    log::info!("Loaded config from {config_path}");
-                    config
-                }
-                Err(e) => {
-                    log::warn!("Failed to parse config file {config_path}: {e}. Using defaults.");
-                    Self::default()
-                }
-            },
+        Self::load_from(&config_file_path())
+    }
+
+    /// Load config from `config_path`, or return defaults if it's missing or
+    /// doesn't parse. Used at startup, where a broken file shouldn't stop the daemon.
+    pub fn load_from(config_path: &str) -> Self {
+        Self::try_load_from(config_path).unwrap_or_else(|e| {
+            log::warn!("{e}. Using defaults.");
+            Self::default()
+        })
+    }
+
+    /// Load config from `config_path`: defaults if it's missing, an error if it
+    /// doesn't parse. The tray's "Reload config" keeps the running config on error.
+    pub fn try_load_from(config_path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(config_path) {
+            Ok(contents) => {
+                let config = toml::from_str(&contents).map_err(|e| format!("Failed to parse config file {config_path}: {e}"))?;
+                log::info!("Loaded config from {config_path}");
+                Ok(config)
+            }
             Err(_) => {
                 log::info!("No config file found at {config_path}. Using defaults.");
-                Self::default()
+                Ok(Self::default())
             }
         }
     }
 }
 
-fn config_file_path() -> String {
+/// Path of the config file: `%APPDATA%\ds4cc\config.toml`, else `ds4cc.toml`.
+pub fn config_file_path() -> String {
     if let Ok(appdata) = std::env::var("APPDATA") {
         format!("{appdata}\\ds4cc\\config.toml")
     } else {
@@ -401,6 +1241,327 @@ fn config_file_path() -> String {
     }
 }
 
+/// Serialize an f32 through its shortest decimal form, so 0.002 is written
+/// as `0.002` rather than its widened f64 value.
+fn serialize_short_f32<S: serde::Serializer>(value: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(value.to_string().parse().unwrap_or(f64::from(*value)))
+}
+
+/// Comments written above root keys and `[section]` headers in the default
+/// config template.
+const TEMPLATE_NOTES: &[(&str, &str)] = &[
+    ("state_dir", "Directory the agent hooks write their ds4cc_agent_* state files to"),
+    ("poll_interval_ms", "How often the state directory is scanned (ms)"),
+    ("idle_timeout_s", "Seconds after \"done\" before the lightbar falls back to idle (0 = never)"),
+    ("stale_timeout_s", "Seconds before a stuck \"working\" agent is ignored"),
+    ("idle_reminder_s", "Seconds an agent may sit idle before a reminder rumble (0 = off)"),
+    ("subagent_filter_s", "Agents that worked less than this (seconds) are treated as subagents"),
+    ("focus_agent", "Only this agent id drives the lightbar (\"\" = aggregate every agent)"),
+    ("[lightbar]", "Lightbar: pulse shape, brightness, done animation, idle mode and sleep"),
+    ("[lightbar.idle]", "Lightbar colors (RGB) per agent state"),
+    ("[buttons]", "Default-profile button mappings: key combos (\"Ctrl+Shift+P\") or action names"),
+    ("[scroll]", "Right stick scrolling"),
+    ("[stick_mouse]", "Left stick as mouse (switched from the tray: Mouse: Left Stick)"),
+    ("[calibration]", "Resting stick centers, measured by \"Calibrate sticks\" in the tray menu"),
+    ("[touchpad]", "Touchpad as mouse"),
+    ("[tmux]", "tmux profile: prefix key and the tmux command sent by each button"),
+    ("[codex]", "Native Codex CLI session poller"),
+    ("[codex.done_thresholds]", "Per-tool done thresholds in seconds, e.g. shell = 120\n# (a \"default\" entry replaces done_threshold_s)"),
+    ("[gemini]", "Native Gemini CLI session poller"),
+    ("[opencode]", "Agent profile: OpenCode leader key and the action sent by each button"),
+    ("[wt]", "Windows Terminal actions (tabs, panes)"),
+    ("[wsl]", "WSL distro the integrations run in (empty = the default distro) and the command timeout"),
+    ("[focus]", "Executables that pause controller mapping while focused, e.g. [\"game.exe\"]"),
+    ("[rumble]", "Rumble feedback"),
+    ("[rumble.patterns]", "Custom rumble patterns, e.g. idle_reminder = [{ left = 255, right = 255, ms = 300 }]\n# Names: working_done, idle_reminder, all_clear, profile_switch, low_battery, error"),
+    ("[mute_led]", "Mute button LED (DualSense): mic, status or off"),
+    ("[mic]", "Mute button target: the controller mic while a headset is plugged into it"),
+    ("[triggers]", "Adaptive trigger resistance (DualSense)"),
+    ("[battery]", "Low-battery warning"),
+    ("[layer]", "Hold layer: while `button` is held, every button sends its binding below"),
+    ("[tap]", "Double-tap bindings for face buttons"),
+    ("[turbo]", "Turbo: holding one of `buttons` repeats `key` every rate_ms"),
+    ("[macros]", "Button macros, e.g. square = [\"ctrl+a\", \"delay:50\", \"ctrl+c\"]\n# (\"text:<string>\" types a snippet as-is; \"hold:ctrl left left\" taps keys with modifiers held)"),
+    ("[gyro]", "Gyro aiming while `button` is held"),
+    ("[notifications]", "Windows toasts (toast = true) for done, error and idle reminders"),
+    ("[pipe]", "Named pipe (\\\\.\\pipe\\ds4cc) for hook state messages"),
+    ("[http]", "Local JSON status endpoint (GET http://127.0.0.1:<port>/state) for dashboards"),
+    ("[hid]", "Controller reads, reconnect scans, and retry backoff for a controller that can't be opened"),
+    ("[controllers]", "Input controller when several are connected (serial or 1-based order)"),
+    ("[idle_reminder]", "Scale the idle reminder by how long the agent worked"),
+    ("[gestures]", "Shake the controller to acknowledge done"),
+    ("[update]", "Self-update from GitHub releases"),
+    ("[tray]", "Tray icon behavior"),
+    ("[logging]", "Also log to %APPDATA%\\ds4cc\\ds4cc.log, rolling over at max_size_mb and keeping `keep` old files"),
+];
+
+/// Comments written above each setting inside a `[section]` of the default
+/// config template, keyed by `section.key`. The RGB components of the
+/// `[lightbar.<state>]` colors share their section's note.
+const FIELD_NOTES: &[(&str, &str)] = &[
+    ("lightbar.pulse_period_ms", "Full cycle of the working/thinking pulse (ms)"),
+    ("lightbar.pulse_waveform", "Pulse shape: sine, sawtooth, triangle or heartbeat"),
+    ("lightbar.brightness", "Scales every lightbar color (0.0–1.0)"),
+    ("lightbar.idle_brightness", "Extra dimming for idle, on top of brightness"),
+    ("lightbar.hardware_brightness", "DualSense LED brightness: high, medium or low"),
+    ("lightbar.done_animation", "Done: solid, blink or fade (done_cycles times, then solid)"),
+    ("lightbar.done_cycles", "Blink/fade cycles of the done animation"),
+    ("lightbar.idle_mode", "Idle color: solid, or rainbow (slow hue cycle)"),
+    ("lightbar.rainbow_period_ms", "Full hue cycle of rainbow idle (ms)"),
+    ("lightbar.blend", "Mix the colors of agents in different states, weighted by count"),
+    ("lightbar.sleep_after_s", "Lightbar off after this long in idle (0 = never); wakes on any change or button"),
+    ("lightbar.restore_last_color", "Keep the last color across self-update / tray Restart"),
+    ("buttons.cross", "Key combo, e.g. \"Ctrl+S\" (empty = unmapped)"),
+    ("buttons.circle", "Key combo (empty = unmapped)"),
+    ("buttons.square", "Key combo, or \"new_session\" for Windows Terminal's new tab"),
+    ("buttons.triangle", "Key combo (empty = unmapped)"),
+    ("buttons.l1", "Key combo (empty = unmapped)"),
+    ("buttons.r1", "Key combo (empty = unmapped)"),
+    ("buttons.dpad_up", "Key combo (empty = unmapped)"),
+    ("buttons.dpad_down", "Key combo (empty = unmapped)"),
+    ("buttons.dpad_left", "Key combo (empty = unmapped)"),
+    ("buttons.dpad_right", "Key combo (empty = unmapped)"),
+    ("buttons.screenshot", "Sent by Share + Options pressed together"),
+    ("buttons.options_tap", "Options quick tap"),
+    ("buttons.options_hold", "Options held past options_hold_ms (empty = the tap combo)"),
+    ("buttons.options_hold_ms", "How long Options must be held to count as a hold (ms)"),
+    ("scroll.dead_zone", "Stick travel ignored around the center (0–127)"),
+    ("scroll.sensitivity", "Scroll speed multiplier"),
+    ("scroll.horizontal", "Scroll sideways with horizontal stick movement"),
+    ("scroll.invert_vertical", "true = natural scrolling (stick up scrolls down)"),
+    ("scroll.invert_horizontal", "true = stick left scrolls right"),
+    ("scroll.mode", "\"wheel\" or \"arrows\" (arrow keys, for TUIs without wheel support)"),
+    ("scroll.lines_per_event", "Lines per wheel event at full deflection (3.0 = one notch)"),
+    ("scroll.smooth", "true = quarter-notch deltas 4x as often (smooth-scrolling apps)"),
+    ("stick_mouse.enabled", "Allow the left stick to drive the cursor"),
+    ("stick_mouse.sensitivity", "Max pixels per frame at full deflection"),
+    ("stick_mouse.dead_zone", "Stick travel ignored around the center (0–127)"),
+    ("stick_mouse.dead_zone_shape", "\"axial\", or \"radial\" (round dead zone, speed ramps up from its edge)"),
+    ("stick_mouse.axis_snap_ratio", "Snap to straight lines when minor/major axis < ratio (0 = off)"),
+    ("stick_mouse.accel_curve", "Response exponent (1.0 = linear, 2.0 = precise near center)"),
+    ("stick_mouse.swap_sticks", "true = right stick moves the cursor, left stick scrolls"),
+    ("stick_mouse.momentary_button", "e.g. \"l3\": stick mouse only while held (replaces the tray toggle)"),
+    ("calibration.left_x", "Left stick resting X (dead zones are measured from here instead of 128)"),
+    ("calibration.left_y", "Left stick resting Y"),
+    ("calibration.right_x", "Right stick resting X"),
+    ("calibration.right_y", "Right stick resting Y"),
+    ("touchpad.enabled", "Touchpad moves the cursor and clicks (false = touchpad button in tmux/Agent mappings)"),
+    ("touchpad.sensitivity", "Cursor speed multiplier for touchpad swipes"),
+    ("tmux.enabled", "Include the tmux profile in the PS cycle"),
+    ("tmux.auto_detect", "Read the prefix and bindings from tmux via WSL"),
+    ("tmux.prefix", "Prefix used when detection finds none"),
+    ("tmux.key_table", "Custom table entered via switch-client -T (e.g. \"apps\")"),
+    ("tmux.root_bindings", "Send root-table keys (bind -n, no prefix) for actions bound there"),
+    ("tmux.l1", "tmux command (resolved to its bound key) or a key combo"),
+    ("tmux.r1", "tmux command or key combo"),
+    ("tmux.l2", "tmux command or key combo"),
+    ("tmux.r2", "tmux command or key combo"),
+    ("tmux.l3", "tmux command or key combo"),
+    ("tmux.r3", "tmux command or key combo"),
+    ("tmux.square", "tmux command or key combo"),
+    ("tmux.share", "tmux command or key combo"),
+    ("tmux.options", "tmux command or key combo"),
+    ("tmux.touchpad", "tmux command or key combo"),
+    ("codex.enabled", "Poll Codex CLI sessions (~/.codex/sessions) via WSL"),
+    ("codex.done_threshold_s", "Seconds a task must run before \"done\" fires (vs. straight to idle)"),
+    ("codex.token_sidecar", "Write per-session token totals to ds4cc_agent_<id>_tokens"),
+    ("codex.token_meter", "Player LEDs show context fill (1–5 dots) during a session"),
+    ("codex.context_window", "Tokens that light all five dots"),
+    ("gemini.enabled", "Poll Gemini CLI sessions (~/.gemini/tmp/*/chats) via WSL"),
+    ("gemini.done_threshold_s", "Seconds a turn must run before \"done\" fires (vs. straight to idle)"),
+    ("opencode.enabled", "Include the Agent (OpenCode) profile in the PS cycle"),
+    ("opencode.auto_detect", "Read keybinds from ~/.config/opencode/opencode.json via WSL"),
+    ("opencode.done_threshold_s", "Seconds before \"done\" fires (passed to the plugin via DS4CC_DONE_THRESHOLD_S)"),
+    ("opencode.leader", "Leader used when detection finds none"),
+    ("opencode.l1", "OpenCode action name or key combo"),
+    ("opencode.r1", "OpenCode action name or key combo"),
+    ("opencode.square", "OpenCode action name or key combo"),
+    ("opencode.share", "OpenCode action name or key combo"),
+    ("opencode.options", "OpenCode action name or key combo"),
+    ("wt.enabled", "Use Windows Terminal actions in the Default profile"),
+    ("wt.auto_detect", "Read keybindings from Windows Terminal's settings.json"),
+    ("wt.square", "WT action name (e.g. \"newTab\") or key combo"),
+    ("wt.l1", "WT action name or key combo"),
+    ("wt.r1", "WT action name or key combo"),
+    ("wt.l2", "WT action name or key combo"),
+    ("wt.r2", "WT action name or key combo"),
+    ("wt.l3", "WT action name or key combo"),
+    ("wt.r3", "WT action name or key combo"),
+    ("wt.share", "WT action name or key combo"),
+    ("wt.options", "WT action name or key combo"),
+    ("wsl.distro", "e.g. \"Ubuntu-24.04\" (as in `wsl -l`); empty = the default distro"),
+    ("wsl.command_timeout_ms", "WSL commands still running after this are killed"),
+    ("focus.passthrough_apps", "e.g. [\"eldenring\", \"vlc.exe\"] — no mapping while focused"),
+    ("rumble.enabled", "false silences every rumble"),
+    ("rumble.intensity", "Scales all rumble patterns (0.0–1.0)"),
+    ("rumble.all_clear", "Distinct rumble when every agent of a parallel run has settled"),
+    ("rumble.all_clear_min_agents", "Agents that must be active at once for the all-clear"),
+    ("rumble.profile_feedback", "Two light bumps when PS switches profile"),
+    ("rumble.on_error", "Short buzz + red lightbar flash when an agent enters error"),
+    ("mute_led.mode", "\"mic\" (lit = muted), \"status\" (lit = agent working) or \"off\""),
+    ("mic.prefer_controller", "Headset in the DualSense jack → mute toggles the controller mic"),
+    ("triggers.enabled", "Constant L2/R2 resistance"),
+    ("triggers.left_start", "Where L2 resistance begins (0–255 along the travel)"),
+    ("triggers.left_force", "L2 resistance (0–255, 0 = free)"),
+    ("triggers.right_start", "Where R2 resistance begins (0–255 along the travel)"),
+    ("triggers.right_force", "R2 resistance (0–255, 0 = free)"),
+    ("battery.enabled", "One rumble + red lightbar flash when the battery runs low"),
+    ("battery.warn_pct", "Warn at or below this charge while discharging"),
+    ("layer.button", "Button that holds the layer, e.g. \"r1\" (empty = disabled)"),
+    ("layer.cross", "Key combo while the layer is held (empty = nothing)"),
+    ("layer.circle", "Key combo while the layer is held"),
+    ("layer.square", "Key combo while the layer is held"),
+    ("layer.triangle", "Key combo while the layer is held"),
+    ("layer.l1", "Key combo while the layer is held"),
+    ("layer.r1", "Key combo while the layer is held"),
+    ("layer.l2", "Key combo while the layer is held"),
+    ("layer.r2", "Key combo while the layer is held"),
+    ("layer.l3", "Key combo while the layer is held"),
+    ("layer.r3", "Key combo while the layer is held"),
+    ("layer.share", "Key combo while the layer is held"),
+    ("layer.options", "Key combo while the layer is held"),
+    ("layer.dpad_up", "Key combo while the layer is held"),
+    ("layer.dpad_down", "Key combo while the layer is held"),
+    ("layer.dpad_left", "Key combo while the layer is held"),
+    ("layer.dpad_right", "Key combo while the layer is held"),
+    ("tap.double_tap_ms", "A second press within this window sends the double-tap combo"),
+    ("tap.cross", "Double-tap combo, e.g. \"Shift+Enter\" (empty = none)"),
+    ("tap.circle", "Double-tap combo (empty = none)"),
+    ("tap.triangle", "Double-tap combo, e.g. \"Shift+Tab\" (empty = none)"),
+    ("turbo.enabled", "Listed buttons repeat `key` while held (replacing their normal action)"),
+    ("turbo.rate_ms", "Repeat interval (ms)"),
+    ("turbo.buttons", "Buttons that repeat, e.g. [\"r2\"]"),
+    ("turbo.key", "Key combo repeated"),
+    ("gyro.enabled", "DualSense: aim the cursor by turning/tilting the controller"),
+    ("gyro.button", "Hold to aim (its normal action is disabled); empty = always on"),
+    ("gyro.sensitivity", "Pixels per raw gyro unit per report"),
+    ("gyro.dead_zone", "Raw angular velocity ignored per axis (hand tremor)"),
+    ("notifications.toast", "Windows toasts (opt-in); the switches below pick which"),
+    ("notifications.done", "\"Agent … done after 12m\" (skipped for subagent-length runs)"),
+    ("notifications.error", "An agent hit an error"),
+    ("notifications.idle_reminder", "Alongside the idle reminder rumble"),
+    ("notifications.debounce_s", "At most one toast of each kind per window (seconds)"),
+    ("pipe.enabled", "Accept \"<agent_id> <state>\" lines on the pipe"),
+    ("http.enabled", "Serve the status endpoint on 127.0.0.1"),
+    ("http.port", "Port of the status endpoint"),
+    ("hid.open_retry_initial_ms", "First backoff when a found controller can't be opened"),
+    ("hid.open_retry_max_ms", "Longest backoff between open attempts"),
+    ("hid.open_give_up_after", "Failed opens before the \"claimed by Steam/DS4Windows\" warning"),
+    ("hid.read_timeout_ms", "Wait per input report read (1–10); higher = less CPU"),
+    ("hid.reconnect_scan_interval_ms", "How often controllers are looked for (250–60000)"),
+    ("controllers.input", "Serial/MAC or 1-based order (\"1\"); others are status-only (empty = all map input)"),
+    ("idle_reminder.adaptive", "Brief work → longer wait before the idle reminder"),
+    ("idle_reminder.reference_work_s", "Work at least this long → plain idle_reminder_s"),
+    ("idle_reminder.max_scale", "Cap on the stretch for very short work"),
+    ("gestures.shake_to_ack", "Shake the controller to dismiss Done (DualSense)"),
+    ("gestures.shake_threshold_g", "Acceleration that counts as a shake spike (g)"),
+    ("gestures.shake_peaks", "Spikes within shake_window_ms"),
+    ("gestures.shake_window_ms", "Window the spikes must fall in (ms)"),
+    ("update.enabled", "false disables \"Check for Updates\""),
+    ("update.repo", "Fork/mirror: \"owner/name\" or a repo API URL"),
+    ("update.installer_name", "Release asset to download and run"),
+    ("update.channel", "\"prerelease\" to get beta builds"),
+    ("update.verify_checksum", "Refuse an installer whose SHA-256 doesn't match the published one"),
+    ("update.check_on_startup", "Silent check at launch; prompts only if an update exists"),
+    ("tray.confirm_exit", "Ask \"Quit DS4CC?\" before Exit"),
+    ("logging.file", "Also log to %APPDATA%\\ds4cc\\ds4cc.log"),
+    ("logging.max_size_mb", "Roll over to ds4cc.log.1 past this size"),
+    ("logging.keep", "Rolled-over files kept"),
+];
+
+/// The default configuration as commented TOML: every setting with its
+/// default value, with a note above each section and setting.
+pub fn default_template() -> String {
+    let body = toml::to_string_pretty(&Config::default()).expect("default config serializes");
+    let mut out = String::from(
+        "# DS4CC configuration — every setting below is at its default value.\n\
+         # All settings are optional: delete the ones you don't change.\n\
+         # After editing, use \"Reload config\" in the tray menu.\n\n",
+    );
+    let mut section: Option<&str> = None;
+    for line in body.lines() {
+        let note = if line.starts_with('[') {
+            section = Some(line.trim_matches(['[', ']']));
+            TEMPLATE_NOTES.iter().find(|(name, _)| *name == line)
+        } else if let Some((key, _)) = line.split_once(" = ") {
+            match section {
+                None => TEMPLATE_NOTES.iter().find(|(name, _)| *name == key),
+                Some(section) => FIELD_NOTES
+                    .iter()
+                    .find(|(name, _)| name.split_once('.') == Some((section, key))),
+            }
+        } else {
+            None
+        };
+        if let Some((_, note)) = note {
+            out.push_str(&format!("# {note}\n"));
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Write [`default_template`] to `path` (creating its directory) unless the
+/// file already exists. Returns true if it was created.
+pub fn write_default_if_missing(path: &std::path::Path) -> std::io::Result<bool> {
+    if path.exists() {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, default_template())?;
+    Ok(true)
+}
+
+/// Store `calibration` as the `[calibration]` table of the config file at
+/// `config_path`, leaving the rest of the file (comments included) as is.
+pub fn save_calibration(config_path: &str, calibration: &CalibrationConfig) -> std::io::Result<()> {
+    let contents = match std::fs::read_to_string(config_path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let body = toml::to_string(calibration).expect("calibration serializes");
+    std::fs::write(config_path, replace_table(&contents, "[calibration]", &body))
+}
+
+/// `contents` with the key lines of the `header` table replaced by `body`
+/// (in place, or appended if the table is missing). Comments and blank lines
+/// are kept, so the note above the next table stays where it was.
+fn replace_table(contents: &str, header: &str, body: &str) -> String {
+    let mut out = String::new();
+    let mut found = false;
+    let mut in_table = false;
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_table = trimmed == header;
+            if in_table {
+                found = true;
+                out.push_str(line);
+                out.push('\n');
+                out.push_str(body);
+                continue;
+            }
+        } else if in_table && !trimmed.is_empty() && !trimmed.starts_with('#') {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if !found {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(header);
+        out.push('\n');
+        out.push_str(body);
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -414,6 +1575,99 @@ mod tests {
         assert_eq!(config.buttons.cross, "Enter");
     }
 
+    #[test]
+    fn deserialize_calibration_centers() {
+        let config: Config = toml::from_str("[calibration]\nleft_x = 135\nright_y = 120").unwrap();
+        assert_eq!(
+            config.calibration,
+            CalibrationConfig { left_x: 135, left_y: 128, right_x: 128, right_y: 120 }
+        );
+    }
+
+    #[test]
+    fn calibration_table_is_replaced_in_place() {
+        let contents = "# Stick centers\n[calibration]\nleft_x = 128\n\n# Touchpad\n[touchpad]\nenabled = false\n";
+        let updated = replace_table(contents, "[calibration]", "left_x = 131\n");
+        assert_eq!(updated, "# Stick centers\n[calibration]\nleft_x = 131\n\n# Touchpad\n[touchpad]\nenabled = false\n");
+
+        // Missing table → appended; the rest is untouched
+        let updated = replace_table("[touchpad]\nenabled = false\n", "[calibration]", "left_x = 131\n");
+        assert_eq!(updated, "[touchpad]\nenabled = false\n\n[calibration]\nleft_x = 131\n");
+        let config: Config = toml::from_str(&updated).unwrap();
+        assert_eq!(config.calibration.left_x, 131);
+        assert!(!config.touchpad.enabled);
+    }
+
+    #[test]
+    fn wsl_command_timeout_has_a_floor() {
+        assert_eq!(WslConfig::default().command_timeout(), std::time::Duration::from_secs(10));
+        let config: Config = toml::from_str("[wsl]\ncommand_timeout_ms = 0").unwrap();
+        assert_eq!(config.wsl.command_timeout(), std::time::Duration::from_millis(100));
+    }
+
+    #[test]
+    fn hid_timings_are_clamped() {
+        let hid = HidConfig::default();
+        assert_eq!(hid.read_timeout_ms(), 5);
+        assert_eq!(hid.reconnect_scan_interval(), std::time::Duration::from_secs(2));
+
+        let config: Config =
+            toml::from_str("[hid]\nread_timeout_ms = 0\nreconnect_scan_interval_ms = 10").unwrap();
+        assert_eq!(config.hid.read_timeout_ms(), 1);
+        assert_eq!(config.hid.reconnect_scan_interval(), std::time::Duration::from_millis(250));
+
+        let config: Config =
+            toml::from_str("[hid]\nread_timeout_ms = 5000\nreconnect_scan_interval_ms = 600000").unwrap();
+        assert_eq!(config.hid.read_timeout_ms(), 10);
+        assert_eq!(config.hid.reconnect_scan_interval(), std::time::Duration::from_secs(60));
+    }
+
+    #[test]
+    fn deserialize_mic_prefer_controller() {
+        let config: Config = toml::from_str("[mic]\nprefer_controller = true").unwrap();
+        assert!(config.mic.prefer_controller);
+        assert!(!Config::default().mic.prefer_controller);
+    }
+
+    #[test]
+    fn deserialize_mute_led_mode() {
+        let config: Config = toml::from_str("[mute_led]\nmode = \"status\"").unwrap();
+        assert_eq!(config.mute_led.mode, MuteLedMode::Status);
+        assert_eq!(Config::default().mute_led.mode, MuteLedMode::Mic);
+    }
+
+    #[test]
+    fn deserialize_macros_table() {
+        let toml_str = "[macros]\nsquare = [\"ctrl+a\", \"delay:50\", \"ctrl+c\"]";
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.macros.0["square"], vec!["ctrl+a", "delay:50", "ctrl+c"]);
+        assert!(Config::default().macros.0.is_empty());
+    }
+
+    #[test]
+    fn deserialize_rumble_patterns() {
+        let toml_str = r#"
+            [rumble.patterns]
+            working_done = [{ left = 200, right = 100, ms = 80 }, { left = 0, right = 0, ms = 40 }]
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.rumble.patterns["working_done"],
+            vec![
+                RumbleStepConfig { left: 200, right: 100, ms: 80 },
+                RumbleStepConfig { left: 0, right: 0, ms: 40 },
+            ]
+        );
+
+        // Out-of-range motor values and durations are rejected at load
+        let too_strong = "[rumble.patterns]\nidle_reminder = [{ left = 300, right = 0, ms = 80 }]";
+        assert!(toml::from_str::<Config>(too_strong).is_err());
+        let zero_ms = "[rumble.patterns]\nidle_reminder = [{ left = 10, right = 0, ms = 0 }]";
+        assert!(toml::from_str::<Config>(zero_ms).is_err());
+        let too_long = "[rumble.patterns]\nidle_reminder = [{ left = 10, right = 0, ms = 60000 }]";
+        assert!(toml::from_str::<Config>(too_long).is_err());
+    }
+
     #[test]
     fn deserialize_partial_toml() {
         let toml_str = r#"
@@ -431,4 +1685,77 @@ mod tests {
         assert_eq!(config.lightbar.working.b, 255);
         assert_eq!(config.buttons.cross, "Enter");
     }
+
+    #[test]
+    fn load_from_picks_up_changed_file() {
+        let path = std::env::temp_dir().join(format!("ds4cc_reload_{}.toml", std::process::id()));
+        let path_str = path.to_str().unwrap();
+
+        // Missing file → defaults
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(Config::load_from(path_str).scroll.sensitivity, ScrollConfig::default().sensitivity);
+
+        std::fs::write(&path, "[scroll]\nsensitivity = 2.5\n[lightbar.idle]\nr = 1\ng = 2\nb = 3\n").unwrap();
+        let first = Config::load_from(path_str);
+        assert_eq!(first.scroll.sensitivity, 2.5);
+        assert_eq!((first.lightbar.idle.r, first.lightbar.idle.g, first.lightbar.idle.b), (1, 2, 3));
+
+        // Edited on disk → the next load sees the new values
+        std::fs::write(&path, "[scroll]\nsensitivity = 0.5\n[rumble]\nintensity = 0.25\n").unwrap();
+        let second = Config::load_from(path_str);
+        assert_eq!(second.scroll.sensitivity, 0.5);
+        assert_eq!(second.rumble.intensity, 0.25);
+        assert_eq!(second.lightbar.idle.r, LightbarConfig::default().idle.r);
+
+        // Broken file → an error for reload, defaults for startup
+        std::fs::write(&path, "[scroll]\nsensitivity = \"fast\"\n").unwrap();
+        let err = Config::try_load_from(path_str).unwrap_err();
+        assert!(err.contains(path_str), "{err}");
+        assert_eq!(Config::load_from(path_str).scroll.sensitivity, ScrollConfig::default().sensitivity);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn default_template_round_trips_to_defaults() {
+        let template = default_template();
+        assert!(template.contains("# Right stick scrolling\n[scroll]"));
+        assert!(template.contains("sensitivity = 0.002\n"));
+        let parsed: Config = toml::from_str(&template).unwrap();
+        assert_eq!(
+            toml::to_string(&parsed).unwrap(),
+            toml::to_string(&Config::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn default_template_comments_every_setting() {
+        let template = default_template();
+        assert!(template.contains("# Scroll speed multiplier\nsensitivity = 1.0\n"));
+        assert!(template.contains("brightness = 1.0\n"));
+        assert!(!template.contains("0.0020000000949949026"));
+        let lines: Vec<&str> = template.lines().collect();
+        let mut section = "";
+        for (i, line) in lines.iter().enumerate() {
+            if line.starts_with('[') {
+                section = line;
+            } else if line.contains(" = ") && !line.starts_with('#') && !section.starts_with("[lightbar.") {
+                assert!(lines[i - 1].starts_with("# "), "no comment above `{line}` in {section}");
+            }
+        }
+    }
+
+    #[test]
+    fn write_default_if_missing_keeps_existing_file() {
+        let dir = std::env::temp_dir().join(format!("ds4cc_template_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("ds4cc").join("config.toml");
+        assert!(write_default_if_missing(&path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), default_template());
+
+        std::fs::write(&path, "poll_interval_ms = 250\n").unwrap();
+        assert!(!write_default_if_missing(&path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "poll_interval_ms = 250\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,4 +1,9 @@
-/// Controller identification: VID/PID matching and connection type detection.
+/// Controller identification: VID/PID matching, connection type detection,
+/// role assignment (input vs. status-only), and which of the discovered
+/// controllers to run.
+
+use crate::config::ControllersConfig;
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ControllerType {
@@ -6,6 +11,7 @@ pub enum ControllerType {
     DualSenseEdge,
     Ds4V1,
     Ds4V2,
+    SwitchPro,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,31 +20,66 @@ pub enum ConnectionType {
     Bluetooth,
 }
 
+/// A discovered controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControllerInfo {
+    pub controller_type: ControllerType,
+    pub connection_type: ConnectionType,
+    pub path: String,
+    /// HID serial number (Bluetooth MAC for DualSense/DS4). Empty if unavailable.
+    pub serial: String,
+}
+
 /// Known VID/PID pairs.
 const SONY_VID: u16 = 0x054C;
 const DUALSENSE_PID: u16 = 0x0CE6;
 const DUALSENSE_EDGE_PID: u16 = 0x0DF2;
 const DS4_V1_PID: u16 = 0x05C4;
 const DS4_V2_PID: u16 = 0x09CC;
+const NINTENDO_VID: u16 = 0x057E;
+const SWITCH_PRO_PID: u16 = 0x2009;
 
 /// HID usage page and usage for gamepad collections.
-pub const GAMEPAD_USAGE_PAGE: u16 = 0x01; // Generic Desktop
-pub const GAMEPAD_USAGE: u16 = 0x05; // Game Pad
+const GAMEPAD_USAGE_PAGE: u16 = 0x01; // Generic Desktop
+const GAMEPAD_USAGE: u16 = 0x05; // Game Pad
+const JOYSTICK_USAGE: u16 = 0x04; // Joystick (how the Switch Pro enumerates)
 
 /// Identify controller type from VID/PID. Returns None for unknown devices.
 pub fn identify(vid: u16, pid: u16) -> Option<ControllerType> {
-    if vid != SONY_VID {
-        return None;
-    }
-    match pid {
-        DUALSENSE_PID => Some(ControllerType::DualSense),
-        DUALSENSE_EDGE_PID => Some(ControllerType::DualSenseEdge),
-        DS4_V1_PID => Some(ControllerType::Ds4V1),
-        DS4_V2_PID => Some(ControllerType::Ds4V2),
+    match (vid, pid) {
+        (SONY_VID, DUALSENSE_PID) => Some(ControllerType::DualSense),
+        (SONY_VID, DUALSENSE_EDGE_PID) => Some(ControllerType::DualSenseEdge),
+        (SONY_VID, DS4_V1_PID) => Some(ControllerType::Ds4V1),
+        (SONY_VID, DS4_V2_PID) => Some(ControllerType::Ds4V2),
+        (NINTENDO_VID, SWITCH_PRO_PID) => Some(ControllerType::SwitchPro),
         _ => None,
     }
 }
 
+/// Whether a HID collection is the gamepad (or joystick) one controllers are
+/// read through — each device also exposes other collections.
+pub fn is_gamepad_usage(usage_page: u16, usage: u16) -> bool {
+    usage_page == GAMEPAD_USAGE_PAGE && matches!(usage, GAMEPAD_USAGE | JOYSTICK_USAGE)
+}
+
+/// The HID device fields `--list-controllers` prints:
+/// (VID, PID, usage page, usage, manufacturer, product).
+pub type DeviceRow<'a> = (u16, u16, u16, u16, &'a str, &'a str);
+
+/// One `--list-controllers` line: IDs, usage, name strings, and what DS4CC
+/// makes of the device — the matched controller type, a known controller's
+/// skipped non-gamepad collection, or `-` for anything unsupported.
+pub fn format_device_row(device: DeviceRow) -> String {
+    let (vid, pid, usage_page, usage, manufacturer, product) = device;
+    let matched = match identify(vid, pid) {
+        Some(ct) if is_gamepad_usage(usage_page, usage) => ct.to_string(),
+        Some(ct) => format!("{ct} (not the gamepad collection, skipped)"),
+        None => "-".to_string(),
+    };
+    let name = format!("{} {}", manufacturer.trim(), product.trim());
+    format!("{vid:04X}:{pid:04X}  {usage_page:04X}/{usage:04X}  {:<40}  {matched}", name.trim())
+}
+
 /// Detect connection type from HID device path.
 /// DS4Windows heuristic: Bluetooth paths on Windows contain "&col02" or similar
 /// patterns and the interface number differs from USB.
@@ -62,21 +103,121 @@ impl ControllerType {
         matches!(self, ControllerType::DualSense | ControllerType::DualSenseEdge)
     }
 
-    /// Returns true if this is a DS4-family controller.
-    pub fn is_ds4(self) -> bool {
-        matches!(self, ControllerType::Ds4V1 | ControllerType::Ds4V2)
-    }
-
     pub fn name(self) -> &'static str {
         match self {
             ControllerType::DualSense => "DualSense",
             ControllerType::DualSenseEdge => "DualSense Edge",
             ControllerType::Ds4V1 => "DualShock 4 v1",
             ControllerType::Ds4V2 => "DualShock 4 v2",
+            ControllerType::SwitchPro => "Switch Pro Controller",
         }
     }
 }
 
+/// What a connected controller is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerRole {
+    /// Buttons/sticks are mapped to keystrokes and mouse; lightbar/rumble show status.
+    Input,
+    /// Lightbar/rumble show status only; input is read but never mapped.
+    StatusOnly,
+}
+
+/// Resolve a controller's role from `[controllers] input`, given its serial
+/// number and 0-based detection order.
+pub fn resolve_role(cfg: &ControllersConfig, serial: &str, order: usize) -> ControllerRole {
+    let wanted = cfg.input.trim();
+    if wanted.is_empty() {
+        return ControllerRole::Input;
+    }
+    let is_input = match wanted.parse::<usize>() {
+        Ok(n) => n == order + 1,
+        Err(_) => {
+            let serial = normalize_serial(serial);
+            !serial.is_empty() && serial == normalize_serial(wanted)
+        }
+    };
+    if is_input { ControllerRole::Input } else { ControllerRole::StatusOnly }
+}
+
+/// The controllers to run, one per physical device, sorted by serial (then
+/// path) so that an order in `[controllers] input` keeps naming the same
+/// controller whatever order HID enumerates them in.
+///
+/// A controller plugged in over USB while paired over Bluetooth shows up
+/// twice; only its USB entry is kept. Entries pair up by serial — a USB
+/// entry's is the MAC read from its pairing report. Entries whose serial is
+/// unknown can't be paired, so they all run.
+pub fn select_devices(found: &[ControllerInfo]) -> Vec<&ControllerInfo> {
+    let usb_serials: HashSet<String> = found
+        .iter()
+        .filter(|c| c.connection_type == ConnectionType::Usb)
+        .map(|c| normalize_serial(&c.serial))
+        .filter(|s| !s.is_empty())
+        .collect();
+    let mut selected: Vec<&ControllerInfo> = found
+        .iter()
+        .filter(|c| c.connection_type == ConnectionType::Usb || !usb_serials.contains(&normalize_serial(&c.serial)))
+        .collect();
+    selected.sort_by_cached_key(|c| {
+        let serial = normalize_serial(&c.serial);
+        (serial.is_empty(), serial, c.path.clone())
+    });
+    selected
+}
+
+/// Feature report that carries a Sony controller's own Bluetooth MAC, read
+/// over USB where HID reports no serial: 0x09 (pairing info) on DualSense,
+/// 0x12 on DS4. None for the Switch Pro.
+pub fn pairing_report_id(ct: ControllerType) -> Option<u8> {
+    match ct {
+        ControllerType::DualSense | ControllerType::DualSenseEdge => Some(0x09),
+        ControllerType::Ds4V1 | ControllerType::Ds4V2 => Some(0x12),
+        ControllerType::SwitchPro => None,
+    }
+}
+
+/// The MAC in a pairing feature report (bytes 1..7, least significant first),
+/// as `aa:bb:cc:dd:ee:ff`. None if the report is short or the MAC is zero.
+pub fn mac_from_pairing_report(report: &[u8]) -> Option<String> {
+    let bytes = report.get(1..7)?;
+    if bytes.iter().all(|&b| b == 0) {
+        return None;
+    }
+    Some(bytes.iter().rev().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(":"))
+}
+
+/// Difference between the running controller sessions and the wanted set,
+/// keyed by HID path.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DeviceDiff {
+    /// Wanted but not running yet (in `wanted` order).
+    pub added: Vec<String>,
+    /// Running but no longer wanted (unplugged, or superseded by USB).
+    pub removed: Vec<String>,
+}
+
+/// Which sessions to start and stop so that exactly `wanted` are running.
+pub fn diff_devices<'a>(running: impl IntoIterator<Item = &'a String>, wanted: &[&str]) -> DeviceDiff {
+    let running: HashSet<&str> = running.into_iter().map(String::as_str).collect();
+    let added = wanted.iter().filter(|p| !running.contains(*p)).map(|p| p.to_string()).collect();
+    let mut removed: Vec<String> = running
+        .iter()
+        .filter(|p| !wanted.contains(p))
+        .map(|p| p.to_string())
+        .collect();
+    removed.sort();
+    DeviceDiff { added, removed }
+}
+
+/// Serials compare case-insensitively, ignoring MAC separators.
+fn normalize_serial(s: &str) -> String {
+    s.chars()
+        .filter(|c| !matches!(c, ':' | '-'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
 impl std::fmt::Display for ControllerType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(self.name())
@@ -102,6 +243,7 @@ mod tests {
         assert_eq!(identify(0x054C, 0x0DF2), Some(ControllerType::DualSenseEdge));
         assert_eq!(identify(0x054C, 0x05C4), Some(ControllerType::Ds4V1));
         assert_eq!(identify(0x054C, 0x09CC), Some(ControllerType::Ds4V2));
+        assert_eq!(identify(0x057E, 0x2009), Some(ControllerType::SwitchPro));
     }
 
     #[test]
@@ -110,6 +252,20 @@ mod tests {
         assert_eq!(identify(0x0001, 0x0CE6), None);
     }
 
+    #[test]
+    fn device_rows_show_ids_usage_and_match() {
+        assert_eq!(
+            format_device_row((0x054C, 0x0CE6, 0x01, 0x05, "Sony Interactive Entertainment", "DualSense Wireless Controller")),
+            format!("054C:0CE6  0001/0005  {:<40}  DualSense", "Sony Interactive Entertainment DualSense Wireless Controller")
+        );
+        assert_eq!(
+            format_device_row((0x057E, 0x2009, 0x01, 0x04, "Nintendo", "Pro Controller")),
+            format!("057E:2009  0001/0004  {:<40}  Switch Pro Controller", "Nintendo Pro Controller")
+        );
+        assert!(format_device_row((0x054C, 0x0CE6, 0x0C, 0x01, "", "")).ends_with("DualSense (not the gamepad collection, skipped)"));
+        assert_eq!(format_device_row((0x046D, 0xC52B, 0x01, 0x02, "", "USB Receiver")), format!("046D:C52B  0001/0002  {:<40}  -", "USB Receiver"));
+    }
+
     #[test]
     fn detect_usb_path() {
         let path = r"\\?\hid#vid_054c&pid_0ce6&mi_03#8&hash&0&0000#{4d1e55b2-f16f-11cf-88cb-001111000030}";
@@ -121,4 +277,94 @@ mod tests {
         let path = r"\\?\hid#{00001124-0000-1000-8000-00805f9b34fb}_vid&0002054c_pid&0ce6#8&hash&0&0000#{4d1e55b2-f16f-11cf-88cb-001111000030}";
         assert_eq!(detect_connection(path), ConnectionType::Bluetooth);
     }
+
+    #[test]
+    fn role_resolution_by_serial_and_order() {
+        let any = ControllersConfig::default();
+        assert_eq!(resolve_role(&any, "a0ab51123456", 1), ControllerRole::Input);
+
+        let by_serial = ControllersConfig { input: "A0:AB:51:12:34:56".into() };
+        assert_eq!(resolve_role(&by_serial, "a0ab51123456", 1), ControllerRole::Input);
+        assert_eq!(resolve_role(&by_serial, "a0ab51999999", 0), ControllerRole::StatusOnly);
+        assert_eq!(resolve_role(&by_serial, "", 0), ControllerRole::StatusOnly);
+
+        let by_order = ControllersConfig { input: "2".into() };
+        assert_eq!(resolve_role(&by_order, "", 1), ControllerRole::Input);
+        assert_eq!(resolve_role(&by_order, "", 0), ControllerRole::StatusOnly);
+    }
+
+    fn info(path: &str, conn: ConnectionType, serial: &str) -> ControllerInfo {
+        ControllerInfo {
+            controller_type: ControllerType::DualSense,
+            connection_type: conn,
+            path: path.into(),
+            serial: serial.into(),
+        }
+    }
+
+    fn paths<'a>(selected: &[&'a ControllerInfo]) -> Vec<&'a str> {
+        selected.iter().map(|c| c.path.as_str()).collect()
+    }
+
+    #[test]
+    fn select_prefers_usb_per_device() {
+        use ConnectionType::{Bluetooth, Usb};
+        // Same controller on both links (matching serials) → USB only; the
+        // second controller on Bluetooth stays
+        let found = [
+            info("usb1", Usb, "A0:AB:51:00:00:01"),
+            info("bt1", Bluetooth, "a0ab51000001"),
+            info("bt2", Bluetooth, "a0ab51000002"),
+        ];
+        assert_eq!(paths(&select_devices(&found)), ["usb1", "bt2"]);
+
+        // A USB entry whose serial is unknown isn't guessed to be any BT entry
+        let found = [info("usb1", Usb, ""), info("bt1", Bluetooth, "a0ab51000001")];
+        assert_eq!(paths(&select_devices(&found)), ["bt1", "usb1"]);
+
+        // Two Bluetooth controllers, nothing plugged in → both run
+        let found = [info("bt1", Bluetooth, "a0ab51000001"), info("bt2", Bluetooth, "a0ab51000002")];
+        assert_eq!(paths(&select_devices(&found)), ["bt1", "bt2"]);
+    }
+
+    #[test]
+    fn select_orders_by_serial_not_enumeration() {
+        use ConnectionType::{Bluetooth, Usb};
+        let found = [
+            info("bt2", Bluetooth, "a0ab51000002"),
+            info("usb9", Usb, ""),
+            info("usb1", Usb, "a0:ab:51:00:00:01"),
+            info("bt1", Bluetooth, "a0ab51000001"),
+        ];
+        assert_eq!(paths(&select_devices(&found)), ["usb1", "bt2", "usb9"]);
+        // The same controllers listed in another order pick the same roles
+        let mut reversed = found.clone();
+        reversed.reverse();
+        assert_eq!(paths(&select_devices(&reversed)), ["usb1", "bt2", "usb9"]);
+    }
+
+    #[test]
+    fn pairing_report_mac_is_read_backwards() {
+        let report = [0x09, 0x01, 0x00, 0x00, 0x51, 0xab, 0xa0, 0x08, 0x25, 0x00];
+        assert_eq!(mac_from_pairing_report(&report).as_deref(), Some("a0:ab:51:00:00:01"));
+        assert_eq!(mac_from_pairing_report(&[0x12, 0, 0, 0, 0, 0, 0, 1]), None);
+        assert_eq!(mac_from_pairing_report(&[0x09, 1, 2]), None);
+        assert_eq!(pairing_report_id(ControllerType::DualSense), Some(0x09));
+        assert_eq!(pairing_report_id(ControllerType::Ds4V2), Some(0x12));
+        assert_eq!(pairing_report_id(ControllerType::SwitchPro), None);
+    }
+
+    #[test]
+    fn diff_reports_added_and_removed_paths() {
+        let running: Vec<String> = vec!["bt1".into(), "bt2".into()];
+        let diff = diff_devices(&running, &["usb1", "bt2"]);
+        assert_eq!(diff.added, ["usb1"]);
+        assert_eq!(diff.removed, ["bt1"]);
+
+        assert_eq!(diff_devices(&running, &["bt1", "bt2"]), DeviceDiff::default());
+        let diff = diff_devices(&[], &["usb1"]);
+        assert_eq!((diff.added, diff.removed), (vec!["usb1".to_string()], vec![]));
+        let diff = diff_devices(&running, &[]);
+        assert_eq!(diff.removed, ["bt1", "bt2"]);
+    }
 }
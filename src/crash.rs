@@ -0,0 +1,111 @@
+/// Panic hook: a panic on any thread (tray, HID, pollers, runtime workers)
+/// is logged with its backtrace — so it reaches the log file, if any — and
+/// shown in a message box. With the console hidden, a crash would otherwise
+/// go unnoticed.
+///
+/// Only the first panic shows the box, and it's shown from its own thread, so
+/// the panicking thread unwinds (releasing its locks) instead of blocking
+/// until the box is dismissed.
+///
+/// Aborted tokio tasks don't panic, so shutting those down never shows it.
+
+use std::any::Any;
+use std::panic::Location;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once the crash dialog has been shown, so a panic cascade opens one box.
+static DIALOG_SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Install the hook. `log_file` is named in the message box when file
+/// logging is on (`[logging] file`).
+pub fn install(log_file: Option<PathBuf>) {
+    std::panic::set_hook(Box::new(move |info| {
+        let thread = std::thread::current();
+        let message = panic_message(thread.name().unwrap_or("<unnamed>"), info.payload(), info.location());
+        log::error!("{message}\n{}", std::backtrace::Backtrace::force_capture());
+        if claim_dialog() {
+            let text = dialog_text(&message, log_file.as_deref());
+            let dialog = std::thread::Builder::new().name("crash-dialog".into()).spawn(move || show_crash_dialog(&text));
+            // A panic on the main thread ends the process, which would take the
+            // dialog thread with it, so wait for the box there.
+            if let (Ok(dialog), Some("main")) = (dialog, thread.name()) {
+                let _ = dialog.join();
+            }
+        }
+    }));
+}
+
+/// True for the first caller only: whether this panic gets the dialog.
+fn claim_dialog() -> bool {
+    !DIALOG_SHOWN.swap(true, Ordering::SeqCst)
+}
+
+/// `thread '<name>' panicked at <file>:<line>: <message>`, like the default hook.
+pub fn panic_message(thread: &str, payload: &(dyn Any + Send), location: Option<&Location<'_>>) -> String {
+    let text = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+    match location {
+        Some(location) => format!("thread '{thread}' panicked at {}:{}: {text}", location.file(), location.line()),
+        None => format!("thread '{thread}' panicked: {text}"),
+    }
+}
+
+/// The message box text: the panic, and where to find the details.
+fn dialog_text(message: &str, log_file: Option<&std::path::Path>) -> String {
+    let details = match log_file {
+        Some(path) => format!("The backtrace is in the log file:\n{}", path.display()),
+        None => "Set [logging] file = true in the config to keep the log in a file.".to_string(),
+    };
+    format!(
+        "DS4CC ran into an unexpected error and may stop working until it's restarted.\n\n\
+         {message}\n\n{details}\n\n(Ctrl+C copies this message.)"
+    )
+}
+
+#[cfg(windows)]
+fn show_crash_dialog(text: &str) {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{MessageBoxW, MB_ICONERROR, MB_OK};
+
+    let text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let caption: Vec<u16> = "DS4CC crashed".encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        MessageBoxW(std::ptr::null_mut(), text.as_ptr(), caption.as_ptr(), MB_OK | MB_ICONERROR);
+    }
+}
+
+#[cfg(not(windows))]
+fn show_crash_dialog(_text: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_payload_is_formatted() {
+        let location = Location::caller();
+        let at = format!("{}:{}", location.file(), location.line());
+
+        let payload: Box<dyn Any + Send> = Box::new("boom");
+        assert_eq!(panic_message("tray", &*payload, Some(location)), format!("thread 'tray' panicked at {at}: boom"));
+
+        let payload: Box<dyn Any + Send> = Box::new(format!("index {} out of range", 7));
+        assert_eq!(panic_message("hid", &*payload, None), "thread 'hid' panicked: index 7 out of range");
+
+        let payload: Box<dyn Any + Send> = Box::new(42u32);
+        assert_eq!(panic_message("main", &*payload, None), "thread 'main' panicked: Box<dyn Any>");
+
+        let text = dialog_text("thread 'tray' panicked: boom", Some(std::path::Path::new("C:\\ds4cc.log")));
+        assert!(text.contains("thread 'tray' panicked: boom\n\nThe backtrace is in the log file:\nC:\\ds4cc.log"));
+    }
+
+    #[test]
+    fn only_the_first_panic_gets_a_dialog() {
+        assert!(claim_dialog());
+        assert!(!claim_dialog());
+        assert!(!claim_dialog());
+    }
+}
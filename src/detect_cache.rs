@@ -0,0 +1,137 @@
+/// Cache of the tmux / OpenCode / Windows Terminal auto-detection results.
+///
+/// Detection shells out to WSL several times per launch. The parsed results
+/// are saved as JSON to `%APPDATA%\ds4cc\detect_cache.json`, keyed by a hash
+/// of the source files' modification times (`~/.tmux.conf`,
+/// `~/.config/tmux/tmux.conf` and `~/.config/opencode/opencode.json` in WSL,
+/// Windows Terminal's settings.json), the running tmux server's start time,
+/// and the config settings that change what is detected.
+/// A matching key reuses the cached results; anything else detects afresh.
+///
+/// "Re-detect bindings" in the tray menu deletes the cache and restarts.
+
+use crate::opencode_detect::OpenCodeDetected;
+use crate::tmux_detect::TmuxDetected;
+use crate::wsl::run_wsl;
+use crate::wt_detect::WtDetected;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// WSL files whose modification times key the cache.
+const WSL_SOURCES: [&str; 3] = ["~/.tmux.conf", "~/.config/tmux/tmux.conf", "~/.config/opencode/opencode.json"];
+
+/// Prints the tmux server's start time. Bindings are read from the running
+/// server, so a restarted one (or none) must not reuse the cache.
+const TMUX_START_TIME: &str = "tmux display -p '#{start_time}'";
+
+/// Cached detection results. A detector that found nothing (None) is run
+/// again next time rather than trusted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DetectCache {
+    /// Hash of the source mtimes and settings the results were detected with.
+    key: String,
+    pub tmux: Option<TmuxDetected>,
+    pub opencode: Option<OpenCodeDetected>,
+    pub wt: Option<WtDetected>,
+}
+
+impl DetectCache {
+    pub fn new(tmux: Option<TmuxDetected>, opencode: Option<OpenCodeDetected>, wt: Option<WtDetected>) -> Self {
+        Self { key: String::new(), tmux, opencode, wt }
+    }
+}
+
+/// `%APPDATA%\ds4cc\detect_cache.json`; None without `APPDATA` (no caching).
+pub fn cache_path() -> Option<PathBuf> {
+    let appdata = std::env::var("APPDATA").ok()?;
+    Some(PathBuf::from(format!("{appdata}\\ds4cc\\detect_cache.json")))
+}
+
+/// Cache key over the source files' mtimes (None = missing) and `settings`,
+/// the config values that change what detection returns.
+pub fn cache_key(mtimes: &[Option<u64>], settings: &str) -> String {
+    let mut input = String::new();
+    for mtime in mtimes {
+        match mtime {
+            Some(secs) => input.push_str(&secs.to_string()),
+            None => input.push('-'),
+        }
+        input.push('\n');
+    }
+    input.push_str(settings);
+    crate::sha256::to_hex(&crate::sha256::digest(input.as_bytes()))
+}
+
+/// Modification times of [`WSL_SOURCES`], the tmux server's start time and
+/// the modification time of Windows Terminal's settings.json, with a single
+/// WSL call.
+pub fn source_mtimes() -> Vec<Option<u64>> {
+    let mut script: String = WSL_SOURCES
+        .iter()
+        .map(|path| format!("stat -c %Y {path} 2>/dev/null || echo -; "))
+        .collect();
+    script.push_str(&format!("{TMUX_START_TIME} 2>/dev/null || echo -"));
+    let output = run_wsl(&script).unwrap_or_default();
+    let mut lines = output.lines();
+    let mut mtimes: Vec<Option<u64>> =
+        (0..=WSL_SOURCES.len()).map(|_| lines.next().and_then(|l| l.trim().parse().ok())).collect();
+    mtimes.push(crate::wt_detect::settings_mtime());
+    mtimes
+}
+
+/// Read the cache at `path`. None if it's missing, unreadable, or was
+/// saved under a different `key`.
+pub fn load(path: &Path, key: &str) -> Option<DetectCache> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let cache: DetectCache = serde_json::from_str(&contents).ok()?;
+    if cache.key != key {
+        log::info!("Detection cache is stale — detecting again");
+        return None;
+    }
+    Some(cache)
+}
+
+/// Save `cache` under `key`, creating the directory if needed.
+pub fn save(path: &Path, key: &str, mut cache: DetectCache) -> std::io::Result<()> {
+    cache.key = key.to_string();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string(&cache).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+/// Delete the cache so the next launch detects afresh.
+pub fn clear() {
+    if let Some(path) = cache_path() {
+        match std::fs::remove_file(&path) {
+            Ok(()) => log::info!("Detection cache cleared"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Failed to delete {}: {e}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_reused_only_with_matching_mtimes() {
+        let dir = std::env::temp_dir().join("ds4cc_detect_cache_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("detect_cache.json");
+
+        let key = cache_key(&[Some(100), None, Some(300)], "tmux.key_table=");
+        save(&path, &key, DetectCache::default()).unwrap();
+        assert!(load(&path, &key).is_some());
+
+        // An edited file, a file that appeared, or a changed setting → stale
+        assert!(load(&path, &cache_key(&[Some(101), None, Some(300)], "tmux.key_table=")).is_none());
+        assert!(load(&path, &cache_key(&[Some(100), Some(5), Some(300)], "tmux.key_table=")).is_none());
+        assert!(load(&path, &cache_key(&[Some(100), None, Some(300)], "tmux.key_table=apps")).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(load(&path, &key).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+/// Event-driven wakeups for the state poller.
+///
+/// A background thread blocks on `ReadDirectoryChangesW` for `state_dir` and
+/// calls back whenever a file whose name starts with the watched prefix is
+/// created, written, renamed or deleted. The poller uses this to rescan at
+/// once instead of waiting for its next tick; the tick keeps running either
+/// way (it drives the time-based transitions), so a missing watch only costs
+/// latency.
+
+use std::path::Path;
+
+/// Start watching `dir` for changes to files named `prefix*`, calling
+/// `on_change` (from the watcher thread) for each batch that touches one.
+/// Errors if the directory can't be opened for watching.
+#[cfg(windows)]
+pub fn spawn(
+    dir: &Path,
+    prefix: &'static str,
+    on_change: impl Fn() + Send + 'static,
+) -> std::io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        CreateFileW, ReadDirectoryChangesW, FILE_FLAG_BACKUP_SEMANTICS, FILE_LIST_DIRECTORY,
+        FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_SHARE_DELETE,
+        FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+
+    let path_w: Vec<u16> = dir.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let handle = unsafe {
+        CreateFileW(
+            path_w.as_ptr(),
+            FILE_LIST_DIRECTORY,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            std::ptr::null(),
+            OPEN_EXISTING,
+            FILE_FLAG_BACKUP_SEMANTICS,
+            std::ptr::null_mut(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(std::io::Error::last_os_error());
+    }
+    // Raw handles aren't Send; the thread takes sole ownership of this one
+    let handle_addr = handle as usize;
+
+    std::thread::spawn(move || {
+        let handle = handle_addr as windows_sys::Win32::Foundation::HANDLE;
+        // FILE_NOTIFY_INFORMATION records are DWORD-aligned
+        let mut buf = vec![0u32; 16 * 1024];
+        loop {
+            let mut bytes = 0u32;
+            let ok = unsafe {
+                ReadDirectoryChangesW(
+                    handle,
+                    buf.as_mut_ptr().cast(),
+                    (buf.len() * 4) as u32,
+                    0,
+                    FILE_NOTIFY_CHANGE_FILE_NAME | FILE_NOTIFY_CHANGE_LAST_WRITE,
+                    &mut bytes,
+                    std::ptr::null_mut(),
+                    None,
+                )
+            };
+            if ok == 0 {
+                log::warn!(
+                    "State dir watch stopped ({}); falling back to polling",
+                    std::io::Error::last_os_error()
+                );
+                break;
+            }
+            // Zero bytes means the buffer overflowed and the batch was dropped:
+            // wake anyway so nothing is missed.
+            let bytes: &[u8] = unsafe {
+                std::slice::from_raw_parts(buf.as_ptr().cast(), (bytes as usize).min(buf.len() * 4))
+            };
+            if bytes.is_empty() || changed_names(bytes).iter().any(|n| n.starts_with(prefix)) {
+                on_change();
+            }
+        }
+        unsafe { CloseHandle(handle) };
+    });
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn spawn(
+    _dir: &Path,
+    _prefix: &'static str,
+    _on_change: impl Fn() + Send + 'static,
+) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "directory watch needs Windows"))
+}
+
+/// File names in a `ReadDirectoryChangesW` result buffer (a chain of
+/// FILE_NOTIFY_INFORMATION records). Stops at the first malformed record.
+#[cfg(any(windows, test))]
+fn changed_names(buf: &[u8]) -> Vec<String> {
+    const HEADER: usize = 12;
+    let u32_at = |at: usize| buf.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    let mut names = Vec::new();
+    let mut offset = 0;
+    while let (Some(next), Some(name_len)) = (u32_at(offset), u32_at(offset + 8)) {
+        let start = offset + HEADER;
+        let Some(name) = buf.get(start..start + name_len as usize) else {
+            break;
+        };
+        let units: Vec<u16> = name.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        names.push(String::from_utf16_lossy(&units));
+        if next == 0 {
+            break;
+        }
+        offset += next as usize;
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a FILE_NOTIFY_INFORMATION chain for `names`.
+    fn notify_buffer(names: &[&str]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for (i, name) in names.iter().enumerate() {
+            let name_bytes: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+            let len = (12 + name_bytes.len()).div_ceil(4) * 4;
+            let next = if i + 1 == names.len() { 0 } else { len as u32 };
+            let start = buf.len();
+            buf.extend_from_slice(&next.to_le_bytes());
+            buf.extend_from_slice(&3u32.to_le_bytes()); // FILE_ACTION_MODIFIED
+            buf.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&name_bytes);
+            buf.resize(start + len, 0);
+        }
+        buf
+    }
+
+    #[test]
+    fn parses_notify_record_chain() {
+        let buf = notify_buffer(&["ds4cc_agent_abc", "other.txt", "ds4cc_agent_é"]);
+        assert_eq!(changed_names(&buf), vec!["ds4cc_agent_abc", "other.txt", "ds4cc_agent_é"]);
+        assert!(changed_names(&[]).is_empty());
+        // Truncated name → stop, keep what parsed so far
+        let mut truncated = notify_buffer(&["ds4cc_agent_abc", "ds4cc_agent_def"]);
+        truncated.truncate(truncated.len() - 6);
+        assert_eq!(changed_names(&truncated), vec!["ds4cc_agent_abc"]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn writing_agent_file_fires_watcher() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let dir = std::env::temp_dir().join(format!("ds4cc_dirwatch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (tx, rx) = mpsc::channel();
+        spawn(&dir, "ds4cc_agent_", move || {
+            let _ = tx.send(());
+        })
+        .unwrap();
+
+        // Unrelated files don't wake the poller
+        std::fs::write(dir.join("unrelated.txt"), "x").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+
+        std::fs::write(dir.join("ds4cc_agent_test"), "working").unwrap();
+        assert!(rx.recv_timeout(Duration::from_secs(2)).is_ok());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
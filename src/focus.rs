@@ -0,0 +1,100 @@
+/// Foreground-app detection for input passthrough.
+///
+/// When the focused window belongs to one of `[focus] passthrough_apps`, a shared
+/// flag is raised and the mapper stops emitting keyboard/mouse actions, so games
+/// and video players receive the controller untouched. Lightbar and rumble are
+/// unaffected — only input mapping is suspended.
+
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+
+/// How often the watcher thread re-checks the foreground window.
+const FOCUS_POLL_MS: u64 = 250;
+
+/// Executable file name of the foreground window's process (e.g. "eldenring.exe").
+/// Returns None if there is no foreground window or the process can't be queried.
+#[cfg(windows)]
+pub fn foreground_exe() -> Option<String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return None;
+        }
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        if pid == 0 {
+            return None;
+        }
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return None;
+        }
+        let mut buf = [0u16; 260];
+        let mut len = buf.len() as u32;
+        let ok = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buf.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        if ok == 0 {
+            return None;
+        }
+        let path = String::from_utf16_lossy(&buf[..len as usize]);
+        path.rsplit('\\').next().map(|s| s.to_string())
+    }
+}
+
+#[cfg(not(windows))]
+pub fn foreground_exe() -> Option<String> {
+    None
+}
+
+/// Case-insensitive match of an executable name against the passthrough list.
+/// Entries may omit the ".exe" suffix ("vlc" matches "vlc.exe").
+pub fn matches_passthrough(exe: &str, apps: &[String]) -> bool {
+    let exe = exe.to_ascii_lowercase();
+    let stem = exe.strip_suffix(".exe").unwrap_or(&exe);
+    apps.iter().any(|app| {
+        let app = app.trim().to_ascii_lowercase();
+        let app = app.strip_suffix(".exe").unwrap_or(&app);
+        !app.is_empty() && app == stem
+    })
+}
+
+/// Spawn a background thread that keeps `active` in sync with whether the
+/// foreground app is in `apps`. No thread is spawned for an empty list.
+pub fn spawn_watcher(apps: Vec<String>, active: Arc<AtomicBool>) {
+    if apps.is_empty() {
+        return;
+    }
+    let _ = std::thread::Builder::new()
+        .name("focus-watch".into())
+        .spawn(move || loop {
+            let matched = foreground_exe().is_some_and(|exe| matches_passthrough(&exe, &apps));
+            if active.swap(matched, Ordering::Relaxed) != matched {
+                if matched {
+                    log::info!("Passthrough app focused — controller input mapping paused");
+                } else {
+                    log::info!("Passthrough app lost focus — controller input mapping resumed");
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(FOCUS_POLL_MS));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passthrough_match_ignores_case_and_suffix() {
+        let apps = vec!["EldenRing.exe".to_string(), "vlc".to_string()];
+        assert!(matches_passthrough("eldenring.exe", &apps));
+        assert!(matches_passthrough("VLC.EXE", &apps));
+        assert!(!matches_passthrough("WindowsTerminal.exe", &apps));
+        assert!(!matches_passthrough("vlc.exe", &[]));
+    }
+}
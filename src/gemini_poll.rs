@@ -0,0 +1,373 @@
+/// Native Gemini CLI session poller.
+///
+/// Gemini CLI records each chat as one JSON document,
+/// `~/.gemini/tmp/<project>/chats/session-*.json`, that it rewrites in place as
+/// the conversation grows (`{"sessionId": …, "messages": [ … ]}`). The poller
+/// reads them via `\\wsl.localhost\` UNC paths, re-reads a document whenever
+/// its modification time or size changes, diffs its `messages` array against
+/// the count seen last time, and writes `ds4cc_agent_gemini_<session>` state
+/// files to `state_dir`.
+///
+/// Message mapping:
+/// - `"type": "user"` → working
+/// - `"type": "gemini"` with tool calls → working, or error if one of them
+///   has `"status": "error"`; `"type": "error"` → error
+/// - `"type": "gemini"` without tool calls (the final answer) → done, or idle
+///   if the turn was shorter than `done_threshold_s`
+///
+/// Gemini attaches tool calls to its latest message after recording the
+/// text, so the last message is re-checked when the document changes, and a
+/// final answer only counts once the document has stayed unchanged for a poll.
+///
+/// Skips silently if WSL is unavailable or Gemini CLI is not installed.
+
+use crate::wsl::run_wsl;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::time::{interval, Duration};
+
+/// Resolve the Windows UNC path to the Gemini CLI sessions directory via WSL.
+///
+/// Returns `None` if WSL is unavailable or Gemini CLI is not installed.
+pub fn resolve_sessions_dir() -> Option<PathBuf> {
+    let output = run_wsl("test -d ~/.gemini/tmp && wslpath -w ~/.gemini/tmp")?;
+    let path_str = output.trim();
+    if path_str.is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(path_str);
+    if path.exists() {
+        log::info!("Gemini sessions dir: {}", path.display());
+        Some(path)
+    } else {
+        log::debug!("Gemini sessions UNC path not accessible: {}", path.display());
+        None
+    }
+}
+
+/// Run the Gemini poller loop. Scans for session documents, reads new
+/// messages, and writes state files to `state_dir`.
+pub async fn run(sessions_dir: PathBuf, state_dir: PathBuf, done_threshold_s: u64, poll_ms: u64) {
+    let mut poller = GeminiPoller::new(sessions_dir.clone(), state_dir.clone(), done_threshold_s);
+    let mut ticker = interval(Duration::from_millis(poll_ms));
+
+    loop {
+        ticker.tick().await;
+        // spawn_blocking because file I/O on UNC paths can block
+        let mut poller_moved = poller;
+        poller_moved = tokio::task::spawn_blocking(move || {
+            poller_moved.poll();
+            poller_moved
+        })
+        .await
+        .unwrap_or_else(|_| {
+            log::error!("Gemini poller task panicked, resetting state");
+            GeminiPoller::new(sessions_dir.clone(), state_dir.clone(), done_threshold_s)
+        });
+        poller = poller_moved;
+    }
+}
+
+/// What the poller last saw of one session document.
+struct SessionDoc {
+    modified: SystemTime,
+    len: u64,
+    /// Length of its `messages` array.
+    messages: usize,
+}
+
+struct GeminiPoller {
+    sessions_dir: PathBuf,
+    state_dir: PathBuf,
+    done_threshold_s: u64,
+
+    /// Session documents seen so far.
+    docs: HashMap<PathBuf, SessionDoc>,
+    /// Documents whose last message is a final answer, written as done/idle
+    /// once the document settles.
+    answered: HashSet<PathBuf>,
+    /// When each session entered "working" state (for done-threshold logic).
+    working_since: HashMap<String, SystemTime>,
+    /// Documents found during the first poll are old sessions: their messages
+    /// are skipped. Documents found later are read from the first message.
+    initial_scan_done: bool,
+}
+
+impl GeminiPoller {
+    fn new(sessions_dir: PathBuf, state_dir: PathBuf, done_threshold_s: u64) -> Self {
+        Self {
+            sessions_dir,
+            state_dir,
+            done_threshold_s,
+            docs: HashMap::new(),
+            answered: HashSet::new(),
+            working_since: HashMap::new(),
+            initial_scan_done: false,
+        }
+    }
+
+    /// One poll cycle: scan for session documents and process the changed ones.
+    fn poll(&mut self) {
+        let mut files = Vec::new();
+        if collect_session_files(&self.sessions_dir, &mut files).is_err() {
+            return; // sessions dir not accessible (WSL may be down)
+        }
+
+        // Forget deleted sessions
+        self.docs.retain(|path, _| files.contains(path));
+        self.answered.retain(|path| files.contains(path));
+
+        for file_path in files {
+            self.poll_file(&file_path);
+        }
+        self.initial_scan_done = true;
+    }
+
+    fn poll_file(&mut self, file_path: &Path) {
+        let Ok(meta) = std::fs::metadata(file_path) else { return };
+        let (modified, len) = (meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), meta.len());
+
+        let known = self.docs.get(file_path);
+        if known.is_some_and(|doc| doc.modified == modified && doc.len == len) {
+            // Unchanged since the last poll: a final answer has settled
+            if self.answered.remove(file_path) {
+                let session_id = session_id(file_path);
+                let state = self.compute_done_state(&session_id);
+                self.write_state(&session_id, state);
+            }
+            return;
+        }
+
+        // Unparseable mid-rewrite: try again next poll
+        let Some(messages) = read_messages(file_path) else { return };
+        let seen = match known {
+            Some(doc) => doc.messages,
+            // Old session at startup: don't replay it
+            None if !self.initial_scan_done => messages.len(),
+            None => 0,
+        };
+        let known = known.is_some();
+        self.docs.insert(file_path.to_path_buf(), SessionDoc { modified, len, messages: messages.len() });
+
+        if messages.len() < seen {
+            // History rewritten shorter (e.g. a resumed or compressed chat): start over from here
+            log::debug!("Gemini session {} shrank to {} messages", file_path.display(), messages.len());
+            return;
+        }
+
+        let session_id = session_id(file_path);
+        // The previous last message may have been updated in place since (tool
+        // calls attached, their statuses changing)
+        if known
+            && let Some(previous) = seen.checked_sub(1).map(|i| &messages[i])
+            && message_type(previous) == Some("gemini")
+        {
+            self.handle_message(file_path, &session_id, previous);
+        }
+        for message in &messages[seen..] {
+            self.handle_message(file_path, &session_id, message);
+        }
+    }
+
+    /// Map a single message to a state file write.
+    fn handle_message(&mut self, file_path: &Path, session_id: &str, message: &serde_json::Value) {
+        match message_type(message) {
+            Some("user") => {
+                self.answered.remove(file_path);
+                self.working_since.insert(session_id.to_string(), SystemTime::now());
+                self.write_state(session_id, "working");
+            }
+            Some("error") => {
+                self.answered.remove(file_path);
+                self.write_state(session_id, "error");
+            }
+            Some("gemini") => {
+                let calls = message
+                    .get("toolCalls")
+                    .and_then(|v| v.as_array())
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                if calls.is_empty() {
+                    // No tool calls (yet): the model's final answer for this turn
+                    self.answered.insert(file_path.to_path_buf());
+                    return;
+                }
+                self.answered.remove(file_path);
+                if calls.iter().any(|c| c.get("status").and_then(|v| v.as_str()) == Some("error")) {
+                    log::debug!("Gemini tool call failed → error");
+                    self.write_state(session_id, "error");
+                } else {
+                    self.write_state(session_id, "working");
+                }
+            }
+            // "info" / "warning" notices don't change the state
+            _ => {}
+        }
+    }
+
+    /// "done" if the turn was "working" for at least `done_threshold_s`, else "idle".
+    fn compute_done_state(&self, session_id: &str) -> &'static str {
+        let long_enough = self
+            .working_since
+            .get(session_id)
+            .and_then(|start| start.elapsed().ok())
+            .is_some_and(|elapsed| elapsed.as_secs() >= self.done_threshold_s);
+        if long_enough { "done" } else { "idle" }
+    }
+
+    fn write_state(&self, session_id: &str, state: &str) {
+        let path = self.state_dir.join(format!("ds4cc_agent_{session_id}"));
+        if let Err(e) = std::fs::write(&path, state) {
+            log::debug!("Failed to write state file {}: {e}", path.display());
+        }
+    }
+}
+
+/// Recursively collect `chats/*.json` session documents under `dir`.
+fn collect_session_files(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            let _ = collect_session_files(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "json")
+            && path.parent().and_then(Path::file_name).is_some_and(|name| name == "chats")
+        {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The `messages` array of a session document. None while it can't be parsed.
+fn read_messages(path: &Path) -> Option<Vec<serde_json::Value>> {
+    let text = std::fs::read_to_string(path).ok()?;
+    let mut doc: serde_json::Value = serde_json::from_str(&text).ok()?;
+    match doc.get_mut("messages")?.take() {
+        serde_json::Value::Array(messages) => Some(messages),
+        _ => None,
+    }
+}
+
+fn message_type(message: &serde_json::Value) -> Option<&str> {
+    message.get("type").and_then(|v| v.as_str())
+}
+
+/// Agent id for a session document: `gemini_` + the file stem.
+fn session_id(file_path: &Path) -> String {
+    let stem = file_path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    format!("gemini_{stem}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rewrite a session document the way Gemini CLI does, with `messages`.
+    fn write_session(path: &Path, messages: &[&str]) {
+        let doc = format!(
+            r#"{{"sessionId":"abc","projectHash":"f00d","startTime":"2025-09-01T10:00:00.000Z","lastUpdated":"2025-09-01T10:00:05.000Z","messages":[{}]}}"#,
+            messages.join(",")
+        );
+        std::fs::write(path, doc).unwrap();
+    }
+
+    fn read_state(state_dir: &Path, id: &str) -> String {
+        std::fs::read_to_string(state_dir.join(format!("ds4cc_agent_{id}"))).unwrap()
+    }
+
+    const USER: &str = r#"{"id":"m1","timestamp":"2025-09-01T10:00:00.000Z","type":"user","content":"fix the bug"}"#;
+    const ANSWER_TEXT: &str = r#"{"id":"m2","timestamp":"2025-09-01T10:00:02.000Z","type":"gemini","content":"Running the tests.","thoughts":[],"tokens":{"input":900,"output":12,"total":912},"model":"gemini-2.5-pro"}"#;
+    const ANSWER_RUNNING: &str = r#"{"id":"m2","timestamp":"2025-09-01T10:00:02.000Z","type":"gemini","content":"Running the tests.","toolCalls":[{"id":"run_shell_command-1","name":"run_shell_command","args":{"command":"cargo test"},"status":"executing","timestamp":"2025-09-01T10:00:02.100Z"}],"model":"gemini-2.5-pro"}"#;
+    const ANSWER_FAILED: &str = r#"{"id":"m2","timestamp":"2025-09-01T10:00:02.000Z","type":"gemini","content":"Running the tests.","toolCalls":[{"id":"run_shell_command-1","name":"run_shell_command","args":{"command":"cargo test"},"status":"error","timestamp":"2025-09-01T10:00:02.100Z"}],"model":"gemini-2.5-pro"}"#;
+    const FINAL: &str = r#"{"id":"m3","timestamp":"2025-09-01T10:00:04.000Z","type":"gemini","content":"Fixed.","model":"gemini-2.5-pro"}"#;
+
+    #[test]
+    fn test_poller_full_lifecycle() {
+        let test_dir = std::env::temp_dir().join("ds4cc_gemini_poll_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let chats_dir = test_dir.join("tmp").join("project-hash").join("chats");
+        let state_dir = test_dir.join("state");
+        std::fs::create_dir_all(&chats_dir).unwrap();
+        std::fs::create_dir_all(&state_dir).unwrap();
+
+        // A session that existed before the daemon started is not replayed
+        write_session(&chats_dir.join("session-old.json"), &[USER]);
+        // Other JSON under the project dir isn't a session
+        std::fs::write(test_dir.join("tmp").join("project-hash").join("logs.json"), "[]").unwrap();
+        let mut poller = GeminiPoller::new(test_dir.join("tmp"), state_dir.clone(), 600);
+        poller.poll();
+        assert!(!state_dir.join("ds4cc_agent_gemini_session-old").exists());
+        assert_eq!(poller.docs.len(), 1);
+
+        // New session: user message → working
+        let file = chats_dir.join("session-abc.json");
+        write_session(&file, &[USER]);
+        poller.poll();
+        let id = "gemini_session-abc";
+        assert_eq!(read_state(&state_dir, id), "working");
+
+        // The model's text arrives before its tool call is attached: not an answer yet
+        write_session(&file, &[USER, ANSWER_TEXT]);
+        poller.poll();
+        assert_eq!(read_state(&state_dir, id), "working");
+        // The same message gains a running tool call in place
+        write_session(&file, &[USER, ANSWER_RUNNING]);
+        poller.poll();
+        poller.poll();
+        assert_eq!(read_state(&state_dir, id), "working");
+
+        // Final answer after a quick turn → idle (below done threshold), once it settles
+        write_session(&file, &[USER, ANSWER_RUNNING, FINAL]);
+        poller.poll();
+        assert_eq!(read_state(&state_dir, id), "working");
+        poller.poll();
+        assert_eq!(read_state(&state_dir, id), "idle");
+
+        // The tool call recorded as failed → error
+        let file = chats_dir.join("session-err.json");
+        write_session(&file, &[USER, ANSWER_RUNNING]);
+        poller.poll();
+        assert_eq!(read_state(&state_dir, "gemini_session-err"), "working");
+        write_session(&file, &[USER, ANSWER_FAILED]);
+        poller.poll();
+        assert_eq!(read_state(&state_dir, "gemini_session-err"), "error");
+
+        // A half-written document is retried on the next poll
+        std::fs::write(&file, r#"{"sessionId":"abc","messages":[{"type":"us"#).unwrap();
+        poller.poll();
+        assert_eq!(read_state(&state_dir, "gemini_session-err"), "error");
+        write_session(&file, &[USER, ANSWER_FAILED, USER]);
+        poller.poll();
+        assert_eq!(read_state(&state_dir, "gemini_session-err"), "working");
+
+        // A deleted session is forgotten
+        std::fs::remove_file(&file).unwrap();
+        poller.poll();
+        assert!(!poller.docs.contains_key(&file));
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn long_turn_ends_done() {
+        let test_dir = std::env::temp_dir().join("ds4cc_gemini_done_test");
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let chats_dir = test_dir.join("tmp").join("chats");
+        let state_dir = test_dir.join("state");
+        std::fs::create_dir_all(&chats_dir).unwrap();
+        std::fs::create_dir_all(&state_dir).unwrap();
+
+        // Zero threshold: every completed turn counts as done
+        let mut poller = GeminiPoller::new(test_dir.join("tmp"), state_dir.clone(), 0);
+        poller.poll();
+        let file = chats_dir.join("session-s.json");
+        write_session(&file, &[USER, FINAL]);
+        poller.poll();
+        poller.poll();
+        assert_eq!(read_state(&state_dir, "gemini_session-s"), "done");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
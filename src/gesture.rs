@@ -0,0 +1,112 @@
+/// Motion gestures detected from the DualSense accelerometer.
+///
+/// Shake: the acceleration magnitude spikes above a threshold (a multiple of
+/// 1 g) several times within a short window. Used to acknowledge a pending
+/// Done state without touching the keyboard (`[gestures] shake_to_ack`).
+
+use std::time::{Duration, Instant};
+
+/// Approximate accelerometer reading for 1 g (controller at rest).
+pub const ACCEL_ONE_G: f32 = 8192.0;
+
+/// Fraction of the threshold the magnitude must fall below before the next
+/// spike counts, so one swing hovering around the threshold counts once.
+const REARM_RATIO: f32 = 0.75;
+
+/// Quiet period after a detected shake, so one long shake fires once.
+const SHAKE_COOLDOWN: Duration = Duration::from_millis(1500);
+
+/// Counts accel-magnitude spikes; reports a shake when enough land in the window.
+pub struct ShakeDetector {
+    threshold: f32,
+    peaks_needed: usize,
+    window: Duration,
+    /// Times of recent spikes still inside the window.
+    peaks: Vec<Instant>,
+    /// True while the magnitude is above the re-arm level (spike in progress).
+    in_spike: bool,
+    cooldown_until: Option<Instant>,
+}
+
+impl ShakeDetector {
+    /// `threshold_g`: spike level in g; `peaks`: spikes required within `window_ms`.
+    pub fn new(threshold_g: f32, peaks: usize, window_ms: u64) -> Self {
+        Self {
+            threshold: threshold_g.max(1.1) * ACCEL_ONE_G,
+            peaks_needed: peaks.max(1),
+            window: Duration::from_millis(window_ms),
+            peaks: Vec::new(),
+            in_spike: false,
+            cooldown_until: None,
+        }
+    }
+
+    /// Feed one accel magnitude sample. Returns true on the sample that
+    /// completes a shake.
+    pub fn update(&mut self, magnitude: f32, now: Instant) -> bool {
+        if self.in_spike {
+            if magnitude < self.threshold * REARM_RATIO {
+                self.in_spike = false;
+            }
+            return false;
+        }
+        if magnitude < self.threshold {
+            return false;
+        }
+        self.in_spike = true;
+        if self.cooldown_until.is_some_and(|t| now < t) {
+            return false;
+        }
+
+        self.peaks.retain(|&t| now.duration_since(t) <= self.window);
+        self.peaks.push(now);
+        if self.peaks.len() >= self.peaks_needed {
+            self.peaks.clear();
+            self.cooldown_until = Some(now + SHAKE_COOLDOWN);
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed (ms offset, magnitude in g) samples; return offsets where a shake fired.
+    fn run(detector: &mut ShakeDetector, samples: &[(u64, f32)]) -> Vec<u64> {
+        let t0 = Instant::now();
+        samples
+            .iter()
+            .filter(|(ms, g)| detector.update(g * ACCEL_ONE_G, t0 + Duration::from_millis(*ms)))
+            .map(|(ms, _)| *ms)
+            .collect()
+    }
+
+    #[test]
+    fn three_spikes_in_window_fire_once() {
+        let mut d = ShakeDetector::new(2.0, 3, 1000);
+        let samples = [
+            (0, 1.0), (50, 2.5), (100, 1.0), // spike 1
+            (150, 2.6), (200, 0.9),          // spike 2
+            (250, 3.0), (300, 1.0),          // spike 3 → shake
+            (350, 2.8), (400, 1.0),          // cooldown: ignored
+        ];
+        assert_eq!(run(&mut d, &samples), vec![250]);
+    }
+
+    #[test]
+    fn hovering_above_threshold_counts_once() {
+        let mut d = ShakeDetector::new(2.0, 3, 1000);
+        // Never drops below the re-arm level (1.5 g) → a single spike
+        let samples = [(0, 2.1), (10, 1.9), (20, 2.2), (30, 1.8), (40, 2.3)];
+        assert!(run(&mut d, &samples).is_empty());
+    }
+
+    #[test]
+    fn spikes_spread_beyond_window_do_not_fire() {
+        let mut d = ShakeDetector::new(2.0, 3, 500);
+        let samples = [(0, 2.5), (50, 1.0), (400, 2.5), (450, 1.0), (1000, 2.5), (1050, 1.0)];
+        assert!(run(&mut d, &samples).is_empty());
+    }
+}
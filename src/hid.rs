@@ -1,47 +1,52 @@
 /// HID device management: open controller, read input reports, write output reports.
 ///
 /// Key DS4Windows patterns replicated here:
-/// - Filter by VID/PID + usage page 0x01 / usage 0x05 (gamepad collection)
+/// - Filter by VID/PID + usage page 0x01 / usage 0x05 (gamepad collection;
+///   the Switch Pro enumerates as a joystick, usage 0x04)
 /// - Activate Bluetooth extended mode via feature report
 /// - Non-blocking read with timeout
-/// - Write errors are non-fatal (log and continue)
+/// - Write errors are non-fatal (log and continue); a run of them is counted
+///   so the output loop can treat a half-disconnected controller as gone
 
-use crate::controller::{self, ConnectionType, ControllerType, GAMEPAD_USAGE, GAMEPAD_USAGE_PAGE};
+use crate::controller::{self, ConnectionType, ControllerInfo, ControllerType};
+use crate::output;
 use hidapi::{HidApi, HidDevice};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 
-/// Information about a discovered controller.
-pub struct ControllerInfo {
-    pub controller_type: ControllerType,
-    pub connection_type: ConnectionType,
-    pub path: String,
-}
-
 /// Find all supported controllers, sorted with USB devices first.
-/// When a controller is connected via both USB and Bluetooth simultaneously,
-/// USB will always appear first — callers can `.next()` to pick the preferred one.
-pub fn find_all_controllers(api: &HidApi) -> Vec<ControllerInfo> {
+/// A controller connected via both USB and Bluetooth appears twice;
+/// `controller::select_devices` keeps its USB entry. USB entries have no HID
+/// serial, so theirs is the MAC from the pairing feature report; `macs`
+/// remembers those reads by path across scans.
+pub fn find_all_controllers(api: &HidApi, macs: &mut HashMap<String, Option<String>>) -> Vec<ControllerInfo> {
     let mut usb = Vec::new();
     let mut bt = Vec::new();
 
     for dev in api.device_list() {
-        if dev.usage_page() != GAMEPAD_USAGE_PAGE || dev.usage() != GAMEPAD_USAGE {
+        if !controller::is_gamepad_usage(dev.usage_page(), dev.usage()) {
             continue;
         }
 
         if let Some(ct) = controller::identify(dev.vendor_id(), dev.product_id()) {
             let path = dev.path().to_string_lossy().to_string();
             let conn = controller::detect_connection(&path);
-            log::info!(
+            log::debug!(
                 "Found {} ({}) at {}",
                 ct,
                 conn,
                 &path[..path.len().min(60)]
             );
+            let mut serial = dev.serial_number().unwrap_or_default().to_string();
+            if serial.is_empty() && conn == ConnectionType::Usb {
+                serial = macs.entry(path.clone()).or_insert_with(|| read_mac(api, dev, ct)).clone().unwrap_or_default();
+            }
             let info = ControllerInfo {
                 controller_type: ct,
                 connection_type: conn,
                 path,
+                serial,
             };
             match conn {
                 ConnectionType::Usb => usb.push(info),
@@ -50,20 +55,49 @@ pub fn find_all_controllers(api: &HidApi) -> Vec<ControllerInfo> {
         }
     }
 
+    macs.retain(|path, _| usb.iter().any(|c| &c.path == path));
     usb.extend(bt);
     usb
 }
 
-/// Quick check: is there a USB controller present?
-/// Used by the background USB scanner thread — avoids allocating a Vec.
-pub fn has_usb_controller(api: &HidApi) -> bool {
-    api.device_list().any(|dev| {
-        dev.usage_page() == GAMEPAD_USAGE_PAGE
-            && dev.usage() == GAMEPAD_USAGE
-            && controller::identify(dev.vendor_id(), dev.product_id()).is_some()
-            && controller::detect_connection(&dev.path().to_string_lossy())
-                == ConnectionType::Usb
-    })
+/// A USB controller's Bluetooth MAC, from its pairing feature report.
+fn read_mac(api: &HidApi, dev: &hidapi::DeviceInfo, ct: ControllerType) -> Option<String> {
+    let report_id = controller::pairing_report_id(ct)?;
+    let device = match dev.open_device(api) {
+        Ok(device) => device,
+        Err(e) => {
+            log::debug!("Can't open {ct} to read its MAC: {e}");
+            return None;
+        }
+    };
+    let mut buf = [0u8; 64];
+    buf[0] = report_id;
+    let mac = match device.get_feature_report(&mut buf) {
+        Ok(n) => controller::mac_from_pairing_report(&buf[..n]),
+        Err(e) => {
+            log::debug!("Failed to read feature report 0x{report_id:02X}: {e}");
+            None
+        }
+    };
+    log::debug!("{ct} over USB has MAC {}", mac.as_deref().unwrap_or("unknown"));
+    mac
+}
+
+/// `--list-controllers`: one line per HID collection (see
+/// [`controller::format_device_row`]).
+pub fn list_devices(api: &HidApi) -> Vec<String> {
+    api.device_list()
+        .map(|dev| {
+            controller::format_device_row((
+                dev.vendor_id(),
+                dev.product_id(),
+                dev.usage_page(),
+                dev.usage(),
+                dev.manufacturer_string().unwrap_or_default(),
+                dev.product_string().unwrap_or_default(),
+            ))
+        })
+        .collect()
 }
 
 /// Open the controller device.
@@ -78,6 +112,53 @@ pub fn open_device(api: &HidApi, info: &ControllerInfo) -> Result<HidDevice, hid
     Ok(device)
 }
 
+/// Retry schedule for a controller that is found but can't be opened.
+///
+/// Delays double from `initial_ms` up to `max_ms`. Failures are counted per
+/// device path; a different path or a successful open starts over.
+pub struct OpenBackoff {
+    initial_ms: u64,
+    max_ms: u64,
+    give_up_after: u32,
+    path: Option<String>,
+    failures: u32,
+}
+
+impl OpenBackoff {
+    pub fn new(initial_ms: u64, max_ms: u64, give_up_after: u32) -> Self {
+        let initial_ms = initial_ms.max(1);
+        Self { initial_ms, max_ms: max_ms.max(initial_ms), give_up_after, path: None, failures: 0 }
+    }
+
+    /// Record a failed open of `path`. Returns the delay before the next attempt.
+    pub fn record_failure(&mut self, path: &str) -> std::time::Duration {
+        if self.path.as_deref() != Some(path) {
+            self.path = Some(path.to_string());
+            self.failures = 0;
+        }
+        self.failures = self.failures.saturating_add(1);
+        let shift = (self.failures - 1).min(20);
+        let delay = self.initial_ms.saturating_mul(1 << shift).min(self.max_ms);
+        std::time::Duration::from_millis(delay)
+    }
+
+    /// True only on the failure that reaches the give-up threshold, so the
+    /// actionable warning is shown once rather than on every retry.
+    pub fn just_gave_up(&self) -> bool {
+        self.give_up_after > 0 && self.failures == self.give_up_after
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Forget the failure history (device opened or went away).
+    pub fn reset(&mut self) {
+        self.path = None;
+        self.failures = 0;
+    }
+}
+
 /// Activate Bluetooth extended mode by reading the appropriate feature report.
 /// DualSense: feature report 0x05
 /// DS4: feature report 0x02
@@ -100,16 +181,58 @@ pub fn activate_bt_extended_mode(
     }
 }
 
+/// Put a Switch Pro controller into full (0x30) input report mode.
+///
+/// Over USB it first needs the handshake that makes it talk HID over the
+/// cable instead of waiting for a console. Over Bluetooth it would otherwise
+/// keep sending simple (0x3F) reports, which lack the battery level.
+pub fn activate_switch_pro_full_mode(
+    device: &HidDevice,
+    conn: ConnectionType,
+) -> Result<(), hidapi::HidError> {
+    if conn == ConnectionType::Usb {
+        device.write(&[0x80, 0x02])?; // handshake
+        device.write(&[0x80, 0x04])?; // USB HID only, no timeout
+    }
+    // Subcommand 0x03: set input report mode → 0x30 (standard full mode)
+    device.write(&output::switch_pro_subcommand(0, 0x03, &[0x30]))?;
+    log::info!("Switch Pro full input mode requested");
+    Ok(())
+}
+
+/// Consecutive failed output writes; any successful write clears it.
+#[derive(Default)]
+pub struct WriteFailures(AtomicU32);
+
+impl WriteFailures {
+    /// Record a write's outcome. Returns the consecutive failure count.
+    pub fn record(&self, ok: bool) -> u32 {
+        if ok {
+            self.0.store(0, Ordering::Relaxed);
+            0
+        } else {
+            self.0.fetch_add(1, Ordering::Relaxed).saturating_add(1)
+        }
+    }
+
+    pub fn get(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
 /// Wrapper around HidDevice for thread-safe write access.
 /// Reads happen on the dedicated HID thread; writes can come from the lightbar/rumble tasks.
 pub struct HidHandle {
     device: Arc<Mutex<HidDevice>>,
+    /// Shared by every clone, so the count covers all writers.
+    write_failures: Arc<WriteFailures>,
 }
 
 impl HidHandle {
     pub fn new(device: HidDevice) -> Self {
         Self {
             device: Arc::new(Mutex::new(device)),
+            write_failures: Arc::default(),
         }
     }
 
@@ -117,15 +240,18 @@ impl HidHandle {
     pub fn clone_handle(&self) -> Self {
         Self {
             device: Arc::clone(&self.device),
+            write_failures: Arc::clone(&self.write_failures),
         }
     }
 
-    /// Read an input report.
+    /// Read an input report, waiting up to `timeout_ms` for one.
     /// Returns Ok(n) with bytes read (0 = no data available).
     /// Returns Err(()) if the device is disconnected.
-    pub fn read(&self, buf: &mut [u8]) -> Result<usize, ()> {
+    /// Writes wait on the device lock for the whole read, so keep `timeout_ms`
+    /// short (`[hid] read_timeout_ms` is capped at 10).
+    pub fn read(&self, buf: &mut [u8], timeout_ms: i32) -> Result<usize, ()> {
         let dev = self.device.lock().unwrap();
-        match dev.read_timeout(buf, 5) {
+        match dev.read_timeout(buf, timeout_ms) {
             Ok(n) => Ok(n),
             Err(e) => {
                 let msg = format!("{e}");
@@ -142,13 +268,20 @@ impl HidHandle {
     /// Write an output report. Errors are logged but not propagated (non-fatal).
     pub fn write(&self, report: &[u8]) -> bool {
         let dev = self.device.lock().unwrap();
-        match dev.write(report) {
+        let ok = match dev.write(report) {
             Ok(_) => true,
             Err(e) => {
                 log::debug!("HID write error (non-fatal): {e}");
                 false
             }
-        }
+        };
+        self.write_failures.record(ok);
+        ok
+    }
+
+    /// Writes that have failed in a row since the last successful one.
+    pub fn consecutive_write_failures(&self) -> u32 {
+        self.write_failures.get()
     }
 }
 
@@ -156,17 +289,30 @@ impl HidHandle {
 mod tests {
     use super::*;
 
+    #[test]
+    fn write_failures_count_until_a_write_succeeds() {
+        let failures = WriteFailures::default();
+        assert_eq!(failures.record(false), 1);
+        assert_eq!(failures.record(false), 2);
+        assert_eq!(failures.get(), 2);
+        assert_eq!(failures.record(true), 0);
+        assert_eq!(failures.get(), 0);
+        assert_eq!(failures.record(false), 1);
+    }
+
     #[test]
     fn usb_sorts_before_bt() {
         let bt = ControllerInfo {
             controller_type: ControllerType::DualSense,
             connection_type: ConnectionType::Bluetooth,
             path: "bt_path".into(),
+            serial: String::new(),
         };
         let usb = ControllerInfo {
             controller_type: ControllerType::DualSense,
             connection_type: ConnectionType::Usb,
             path: "usb_path".into(),
+            serial: String::new(),
         };
         // Simulate the two-vec ordering from find_all_controllers
         let mut usb_vec = vec![usb];
@@ -182,6 +328,7 @@ mod tests {
             controller_type: ControllerType::DualSense,
             connection_type: ConnectionType::Bluetooth,
             path: "bt_path".into(),
+            serial: String::new(),
         };
         let mut usb_vec: Vec<ControllerInfo> = Vec::new();
         let bt_vec = vec![bt];
@@ -189,4 +336,26 @@ mod tests {
         assert_eq!(usb_vec.len(), 1);
         assert_eq!(usb_vec[0].connection_type, ConnectionType::Bluetooth);
     }
+
+    #[test]
+    fn open_backoff_doubles_up_to_max() {
+        let mut b = OpenBackoff::new(500, 4000, 5);
+        let delays: Vec<u64> = (0..6).map(|_| b.record_failure("p").as_millis() as u64).collect();
+        assert_eq!(delays, vec![500, 1000, 2000, 4000, 4000, 4000]);
+
+        // A different device path starts a fresh schedule
+        assert_eq!(b.record_failure("q").as_millis(), 500);
+        b.reset();
+        assert_eq!(b.failures(), 0);
+    }
+
+    #[test]
+    fn open_backoff_gives_up_once_at_threshold() {
+        let mut b = OpenBackoff::new(500, 30_000, 3);
+        let hits: Vec<bool> = (0..5).map(|_| { b.record_failure("p"); b.just_gave_up() }).collect();
+        assert_eq!(hits, vec![false, false, true, false, false]);
+
+        let mut never = OpenBackoff::new(500, 30_000, 0);
+        assert!((0..10).all(|_| { never.record_failure("p"); !never.just_gave_up() }));
+    }
 }
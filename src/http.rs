@@ -0,0 +1,339 @@
+/// Local HTTP status endpoint for external dashboards (`[http] enabled`).
+///
+/// `GET /state` on `127.0.0.1:<port>` returns the aggregated agent state, the
+/// active profile, each agent's state, the weakest controller battery and the
+/// focused agent:
+/// `{"state":"working","profile":"default","agents":{"sess1":"working"},"battery":70,"focus":null}`
+/// (`battery` is null while unknown). `PUT /focus/<agent_id>` makes the
+/// lightbar follow that agent only (the id is percent-decoded) and
+/// `DELETE /focus` clears it; both answer with the state and, like every
+/// route, require a loopback `Host`. `GET /config` returns what every button resolved to, per
+/// scope (`always`, a profile, or `layer`), as the input controller's mapper
+/// sees it: `{"bindings":{"default":{"square":"Ctrl+Shift+1"},…}}`. Any other
+/// path is a 404.
+///
+/// A hand-rolled handler on std threads — the listener binds to loopback only
+/// and serves one request per connection. No CORS headers are sent, and a
+/// request whose `Host` isn't `127.0.0.1:<port>` or `localhost:<port>` gets a
+/// 403, so a web page can't reach the endpoint through DNS rebinding.
+
+use crate::input::BatteryStatus;
+use crate::mapper::{Profile, ResolvedBinding};
+use crate::state::{AgentState, AgentSummary};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// How long a client gets to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+/// Most a client may send (request line plus headers); the rest is ignored.
+const MAX_REQUEST_BYTES: u64 = 8192;
+
+/// Everything the endpoint reports, at one moment.
+pub struct Status {
+    pub summary: AgentSummary,
+    pub profile: Profile,
+    pub agents: HashMap<String, AgentState>,
+    pub battery: Option<BatteryStatus>,
+    pub focus: Option<String>,
+    /// Resolved button bindings (`/config`); empty until a controller connects.
+    pub bindings: Vec<ResolvedBinding>,
+}
+
+/// The channels the endpoint reads its [`Status`] from.
+#[derive(Clone)]
+pub struct StatusSources {
+    pub state_rx: watch::Receiver<AgentSummary>,
+    pub profile_rx: watch::Receiver<Profile>,
+    pub agents_rx: watch::Receiver<HashMap<String, AgentState>>,
+    pub battery_rx: watch::Receiver<Option<BatteryStatus>>,
+    pub bindings_rx: watch::Receiver<Vec<ResolvedBinding>>,
+    /// The focused agent, read for `/state` and set by `/focus`.
+    pub focus_tx: Arc<watch::Sender<Option<String>>>,
+}
+
+impl StatusSources {
+    fn snapshot(&self) -> Status {
+        Status {
+            summary: *self.state_rx.borrow(),
+            profile: *self.profile_rx.borrow(),
+            agents: self.agents_rx.borrow().clone(),
+            battery: *self.battery_rx.borrow(),
+            focus: self.focus_tx.borrow().clone(),
+            bindings: self.bindings_rx.borrow().clone(),
+        }
+    }
+}
+
+/// The `/state` JSON body. Agents are sorted by id.
+pub fn state_json(status: &Status) -> String {
+    let agents: BTreeMap<&str, String> =
+        status.agents.iter().map(|(id, state)| (id.as_str(), state.to_string())).collect();
+    serde_json::json!({
+        "state": status.summary.state.to_string(),
+        "profile": status.profile.to_string(),
+        "agents": agents,
+        "battery": status.battery.map(|b| b.percent),
+        "focus": status.focus,
+    })
+    .to_string()
+}
+
+/// The `/config` JSON body: `{"bindings":{<scope>:{<button>:<action>}}}`.
+pub fn config_json(bindings: &[ResolvedBinding]) -> String {
+    let mut scopes: BTreeMap<String, BTreeMap<&str, &str>> = BTreeMap::new();
+    for binding in bindings {
+        scopes.entry(binding.scope.to_string()).or_default().insert(&binding.button, &binding.action);
+    }
+    serde_json::json!({ "bindings": scopes }).to_string()
+}
+
+/// What a request asks for.
+#[derive(Debug, PartialEq)]
+enum Route {
+    State,
+    Config,
+    /// Follow one agent (Some) or all of them again (None).
+    Focus(Option<String>),
+    MethodNotAllowed,
+    NotFound,
+}
+
+/// Route a request by its first line (e.g. `GET /state HTTP/1.1`).
+fn route(request_line: &str) -> Route {
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+    match (method, path) {
+        ("GET", "/state") => Route::State,
+        ("GET", "/config") => Route::Config,
+        ("DELETE", "/focus") => Route::Focus(None),
+        ("PUT", path) if path.starts_with("/focus/") && path.len() > "/focus/".len() => {
+            match percent_decode(&path["/focus/".len()..]) {
+                Some(id) => Route::Focus(Some(id)),
+                None => Route::NotFound,
+            }
+        }
+        (_, "/state" | "/config" | "/focus") => Route::MethodNotAllowed,
+        _ => Route::NotFound,
+    }
+}
+
+/// Decode `%XX` escapes in a path segment. None if an escape is malformed or
+/// the result isn't UTF-8.
+fn percent_decode(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Whether a request's `Host` header names this server. Browsers send the
+/// page's own host name, so this turns away DNS-rebinding pages.
+fn host_allowed(host: Option<&str>, port: u16) -> bool {
+    host.is_some_and(|host| {
+        host == format!("127.0.0.1:{port}") || host.eq_ignore_ascii_case(&format!("localhost:{port}"))
+    })
+}
+
+/// Full HTTP response to a request whose first line is `request_line`.
+/// `status` is taken after any focus change the request made.
+pub fn respond(request_line: &str, status: &Status) -> String {
+    match route(request_line) {
+        Route::State | Route::Focus(_) => response("200 OK", "application/json", &state_json(status)),
+        Route::Config => response("200 OK", "application/json", &config_json(&status.bindings)),
+        Route::MethodNotAllowed => response("405 Method Not Allowed", "text/plain", "Method not allowed\n"),
+        Route::NotFound => response("404 Not Found", "text/plain", "Not found\n"),
+    }
+}
+
+fn response(code: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {code}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Answer one request: turn away foreign `Host` headers, apply any focus
+/// change, then respond with the current status.
+fn handle(request_line: &str, host: Option<&str>, port: u16, sources: &StatusSources) -> String {
+    if !host_allowed(host, port) {
+        return response("403 Forbidden", "text/plain", "Forbidden\n");
+    }
+    if let Route::Focus(focus) = route(request_line) {
+        sources.focus_tx.send_replace(focus);
+    }
+    respond(request_line, &sources.snapshot())
+}
+
+/// Listen on `127.0.0.1:port` and answer requests on background threads.
+pub fn spawn_server(port: u16, sources: StatusSources) -> std::io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    std::thread::Builder::new().name("http".into()).spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            let sources = sources.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = serve_client(stream, port, &sources) {
+                    log::debug!("HTTP client failed: {e}");
+                }
+            });
+        }
+    })?;
+    Ok(())
+}
+
+/// Read one request from `stream` and write the response.
+fn serve_client(stream: TcpStream, port: u16, sources: &StatusSources) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers so closing the socket doesn't reset the connection
+    let mut host = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("host")
+        {
+            host = Some(value.trim().to_string());
+        }
+        header.clear();
+    }
+    let response = handle(&request_line, host.as_deref(), port, sources);
+    (&stream).write_all(response.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapper::BindingScope;
+    use crate::state::StateCounts;
+
+    fn status() -> Status {
+        let agents: HashMap<_, _> = [
+            ("sess2".to_string(), AgentState::Done),
+            ("sess1".to_string(), AgentState::Working),
+        ]
+        .into_iter()
+        .collect();
+        Status {
+            summary: AgentSummary { state: AgentState::Working, counts: StateCounts::from_agents(&agents) },
+            profile: Profile::Default,
+            agents,
+            battery: Some(BatteryStatus { percent: 70, charging: false }),
+            focus: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn state_request_returns_json() {
+        let response = respond("GET /state HTTP/1.1\r\n", &status());
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        assert!(!head.contains("Access-Control-Allow-Origin"));
+        assert_eq!(
+            body,
+            r#"{"agents":{"sess1":"working","sess2":"done"},"battery":70,"focus":null,"profile":"default","state":"working"}"#
+        );
+    }
+
+    #[test]
+    fn unknown_battery_is_null_and_other_paths_are_404() {
+        let status = Status { battery: None, agents: HashMap::new(), ..status() };
+        let value: serde_json::Value = serde_json::from_str(&state_json(&status)).unwrap();
+        assert!(value["battery"].is_null());
+        assert_eq!(value["agents"], serde_json::json!({}));
+
+        assert!(respond("GET /state?x=1 HTTP/1.1", &status).starts_with("HTTP/1.1 200"));
+        assert!(respond("GET / HTTP/1.1", &status).starts_with("HTTP/1.1 404"));
+        assert!(respond("POST /state HTTP/1.1", &status).starts_with("HTTP/1.1 405"));
+        assert!(respond("", &status).starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn config_request_lists_bindings_per_scope() {
+        let binding = |scope, button: &str, action: &str| ResolvedBinding {
+            scope,
+            button: button.to_string(),
+            action: action.to_string(),
+        };
+        let status = Status {
+            bindings: vec![
+                binding(BindingScope::Always, "cross", "Enter"),
+                binding(BindingScope::Profile(Profile::Default), "square", "Ctrl+Shift+1"),
+                binding(BindingScope::Profile(Profile::Tmux), "square", "Ctrl+B, C"),
+            ],
+            ..status()
+        };
+        let response = respond("GET /config HTTP/1.1", &status);
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert_eq!(
+            body,
+            r#"{"bindings":{"always":{"cross":"Enter"},"default":{"square":"Ctrl+Shift+1"},"tmux":{"square":"Ctrl+B, C"}}}"#
+        );
+        assert!(respond("PUT /config HTTP/1.1", &status).starts_with("HTTP/1.1 405"));
+    }
+
+    #[test]
+    fn only_loopback_host_headers_are_allowed() {
+        assert!(host_allowed(Some("127.0.0.1:7878"), 7878));
+        assert!(host_allowed(Some("localhost:7878"), 7878));
+        assert!(host_allowed(Some("LOCALHOST:7878"), 7878));
+        assert!(!host_allowed(Some("127.0.0.1:7879"), 7878));
+        assert!(!host_allowed(Some("evil.example:7878"), 7878));
+        assert!(!host_allowed(Some("127.0.0.1"), 7878));
+        assert!(!host_allowed(None, 7878));
+    }
+
+    #[test]
+    fn focus_changes_need_a_loopback_host() {
+        let sources = StatusSources {
+            state_rx: watch::channel(AgentSummary::default()).1,
+            profile_rx: watch::channel(Profile::Default).1,
+            agents_rx: watch::channel(HashMap::new()).1,
+            battery_rx: watch::channel(None).1,
+            bindings_rx: watch::channel(Vec::new()).1,
+            focus_tx: Arc::new(watch::channel(None).0),
+        };
+        let response = handle("PUT /focus/sess1 HTTP/1.1", Some("attacker.example:7878"), 7878, &sources);
+        assert!(response.starts_with("HTTP/1.1 403"));
+        assert_eq!(*sources.focus_tx.borrow(), None);
+        assert!(handle("DELETE /focus HTTP/1.1", None, 7878, &sources).starts_with("HTTP/1.1 403"));
+
+        let response = handle("PUT /focus/sess1 HTTP/1.1", Some("localhost:7878"), 7878, &sources);
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert_eq!(sources.focus_tx.borrow().as_deref(), Some("sess1"));
+    }
+
+    #[test]
+    fn focus_requests_set_and_clear_the_focus() {
+        assert_eq!(route("PUT /focus/sess1 HTTP/1.1"), Route::Focus(Some("sess1".to_string())));
+        assert_eq!(route("DELETE /focus HTTP/1.1"), Route::Focus(None));
+        assert_eq!(route("PUT /focus/ HTTP/1.1"), Route::NotFound);
+        assert_eq!(route("GET /focus HTTP/1.1"), Route::MethodNotAllowed);
+        assert_eq!(route("PUT /focus/gemini%5Fsession%2D1 HTTP/1.1"), Route::Focus(Some("gemini_session-1".to_string())));
+        assert_eq!(route("PUT /focus/%E2%9C%93 HTTP/1.1"), Route::Focus(Some("✓".to_string())));
+        assert_eq!(route("PUT /focus/bad%4 HTTP/1.1"), Route::NotFound);
+        assert_eq!(route("PUT /focus/%zz HTTP/1.1"), Route::NotFound);
+
+        let status = Status { focus: Some("sess1".to_string()), ..status() };
+        let value: serde_json::Value = serde_json::from_str(&state_json(&status)).unwrap();
+        assert_eq!(value["focus"], "sess1");
+    }
+}
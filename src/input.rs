@@ -12,6 +12,12 @@
 ///   Byte 7: buttons byte 0 (hat + square/cross/circle/triangle)
 ///   Byte 8: buttons byte 1 (L1/R1/L2btn/R2btn/share/options/L3/R3)
 ///   Byte 9: buttons byte 2 (PS/touchpad/mute)
+///   Bytes 15–20: gyro X/Y/Z (i16 LE each)
+///   Bytes 21–26: accel X/Y/Z (i16 LE each)
+///   Bytes 27–30: sensor timestamp (u32 LE, 1/3 µs ticks)
+///   Bytes 32–39: touchpad contacts
+///   Byte 52: battery status (low nibble = level 0–10, high nibble = charge state)
+///   Byte 53: audio jack (bit 0 = headphones, bit 1 = headset mic)
 ///
 /// DualSense BT: Report ID 0x31, 78 bytes total (extended mode)
 ///   Same layout but offset by +1 byte (report ID prefix on BT)
@@ -22,10 +28,26 @@
 ///   Byte 4: buttons byte 0 (hat + square/cross/circle/triangle)  [NOTE: offset differs]
 ///   Byte 5: buttons byte 1
 ///   Byte 6: buttons byte 2
+///   Byte 29: battery status (low nibble = level, bit 4 = cable connected)
 ///
 /// DS4 BT: Report ID 0x11, 78 bytes (extended mode)
 ///   Offset by +2 bytes from USB layout
 ///   Last 4 bytes are CRC-32
+///
+/// Switch Pro standard report: Report ID 0x30 (also 0x21 subcommand replies),
+/// same layout over USB and Bluetooth, no CRC
+///   Byte 2: battery (high nibble = level 0–8 in even steps, bit 4 = charging)
+///   Byte 3: right buttons (Y/X/B/A/SR/SL/R/ZR)
+///   Byte 4: shared buttons (minus/plus/R-stick/L-stick/home/capture)
+///   Byte 5: left buttons (down/up/right/left/SR/SL/L/ZL)
+///   Bytes 6–8: left stick, two packed 12-bit axes (X, then Y pointing up)
+///   Bytes 9–11: right stick, same packing
+///
+/// Switch Pro BT simple report: Report ID 0x3F (sent until full mode is set)
+///   Byte 1: B/A/Y/X/L/R/ZL/ZR
+///   Byte 2: minus/plus/L-stick/R-stick/home/capture
+///   Byte 3: hat (same encoding as Sony)
+///   Bytes 4–11: LX/LY/RX/RY as u16 LE, 0x8000 = center, Y pointing down
 
 use crate::controller::{ConnectionType, ControllerType};
 use crate::crc32;
@@ -42,6 +64,37 @@ pub struct TouchPoint {
     pub y: u16,
 }
 
+/// Raw motion sensor sample from the DualSense IMU.
+///
+/// Values are uncalibrated device units (signed 16-bit). At rest the
+/// accelerometer reads roughly ±8192 on the axis facing gravity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MotionData {
+    /// Angular velocity: pitch, yaw, roll.
+    pub gyro: [i16; 3],
+    /// Linear acceleration: X, Y, Z.
+    pub accel: [i16; 3],
+    /// Sensor timestamp in 1/3 µs ticks (wraps); use deltas between reports.
+    pub timestamp: u32,
+}
+
+impl MotionData {
+    /// Magnitude of the acceleration vector in device units.
+    pub fn accel_magnitude(&self) -> f32 {
+        self.accel.iter().map(|&a| (a as f32) * (a as f32)).sum::<f32>().sqrt()
+    }
+}
+
+/// Battery level and charge state reported by the controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    /// Charge level 0–100. The controller reports tenths, so this is the
+    /// midpoint of the reported step (e.g. 75 for level 7), like hid-playstation.
+    pub percent: u8,
+    /// Plugged in and still charging (false when full or on battery).
+    pub charging: bool,
+}
+
 /// D-pad direction decoded from the 4-bit hat field.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DPad {
@@ -57,6 +110,25 @@ pub enum DPad {
     UpLeft,
 }
 
+impl DPad {
+    /// Direction from four d-pad buttons. Opposite directions cancel out.
+    pub fn from_directions(up: bool, down: bool, left: bool, right: bool) -> Self {
+        let vertical = up as i8 - down as i8;
+        let horizontal = right as i8 - left as i8;
+        match (vertical, horizontal) {
+            (1, 0) => DPad::Up,
+            (1, 1) => DPad::UpRight,
+            (0, 1) => DPad::Right,
+            (-1, 1) => DPad::DownRight,
+            (-1, 0) => DPad::Down,
+            (-1, -1) => DPad::DownLeft,
+            (0, -1) => DPad::Left,
+            (1, -1) => DPad::UpLeft,
+            _ => DPad::Neutral,
+        }
+    }
+}
+
 /// All button states in a single struct.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct ButtonState {
@@ -78,6 +150,135 @@ pub struct ButtonState {
     pub dpad: DPad,
 }
 
+/// A single digital control, addressable by name from config (e.g. `button = "r1"`).
+/// D-pad directions count as buttons; diagonals hold both directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Cross,
+    Circle,
+    Square,
+    Triangle,
+    L1,
+    R1,
+    L2,
+    R2,
+    Share,
+    Options,
+    L3,
+    R3,
+    Ps,
+    Touchpad,
+    Mute,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+}
+
+impl Button {
+    /// Parse a config button name. Case-insensitive; accepts PlayStation and
+    /// Xbox-ish aliases ("create" = share, "x" = cross).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cross" | "x" => Some(Button::Cross),
+            "circle" => Some(Button::Circle),
+            "square" => Some(Button::Square),
+            "triangle" => Some(Button::Triangle),
+            "l1" => Some(Button::L1),
+            "r1" => Some(Button::R1),
+            "l2" => Some(Button::L2),
+            "r2" => Some(Button::R2),
+            "share" | "create" => Some(Button::Share),
+            "options" => Some(Button::Options),
+            "l3" => Some(Button::L3),
+            "r3" => Some(Button::R3),
+            "ps" => Some(Button::Ps),
+            "touchpad" => Some(Button::Touchpad),
+            "mute" => Some(Button::Mute),
+            "dpad_up" | "up" => Some(Button::DpadUp),
+            "dpad_down" | "down" => Some(Button::DpadDown),
+            "dpad_left" | "left" => Some(Button::DpadLeft),
+            "dpad_right" | "right" => Some(Button::DpadRight),
+            _ => None,
+        }
+    }
+
+    /// Canonical config name (the form `from_name` documents first).
+    pub fn name(self) -> &'static str {
+        match self {
+            Button::Cross => "cross",
+            Button::Circle => "circle",
+            Button::Square => "square",
+            Button::Triangle => "triangle",
+            Button::L1 => "l1",
+            Button::R1 => "r1",
+            Button::L2 => "l2",
+            Button::R2 => "r2",
+            Button::Share => "share",
+            Button::Options => "options",
+            Button::L3 => "l3",
+            Button::R3 => "r3",
+            Button::Ps => "ps",
+            Button::Touchpad => "touchpad",
+            Button::Mute => "mute",
+            Button::DpadUp => "dpad_up",
+            Button::DpadDown => "dpad_down",
+            Button::DpadLeft => "dpad_left",
+            Button::DpadRight => "dpad_right",
+        }
+    }
+}
+
+impl ButtonState {
+    /// Whether `button` is currently held.
+    pub fn pressed(&self, button: Button) -> bool {
+        match button {
+            Button::Cross => self.cross,
+            Button::Circle => self.circle,
+            Button::Square => self.square,
+            Button::Triangle => self.triangle,
+            Button::L1 => self.l1,
+            Button::R1 => self.r1,
+            Button::L2 => self.l2,
+            Button::R2 => self.r2,
+            Button::Share => self.share,
+            Button::Options => self.options,
+            Button::L3 => self.l3,
+            Button::R3 => self.r3,
+            Button::Ps => self.ps,
+            Button::Touchpad => self.touchpad,
+            Button::Mute => self.mute,
+            Button::DpadUp => matches!(self.dpad, DPad::Up | DPad::UpLeft | DPad::UpRight),
+            Button::DpadDown => matches!(self.dpad, DPad::Down | DPad::DownLeft | DPad::DownRight),
+            Button::DpadLeft => matches!(self.dpad, DPad::Left | DPad::UpLeft | DPad::DownLeft),
+            Button::DpadRight => matches!(self.dpad, DPad::Right | DPad::UpRight | DPad::DownRight),
+        }
+    }
+
+    /// Mark `button` as released. D-pad directions are left alone (the hat is
+    /// a single value, not per-direction bits).
+    pub fn release(&mut self, button: Button) {
+        match button {
+            Button::Cross => self.cross = false,
+            Button::Circle => self.circle = false,
+            Button::Square => self.square = false,
+            Button::Triangle => self.triangle = false,
+            Button::L1 => self.l1 = false,
+            Button::R1 => self.r1 = false,
+            Button::L2 => self.l2 = false,
+            Button::R2 => self.r2 = false,
+            Button::Share => self.share = false,
+            Button::Options => self.options = false,
+            Button::L3 => self.l3 = false,
+            Button::R3 => self.r3 = false,
+            Button::Ps => self.ps = false,
+            Button::Touchpad => self.touchpad = false,
+            Button::Mute => self.mute = false,
+            Button::DpadUp | Button::DpadDown | Button::DpadLeft | Button::DpadRight => {}
+        }
+    }
+}
+
 /// Normalized input from any supported controller.
 #[derive(Debug, Clone, Copy)]
 pub struct UnifiedInput {
@@ -88,6 +289,12 @@ pub struct UnifiedInput {
     pub buttons: ButtonState,
     /// Touchpad contacts (DualSense only; DS4 always returns [default; 2]).
     pub touchpad: [TouchPoint; 2],
+    /// Gyro/accel sample (DualSense only; None for DS4 or truncated reports).
+    pub motion: Option<MotionData>,
+    /// Battery state (None for truncated reports or an error status).
+    pub battery: Option<BatteryStatus>,
+    /// Headphones or a headset plugged into the audio jack (DualSense only).
+    pub headset: bool,
 }
 
 impl Default for UnifiedInput {
@@ -99,6 +306,9 @@ impl Default for UnifiedInput {
             r2_analog: 0,
             buttons: ButtonState::default(),
             touchpad: [TouchPoint::default(); 2],
+            motion: None,
+            battery: None,
+            headset: false,
         }
     }
 }
@@ -107,6 +317,8 @@ impl Default for UnifiedInput {
 #[derive(Debug)]
 pub enum ParseError {
     TooShort { expected: usize, got: usize },
+    /// A report this parser doesn't decode (e.g. a Switch Pro handshake reply).
+    UnknownReport(u8),
 }
 
 impl std::fmt::Display for ParseError {
@@ -115,6 +327,7 @@ impl std::fmt::Display for ParseError {
             ParseError::TooShort { expected, got } => {
                 write!(f, "report too short: expected {expected}, got {got}")
             }
+            ParseError::UnknownReport(id) => write!(f, "unsupported report 0x{id:02X}"),
         }
     }
 }
@@ -176,25 +389,102 @@ fn parse_touch_points(data: &[u8], off: usize) -> [TouchPoint; 2] {
     if data.len() < off + 40 {
         return [TouchPoint::default(); 2];
     }
+    [decode_touch_point(data, off + 32), decode_touch_point(data, off + 36)]
+}
 
-    let decode = |base: usize| -> TouchPoint {
-        let contact  = data[base];
-        let x_lo     = data[base + 1] as u16;
-        let mid      = data[base + 2];
-        let y_hi     = data[base + 3] as u16;
+/// Parse the two DS4 touchpad contact points starting at `data[off + 34]`.
+///
+/// Same 4-byte contact encoding as DualSense (X range 0–1919, Y 0–942), but
+/// after the IMU block and a touch-packet count + timestamp (off+32, off+33).
+/// Only the most recent touch packet is read. Returns
+/// `[TouchPoint::default(); 2]` silently when the buffer is too short.
+fn parse_ds4_touch_points(data: &[u8], off: usize) -> [TouchPoint; 2] {
+    // Need off+34 .. off+41 inclusive (8 bytes for 2 contacts)
+    if data.len() < off + 42 {
+        return [TouchPoint::default(); 2];
+    }
+    [decode_touch_point(data, off + 34), decode_touch_point(data, off + 38)]
+}
 
-        let active = (contact & 0x80) == 0;
-        let x = x_lo | (((mid & 0x0F) as u16) << 8);
-        let y = ((mid >> 4) as u16) | (y_hi << 4);
+/// Decode one 4-byte touch contact at `data[base]`.
+fn decode_touch_point(data: &[u8], base: usize) -> TouchPoint {
+    let contact  = data[base];
+    let x_lo     = data[base + 1] as u16;
+    let mid      = data[base + 2];
+    let y_hi     = data[base + 3] as u16;
 
-        if active {
-            log::debug!("Touchpad contact@{base}: active x={x} y={y}");
-        }
+    let active = (contact & 0x80) == 0;
+    let x = x_lo | (((mid & 0x0F) as u16) << 8);
+    let y = ((mid >> 4) as u16) | (y_hi << 4);
 
-        TouchPoint { active, x, y }
-    };
+    if active {
+        log::debug!("Touchpad contact@{base}: active x={x} y={y}");
+    }
 
-    [decode(off + 32), decode(off + 36)]
+    TouchPoint { active, x, y }
+}
+
+/// Parse the DualSense motion block starting at `data[off + 15]`.
+///
+/// Layout (from Linux `hid-playstation.c`), all little-endian:
+///   off+15..off+20: gyro X/Y/Z (i16)
+///   off+21..off+26: accel X/Y/Z (i16)
+///   off+27..off+30: sensor timestamp (u32)
+///
+/// Returns None when the buffer is too short to contain the block.
+fn parse_motion(data: &[u8], off: usize) -> Option<MotionData> {
+    if data.len() < off + 31 {
+        return None;
+    }
+    let i16_at = |i: usize| i16::from_le_bytes([data[off + i], data[off + i + 1]]);
+    Some(MotionData {
+        gyro: [i16_at(15), i16_at(17), i16_at(19)],
+        accel: [i16_at(21), i16_at(23), i16_at(25)],
+        timestamp: u32::from_le_bytes([data[off + 27], data[off + 28], data[off + 29], data[off + 30]]),
+    })
+}
+
+/// Convert a 0–10 battery level to a percentage (midpoint of the step, capped at 100).
+fn level_percent(level: u8) -> u8 {
+    (level.min(10) * 10 + 5).min(100)
+}
+
+/// Decode the DualSense battery status byte at `data[off + 52]`.
+///
+/// Low nibble = level (0–10), high nibble = charge state:
+///   0x0 discharging, 0x1 charging, 0x2 full, others = error (None).
+fn parse_dualsense_battery(data: &[u8], off: usize) -> Option<BatteryStatus> {
+    let status = *data.get(off + 52)?;
+    let level = status & 0x0F;
+    match status >> 4 {
+        0x0 => Some(BatteryStatus { percent: level_percent(level), charging: false }),
+        0x1 => Some(BatteryStatus { percent: level_percent(level), charging: true }),
+        0x2 => Some(BatteryStatus { percent: 100, charging: false }),
+        _ => None,
+    }
+}
+
+/// Whether anything is plugged into the DualSense audio jack (`data[off + 53]`:
+/// bit 0 = headphones, bit 1 = headset mic). False for truncated reports.
+fn parse_dualsense_headset(data: &[u8], off: usize) -> bool {
+    data.get(off + 53).is_some_and(|jack| jack & 0x03 != 0)
+}
+
+/// Decode the DS4 battery status byte at `data[off + 29]`.
+///
+/// Low nibble = level; bit 4 = cable connected. On cable, levels 0–10 mean
+/// charging and 11 means full; on battery, levels run 0–10.
+fn parse_ds4_battery(data: &[u8], off: usize) -> Option<BatteryStatus> {
+    let status = *data.get(off + 29)?;
+    let level = status & 0x0F;
+    if status & 0x10 == 0 {
+        return Some(BatteryStatus { percent: level_percent(level), charging: false });
+    }
+    match level {
+        0..=10 => Some(BatteryStatus { percent: level_percent(level), charging: true }),
+        11 => Some(BatteryStatus { percent: 100, charging: false }),
+        _ => None,
+    }
 }
 
 /// Parse a DualSense USB input report.
@@ -217,6 +507,9 @@ fn parse_dualsense_usb(data: &[u8]) -> Result<UnifiedInput, ParseError> {
         // (off+6 is a counter)
         buttons: parse_buttons(data[off + 7], data[off + 8], data[off + 9]),
         touchpad: parse_touch_points(data, off),
+        motion: parse_motion(data, off),
+        battery: parse_dualsense_battery(data, off),
+        headset: parse_dualsense_headset(data, off),
     })
 }
 
@@ -237,6 +530,9 @@ fn parse_dualsense_bt(data: &[u8]) -> Result<UnifiedInput, ParseError> {
         r2_analog: data[off + 5],
         buttons: parse_buttons(data[off + 7], data[off + 8], data[off + 9]),
         touchpad: parse_touch_points(data, off),
+        motion: parse_motion(data, off),
+        battery: parse_dualsense_battery(data, off),
+        headset: parse_dualsense_headset(data, off),
     })
 }
 
@@ -260,8 +556,10 @@ fn parse_ds4_usb(data: &[u8]) -> Result<UnifiedInput, ParseError> {
         },
         l2_analog: data[off + 7],
         r2_analog: data[off + 8],
-        // DS4 touchpad has a different layout — not yet implemented.
-        touchpad: [TouchPoint::default(); 2],
+        touchpad: parse_ds4_touch_points(data, off),
+        motion: None,
+        battery: parse_ds4_battery(data, off),
+        headset: false,
     })
 }
 
@@ -285,11 +583,153 @@ fn parse_ds4_bt(data: &[u8]) -> Result<UnifiedInput, ParseError> {
         },
         l2_analog: data[off + 7],
         r2_analog: data[off + 8],
-        // DS4 touchpad has a different layout — not yet implemented.
-        touchpad: [TouchPoint::default(); 2],
+        touchpad: parse_ds4_touch_points(data, off),
+        motion: None,
+        battery: parse_ds4_battery(data, off),
+        headset: false,
     })
 }
 
+/// Factory-typical Switch Pro stick calibration (12-bit raw units): the
+/// controller's own calibration lives in its SPI flash, which isn't read yet.
+const SWITCH_STICK_CENTER: i32 = 2048;
+/// Raw deflection from center that counts as full tilt.
+const SWITCH_STICK_REACH: i32 = 1400;
+
+/// Scale a raw 12-bit Switch stick axis to Sony's 0–255 (128 = center).
+/// `invert` flips the direction (Switch Y points up, Sony Y points down).
+fn switch_stick_axis(raw: u16, invert: bool) -> u8 {
+    let mut delta = raw as i32 - SWITCH_STICK_CENTER;
+    if invert {
+        delta = -delta;
+    }
+    (128 + delta * 128 / SWITCH_STICK_REACH).clamp(0, 255) as u8
+}
+
+/// Unpack one Switch stick (3 bytes, two 12-bit axes) at `data[at]` to Sony (x, y).
+fn switch_stick(data: &[u8], at: usize) -> (u8, u8) {
+    let x = data[at] as u16 | ((data[at + 1] as u16 & 0x0F) << 8);
+    let y = (data[at + 1] as u16 >> 4) | ((data[at + 2] as u16) << 4);
+    (switch_stick_axis(x, false), switch_stick_axis(y, true))
+}
+
+/// Digital trigger as an analog value.
+fn digital_trigger(pressed: bool) -> u8 {
+    if pressed { 255 } else { 0 }
+}
+
+/// Map Switch Pro buttons to Sony names by position: B (bottom) → cross,
+/// A (right) → circle, Y (left) → square, X (top) → triangle. Minus/plus →
+/// share/options, home → PS, capture → touchpad click.
+fn switch_buttons(
+    (b, a, y, x): (bool, bool, bool, bool),
+    (l, r, zl, zr): (bool, bool, bool, bool),
+    (minus, plus, lstick, rstick): (bool, bool, bool, bool),
+    (home, capture): (bool, bool),
+    dpad: DPad,
+) -> ButtonState {
+    ButtonState {
+        cross: b,
+        circle: a,
+        square: y,
+        triangle: x,
+        l1: l,
+        r1: r,
+        l2: zl,
+        r2: zr,
+        share: minus,
+        options: plus,
+        l3: lstick,
+        r3: rstick,
+        ps: home,
+        touchpad: capture,
+        mute: false,
+        dpad,
+    }
+}
+
+/// Decode the Switch Pro battery byte: the high nibble is the level (0, 2, 4,
+/// 6 or 8 = full) with its low bit flagging charging.
+fn parse_switch_battery(status: u8) -> BatteryStatus {
+    let level = (status >> 4) & 0x0E;
+    BatteryStatus { percent: level.min(8) * 25 / 2, charging: status & 0x10 != 0 }
+}
+
+/// Parse a Switch Pro standard input report (0x30, or a 0x21 subcommand reply,
+/// which carries the same input section). hidapi includes the report ID.
+fn parse_switch_pro_standard(data: &[u8]) -> Result<UnifiedInput, ParseError> {
+    if data.len() < 12 {
+        return Err(ParseError::TooShort { expected: 12, got: data.len() });
+    }
+    let (right, shared, left) = (data[3], data[4], data[5]);
+    let bit = |byte: u8, mask: u8| byte & mask != 0;
+    let buttons = switch_buttons(
+        (bit(right, 0x04), bit(right, 0x08), bit(right, 0x01), bit(right, 0x02)),
+        (bit(left, 0x40), bit(right, 0x40), bit(left, 0x80), bit(right, 0x80)),
+        (bit(shared, 0x01), bit(shared, 0x02), bit(shared, 0x08), bit(shared, 0x04)),
+        (bit(shared, 0x10), bit(shared, 0x20)),
+        DPad::from_directions(bit(left, 0x02), bit(left, 0x01), bit(left, 0x08), bit(left, 0x04)),
+    );
+    Ok(UnifiedInput {
+        left_stick: switch_stick(data, 6),
+        right_stick: switch_stick(data, 9),
+        l2_analog: digital_trigger(buttons.l2),
+        r2_analog: digital_trigger(buttons.r2),
+        buttons,
+        // Motion isn't decoded: the IMU's axes and units differ from Sony's
+        motion: None,
+        battery: Some(parse_switch_battery(data[2])),
+        ..UnifiedInput::default()
+    })
+}
+
+/// Parse a Switch Pro simple HID report (0x3F), sent over Bluetooth until the
+/// controller is switched to full reports.
+fn parse_switch_pro_simple(data: &[u8]) -> Result<UnifiedInput, ParseError> {
+    if data.len() < 12 {
+        return Err(ParseError::TooShort { expected: 12, got: data.len() });
+    }
+    let (b1, b2) = (data[1], data[2]);
+    let bit = |byte: u8, mask: u8| byte & mask != 0;
+    let buttons = switch_buttons(
+        (bit(b1, 0x01), bit(b1, 0x02), bit(b1, 0x04), bit(b1, 0x08)),
+        (bit(b1, 0x10), bit(b1, 0x20), bit(b1, 0x40), bit(b1, 0x80)),
+        (bit(b2, 0x01), bit(b2, 0x02), bit(b2, 0x04), bit(b2, 0x08)),
+        (bit(b2, 0x10), bit(b2, 0x20)),
+        decode_hat(data[3]),
+    );
+    // 16-bit axes, already Sony-oriented: keep the high byte
+    let axis = |at: usize| data[at + 1];
+    Ok(UnifiedInput {
+        left_stick: (axis(4), axis(6)),
+        right_stick: (axis(8), axis(10)),
+        l2_analog: digital_trigger(buttons.l2),
+        r2_analog: digital_trigger(buttons.r2),
+        buttons,
+        ..UnifiedInput::default()
+    })
+}
+
+/// Parse a Switch Pro USB input report. Only the standard (0x30) layout is
+/// sent over USB once the handshake is done.
+fn parse_switch_pro_usb(data: &[u8]) -> Result<UnifiedInput, ParseError> {
+    match data.first() {
+        Some(0x30 | 0x21) => parse_switch_pro_standard(data),
+        Some(&id) => Err(ParseError::UnknownReport(id)),
+        None => Err(ParseError::TooShort { expected: 12, got: 0 }),
+    }
+}
+
+/// Parse a Switch Pro Bluetooth input report: standard (0x30/0x21) or simple (0x3F).
+fn parse_switch_pro_bt(data: &[u8]) -> Result<UnifiedInput, ParseError> {
+    match data.first() {
+        Some(0x30 | 0x21) => parse_switch_pro_standard(data),
+        Some(0x3F) => parse_switch_pro_simple(data),
+        Some(&id) => Err(ParseError::UnknownReport(id)),
+        None => Err(ParseError::TooShort { expected: 12, got: 0 }),
+    }
+}
+
 /// Top-level parse dispatcher.
 pub fn parse(
     ct: ControllerType,
@@ -309,20 +749,35 @@ pub fn parse(
         (ControllerType::Ds4V1 | ControllerType::Ds4V2, ConnectionType::Bluetooth) => {
             parse_ds4_bt(data)
         }
+        (ControllerType::SwitchPro, ConnectionType::Usb) => parse_switch_pro_usb(data),
+        (ControllerType::SwitchPro, ConnectionType::Bluetooth) => parse_switch_pro_bt(data),
     }
 }
 
 /// Validate CRC on a Bluetooth report. Call this BEFORE parse() with the full
 /// raw report bytes (including report ID if present).
 pub fn validate_bt_crc(ct: ControllerType, raw: &[u8]) -> bool {
-    let _ = ct; // same seed for all BT input reports
-    crc32::validate(crc32::SEED_INPUT, raw)
+    // Switch Pro reports carry no CRC; Sony uses the same seed for all BT input reports
+    ct == ControllerType::SwitchPro || crc32::validate(crc32::SEED_INPUT, raw)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn button_names_and_diagonals() {
+        assert_eq!(Button::from_name("R1"), Some(Button::R1));
+        assert_eq!(Button::from_name("create"), Some(Button::Share));
+        assert_eq!(Button::from_name("dpad_up"), Some(Button::DpadUp));
+        assert_eq!(Button::from_name("start"), None);
+
+        let state = ButtonState { dpad: DPad::UpLeft, ..Default::default() };
+        assert!(state.pressed(Button::DpadUp));
+        assert!(state.pressed(Button::DpadLeft));
+        assert!(!state.pressed(Button::DpadRight));
+    }
+
     #[test]
     fn hat_decode() {
         assert_eq!(decode_hat(0), DPad::Up);
@@ -346,6 +801,146 @@ mod tests {
         assert_eq!(input.buttons.dpad, DPad::Neutral);
     }
 
+    // ── Switch Pro parsing tests ─────────────────────────────────────────
+
+    /// Pack a 12-bit (x, y) stick pair the way the Switch Pro reports it.
+    fn put_switch_stick(data: &mut [u8], at: usize, x: u16, y: u16) {
+        data[at] = (x & 0xFF) as u8;
+        data[at + 1] = ((x >> 8) as u8 & 0x0F) | (((y & 0x0F) as u8) << 4);
+        data[at + 2] = (y >> 4) as u8;
+    }
+
+    /// A standard 0x30 report (64 bytes, as read over USB or BT), sticks centered.
+    fn switch_standard_report() -> [u8; 64] {
+        let mut data = [0u8; 64];
+        data[0] = 0x30;
+        data[1] = 0x42; // timer
+        data[2] = 0x4E; // battery: level 4 of 8, discharging; low nibble = connection info
+        put_switch_stick(&mut data, 6, 2048, 2048);
+        put_switch_stick(&mut data, 9, 2048, 2048);
+        data
+    }
+
+    #[test]
+    fn switch_pro_standard_report_buttons_and_battery() {
+        let mut data = switch_standard_report();
+        data[3] = 0x04 | 0x02 | 0x80; // B, X, ZR
+        data[4] = 0x02 | 0x10 | 0x20; // plus, home, capture
+        data[5] = 0x02 | 0x08 | 0x40; // up + left, L
+        for input in [parse(ControllerType::SwitchPro, ConnectionType::Usb, &data).unwrap(),
+                      parse(ControllerType::SwitchPro, ConnectionType::Bluetooth, &data).unwrap()] {
+            let b = input.buttons;
+            // Positional: B (bottom) → cross, X (top) → triangle
+            assert!(b.cross && b.triangle && !b.circle && !b.square);
+            assert!(b.r2 && !b.l2 && b.l1 && !b.r1);
+            assert!(b.options && !b.share && b.ps && b.touchpad && !b.mute);
+            assert_eq!(b.dpad, DPad::UpLeft);
+            assert_eq!((input.l2_analog, input.r2_analog), (0, 255));
+            assert_eq!(input.left_stick, (128, 128));
+            assert_eq!(input.battery, Some(BatteryStatus { percent: 50, charging: false }));
+        }
+    }
+
+    #[test]
+    fn switch_pro_sticks_scale_and_flip_y() {
+        let mut data = switch_standard_report();
+        // Left: full right + full up; right: slightly left of center + full down
+        put_switch_stick(&mut data, 6, 2048 + 1400, 2048 + 1600);
+        put_switch_stick(&mut data, 9, 2048 - 700, 300);
+        let input = parse_switch_pro_usb(&data).unwrap();
+        assert_eq!(input.left_stick, (255, 0));
+        assert_eq!(input.right_stick, (64, 255));
+    }
+
+    #[test]
+    fn switch_pro_bt_simple_report() {
+        let mut data = [0u8; 12];
+        data[0] = 0x3F;
+        data[1] = 0x02 | 0x40; // A, ZL
+        data[2] = 0x01; // minus
+        data[3] = 0x06; // hat: left
+        data[4..12].copy_from_slice(&[0x00, 0x80, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x80]);
+        let input = parse(ControllerType::SwitchPro, ConnectionType::Bluetooth, &data).unwrap();
+        assert!(input.buttons.circle && input.buttons.l2 && input.buttons.share);
+        assert_eq!(input.buttons.dpad, DPad::Left);
+        assert_eq!(input.l2_analog, 255);
+        assert_eq!(input.left_stick, (128, 255));
+        assert_eq!(input.right_stick, (0, 128));
+        assert_eq!(input.battery, None);
+    }
+
+    #[test]
+    fn switch_pro_rejects_other_reports() {
+        // USB handshake reply; simple reports only come over BT
+        assert!(matches!(parse_switch_pro_usb(&[0x81, 0x02]), Err(ParseError::UnknownReport(0x81))));
+        assert!(matches!(parse_switch_pro_usb(&[0x3F; 12]), Err(ParseError::UnknownReport(0x3F))));
+        assert!(matches!(parse_switch_pro_bt(&[0x30, 0x00]), Err(ParseError::TooShort { .. })));
+        // No CRC to check
+        assert!(validate_bt_crc(ControllerType::SwitchPro, &[0x30, 0x00]));
+    }
+
+    // ── MotionData parsing tests ─────────────────────────────────────────
+
+    /// Write a synthetic motion block (gyro, accel, timestamp) at `off`.
+    fn put_motion(data: &mut [u8], off: usize, gyro: [i16; 3], accel: [i16; 3], ts: u32) {
+        for (i, v) in gyro.iter().chain(accel.iter()).enumerate() {
+            data[off + 15 + i * 2..off + 17 + i * 2].copy_from_slice(&v.to_le_bytes());
+        }
+        data[off + 27..off + 31].copy_from_slice(&ts.to_le_bytes());
+    }
+
+    #[test]
+    fn motion_decodes_usb_offsets() {
+        let mut data = [0u8; 64];
+        data[7] = 0x08;
+        put_motion(&mut data, 0, [100, -200, 300], [-8192, 16, 8000], 0x0102_0304);
+        let motion = parse_dualsense_usb(&data).unwrap().motion.unwrap();
+        assert_eq!(motion.gyro, [100, -200, 300]);
+        assert_eq!(motion.accel, [-8192, 16, 8000]);
+        assert_eq!(motion.timestamp, 0x0102_0304);
+        // Little-endian: low byte first
+        assert_eq!(data[15], 100);
+        assert_eq!(data[21..23], (-8192i16).to_le_bytes());
+    }
+
+    #[test]
+    fn motion_decodes_bt_offsets() {
+        // BT: report ID 0x31 + 1-byte header → payload at off=2
+        let mut data = [0u8; 78];
+        data[0] = 0x31;
+        data[2 + 7] = 0x08;
+        put_motion(&mut data, 2, [-1, 2, -3], [4, -5, 8192], 42);
+        let motion = parse_dualsense_bt(&data).unwrap().motion.unwrap();
+        assert_eq!(motion.gyro, [-1, 2, -3]);
+        assert_eq!(motion.accel, [4, -5, 8192]);
+        assert_eq!(motion.timestamp, 42);
+    }
+
+    #[test]
+    fn gyro_decodes_known_raw_bytes() {
+        // USB payload (no report ID): gyro pitch/yaw/roll at bytes 15..21
+        let mut data = [0u8; 64];
+        data[7] = 0x08;
+        data[15..21].copy_from_slice(&[0x34, 0x12, 0xCC, 0xFF, 0x00, 0x80]);
+        let motion = parse_dualsense_usb(&data).unwrap().motion.unwrap();
+        assert_eq!(motion.gyro, [0x1234, -52, i16::MIN]);
+
+        // Same block over BT sits two bytes later (report ID + header)
+        let mut bt = [0u8; 78];
+        bt[0] = 0x31;
+        bt[2 + 7] = 0x08;
+        bt[17..23].copy_from_slice(&[0x34, 0x12, 0xCC, 0xFF, 0x00, 0x80]);
+        assert_eq!(parse_dualsense_bt(&bt).unwrap().motion.unwrap().gyro, [0x1234, -52, i16::MIN]);
+    }
+
+    #[test]
+    fn motion_absent_on_short_report() {
+        let data = [0x08u8; 20];
+        assert!(parse_dualsense_usb(&data).unwrap().motion.is_none());
+        let accel = MotionData { accel: [3, 4, 0], ..Default::default() };
+        assert_eq!(accel.accel_magnitude(), 5.0);
+    }
+
     // ── TouchPoint parsing tests ─────────────────────────────────────────
 
     /// Build a 64-byte DualSense USB report (no report-ID prefix)
@@ -401,6 +996,51 @@ mod tests {
         assert_eq!(pts[0].x, 0);
     }
 
+    /// Build a 64-byte DS4 USB report (with report ID 0x01, so off=1)
+    /// with touch point 0 encoded at byte 35.
+    fn make_ds4_usb_with_touch(contact: u8, x: u16, y: u16) -> [u8; 64] {
+        let mut data = [0u8; 64];
+        data[0] = 0x01;
+        data[5] = 0x08; // hat=neutral (8)
+        data[35] = contact;
+        data[36] = (x & 0xFF) as u8;
+        data[37] = ((x >> 8) & 0x0F) as u8 | (((y & 0x0F) as u8) << 4);
+        data[38] = ((y >> 4) & 0xFF) as u8;
+        // Touch point 1: inactive
+        data[39] = 0x80;
+        data
+    }
+
+    #[test]
+    fn ds4_touch_point_active() {
+        let data = make_ds4_usb_with_touch(0x00, 100, 200);
+        let pts = parse_ds4_usb(&data).unwrap().touchpad;
+        assert!(pts[0].active, "bit7=0 should be active");
+        assert_eq!(pts[0].x, 100);
+        assert_eq!(pts[0].y, 200);
+        assert!(!pts[1].active, "second point not set");
+    }
+
+    #[test]
+    fn ds4_touch_point_x_max() {
+        let data = make_ds4_usb_with_touch(0x00, 1919, 942);
+        let pts = parse_ds4_usb(&data).unwrap().touchpad;
+        assert_eq!(pts[0].x, 1919);
+        assert_eq!(pts[0].y, 942);
+    }
+
+    #[test]
+    fn ds4_bt_touch_point_offset() {
+        // BT: report ID 0x11 + 2-byte header → payload at off=3
+        let usb = make_ds4_usb_with_touch(0x01, 640, 300);
+        let mut bt = [0u8; 78];
+        bt[0] = 0x11;
+        bt[3..66].copy_from_slice(&usb[1..]);
+        let pts = parse_ds4_bt(&bt).unwrap().touchpad;
+        assert!(pts[0].active);
+        assert_eq!((pts[0].x, pts[0].y), (640, 300));
+    }
+
     #[test]
     fn touch_points_short_buffer_returns_default() {
         // Buffer smaller than off+40
@@ -424,6 +1064,87 @@ mod tests {
         assert_eq!(input.touchpad[0].x, 50 | (3 << 8)); // = 818
     }
 
+    #[test]
+    fn dualsense_battery_usb_charging_and_bt_discharging() {
+        // USB: charging (0x1_), level 4 → 45%
+        let mut usb = [0u8; 64];
+        usb[7] = 0x08;
+        usb[52] = 0x14;
+        assert_eq!(
+            parse_dualsense_usb(&usb).unwrap().battery,
+            Some(BatteryStatus { percent: 45, charging: true })
+        );
+
+        // BT: report ID + tag → payload at off=2; discharging, level 7 → 75%
+        let mut bt = [0u8; 78];
+        bt[0] = 0x31;
+        bt[2 + 7] = 0x08;
+        bt[2 + 52] = 0x07;
+        assert_eq!(
+            parse_dualsense_bt(&bt).unwrap().battery,
+            Some(BatteryStatus { percent: 75, charging: false })
+        );
+
+        // Full, and an error state
+        usb[52] = 0x2A;
+        assert_eq!(parse_dualsense_usb(&usb).unwrap().battery, Some(BatteryStatus { percent: 100, charging: false }));
+        usb[52] = 0xB3;
+        assert_eq!(parse_dualsense_usb(&usb).unwrap().battery, None);
+    }
+
+    #[test]
+    fn ds4_battery_usb_charging_and_bt_discharging() {
+        // USB (report ID at 0 → off=1): cable + level 6 → charging 65%
+        let mut usb = [0u8; 64];
+        usb[0] = 0x01;
+        usb[1 + 29] = 0x16;
+        assert_eq!(
+            parse_ds4_usb(&usb).unwrap().battery,
+            Some(BatteryStatus { percent: 65, charging: true })
+        );
+        // Cable + level 11 → full
+        usb[1 + 29] = 0x1B;
+        assert_eq!(parse_ds4_usb(&usb).unwrap().battery, Some(BatteryStatus { percent: 100, charging: false }));
+
+        // BT (off=3): no cable, level 3 → 35%
+        let mut bt = [0u8; 78];
+        bt[0] = 0x11;
+        bt[3 + 29] = 0x03;
+        assert_eq!(
+            parse_ds4_bt(&bt).unwrap().battery,
+            Some(BatteryStatus { percent: 35, charging: false })
+        );
+    }
+
+    #[test]
+    fn dualsense_headset_jack_bit() {
+        let mut usb = [0u8; 64];
+        usb[7] = 0x08;
+        assert!(!parse_dualsense_usb(&usb).unwrap().headset);
+        usb[53] = 0x01; // headphones
+        assert!(parse_dualsense_usb(&usb).unwrap().headset);
+        usb[53] = 0x1A; // headset mic (bit 1) plus unrelated USB flags
+        assert!(parse_dualsense_usb(&usb).unwrap().headset);
+        usb[53] = 0x18; // USB data/power only
+        assert!(!parse_dualsense_usb(&usb).unwrap().headset);
+
+        // BT: same byte, shifted by the report ID + header
+        let mut bt = [0u8; 78];
+        bt[0] = 0x31;
+        bt[2 + 7] = 0x08;
+        bt[2 + 53] = 0x03;
+        assert!(parse_dualsense_bt(&bt).unwrap().headset);
+        // Truncated report → no headset
+        assert!(!parse_dualsense_usb(&usb[..40]).unwrap().headset);
+    }
+
+    #[test]
+    fn battery_missing_from_short_report() {
+        let data = [0u8; 20];
+        assert_eq!(parse_dualsense_usb(&data).unwrap().battery, None);
+        assert_eq!(parse_ds4_usb(&data).unwrap().battery, None);
+    }
+
     #[test]
     fn parse_ds4_usb_basic() {
         let mut data = [0u8; 64];
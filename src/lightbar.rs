@@ -1,35 +1,257 @@
 /// Lightbar engine: maps agent state + elapsed time to RGB color.
 ///
 /// States:
-///   Idle    → orange, solid
-///   Working → blue, pulsing (sinusoidal brightness)
-///   Done    → green, solid
+///   Idle    → orange, solid (or a slow rainbow with `idle_mode = "rainbow"`)
+///   Working → blue, pulsing (sine by default; `pulse_waveform` picks the curve)
+///   Thinking → purple, pulsing — the model is reasoning rather than running tools
+///   Done    → green, solid (optionally blinking or fading a few times first)
 ///   Error   → same as Working (blue pulse) — agent is still active, self-recovering silently
+///
+/// With `sleep_after_s` set, the lightbar goes dark after that long in Idle
+/// and wakes on the next state change or button press.
+///
+/// With `blend` set, several agents in different states mix their colors,
+/// weighted by how many agents are in each state.
+///
+/// The last displayed color is persisted when DS4CC relaunches itself (update
+/// or tray Restart) and shown again right away on the next start, so the
+/// lightbar doesn't flicker through orange while the agent feed comes back.
+
+use crate::config::{ColorConfig, DoneAnimation, IdleMode, LightbarConfig, PulseWaveform};
+use crate::state::{AgentState, StateCounts};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+/// How long the lightbar shows red after an agent enters Error (`[rumble] on_error`).
+const ERROR_FLASH_MS: u64 = 1200;
+/// Error flash color.
+const ERROR_FLASH_COLOR: (u8, u8, u8) = (255, 0, 0);
+
+/// One blink/fade cycle of the done animation.
+const DONE_CYCLE_MS: u64 = 500;
+
+/// File (in the state dir) holding the color to restore on the next start.
+pub const LAST_COLOR_FILE: &str = "ds4cc_lightbar_last";
 
-use crate::config::LightbarConfig;
-use crate::state::AgentState;
+/// Last color sent to the controller (before `brightness`), packed as 0x00RRGGBB.
+static LAST_COLOR: AtomicU32 = AtomicU32::new(u32::MAX);
+/// Where `persist_last_color` writes; set once at startup.
+static LAST_COLOR_PATH: OnceLock<PathBuf> = OnceLock::new();
 
 /// Compute the current lightbar RGB given state and time.
-pub fn compute_color(
+#[cfg(test)]
+fn compute_color(config: &LightbarConfig, state: AgentState, elapsed_ms: u64) -> (u8, u8, u8) {
+    apply_brightness(config, base_color(config, state, elapsed_ms))
+}
+
+/// Lightbar RGB for `state` before [`apply_brightness`] (Idle already
+/// carries `idle_brightness`).
+pub fn base_color(config: &LightbarConfig, state: AgentState, elapsed_ms: u64) -> (u8, u8, u8) {
+    let color = state_color(config, state, elapsed_ms);
+    if state == AgentState::Idle {
+        return scale(color, config.idle_brightness.clamp(0.0, 1.0));
+    }
+    color
+}
+
+/// `color` at the configured `brightness`. Every color sent to the lightbar
+/// goes through this, flashes and a restored color included.
+pub fn apply_brightness(config: &LightbarConfig, color: (u8, u8, u8)) -> (u8, u8, u8) {
+    scale(color, config.brightness.clamp(0.0, 1.0))
+}
+
+/// Average of the configured state colors, weighted by how many agents are in
+/// each state. Error uses its own `error` color here (rather than mirroring
+/// Working) so it stands out in the mix. No agents → the idle color. Like
+/// [`base_color`], before [`apply_brightness`].
+pub fn compute_blended_color(
     config: &LightbarConfig,
-    state: AgentState,
+    counts: &StateCounts,
     elapsed_ms: u64,
 ) -> (u8, u8, u8) {
+    let total = counts.total();
+    if total == 0 {
+        return base_color(config, AgentState::Idle, elapsed_ms);
+    }
+    let mut sum = [0usize; 3];
+    for (state, n) in counts.iter() {
+        let (r, g, b) = match state {
+            AgentState::Error => (config.error.r, config.error.g, config.error.b),
+            _ => state_color(config, state, elapsed_ms),
+        };
+        sum[0] += r as usize * n;
+        sum[1] += g as usize * n;
+        sum[2] += b as usize * n;
+    }
+    let avg = |c: usize| ((c + total / 2) / total) as u8;
+    (avg(sum[0]), avg(sum[1]), avg(sum[2]))
+}
+
+/// Full-brightness color for `state` (before `brightness` scaling).
+fn state_color(config: &LightbarConfig, state: AgentState, elapsed_ms: u64) -> (u8, u8, u8) {
     match state {
-        AgentState::Idle => (config.idle.r, config.idle.g, config.idle.b),
-        AgentState::Done => (config.done.r, config.done.g, config.done.b),
+        AgentState::Idle => match config.idle_mode {
+            IdleMode::Solid => (config.idle.r, config.idle.g, config.idle.b),
+            IdleMode::Rainbow => {
+                let period = config.rainbow_period_ms.max(1);
+                hsv_to_rgb((elapsed_ms % period) as f64 * 360.0 / period as f64)
+            }
+        },
+        AgentState::Done => done_color(config, elapsed_ms),
         // Error mirrors Working: agent is still active, recovering from the error silently.
         // No visual alarm — the lightbar just keeps pulsing blue.
-        AgentState::Error | AgentState::Working => {
-            // Sinusoidal pulse: brightness oscillates between 0.3 and 1.0
-            let period = config.pulse_period_ms as f64;
-            let phase = (elapsed_ms as f64 / period) * std::f64::consts::TAU;
-            let brightness = 0.65 + 0.35 * phase.sin(); // range [0.3, 1.0]
-            let r = (config.working.r as f64 * brightness) as u8;
-            let g = (config.working.g as f64 * brightness) as u8;
-            let b = (config.working.b as f64 * brightness) as u8;
-            (r, g, b)
+        AgentState::Error | AgentState::Working => pulse(config, &config.working, elapsed_ms),
+        AgentState::Thinking => pulse(config, &config.thinking, elapsed_ms),
+    }
+}
+
+/// `color` with every channel multiplied by `factor` (0.0–1.0).
+fn scale((r, g, b): (u8, u8, u8), factor: f32) -> (u8, u8, u8) {
+    let ch = |c: u8| (c as f32 * factor).round() as u8;
+    (ch(r), ch(g), ch(b))
+}
+
+/// `color` with a brightness pulse between 0.3 and 1.0, shaped by the
+/// configured waveform.
+fn pulse(config: &LightbarConfig, color: &ColorConfig, elapsed_ms: u64) -> (u8, u8, u8) {
+    let level = wave_level(config.pulse_waveform, elapsed_ms, config.pulse_period_ms);
+    let brightness = 0.3 + 0.7 * level; // range [0.3, 1.0]
+    let r = (color.r as f64 * brightness) as u8;
+    let g = (color.g as f64 * brightness) as u8;
+    let b = (color.b as f64 * brightness) as u8;
+    (r, g, b)
+}
+
+/// The done color `elapsed_ms` into the Done state, following `done_animation`.
+fn done_color(config: &LightbarConfig, elapsed_ms: u64) -> (u8, u8, u8) {
+    let color = (config.done.r, config.done.g, config.done.b);
+    if elapsed_ms >= config.done_cycles as u64 * DONE_CYCLE_MS {
+        return color;
+    }
+    let t = (elapsed_ms % DONE_CYCLE_MS) as f32 / DONE_CYCLE_MS as f32;
+    match config.done_animation {
+        DoneAnimation::Solid => color,
+        // On for the first half of each cycle, off for the second
+        DoneAnimation::Blink if t < 0.5 => color,
+        DoneAnimation::Blink => (0, 0, 0),
+        // Full → off at mid-cycle → full
+        DoneAnimation::Fade => scale(color, (2.0 * t - 1.0).abs()),
+    }
+}
+
+/// Fully saturated, full-value color at `hue` degrees (0 = red, 120 = green, 240 = blue).
+fn hsv_to_rgb(hue: f64) -> (u8, u8, u8) {
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    let (r, g, b) = match h as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let ch = |c: f64| (c * 255.0).round() as u8;
+    (ch(r), ch(g), ch(b))
+}
+
+/// Waveform level in [0, 1] at `elapsed_ms` into a pulse of `period_ms`.
+fn wave_level(waveform: PulseWaveform, elapsed_ms: u64, period_ms: u64) -> f64 {
+    let t = (elapsed_ms % period_ms.max(1)) as f64 / period_ms.max(1) as f64;
+    match waveform {
+        PulseWaveform::Sine => 0.5 + 0.5 * (t * std::f64::consts::TAU).sin(),
+        PulseWaveform::Sawtooth => t,
+        PulseWaveform::Triangle => 1.0 - (2.0 * t - 1.0).abs(),
+        // Full beat at the start of the period, a weaker one a quarter later
+        PulseWaveform::Heartbeat => beat(t, 0.0).max(0.6 * beat(t, 0.25)),
+    }
+}
+
+/// Triangular spike of width 0.2 periods centered on `center` (wraps around).
+fn beat(t: f64, center: f64) -> f64 {
+    const HALF_WIDTH: f64 = 0.1;
+    let d = (t - center).abs();
+    let d = d.min(1.0 - d);
+    (1.0 - d / HALF_WIDTH).max(0.0)
+}
+
+/// Lightbar override `since_ms` after an agent entered Error: solid red, or
+/// None once the flash is over.
+pub fn error_flash_color(since_ms: u64) -> Option<(u8, u8, u8)> {
+    (since_ms < ERROR_FLASH_MS).then_some(ERROR_FLASH_COLOR)
+}
+
+/// Tracks how long the lightbar has been Idle without input, for `sleep_after_s`.
+pub struct SleepTimer {
+    /// Zero = sleep disabled.
+    after: Duration,
+    /// Start of the current quiet Idle stretch (None while not Idle).
+    idle_since: Option<Instant>,
+}
+
+impl SleepTimer {
+    pub fn new(sleep_after_s: u64) -> Self {
+        Self { after: Duration::from_secs(sleep_after_s), idle_since: None }
+    }
+
+    /// Feed the current state and whether the controller saw input since the
+    /// last call. Returns true while the lightbar should be asleep (dark).
+    pub fn update(&mut self, state: AgentState, input: bool, now: Instant) -> bool {
+        if self.after.is_zero() {
+            return false;
+        }
+        if state != AgentState::Idle || input {
+            // Wake: the quiet stretch restarts (or ends, outside Idle)
+            self.idle_since = (state == AgentState::Idle).then_some(now);
+            return false;
         }
+        let since = *self.idle_since.get_or_insert(now);
+        now.duration_since(since) >= self.after
+    }
+}
+
+/// Record the color just sent to the controller (cheap; called every frame).
+pub fn remember_color((r, g, b): (u8, u8, u8)) {
+    LAST_COLOR.store(u32::from_be_bytes([0, r, g, b]), Ordering::Relaxed);
+}
+
+/// Enable persistence into `state_dir` and take any color saved by the previous
+/// run. The file is removed so it only applies to the start right after it.
+pub fn init_persistence(state_dir: &Path) -> Option<(u8, u8, u8)> {
+    let path = state_dir.join(LAST_COLOR_FILE);
+    let restored = take_last_color(&path);
+    let _ = LAST_COLOR_PATH.set(path);
+    restored
+}
+
+/// Save the last displayed color before a self-relaunch. No-op if nothing
+/// was displayed yet or persistence wasn't initialized.
+pub fn persist_last_color() {
+    let packed = LAST_COLOR.load(Ordering::Relaxed);
+    let Some(path) = LAST_COLOR_PATH.get() else { return };
+    if packed == u32::MAX {
+        return;
+    }
+    let [_, r, g, b] = packed.to_be_bytes();
+    if let Err(e) = save_last_color(path, (r, g, b)) {
+        log::debug!("Failed to persist lightbar color: {e}");
+    }
+}
+
+fn save_last_color(path: &Path, (r, g, b): (u8, u8, u8)) -> std::io::Result<()> {
+    std::fs::write(path, format!("{r},{g},{b}"))
+}
+
+/// Read and delete a saved color. None if missing or malformed.
+fn take_last_color(path: &Path) -> Option<(u8, u8, u8)> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let _ = std::fs::remove_file(path);
+    let mut parts = content.trim().split(',').map(|p| p.trim().parse::<u8>());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => Some((r, g, b)),
+        _ => None,
     }
 }
 
@@ -86,4 +308,195 @@ mod tests {
         }
     }
 
+    #[test]
+    fn thinking_pulses_its_own_color() {
+        let cfg = default_config();
+        let quarter = cfg.pulse_period_ms / 4;
+        // Peak of the pulse is the configured color (purple), not working blue
+        assert_eq!(compute_color(&cfg, AgentState::Thinking, quarter), (160, 0, 255));
+        assert_ne!(
+            compute_color(&cfg, AgentState::Thinking, quarter),
+            compute_color(&cfg, AgentState::Working, quarter)
+        );
+        let three_quarter = (cfg.pulse_period_ms * 3) / 4;
+        let (_, _, b_min) = compute_color(&cfg, AgentState::Thinking, three_quarter);
+        assert!(b_min < 255);
+    }
+
+    #[test]
+    fn waveforms_at_known_phases() {
+        let period = 2000;
+        let at = |w, ms| wave_level(w, ms, period);
+        let (zero, quarter, half) = (0, period / 4, period / 2);
+
+        // Sine: mid → peak → mid
+        assert!(at(PulseWaveform::Sine, quarter) > at(PulseWaveform::Sine, zero));
+        assert!((at(PulseWaveform::Sine, zero) - at(PulseWaveform::Sine, half)).abs() < 1e-9);
+        // Sawtooth: steady ramp, drops back at the period
+        assert!(at(PulseWaveform::Sawtooth, zero) < at(PulseWaveform::Sawtooth, quarter));
+        assert!(at(PulseWaveform::Sawtooth, quarter) < at(PulseWaveform::Sawtooth, half));
+        assert_eq!(at(PulseWaveform::Sawtooth, period), 0.0);
+        // Triangle: peaks at half period, symmetric around it
+        assert!(at(PulseWaveform::Triangle, zero) < at(PulseWaveform::Triangle, quarter));
+        assert_eq!(at(PulseWaveform::Triangle, half), 1.0);
+        assert_eq!(at(PulseWaveform::Triangle, quarter), at(PulseWaveform::Triangle, period * 3 / 4));
+        // Heartbeat: strong beat, weaker beat, then rest
+        assert_eq!(at(PulseWaveform::Heartbeat, zero), 1.0);
+        assert!(at(PulseWaveform::Heartbeat, quarter) > at(PulseWaveform::Heartbeat, half));
+        assert!(at(PulseWaveform::Heartbeat, quarter) < 1.0);
+        assert_eq!(at(PulseWaveform::Heartbeat, half), 0.0);
+    }
+
+    #[test]
+    fn compute_color_uses_configured_waveform() {
+        let mut cfg = default_config();
+        cfg.pulse_waveform = PulseWaveform::Triangle;
+        let half = cfg.pulse_period_ms / 2;
+        // Triangle peaks at half period, where the sine is back at mid level
+        assert_eq!(compute_color(&cfg, AgentState::Working, half), (0, 100, 255));
+        let (_, _, b_start) = compute_color(&cfg, AgentState::Working, 0);
+        assert_eq!(b_start, (255.0 * 0.3) as u8);
+    }
+
+    #[test]
+    fn brightness_scales_final_color() {
+        let mut cfg = default_config();
+        cfg.brightness = 0.5;
+        assert_eq!(compute_color(&cfg, AgentState::Done, 0), (0, 128, 0));
+        assert_eq!(compute_color(&cfg, AgentState::Idle, 0), (128, 70, 0));
+
+        // Idle dims further on top of the global brightness; other states don't
+        cfg.idle_brightness = 0.5;
+        assert_eq!(compute_color(&cfg, AgentState::Idle, 0), (64, 35, 0));
+        assert_eq!(compute_color(&cfg, AgentState::Done, 0), (0, 128, 0));
+
+        // Out-of-range values are clamped
+        cfg.brightness = 3.0;
+        cfg.idle_brightness = -1.0;
+        assert_eq!(compute_color(&cfg, AgentState::Done, 0), (0, 255, 0));
+        assert_eq!(compute_color(&cfg, AgentState::Idle, 0), (0, 0, 0));
+
+        // Override colors (flashes, a restored color) are dimmed the same way
+        cfg.brightness = 0.5;
+        assert_eq!(apply_brightness(&cfg, (255, 0, 0)), (128, 0, 0));
+    }
+
+    #[test]
+    fn blend_two_working_one_error() {
+        let mut cfg = default_config();
+        cfg.error = ColorConfig { r: 255, g: 0, b: 0 };
+        let counts = StateCounts { working: 2, error: 1, ..Default::default() };
+        // At the pulse peak working is full blue (0, 100, 255)
+        let quarter = cfg.pulse_period_ms / 4;
+        assert_eq!(compute_blended_color(&cfg, &counts, quarter), (85, 67, 170));
+
+        // A single state blends to itself; no agents → idle
+        let done = StateCounts { done: 3, ..Default::default() };
+        assert_eq!(compute_blended_color(&cfg, &done, 0), (0, 255, 0));
+        assert_eq!(compute_blended_color(&cfg, &StateCounts::default(), 0), (255, 140, 0));
+    }
+
+    #[test]
+    fn done_blinks_then_holds() {
+        let mut cfg = default_config();
+        cfg.done_animation = DoneAnimation::Blink;
+        cfg.done_cycles = 2;
+        let green = (0, 255, 0);
+        assert_eq!(compute_color(&cfg, AgentState::Done, 0), green);
+        assert_eq!(compute_color(&cfg, AgentState::Done, 249), green);
+        assert_eq!(compute_color(&cfg, AgentState::Done, 250), (0, 0, 0));
+        assert_eq!(compute_color(&cfg, AgentState::Done, 500), green);
+        assert_eq!(compute_color(&cfg, AgentState::Done, 750), (0, 0, 0));
+        // After done_cycles the color holds solid
+        for t in [1000, 1250, 5000] {
+            assert_eq!(compute_color(&cfg, AgentState::Done, t), green, "t={t}ms");
+        }
+    }
+
+    #[test]
+    fn done_fades_then_holds() {
+        let mut cfg = default_config();
+        cfg.done_animation = DoneAnimation::Fade;
+        cfg.done_cycles = 1;
+        assert_eq!(compute_color(&cfg, AgentState::Done, 0), (0, 255, 0));
+        let (_, g_quarter, _) = compute_color(&cfg, AgentState::Done, 125);
+        assert_eq!(compute_color(&cfg, AgentState::Done, 250), (0, 0, 0));
+        assert!(g_quarter > 0 && g_quarter < 255);
+        assert_eq!(compute_color(&cfg, AgentState::Done, 500), (0, 255, 0));
+        // Solid (the default) never animates
+        assert_eq!(compute_color(&default_config(), AgentState::Done, 250), (0, 255, 0));
+    }
+
+    #[test]
+    fn error_flash_is_brief_red() {
+        assert_eq!(error_flash_color(0), Some((255, 0, 0)));
+        assert_eq!(error_flash_color(ERROR_FLASH_MS - 1), Some((255, 0, 0)));
+        assert_eq!(error_flash_color(ERROR_FLASH_MS), None);
+    }
+
+    #[test]
+    fn hsv_primary_hues() {
+        assert_eq!(hsv_to_rgb(0.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(120.0), (0, 255, 0));
+        assert_eq!(hsv_to_rgb(240.0), (0, 0, 255));
+        assert_eq!(hsv_to_rgb(60.0), (255, 255, 0));
+        assert_eq!(hsv_to_rgb(360.0), (255, 0, 0));
+    }
+
+    #[test]
+    fn rainbow_idle_cycles_hue() {
+        let mut cfg = default_config();
+        cfg.idle_mode = IdleMode::Rainbow;
+        cfg.rainbow_period_ms = 3000;
+        assert_eq!(compute_color(&cfg, AgentState::Idle, 0), (255, 0, 0));
+        assert_eq!(compute_color(&cfg, AgentState::Idle, 1000), (0, 255, 0));
+        assert_eq!(compute_color(&cfg, AgentState::Idle, 2000), (0, 0, 255));
+        assert_eq!(compute_color(&cfg, AgentState::Idle, 3000), (255, 0, 0));
+        // Other states keep their configured colors
+        assert_eq!(compute_color(&cfg, AgentState::Done, 1000), (0, 255, 0));
+        assert_eq!(compute_color(&cfg, AgentState::Done, 2000), (0, 255, 0));
+    }
+
+    #[test]
+    fn sleep_after_idle_threshold_and_wake() {
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+        let mut timer = SleepTimer::new(60);
+
+        assert!(!timer.update(AgentState::Idle, false, at(0)));
+        assert!(!timer.update(AgentState::Idle, false, at(59)));
+        assert!(timer.update(AgentState::Idle, false, at(60)));
+
+        // A button press wakes it and restarts the countdown
+        assert!(!timer.update(AgentState::Idle, true, at(70)));
+        assert!(!timer.update(AgentState::Idle, false, at(129)));
+        assert!(timer.update(AgentState::Idle, false, at(130)));
+
+        // Any non-idle state wakes it; back to Idle starts over
+        assert!(!timer.update(AgentState::Working, false, at(200)));
+        assert!(!timer.update(AgentState::Done, false, at(1000)));
+        assert!(!timer.update(AgentState::Idle, false, at(1001)));
+        assert!(timer.update(AgentState::Idle, false, at(1061)));
+    }
+
+    #[test]
+    fn sleep_disabled_at_zero() {
+        let t0 = Instant::now();
+        let mut timer = SleepTimer::new(0);
+        assert!(!timer.update(AgentState::Idle, false, t0));
+        assert!(!timer.update(AgentState::Idle, false, t0 + Duration::from_secs(86_400)));
+    }
+
+    #[test]
+    fn last_color_round_trip_is_one_shot() {
+        let path = std::env::temp_dir().join("ds4cc_test_lightbar_last");
+        save_last_color(&path, (0, 100, 255)).unwrap();
+        assert_eq!(take_last_color(&path), Some((0, 100, 255)));
+        // Consumed on read: a later start doesn't restore a stale color
+        assert_eq!(take_last_color(&path), None);
+
+        std::fs::write(&path, "garbage").unwrap();
+        assert_eq!(take_last_color(&path), None);
+    }
+
 }
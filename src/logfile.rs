@@ -0,0 +1,229 @@
+/// Optional log file (`[logging] file`) alongside the console log.
+///
+/// The daemon usually runs without a visible console (auto-start, tray), so
+/// stderr is easy to lose. With file logging on, every log line is also
+/// appended to `%APPDATA%\ds4cc\ds4cc.log`. Once the file would grow past
+/// `max_size_mb` it rolls over: `ds4cc.log` → `ds4cc.log.1` → `ds4cc.log.2` …,
+/// keeping the newest `keep` old files.
+///
+/// The logger starts before the config is read, so lines logged while
+/// loading it only reach the console.
+
+use crate::config::LoggingConfig;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The open log file, once [`configure`] enabled it.
+static SINK: Mutex<Option<RotatingFile>> = Mutex::new(None);
+
+/// `%APPDATA%\ds4cc\ds4cc.log`; None without `APPDATA`.
+pub fn log_path() -> Option<PathBuf> {
+    let appdata = std::env::var("APPDATA").ok()?;
+    Some(PathBuf::from(format!("{appdata}\\ds4cc\\ds4cc.log")))
+}
+
+/// Start writing log lines to the log file if `[logging] file` is set.
+pub fn configure(cfg: &LoggingConfig) {
+    if !cfg.file {
+        return;
+    }
+    let Some(path) = log_path() else {
+        log::warn!("File logging needs APPDATA — logging to the console only");
+        return;
+    };
+    match RotatingFile::open(&path, cfg.max_size_bytes(), cfg.keep) {
+        Ok(file) => {
+            *SINK.lock().unwrap() = Some(file);
+            log::info!("Logging to {}", path.display());
+        }
+        Err(e) => log::warn!("Failed to open log file {}: {e}", path.display()),
+    }
+}
+
+/// Logger target: stderr, plus the log file when one is configured.
+pub struct Tee;
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _ = io::stderr().write_all(buf);
+        if let Some(file) = SINK.lock().unwrap().as_mut() {
+            // A full disk shouldn't take the console log down with it
+            let _ = file.write_all(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(file) = SINK.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+        io::stderr().flush()
+    }
+}
+
+/// Wait before trying again after a roll-over failed (e.g. another process
+/// has the log open, so Windows refuses to rename it).
+const ROLL_RETRY: Duration = Duration::from_secs(60);
+
+/// A file that rolls over to numbered backups when it would exceed `max_bytes`.
+struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: u32,
+    /// None only while rolling over (Windows can't rename an open file).
+    file: Option<File>,
+    size: u64,
+    /// After a failed roll-over: no new attempt before this.
+    retry_roll_at: Option<Instant>,
+}
+
+/// `ds4cc.log` → `ds4cc.log.<n>`.
+fn backup_path(path: &Path, n: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, creating its directory if needed.
+    fn open(path: &Path, max_bytes: u64, keep: u32) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path: path.to_path_buf(), max_bytes, keep, file: Some(file), size, retry_roll_at: None })
+    }
+
+    /// Roll over (see [`RotatingFile::rotate`]). If that fails, the current
+    /// file is reopened for appending so logging carries on.
+    fn roll(&mut self) -> io::Result<()> {
+        drop(self.file.take());
+        match self.rotate() {
+            Ok(()) => {
+                self.size = 0;
+                Ok(())
+            }
+            Err(e) => {
+                let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+                self.size = file.metadata()?.len();
+                self.file = Some(file);
+                Err(e)
+            }
+        }
+    }
+
+    /// Move the current file aside, shift the backups up by one (dropping the
+    /// oldest), make it `.1`, and start an empty one. The backups are only
+    /// shifted once the current file could be moved, so a failure loses none.
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep > 0 {
+            let staged = backup_path(&self.path, 0);
+            std::fs::rename(&self.path, &staged)?;
+            for n in (1..self.keep).rev() {
+                let _ = std::fs::rename(backup_path(&self.path, n), backup_path(&self.path, n + 1));
+            }
+            std::fs::rename(&staged, backup_path(&self.path, 1))?;
+        }
+        self.file = Some(File::create(&self.path)?);
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Never roll an empty file: a single oversized line still gets written
+        let full = self.size > 0 && self.size + buf.len() as u64 > self.max_bytes;
+        let may_roll = self.retry_roll_at.is_none_or(|at| Instant::now() >= at);
+        if self.file.is_none() || (full && may_roll) {
+            match self.roll() {
+                Ok(()) => self.retry_roll_at = None,
+                // Keep appending to the current file until the next attempt
+                Err(_) if self.file.is_some() => self.retry_roll_at = Some(Instant::now() + ROLL_RETRY),
+                Err(e) => return Err(e),
+            }
+        }
+        let written = self.file.as_mut().map_or(Ok(0), |file| file.write(buf))?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), |file| file.flush())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_rolls_over_past_max_size() {
+        let dir = std::env::temp_dir().join(format!("ds4cc_logfile_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("ds4cc.log");
+        let read = |p: &Path| std::fs::read_to_string(p).unwrap_or_default();
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        file.write_all(b"first\n").unwrap();
+        file.write_all(b"2nd\n").unwrap();
+        assert_eq!(read(&path), "first\n2nd\n");
+        assert!(!backup_path(&path, 1).exists());
+
+        // 10 bytes + 6 would pass the limit → roll before writing
+        file.write_all(b"third\n").unwrap();
+        assert_eq!(read(&path), "third\n");
+        assert_eq!(read(&backup_path(&path, 1)), "first\n2nd\n");
+
+        file.write_all(b"fourth\n").unwrap();
+        file.write_all(b"fifth\n").unwrap();
+        assert_eq!(read(&path), "fifth\n");
+        assert_eq!(read(&backup_path(&path, 1)), "fourth\n");
+        assert_eq!(read(&backup_path(&path, 2)), "third\n");
+        // Only `keep` backups survive
+        assert!(!backup_path(&path, 3).exists());
+
+        // Reopening appends and counts the existing size
+        drop(file);
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        file.write_all(b"sixth\n").unwrap();
+        assert_eq!(read(&path), "sixth\n");
+        assert_eq!(read(&backup_path(&path, 1)), "fifth\n");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_roll_keeps_appending_and_retries_later() {
+        let dir = std::env::temp_dir().join(format!("ds4cc_logfile_roll_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("ds4cc.log");
+        let read = |p: &Path| std::fs::read_to_string(p).unwrap_or_default();
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        file.write_all(b"first\n").unwrap();
+        std::fs::write(backup_path(&path, 1), "old\n").unwrap();
+        // A non-empty directory in the way makes moving the log aside fail
+        std::fs::create_dir_all(backup_path(&path, 0).join("blocker")).unwrap();
+        file.write_all(b"second\n").unwrap();
+        assert_eq!(read(&path), "first\nsecond\n");
+        assert_eq!(read(&backup_path(&path, 1)), "old\n");
+        assert!(!backup_path(&path, 2).exists());
+
+        // No new attempt until the retry time, even once it would succeed
+        std::fs::remove_dir_all(backup_path(&path, 0)).unwrap();
+        file.write_all(b"third\n").unwrap();
+        assert_eq!(read(&path), "first\nsecond\nthird\n");
+
+        file.retry_roll_at = Some(Instant::now());
+        file.write_all(b"fourth\n").unwrap();
+        assert_eq!(read(&path), "fourth\n");
+        assert_eq!(read(&backup_path(&path, 1)), "first\nsecond\nthird\n");
+        assert_eq!(read(&backup_path(&path, 2)), "old\n");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
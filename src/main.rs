@@ -1,36 +1,59 @@
+mod battery;
+mod calibration;
 mod codex_poll;
 mod config;
 mod controller;
+mod crash;
 mod crc32;
+mod detect_cache;
+mod dirwatch;
+mod focus;
+mod gemini_poll;
+mod gesture;
 mod hid;
+mod http;
 mod input;
 mod lightbar;
+mod logfile;
 mod mapper;
 mod mic;
+mod notify;
 mod opencode_detect;
 mod output;
+mod pipe;
 mod rumble;
 mod setup;
+mod sha256;
 mod state;
 mod tmux_detect;
 mod tray;
 mod update;
 mod wsl;
 mod wt_detect;
+mod xinput;
 
 use crate::controller::ConnectionType;
 use crate::output::OutputState;
 use crate::state::AgentState;
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering};
 use std::time::Instant;
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{broadcast, mpsc, watch, Notify};
 use tokio::time::{sleep, Duration};
 
 #[tokio::main]
 async fn main() {
+    // Console commands print to the console and exit (no daemon)
+    let command = std::env::args().skip(1).find_map(|arg| match arg.as_str() {
+        "--diagnose" => Some(CliCommand::Diagnose),
+        "--dump-detect" => Some(CliCommand::DumpDetect),
+        "--list-controllers" => Some(CliCommand::ListControllers),
+        _ => None,
+    });
+
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
         .format(|buf, record| {
             use std::io::Write;
@@ -42,23 +65,39 @@ async fn main() {
             let time_part = time_part.trim_end_matches('Z');
             write!(buf, "{time_part} {:<5} {}\r\n", record.level(), record.args())
         })
+        .target(env_logger::Target::Pipe(Box::new(logfile::Tee)))
         .init();
 
     // Hide console window immediately — app runs as a tray icon.
     // Logs still accumulate; user can show the console via tray menu.
     #[cfg(windows)]
-    unsafe {
-        use windows_sys::Win32::System::Console::GetConsoleWindow;
-        use windows_sys::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
-        let hwnd = GetConsoleWindow();
-        if !hwnd.is_null() {
-            ShowWindow(hwnd, SW_HIDE);
+    if command.is_none() {
+        unsafe {
+            use windows_sys::Win32::System::Console::GetConsoleWindow;
+            use windows_sys::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
+            let hwnd = GetConsoleWindow();
+            if !hwnd.is_null() {
+                ShowWindow(hwnd, SW_HIDE);
+            }
         }
     }
 
     log::info!("DS4CC v2 starting...");
 
-    let cfg = config::Config::load();
+    let cfg = Arc::new(config::Config::load());
+    logfile::configure(&cfg.logging);
+    wsl::configure(&cfg.wsl);
+
+    match command {
+        Some(CliCommand::Diagnose) => return print_diagnostics(&cfg),
+        Some(CliCommand::DumpDetect) => return dump_detect(&cfg),
+        Some(CliCommand::ListControllers) => return list_controllers(),
+        None => {}
+    }
+
+    // From here on the console is hidden: make panics visible
+    crash::install(cfg.logging.file.then(logfile::log_path).flatten());
+
     log::info!("State dir: {}", cfg.state_dir);
 
     // Clean up leftover agent files from previous (possibly crashed) sessions,
@@ -87,7 +126,18 @@ async fn main() {
         }
     }
 
-    // Auto-install Claude Code hooks + OpenCode plugin (first run / after update).
+    // Color saved by the previous instance if it relaunched itself (update/restart).
+    // Shown on the first connection only.
+    let mut restored_color = if cfg.lightbar.restore_last_color {
+        lightbar::init_persistence(std::path::Path::new(&cfg.state_dir))
+    } else {
+        None
+    };
+    if let Some((r, g, b)) = restored_color {
+        log::info!("Restoring last lightbar color ({r}, {g}, {b})");
+    }
+
+    // Auto-install Claude Code hooks, OpenCode plugin and Aider wrapper (first run / after update).
     // Runs in background — startup is not blocked.  Subsequent runs are instant
     // (version stamp check) so there is no recurring overhead.
     tokio::spawn(async {
@@ -95,37 +145,22 @@ async fn main() {
             let mut installed = Vec::new();
             if result.claude_code { installed.push("Claude Code hook"); }
             if result.opencode    { installed.push("OpenCode plugin"); }
+            if result.aider       { installed.push("Aider wrapper"); }
             if !installed.is_empty() {
                 log::info!("Hooks installed: {}. Restart your AI tools to activate.", installed.join(", "));
             }
         }
     });
 
-    // Auto-detect tmux configuration (prefix + key bindings) via WSL
-    let tmux_detected = if cfg.tmux.auto_detect && cfg.tmux.enabled {
-        tmux_detect::detect()
-    } else {
-        None
-    };
-
-    // Auto-detect OpenCode keybinds from ~/.config/opencode/opencode.json via WSL
-    let opencode_detected = if cfg.opencode.auto_detect && cfg.opencode.enabled {
-        opencode_detect::detect()
-    } else {
-        None
-    };
-
-    // Auto-detect Windows Terminal keybindings from settings.json
-    let wt_detected = if cfg.wt.auto_detect && cfg.wt.enabled {
-        wt_detect::detect()
-    } else {
-        None
-    };
+    // Auto-detect tmux / OpenCode / Windows Terminal key bindings
+    let (tmux_detected, opencode_detected, wt_detected) = detect_bindings(&cfg, true);
 
     // Spawn native Codex JSONL poller (reads session files via WSL UNC path)
     if cfg.codex.enabled {
         let state_dir = PathBuf::from(&cfg.state_dir);
         let done_threshold_s = cfg.codex.done_threshold_s;
+        let done_thresholds = cfg.codex.done_thresholds.clone().into_iter().collect();
+        let token_sidecar = cfg.codex.token_sidecar || cfg.codex.token_meter;
         let poll_ms = cfg.poll_interval_ms;
         tokio::spawn(async move {
             // Resolve the WSL sessions path (blocking I/O)
@@ -134,7 +169,31 @@ async fn main() {
                 .ok()
                 .flatten();
             if let Some(dir) = sessions_dir {
-                codex_poll::run(dir, state_dir, done_threshold_s, poll_ms).await;
+                codex_poll::run(
+                    dir,
+                    state_dir,
+                    done_threshold_s,
+                    done_thresholds,
+                    token_sidecar,
+                    poll_ms,
+                )
+                .await;
+            }
+        });
+    }
+
+    // Spawn Gemini CLI session poller (same approach as Codex)
+    if cfg.gemini.enabled {
+        let state_dir = PathBuf::from(&cfg.state_dir);
+        let done_threshold_s = cfg.gemini.done_threshold_s;
+        let poll_ms = cfg.poll_interval_ms;
+        tokio::spawn(async move {
+            let sessions_dir = tokio::task::spawn_blocking(gemini_poll::resolve_sessions_dir)
+                .await
+                .ok()
+                .flatten();
+            if let Some(dir) = sessions_dir {
+                gemini_poll::run(dir, state_dir, done_threshold_s, poll_ms).await;
             }
         });
     }
@@ -142,9 +201,67 @@ async fn main() {
     // Shared mouse mode toggle: false = touchpad, true = left stick.
     // Owned here; cloned into tray thread and each input loop iteration.
     let mouse_stick_active = Arc::new(AtomicBool::new(false));
+    // Bumped by the input loops on any button change; wakes sleeping lightbars
+    let input_activity = Arc::new(AtomicU64::new(0));
+
+    // Foreground passthrough: pause input mapping while a listed app is focused
+    let passthrough_active = Arc::new(AtomicBool::new(false));
+    focus::spawn_watcher(cfg.focus.passthrough_apps.clone(), Arc::clone(&passthrough_active));
+
+    // Latest controller battery reading, shown in the tray tooltip
+    let (battery_tx, battery_rx) = watch::channel(None::<input::BatteryStatus>);
+
+    // State channel (persists across reconnections)
+    let (state_tx, state_rx) = watch::channel(state::AgentSummary::default());
+    // Per-agent states and the active profile, for the HTTP endpoint
+    let (agents_tx, agents_rx) = watch::channel(HashMap::new());
+    let (profile_tx, profile_rx) = watch::channel(mapper::Profile::Default);
+    // What the input controller's buttons resolved to, for `GET /config`
+    let (bindings_tx, bindings_rx) = watch::channel(Vec::new());
+    // Agent the lightbar follows (`focus_agent`, changed from the tray or over HTTP)
+    let focus_agent = Some(cfg.focus_agent.trim().to_string()).filter(|id| !id.is_empty());
+    let (focus_tx, focus_rx) = watch::channel(focus_agent);
+    let focus_tx = Arc::new(focus_tx);
+
+    // Live config: the tray's "Reload config" publishes the re-read file here
+    let (config_tx, config_rx) = watch::channel(Arc::clone(&cfg));
+    // Tray Exit → output loop sends a final all-off report
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
-    // Tray icon
-    let tray_tx = tray::spawn(mapper::Profile::Default, Arc::clone(&mouse_stick_active));
+    // Tray icon (profile picks from its menu go to every input loop)
+    let (tray_request_tx, _) = broadcast::channel(8);
+    let enabled_profiles = [
+        (mapper::Profile::Default, true),
+        (mapper::Profile::Tmux, cfg.tmux.enabled),
+        (mapper::Profile::Agent, cfg.opencode.enabled),
+    ]
+    .into_iter()
+    .filter_map(|(profile, enabled)| enabled.then_some(profile))
+    .collect();
+    let tray_tx = tray::spawn(
+        mapper::Profile::Default,
+        enabled_profiles,
+        Arc::clone(&mouse_stick_active),
+        cfg.update.clone(),
+        cfg.tray.confirm_exit,
+        tray::TrayLinks {
+            battery_rx,
+            agent_rx: state_rx.clone(),
+            agents_rx: agents_rx.clone(),
+            focus_tx: Arc::clone(&focus_tx),
+            requests: tray_request_tx.clone(),
+            config_tx,
+            shutdown_tx,
+        },
+    );
+
+    // Background update check: silent unless an update is available
+    if cfg.update.enabled && cfg.update.check_on_startup {
+        let update_cfg = cfg.update.clone();
+        let _ = std::thread::Builder::new()
+            .name("update-check".into())
+            .spawn(move || update::check_for_update(&update_cfg, true));
+    }
 
     // Initialize HID
     let mut api = match hidapi::HidApi::new() {
@@ -155,298 +272,903 @@ async fn main() {
         }
     };
 
-    // State channel (persists across reconnections)
-    let (state_tx, state_rx) = watch::channel(AgentState::Idle);
-    // Per-agent rumble channels (Arc<Mutex> so they survive reconnections)
-    let (idle_reminder_tx, idle_reminder_rx) = mpsc::channel::<()>(4);
-    let (done_rumble_tx, done_rumble_rx) = mpsc::channel::<()>(4);
-    let idle_reminder_rx = Arc::new(tokio::sync::Mutex::new(idle_reminder_rx));
-    let done_rumble_rx = Arc::new(tokio::sync::Mutex::new(done_rumble_rx));
+    // Per-agent rumble channels (state poller → event fan-out)
+    let (idle_reminder_tx, mut idle_reminder_rx) = mpsc::channel::<u8>(4);
+    let (done_rumble_tx, mut done_rumble_rx) = mpsc::channel::<()>(4);
+    let (error_tx, mut error_rx) = mpsc::channel::<()>(4);
+    let (all_clear_tx, mut all_clear_rx) = mpsc::channel::<()>(4);
+    // Acknowledge a pending Done (shake gesture → state poller)
+    let (ack_tx, ack_rx) = mpsc::channel::<()>(4);
+    // Low-battery warning (battery watcher → event fan-out)
+    let (low_battery_tx, mut low_battery_rx) = mpsc::channel::<()>(4);
+    if cfg.battery.enabled {
+        // The latch lives here so it persists across reconnections
+        let mut battery_rx = battery_tx.subscribe();
+        let mut latch = battery::LowBatteryLatch::new(cfg.battery.warn_pct);
+        tokio::spawn(async move {
+            while battery_rx.changed().await.is_ok() {
+                let reading = *battery_rx.borrow_and_update();
+                if latch.update(reading) {
+                    log::warn!("Controller battery low ({}%)", reading.map_or(0, |b| b.percent));
+                    let _ = low_battery_tx.try_send(());
+                }
+            }
+        });
+    }
+    if cfg.http.enabled {
+        let sources = http::StatusSources {
+            state_rx: state_rx.clone(),
+            profile_rx,
+            agents_rx,
+            battery_rx: battery_tx.subscribe(),
+            bindings_rx,
+            focus_tx,
+        };
+        match http::spawn_server(cfg.http.port, sources) {
+            Ok(()) => log::info!("Serving agent state on http://127.0.0.1:{}/state", cfg.http.port),
+            Err(e) => log::warn!("HTTP endpoint unavailable on port {}: {e}", cfg.http.port),
+        }
+    }
+    let battery = battery::BatteryBoard::new(battery_tx);
+
+    // Fan the rumble/flash signals out to every connected controller's output loop
+    let (events_tx, _) = broadcast::channel::<OutputEvent>(16);
+    let fan_out = events_tx.clone();
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                Some(level) = idle_reminder_rx.recv() => OutputEvent::IdleReminder(level),
+                Some(()) = done_rumble_rx.recv() => OutputEvent::Done,
+                Some(()) = error_rx.recv() => OutputEvent::Error,
+                Some(()) = all_clear_rx.recv() => OutputEvent::AllClear,
+                Some(()) = low_battery_rx.recv() => OutputEvent::LowBattery,
+                else => break,
+            };
+            // No controller connected → nothing to rumble
+            let _ = fan_out.send(event);
+        }
+    });
 
     // Spawn state poller (scans ds4cc_agent_* files in state_dir)
     let state_dir = PathBuf::from(&cfg.state_dir);
-    let poll_ms = cfg.poll_interval_ms;
-    let idle_timeout_s = cfg.idle_timeout_s;
-    let stale_timeout_s = cfg.stale_timeout_s;
-    let idle_reminder_s = cfg.idle_reminder_s;
-    let subagent_filter_s = cfg.subagent_filter_s;
+    let poll_settings = state::PollSettings {
+        poll_ms: cfg.poll_interval_ms,
+        idle_timeout_s: cfg.idle_timeout_s,
+        stale_timeout_s: cfg.stale_timeout_s,
+        idle_reminder_s: cfg.idle_reminder_s,
+        idle_reminder_adaptive: cfg.idle_reminder.adaptive,
+        idle_reminder_reference_s: cfg.idle_reminder.reference_work_s,
+        idle_reminder_max_scale: cfg.idle_reminder.max_scale,
+        done_threshold_ms: WORKING_DONE_MIN_MS,
+        subagent_filter_s: cfg.subagent_filter_s,
+        all_clear: cfg.rumble.all_clear,
+        all_clear_min_agents: cfg.rumble.all_clear_min_agents,
+        on_error: cfg.rumble.on_error,
+        toast: notify::ToastSettings {
+            done: cfg.notifications.toast && cfg.notifications.done,
+            error: cfg.notifications.toast && cfg.notifications.error,
+            idle_reminder: cfg.notifications.toast && cfg.notifications.idle_reminder,
+            debounce_s: cfg.notifications.debounce_s,
+        },
+        pipe: cfg.pipe.enabled,
+    };
     tokio::spawn(async move {
-        state::poll_state_file(state_dir, poll_ms, idle_timeout_s, stale_timeout_s, idle_reminder_s, WORKING_DONE_MIN_MS, subagent_filter_s, state_tx, idle_reminder_tx, done_rumble_tx).await;
+        let signals = state::PollSignals {
+            idle_reminder: idle_reminder_tx,
+            done_rumble: done_rumble_tx,
+            error: error_tx,
+            all_clear: all_clear_tx,
+            agents: agents_tx,
+            focus: focus_rx,
+        };
+        state::poll_state_file(state_dir, poll_settings, state_tx, signals, ack_rx).await;
     });
 
-    // Main connection loop — reconnects on disconnect
+    // One writer for "Calibrate sticks", so saves never race on the config file
+    let (calibration_tx, mut calibration_rx) = mpsc::channel::<config::CalibrationConfig>(4);
+    tokio::spawn(async move {
+        while let Some(centers) = calibration_rx.recv().await {
+            let saved = tokio::task::spawn_blocking(move || config::save_calibration(&config::config_file_path(), &centers)).await;
+            if let Ok(Err(e)) = saved {
+                log::warn!("Failed to save stick calibration: {e}");
+            }
+        }
+    });
+
+    // Everything a controller session shares with the rest of the daemon
+    let shared = SessionShared {
+        config_rx,
+        shutdown_rx,
+        state_rx,
+        events_tx,
+        tray_tx: tray_tx.clone(),
+        tray_requests: tray_request_tx,
+        detected: Arc::new((tmux_detected, opencode_detected, wt_detected)),
+        mouse_stick_active,
+        passthrough_active,
+        input_activity,
+        ack_tx,
+        battery,
+        profile_tx: Arc::new(profile_tx),
+        bindings_tx: Arc::new(bindings_tx),
+        calibration_claim: Arc::new(AtomicU64::new(0)),
+        calibration_tx,
+        rescan: Arc::new(Notify::new()),
+    };
+
+    // Open-retry backoff per controller that is found but can't be opened
+    let mut open_backoff: HashMap<String, (hid::OpenBackoff, Instant)> = HashMap::new();
+    let mut busy_warning = false;
+    // One session (input + output loop) per connected controller, keyed by HID path
+    let mut sessions: HashMap<String, Session> = HashMap::new();
+    // Bluetooth MACs read from USB controllers, by HID path
+    let mut macs = HashMap::new();
+    let mut none_logged = false;
+
+    // Supervisor loop: start a session for every new controller, stop the
+    // ones that went away (or that a USB connection supersedes)
     loop {
-        // Find controller (USB priority: find_all_controllers returns USB first)
-        let (info, device, bt_paired) = loop {
-            if let Err(e) = api.refresh_devices() {
-                log::debug!("HID refresh failed: {e}");
+        sessions.retain(|_, session| !session.task.is_finished());
+
+        if let Err(e) = api.refresh_devices() {
+            log::debug!("HID refresh failed: {e}");
+        }
+        let found = hid::find_all_controllers(&api, &mut macs);
+        let wanted = controller::select_devices(&found);
+        // Xbox pads are only used while no Sony controller is present
+        // (DS4Windows and Steam Input mirror Sony controllers as XInput pads)
+        let pads = if wanted.is_empty() { xinput::connected_pads() } else { Vec::new() };
+        let pad_paths: Vec<String> = pads.iter().map(|&index| xinput::pad_path(index)).collect();
+        let wanted_paths: Vec<&str> = wanted
+            .iter()
+            .map(|c| c.path.as_str())
+            .chain(pad_paths.iter().map(String::as_str))
+            .collect();
+        let diff = controller::diff_devices(sessions.keys(), &wanted_paths);
+
+        for path in &diff.removed {
+            if let Some(session) = sessions.remove(path) {
+                log::info!("Controller superseded or gone — stopping its session");
+                session.stop.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let cfg = shared.config_rx.borrow().clone();
+        for (order, info) in wanted.iter().enumerate() {
+            if !diff.added.contains(&info.path) {
+                continue;
+            }
+            if open_backoff.get(&info.path).is_some_and(|(_, retry_at)| Instant::now() < *retry_at) {
+                continue;
             }
-            let all = hid::find_all_controllers(&api);
-            let has_bt = all.iter().any(|c| c.connection_type == ConnectionType::Bluetooth);
-            match all.into_iter().next() {
-                Some(info) => match hid::open_device(&api, &info) {
-                    Ok(dev) => break (info, dev, has_bt),
-                    Err(e) => {
-                        log::warn!("Found controller but failed to open: {e}");
+            let device = match hid::open_device(&api, info) {
+                Ok(device) => device,
+                Err(e) => {
+                    let (backoff, retry_at) = open_backoff.entry(info.path.clone()).or_insert_with(|| {
+                        let backoff = hid::OpenBackoff::new(
+                            cfg.hid.open_retry_initial_ms,
+                            cfg.hid.open_retry_max_ms,
+                            cfg.hid.open_give_up_after,
+                        );
+                        (backoff, Instant::now())
+                    });
+                    let delay = backoff.record_failure(&info.path);
+                    *retry_at = Instant::now() + delay;
+                    log::warn!(
+                        "Found controller but failed to open (attempt {}): {e}. Retrying in {:.1}s...",
+                        backoff.failures(),
+                        delay.as_secs_f32()
+                    );
+                    if backoff.just_gave_up() {
+                        log::error!(
+                            "{} ({}) still can't be opened after {} attempts — the device may be \
+                             claimed exclusively by Steam or DS4Windows. Close it (or disable Steam \
+                             Input for this controller); DS4CC keeps retrying.",
+                            info.controller_type,
+                            info.connection_type,
+                            backoff.failures()
+                        );
+                        busy_warning = true;
+                        let _ = tray_tx.send(tray::TrayCmd::Warning(Some(
+                            "Controller busy — close Steam/DS4Windows".into(),
+                        )));
                     }
-                },
-                None => {
-                    log::info!("No controller found. Retrying in 2s...");
+                    continue;
                 }
+            };
+            if let Some((backoff, _)) = open_backoff.get_mut(&info.path) {
+                backoff.reset();
             }
-            sleep(Duration::from_secs(2)).await;
-        };
 
+            let role = controller::resolve_role(&cfg.controllers, &info.serial, order);
+            let stop = Arc::new(AtomicBool::new(false));
+            let task = tokio::spawn(run_session(
+                (*info).clone(),
+                device,
+                role,
+                restored_color.take(),
+                shared.clone(),
+                Arc::clone(&stop),
+            ));
+            sessions.insert(info.path.clone(), Session { stop, task });
+        }
+
+        for (order, (&index, path)) in pads.iter().zip(&pad_paths).enumerate() {
+            if !diff.added.contains(path) {
+                continue;
+            }
+            let role = controller::resolve_role(&cfg.controllers, "", order);
+            let stop = Arc::new(AtomicBool::new(false));
+            let task = tokio::spawn(run_xinput_session(index, role, shared.clone(), Arc::clone(&stop)));
+            sessions.insert(path.clone(), Session { stop, task });
+        }
+
+        // Forget failures of controllers that went away; clear the warning
+        // once nothing is stuck
+        open_backoff.retain(|path, _| found.iter().any(|c| &c.path == path));
+        if busy_warning && open_backoff.values().all(|(b, _)| b.failures() == 0) {
+            busy_warning = false;
+            let _ = tray_tx.send(tray::TrayCmd::Warning(None));
+        }
+
+        let none_found = found.is_empty() && pads.is_empty();
+        if none_found && !none_logged {
+            log::info!("No controller found. Waiting for one to connect...");
+        }
+        none_logged = none_found;
+
+        // Rescan on a timer, or right away when a session ends
+        tokio::select! {
+            _ = sleep(cfg.hid.reconnect_scan_interval()) => {}
+            _ = shared.rescan.notified() => {}
+        }
+    }
+}
+
+/// Signals from the state poller and battery watcher, fanned out to every
+/// controller's output loop.
+#[derive(Debug, Clone, Copy)]
+enum OutputEvent {
+    /// Idle reminder level (1 = first, higher = ignored for longer).
+    IdleReminder(u8),
+    Done,
+    Error,
+    AllClear,
+    LowBattery,
+}
+
+/// Channels and shared state every controller session gets a copy of.
+#[derive(Clone)]
+struct SessionShared {
+    config_rx: watch::Receiver<Arc<config::Config>>,
+    shutdown_rx: watch::Receiver<bool>,
+    state_rx: watch::Receiver<state::AgentSummary>,
+    events_tx: broadcast::Sender<OutputEvent>,
+    tray_tx: std::sync::mpsc::Sender<tray::TrayCmd>,
+    tray_requests: broadcast::Sender<tray::TrayRequest>,
+    detected: Arc<DetectedBindings>,
+    mouse_stick_active: Arc<AtomicBool>,
+    passthrough_active: Arc<AtomicBool>,
+    input_activity: Arc<AtomicU64>,
+    ack_tx: mpsc::Sender<()>,
+    battery: battery::BatteryBoard,
+    /// Active profile of the input loops, for the HTTP endpoint.
+    profile_tx: Arc<watch::Sender<mapper::Profile>>,
+    /// Resolved bindings of the input controller, for the HTTP endpoint.
+    bindings_tx: Arc<watch::Sender<Vec<mapper::ResolvedBinding>>>,
+    /// Newest "Calibrate sticks" request an input loop has taken.
+    calibration_claim: Arc<AtomicU64>,
+    /// Measured stick centers, saved to the config file one at a time.
+    calibration_tx: mpsc::Sender<config::CalibrationConfig>,
+    /// Wakes the supervisor when a session ends.
+    rescan: Arc<Notify>,
+}
+
+/// Key bindings found by tmux / OpenCode / Windows Terminal auto-detection.
+type DetectedBindings = (
+    Option<tmux_detect::TmuxDetected>,
+    Option<opencode_detect::OpenCodeDetected>,
+    Option<wt_detect::WtDetected>,
+);
+
+/// A running controller session.
+struct Session {
+    /// Set to end the session (its controller is superseded by USB).
+    stop: Arc<AtomicBool>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// One connected controller: its output loop runs alongside its input loop
+/// until the device disconnects or `stop` is set.
+async fn run_session(
+    info: controller::ControllerInfo,
+    device: hidapi::HidDevice,
+    role: controller::ControllerRole,
+    restore_color: Option<(u8, u8, u8)>,
+    shared: SessionShared,
+    stop: Arc<AtomicBool>,
+) {
+    let (ct, conn) = (info.controller_type, info.connection_type);
+    log::info!("Connected: {ct} ({conn})");
+    if role == controller::ControllerRole::StatusOnly {
         log::info!(
-            "Connected: {} ({})",
-            info.controller_type,
-            info.connection_type
+            "Status-only controller ([controllers] input = \"{}\") — input mapping disabled",
+            shared.config_rx.borrow().controllers.input
         );
-        if bt_paired && info.connection_type == ConnectionType::Usb {
-            log::info!("Bluetooth also paired — will serve as fallback if USB is disconnected");
+    }
+
+    // Activate BT extended mode (Switch Pro: full report mode) if needed
+    if ct == controller::ControllerType::SwitchPro {
+        if let Err(e) = hid::activate_switch_pro_full_mode(&device, conn) {
+            log::error!("Failed to set Switch Pro report mode: {e}");
+        }
+    } else if conn == ConnectionType::Bluetooth {
+        if let Err(e) = hid::activate_bt_extended_mode(&device, ct) {
+            log::error!("Failed to activate BT extended mode: {e}");
+            log::error!("Controller may not work correctly over Bluetooth.");
         }
+    }
 
-        // Activate BT extended mode if needed
-        if info.connection_type == ConnectionType::Bluetooth {
-            if let Err(e) = hid::activate_bt_extended_mode(&device, info.controller_type) {
-                log::error!("Failed to activate BT extended mode: {e}");
-                log::error!("Controller may not work correctly over Bluetooth.");
-            }
+    // Controllers start in touchpad mode (their native pointer input).
+    let _ = shared.tray_tx.send(tray::TrayCmd::SetStickMode(false));
+
+    let handle = hid::HidHandle::new(device);
+
+    // Player indicator LED state (AtomicU8 so both loops can read/write it).
+    // Starts at Player 1; the input loop switches it to a restored profile.
+    let player_leds = Arc::new(AtomicU8::new(PLAYER1_LEDS));
+    // Profile switch confirmation (input loop → output loop)
+    let (profile_rumble_tx, profile_rumble_rx) = mpsc::channel::<()>(4);
+
+    let output_handle = handle.clone_handle();
+    let output_shared = shared.clone();
+    let player_leds_out = Arc::clone(&player_leds);
+    let output_stop = Arc::clone(&stop);
+    let output_task = tokio::spawn(async move {
+        let lost = run_output_loop(output_handle, ct, conn, &output_shared, restore_color, player_leds_out, profile_rumble_rx).await;
+        if lost {
+            output_stop.store(true, Ordering::Relaxed);
         }
+    });
 
-        // DS4 has no touchpad parsing — auto-enable stick mouse mode.
-        // DualSense switches back to touchpad mode (its native input).
-        let stick = info.controller_type.is_ds4();
-        let _ = tray_tx.send(tray::TrayCmd::SetStickMode(stick));
-
-        let handle = hid::HidHandle::new(device);
-        let ct = info.controller_type;
-        let conn = info.connection_type;
-
-        // If connected over Bluetooth, spawn a background USB scanner thread.
-        // It sets `usb_available` when a USB controller appears so the input loop
-        // can exit and the main loop re-scans (picking USB with higher priority).
-        let (usb_available, scanner_stop): (Option<Arc<AtomicBool>>, Option<Arc<AtomicBool>>) =
-            if conn == ConnectionType::Bluetooth {
-                let flag = Arc::new(AtomicBool::new(false));
-                let stop = Arc::new(AtomicBool::new(false));
-                let flag_clone = Arc::clone(&flag);
-                let stop_clone = Arc::clone(&stop);
-                let _ = std::thread::Builder::new()
-                    .name("usb-scanner".into())
-                    .spawn(move || {
-                        let Ok(mut scanner_api) = hidapi::HidApi::new() else {
-                            log::warn!("USB scanner: failed to create HidApi instance");
-                            return;
-                        };
-                        loop {
-                            std::thread::sleep(std::time::Duration::from_secs(5));
-                            if stop_clone.load(Ordering::Relaxed) {
-                                log::debug!("USB scanner: stop signal received");
-                                return;
-                            }
-                            if let Err(e) = scanner_api.refresh_devices() {
-                                log::debug!("USB scanner refresh failed: {e}");
-                                continue;
-                            }
-                            if hid::has_usb_controller(&scanner_api) {
-                                log::info!("USB scanner: USB controller detected, signaling switch");
-                                flag_clone.store(true, Ordering::Relaxed);
-                                return;
-                            }
-                        }
-                    });
-                (Some(flag), Some(stop))
-            } else {
-                (None, None)
-            };
+    // Returns when the device disconnects or `stop` is set
+    let source = InputSource::Hid { handle, ct, conn };
+    run_input_loop(source, &info.path, role, &shared, player_leds, profile_rumble_tx, &stop).await;
 
-        // Shared player indicator LED state (AtomicU8 so both loops can read/write it).
-        // Start at Player 1 (Default profile) on every connection.
-        let player_leds = Arc::new(AtomicU8::new(PLAYER1_LEDS));
-
-        // Spawn output loop for this connection
-        let output_handle = handle.clone_handle();
-        let lightbar_cfg_clone = cfg.lightbar.clone();
-        let mut state_rx_output = state_rx.clone();
-        let player_leds_out = Arc::clone(&player_leds);
-        let idle_rx = Arc::clone(&idle_reminder_rx);
-        let done_rx = Arc::clone(&done_rumble_rx);
-        let output_task = tokio::spawn(async move {
-            run_output_loop(output_handle, ct, conn, lightbar_cfg_clone, &mut state_rx_output, player_leds_out, idle_rx, done_rx).await;
-        });
+    output_task.abort();
+    log::info!("Disconnected: {ct} ({conn})");
+    shared.battery.report(&info.path, None);
+    shared.rescan.notify_one();
+}
+
+/// An Xbox pad in XInput slot `index`: input only, as it has no lightbar,
+/// player LEDs or touchpad to drive.
+async fn run_xinput_session(
+    index: u32,
+    role: controller::ControllerRole,
+    shared: SessionShared,
+    stop: Arc<AtomicBool>,
+) {
+    log::info!("Connected: Xbox controller (XInput slot {})", index + 1);
+    if role == controller::ControllerRole::StatusOnly {
+        log::info!("Xbox controller has no status outputs — mapping disabled by [controllers] input");
+    }
+    let _ = shared.tray_tx.send(tray::TrayCmd::SetStickMode(false));
+
+    // Nothing reads these: no player LEDs, no profile-switch rumble
+    let player_leds = Arc::new(AtomicU8::new(PLAYER1_LEDS));
+    let (profile_rumble_tx, _) = mpsc::channel::<()>(1);
+
+    let path = xinput::pad_path(index);
+    run_input_loop(InputSource::XInput(index), &path, role, &shared, player_leds, profile_rumble_tx, &stop).await;
+
+    log::info!("Disconnected: Xbox controller (XInput slot {})", index + 1);
+    shared.rescan.notify_one();
+}
+
+/// Run key-binding auto-detection for every enabled integration.
+/// With `use_cache`, results cached under unchanged source files are reused
+/// and fresh results are saved (see `detect_cache`).
+fn detect_bindings(cfg: &config::Config, use_cache: bool) -> DetectedBindings {
+    let cache = if use_cache { detect_cache::cache_path() } else { None }.map(|path| {
+        let settings = format!(
+            "wsl.distro={}\ntmux.key_table={}\ntmux.root_bindings={}",
+            cfg.wsl.distro, cfg.tmux.key_table, cfg.tmux.root_bindings
+        );
+        let key = detect_cache::cache_key(&detect_cache::source_mtimes(), &settings);
+        let cached = detect_cache::load(&path, &key);
+        (path, key, cached)
+    });
+    let cached = cache.as_ref().and_then(|(_, _, cached)| cached.clone()).unwrap_or_default();
+    if cache.as_ref().is_some_and(|(_, _, cached)| cached.is_some()) {
+        log::info!("Using cached key-binding detection");
+    }
+    let mut found_fresh = false;
+
+    // Auto-detect tmux configuration (prefix + key bindings) via WSL
+    let tmux_detected = if cfg.tmux.auto_detect && cfg.tmux.enabled {
+        cached.tmux.or_else(|| {
+            let detected = tmux_detect::detect(&cfg.tmux.key_table, cfg.tmux.root_bindings);
+            found_fresh |= detected.is_some();
+            detected
+        })
+    } else {
+        None
+    };
+
+    // Auto-detect OpenCode keybinds from ~/.config/opencode/opencode.json via WSL
+    let opencode_detected = if cfg.opencode.auto_detect && cfg.opencode.enabled {
+        cached.opencode.or_else(|| {
+            let detected = opencode_detect::detect();
+            found_fresh |= detected.is_some();
+            detected
+        })
+    } else {
+        None
+    };
+
+    // Auto-detect Windows Terminal keybindings from settings.json
+    let wt_detected = if cfg.wt.auto_detect && cfg.wt.enabled {
+        cached.wt.or_else(|| {
+            let detected = wt_detect::detect();
+            found_fresh |= detected.is_some();
+            detected
+        })
+    } else {
+        None
+    };
+
+    if let Some((path, key, _)) = cache.filter(|_| found_fresh) {
+        let entry = detect_cache::DetectCache::new(
+            tmux_detected.clone(),
+            opencode_detected.clone(),
+            wt_detected.clone(),
+        );
+        if let Err(e) = detect_cache::save(&path, &key, entry) {
+            log::warn!("Failed to save {}: {e}", path.display());
+        }
+    }
+
+    (tmux_detected, opencode_detected, wt_detected)
+}
+
+/// One-shot command-line modes.
+enum CliCommand {
+    /// `--diagnose`: print what every button resolved to.
+    Diagnose,
+    /// `--dump-detect`: print the auto-detected action → key maps.
+    DumpDetect,
+    /// `--list-controllers`: print every HID device and whether it matched.
+    ListControllers,
+}
+
+/// `--list-controllers`: print every HID collection with its IDs, usage and
+/// name strings, and whether it was recognized as a controller.
+fn list_controllers() {
+    let api = match hidapi::HidApi::new() {
+        Ok(api) => api,
+        Err(e) => {
+            println!("Failed to initialize HID API: {e}");
+            return;
+        }
+    };
+    println!("DS4CC v{} — HID devices", env!("CARGO_PKG_VERSION"));
+    println!("VID:PID    page/usage  {:<40}  matched", "manufacturer / product");
+    for row in hid::list_devices(&api) {
+        println!("{row}");
+    }
+    let found = hid::find_all_controllers(&api, &mut HashMap::new());
+    println!("\n{} supported controller(s) found", found.len());
+    for info in &found {
+        println!("  {} ({}) {}", info.controller_type, info.connection_type, info.path);
+    }
+}
+
+/// `--dump-detect`: run tmux / OpenCode / Windows Terminal detection and
+/// print each detector's raw action → key map.
+fn dump_detect(cfg: &config::Config) {
+    use mapper::{format_action_table, format_key_combo};
+    use opencode_detect::ActionBinding;
+
+    let (tmux_detected, opencode_detected, wt_detected) = detect_bindings(cfg, false);
+    let combo = |keys: &Vec<mapper::VKey>| format_key_combo(keys);
+    println!("DS4CC v{} — detected bindings", env!("CARGO_PKG_VERSION"));
+
+    match &tmux_detected {
+        Some(tmux) => {
+            let prefix = tmux.bound_prefix().map_or_else(|| "none".to_string(), |p| format_key_combo(p));
+            println!("\n[tmux] prefix {prefix}");
+            print!("{}", format_action_table(tmux.actions(), combo));
+            if !tmux.root_actions().is_empty() {
+                println!("\n[tmux root]");
+                print!("{}", format_action_table(tmux.root_actions(), combo));
+            }
+            if let Some(table) = &tmux.key_table {
+                println!("\n[tmux table {}] entered with {}", table.name, format_key_combo(&table.enter_key));
+                print!("{}", format_action_table(table.actions(), combo));
+            }
+        }
+        None => println!("\n[tmux] not detected"),
+    }
 
-        // Run input loop — returns when device disconnects or USB scanner signals
-        run_input_loop(handle, ct, conn, &cfg.scroll, &cfg.stick_mouse, &cfg.touchpad, &cfg.tmux, tmux_detected.as_ref(), &cfg.opencode, opencode_detected.as_ref(), &cfg.wt, wt_detected.as_ref(), &tray_tx, Arc::clone(&player_leds), Arc::clone(&mouse_stick_active), usb_available.clone()).await;
+    match &opencode_detected {
+        Some(opencode) => {
+            let leader = opencode.leader.as_ref().map_or_else(|| "none".to_string(), |l| format_key_combo(l));
+            println!("\n[opencode] leader {leader}");
+            print!(
+                "{}",
+                format_action_table(opencode.actions(), |binding| match binding {
+                    ActionBinding::Combo(keys) => format_key_combo(keys),
+                    ActionBinding::LeaderKey(keys) => format!("<leader> {}", format_key_combo(keys)),
+                    ActionBinding::LeaderSequence(steps) => {
+                        let steps: Vec<String> = steps.iter().map(|keys| format_key_combo(keys)).collect();
+                        format!("<leader> {}", steps.join(", "))
+                    }
+                })
+            );
+        }
+        None => println!("\n[opencode] not detected"),
+    }
 
-        // Input loop exited — cancel output task and stop USB scanner
-        output_task.abort();
-        if let Some(ref stop) = scanner_stop {
-            stop.store(true, Ordering::Relaxed);
+    match &wt_detected {
+        Some(wt) => {
+            println!("\n[wt]");
+            print!("{}", format_action_table(wt.actions(), combo));
         }
+        None => println!("\n[wt] not detected"),
+    }
+}
 
-        // Determine why the input loop exited and reconnect accordingly
-        let switching_to_usb = usb_available
-            .as_ref()
-            .is_some_and(|f| f.load(Ordering::Relaxed));
+/// `--diagnose`: run detection and print what every button resolved to.
+fn print_diagnostics(cfg: &config::Config) {
+    let (tmux_detected, opencode_detected, wt_detected) = detect_bindings(cfg, false);
+    let found = |detected: bool| if detected { "detected" } else { "not detected (defaults)" };
+    println!("DS4CC v{} — resolved bindings", env!("CARGO_PKG_VERSION"));
+    println!("tmux:             {}", found(tmux_detected.is_some()));
+    println!("OpenCode:         {}", found(opencode_detected.is_some()));
+    println!("Windows Terminal: {}", found(wt_detected.is_some()));
 
-        if switching_to_usb {
-            log::info!("Switching to USB controller...");
-            // No sleep — USB is already present, re-scan will find it immediately
-        } else if conn == ConnectionType::Usb {
-            log::info!("USB disconnected. Scanning for Bluetooth fallback...");
-            sleep(Duration::from_millis(200)).await;
-        } else {
-            log::info!("Controller disconnected. Scanning for new connection...");
-            sleep(Duration::from_secs(1)).await;
+    let detected = mapper::Detected {
+        tmux: tmux_detected.as_ref(),
+        opencode: opencode_detected.as_ref(),
+        wt: wt_detected.as_ref(),
+    };
+    let mapper = mapper::MapperState::new(
+        cfg,
+        detected,
+        Arc::new(AtomicBool::new(false)),
+        Arc::new(AtomicBool::new(false)),
+        None,
+    );
+    let mut scope = None;
+    for binding in mapper.resolved_bindings() {
+        if scope != Some(binding.scope) {
+            println!("\n[{}]", binding.scope);
+            scope = Some(binding.scope);
         }
+        println!("  {:<18} {}", binding.button, binding.action);
     }
 }
 
-/// Input loop: read HID reports, parse, map to keystrokes.
-/// Returns when the device disconnects or `usb_switch_flag` is set (BT→USB switch).
+/// Where a session's input comes from.
+enum InputSource {
+    /// A Sony controller's HID input reports.
+    Hid {
+        handle: hid::HidHandle,
+        ct: controller::ControllerType,
+        conn: controller::ConnectionType,
+    },
+    /// An XInput (Xbox) pad, polled by user slot.
+    XInput(u32),
+}
+
+/// Input loop: read HID reports (or poll XInput), parse, map to keystrokes.
+/// Returns when the device disconnects or `stop` is set (superseded by USB,
+/// or the output loop gave up on a controller that stopped taking writes).
 async fn run_input_loop(
-    handle: hid::HidHandle,
-    ct: controller::ControllerType,
-    conn: controller::ConnectionType,
-    scroll_cfg: &config::ScrollConfig,
-    stick_mouse_cfg: &config::StickMouseConfig,
-    touchpad_cfg: &config::TouchpadConfig,
-    tmux_cfg: &config::TmuxConfig,
-    tmux_detected: Option<&tmux_detect::TmuxDetected>,
-    opencode_cfg: &config::OpenCodeConfig,
-    opencode_detected: Option<&opencode_detect::OpenCodeDetected>,
-    wt_cfg: &config::WtConfig,
-    wt_detected: Option<&wt_detect::WtDetected>,
-    tray_tx: &std::sync::mpsc::Sender<tray::TrayCmd>,
+    source: InputSource,
+    path: &str,
+    role: controller::ControllerRole,
+    shared: &SessionShared,
     player_leds: Arc<AtomicU8>,
-    mouse_stick_active: Arc<AtomicBool>,
-    usb_switch_flag: Option<Arc<AtomicBool>>,
+    profile_rumble_tx: mpsc::Sender<()>,
+    stop: &AtomicBool,
 ) {
+    // Only the DualSense has a mic (and a mute button)
+    let has_mic = matches!(&source, InputSource::Hid { ct, .. } if ct.is_dualsense());
+    let (tmux_detected, opencode_detected, wt_detected) = &*shared.detected;
+    let tray_tx = &shared.tray_tx;
+    let mut config_rx = shared.config_rx.clone();
+    let mut tray_requests = shared.tray_requests.subscribe();
+    let cfg = config_rx.borrow_and_update().clone();
+    let mut profile_feedback = cfg.rumble.profile_feedback;
+    let mut prefer_controller_mic = cfg.mic.prefer_controller;
+    let read_timeout_ms = cfg.hid.read_timeout_ms();
+    // Shake-to-ack runs for every role: a status-only controller can dismiss Done too
+    let mut shake = cfg.gestures.shake_to_ack.then(|| {
+        gesture::ShakeDetector::new(
+            cfg.gestures.shake_threshold_g,
+            cfg.gestures.shake_peaks,
+            cfg.gestures.shake_window_ms,
+        )
+    });
+    let gestures_paused = &shared.passthrough_active;
+    let detected = mapper::Detected {
+        tmux: tmux_detected.as_ref(),
+        opencode: opencode_detected.as_ref(),
+        wt: wt_detected.as_ref(),
+    };
     let mut mapper_state = mapper::MapperState::new(
-        scroll_cfg,
-        stick_mouse_cfg,
-        touchpad_cfg,
-        tmux_cfg,
-        tmux_detected,
-        opencode_cfg,
-        opencode_detected,
-        wt_cfg,
-        wt_detected,
-        mouse_stick_active,
+        &cfg,
+        detected,
+        Arc::clone(&shared.mouse_stick_active),
+        Arc::clone(&shared.passthrough_active),
+        Some(std::path::Path::new(&cfg.state_dir)),
     );
+    mapper_state.set_calibration(&cfg.calibration);
+    if role != controller::ControllerRole::StatusOnly {
+        shared.bindings_tx.send_replace(mapper_state.resolved_bindings());
+    }
+    // Set while "Calibrate sticks" is sampling the resting sticks
+    let mut calibrating: Option<calibration::StickSampler> = None;
     let mut buf = [0u8; 128];
     let mut consecutive_errors = 0u32;
     let mut first_report = true;
     let mut last_profile = mapper_state.profile();
     let mut last_mute = false;
+    // The mic the mute button toggles (and the mute LED shows)
+    let mut mic_target = mic::MicTarget::SystemDefault;
+    let mut last_buttons = input::ButtonState::default();
 
-    loop {
-        match handle.read(&mut buf) {
-            Err(()) => {
-                // Device disconnected
-                return;
-            }
-            Ok(0) => {
-                // No data available — yield and retry
-                sleep(Duration::from_millis(4)).await;
-                consecutive_errors = 0;
+    // The mapper may have resumed a saved profile — show it right away
+    let _ = tray_tx.send(tray::TrayCmd::SetProfile(last_profile));
+    shared.profile_tx.send_replace(last_profile);
+    player_leds.store(profile_leds(last_profile), Ordering::Relaxed);
 
-                // Check if USB scanner detected a USB controller (BT→USB switch)
-                if let Some(ref flag) = usb_switch_flag {
-                    if flag.load(Ordering::Relaxed) {
-                        log::info!("USB controller available — switching from Bluetooth");
-                        return;
-                    }
+    loop {
+        // The supervisor stops sessions whose controller is now on USB too
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let unified = match &source {
+            InputSource::Hid { handle, ct, conn } => match handle.read(&mut buf, read_timeout_ms) {
+                Err(()) => {
+                    // Device disconnected
+                    break;
                 }
-
-                continue;
-            }
-            Ok(n) => {
-                let data = &buf[..n];
-
-                if first_report {
-                    let hex: Vec<String> = data.iter().take(16).map(|b| format!("{b:02X}")).collect();
-                    log::info!("First report ({n} bytes): {}", hex.join(" "));
-                    first_report = false;
+                Ok(0) => {
+                    // No data available — yield and retry
+                    sleep(Duration::from_millis(4)).await;
+                    consecutive_errors = 0;
+                    continue;
                 }
+                Ok(n) => {
+                    let data = &buf[..n];
 
-                // Validate CRC on Bluetooth
-                if conn == ConnectionType::Bluetooth && !input::validate_bt_crc(ct, data) {
-                    consecutive_errors += 1;
-                    if consecutive_errors % 100 == 1 {
-                        log::warn!("BT CRC validation failed ({consecutive_errors} times)");
+                    if first_report {
+                        let hex: Vec<String> = data.iter().take(16).map(|b| format!("{b:02X}")).collect();
+                        log::info!("First report ({n} bytes): {}", hex.join(" "));
+                        first_report = false;
                     }
-                    continue;
-                }
 
-                match input::parse(ct, conn, data) {
-                    Ok(unified) => {
-                        consecutive_errors = 0;
-                        let actions = mapper_state.update(&unified);
-                        for action in &actions {
-                            #[cfg(windows)]
-                            mapper::execute_action(action);
-                            log::debug!("Action: {action:?}");
+                    // Validate CRC on Bluetooth
+                    if *conn == ConnectionType::Bluetooth && !input::validate_bt_crc(*ct, data) {
+                        consecutive_errors += 1;
+                        if consecutive_errors % 100 == 1 {
+                            log::warn!("BT CRC validation failed ({consecutive_errors} times)");
                         }
+                        continue;
+                    }
 
-                        // Mute button — toggle system mic on press (DualSense only; DS4 has no mic)
-                        let mute_now = unified.buttons.mute;
-                        if ct.is_dualsense() && mute_now && !last_mute {
-                            tokio::task::spawn_blocking(mic::toggle_mute);
-                        }
-                        last_mute = mute_now;
-
-                        // Update tray icon and player LED on profile change
-                        let current_profile = mapper_state.profile();
-                        if current_profile != last_profile {
-                            let _ = tray_tx.send(tray::TrayCmd::SetProfile(current_profile));
-                            last_profile = current_profile;
-
-                            // Instantly show the new profile's player indicator LED.
-                            let target_leds = match current_profile {
-                                mapper::Profile::Default => PLAYER1_LEDS,
-                                mapper::Profile::Tmux    => PLAYER2_LEDS,
-                            };
-                            player_leds.store(target_leds, Ordering::Relaxed);
+                    match input::parse(*ct, *conn, data) {
+                        Ok(unified) => unified,
+                        Err(e) => {
+                            consecutive_errors += 1;
+                            if consecutive_errors % 100 == 1 {
+                                log::warn!("Input parse error ({consecutive_errors}): {e}");
+                            }
+                            continue;
                         }
                     }
-                    Err(e) => {
-                        consecutive_errors += 1;
-                        if consecutive_errors % 100 == 1 {
-                            log::warn!("Input parse error ({consecutive_errors}): {e}");
-                        }
+                }
+            },
+            InputSource::XInput(index) => {
+                // XInput has no report stream: poll at the Sony report rate
+                sleep(Duration::from_millis(4)).await;
+                match xinput::read(*index) {
+                    Some(pad) => xinput::to_unified(&pad),
+                    // Pad unplugged
+                    None => break,
+                }
+            }
+        };
+        consecutive_errors = 0;
+        shared.battery.report(path, unified.battery);
+        if unified.buttons != last_buttons {
+            last_buttons = unified.buttons;
+            shared.input_activity.fetch_add(1, Ordering::Relaxed);
+        }
+        if let (Some(detector), Some(motion)) = (shake.as_mut(), unified.motion) {
+            let shaken = detector.update(motion.accel_magnitude(), Instant::now());
+            if shaken && !gestures_paused.load(Ordering::Relaxed) {
+                log::info!("Shake detected — acknowledging Done");
+                let _ = shared.ack_tx.try_send(());
+            }
+        }
+        // Status-only controllers keep reading (to notice disconnects) but never map
+        if role == controller::ControllerRole::StatusOnly {
+            continue;
+        }
+        // Profile picked from the tray menu; the change is
+        // announced below like a PS switch
+        // (a lagging receiver just skips to the newest picks)
+        while let Ok(request) = tray_requests.try_recv() {
+            match request {
+                tray::TrayRequest::SetProfile(profile) => {
+                    if !mapper_state.set_profile(profile) {
+                        log::warn!("Tray: profile {profile} is disabled or already active");
+                    }
+                }
+                tray::TrayRequest::CalibrateSticks(id) => {
+                    // With several input controllers, the first to see the request takes it
+                    if shared.calibration_claim.fetch_max(id, Ordering::Relaxed) < id {
+                        log::info!("Calibrating the sticks of {path}");
+                        calibrating = Some(calibration::StickSampler::new(Instant::now()));
+                    }
+                }
+            }
+        }
+        if let Some(sampler) = calibrating.as_mut() {
+            sampler.add(&unified);
+            if sampler.is_done(Instant::now()) {
+                match sampler.finish() {
+                    Ok(centers) => {
+                        log::info!(
+                            "Sticks calibrated: left ({}, {}), right ({}, {})",
+                            centers.left_x, centers.left_y, centers.right_x, centers.right_y
+                        );
+                        mapper_state.set_calibration(&centers);
+                        let _ = shared.calibration_tx.try_send(centers);
                     }
+                    Err(e) => log::warn!("Stick calibration failed: {e}"),
                 }
+                calibrating = None;
+            }
+        }
+        // Config reloaded from the tray: retune motion in place
+        if config_rx.has_changed().unwrap_or(false) {
+            let live = config_rx.borrow_and_update().clone();
+            mapper_state.apply_live_config(&live.scroll, &live.stick_mouse, &live.gyro, &live.touchpad);
+            mapper_state.set_calibration(&live.calibration);
+            profile_feedback = live.rumble.profile_feedback;
+            prefer_controller_mic = live.mic.prefer_controller;
+        }
+        let actions = mapper_state.update(&unified);
+        for action in &actions {
+            #[cfg(windows)]
+            mapper::execute_action(action);
+            log::debug!("Action: {action:?}");
+        }
+
+        // Mute button — toggle system mic on press (DualSense only; DS4 has no mic).
+        // A headset on the controller can take the toggle (`[mic] prefer_controller`);
+        // the mute LED then shows that mic's state
+        let mute_now = unified.buttons.mute;
+        if has_mic {
+            let target = mic::choose_target(prefer_controller_mic, unified.headset);
+            if mute_now && !last_mute {
+                mic_target = target;
+                tokio::task::spawn_blocking(move || mic::toggle_mute(target));
+            } else if target != mic_target {
+                mic_target = target;
+                tokio::task::spawn_blocking(move || mic::refresh(target));
+            }
+        }
+        last_mute = mute_now;
+
+        // Update tray icon and player LED on profile change
+        let current_profile = mapper_state.profile();
+        if current_profile != last_profile {
+            let _ = tray_tx.send(tray::TrayCmd::SetProfile(current_profile));
+            shared.profile_tx.send_replace(current_profile);
+            last_profile = current_profile;
+
+            // Instantly show the new profile's player indicator LED.
+            player_leds.store(profile_leds(current_profile), Ordering::Relaxed);
+            if profile_feedback {
+                let _ = profile_rumble_tx.try_send(());
             }
         }
     }
+
+    // Don't leave Ctrl+Win or the left mouse button held down
+    for action in mapper_state.release_holds() {
+        #[cfg(windows)]
+        mapper::execute_action(&action);
+        log::debug!("Action: {action:?}");
+    }
 }
 
 /// Minimum working duration before the Working → Done rumble fires.
 /// Short tasks don't warrant a notification; only surface it for real work.
 const WORKING_DONE_MIN_MS: u64 = 10 * 60 * 1000; // 10 minutes
 
+/// Consecutive failed output writes (~1s of frames) that count as a disconnect.
+const WRITE_FAILURE_LIMIT: u32 = 30;
+
+/// How long a restored lightbar color is held if the agent feed stays quiet.
+const RESTORE_HOLD: Duration = Duration::from_secs(5);
+
 /// Player indicator LED presets — mimics PS5 native player assignment.
 ///   Player 1 (Default profile) → center dot only
 ///   Player 2 (Tmux profile)    → inner two dots (center-left + center-right)
+///   Player 3 (Agent profile)   → center + outer two dots
 const PLAYER1_LEDS: u8 = 0x04; // center only
 const PLAYER2_LEDS: u8 = 0x0A; // inner two (0x02 | 0x08)
+const PLAYER3_LEDS: u8 = 0x15; // center + outer two (0x01 | 0x04 | 0x10)
 
-/// Output loop: update lightbar based on aggregated state, fire rumble from per-agent signals.
+/// Player indicator LEDs for a profile.
+fn profile_leds(profile: mapper::Profile) -> u8 {
+    match profile {
+        mapper::Profile::Default => PLAYER1_LEDS,
+        mapper::Profile::Tmux    => PLAYER2_LEDS,
+        mapper::Profile::Agent   => PLAYER3_LEDS,
+    }
+}
+
+/// Output settings that follow "Reload config" without a reconnect.
+struct LiveOutput {
+    lightbar: config::LightbarConfig,
+    mute_led_mode: config::MuteLedMode,
+    triggers: (output::TriggerEffect, output::TriggerEffect),
+    rumble_level: rumble::RumbleLevel,
+    rumble_patterns: rumble::RumblePatterns,
+}
+
+impl LiveOutput {
+    fn from_config(cfg: &config::Config) -> Self {
+        Self {
+            lightbar: cfg.lightbar.clone(),
+            mute_led_mode: cfg.mute_led.mode,
+            triggers: output::trigger_effects(&cfg.triggers),
+            rumble_level: rumble::RumbleLevel::new(cfg.rumble.enabled, cfg.rumble.intensity),
+            rumble_patterns: rumble::RumblePatterns::from_config(&cfg.rumble.patterns),
+        }
+    }
+}
+
+/// Output loop: update lightbar based on aggregated state, fire rumble from the
+/// fanned-out agent/battery events. Returns true if it gave up because the
+/// controller stopped taking writes.
 async fn run_output_loop(
     handle: hid::HidHandle,
     ct: controller::ControllerType,
     conn: controller::ConnectionType,
-    lightbar_cfg: config::LightbarConfig,
-    state_rx: &mut watch::Receiver<AgentState>,
+    shared: &SessionShared,
+    restore_color: Option<(u8, u8, u8)>,
     player_leds: Arc<AtomicU8>,
-    idle_reminder_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<()>>>,
-    done_rumble_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<()>>>,
-) {
+    mut profile_rumble_rx: mpsc::Receiver<()>,
+) -> bool {
+    let mut config_rx = shared.config_rx.clone();
+    let mut shutdown_rx = shared.shutdown_rx.clone();
+    let mut state_rx = shared.state_rx.clone();
+    let mut events = shared.events_tx.subscribe();
+    let cfg = config_rx.borrow_and_update().clone();
+    let token_meter = cfg
+        .codex
+        .token_meter
+        .then(|| (PathBuf::from(&cfg.state_dir), cfg.codex.context_window));
+    let mut live = LiveOutput::from_config(&cfg);
     let mut bt_seq = 0u8;
     let mut current_state = AgentState::Idle;
+    let mut current_counts = state::StateCounts::default();
     let mut state_start = Instant::now();
+    // Restored color is held until the agent feed reports a state, or for RESTORE_HOLD
+    let mut restore_color = restore_color;
+    // Low-battery red flash, overriding the agent color while it runs
+    let mut low_battery_since: Option<Instant> = None;
+    // Red flash after an agent enters Error (`[rumble] on_error`)
+    let mut error_since: Option<Instant> = None;
 
     // Shared rumble motor values — updated by fire_rumble, read by the ticker each frame.
     // This ensures the ticker doesn't overwrite active rumble with zeros every 33ms.
@@ -456,51 +1178,148 @@ async fn run_output_loop(
     // Prime mic mute state from system before first frame
     tokio::task::spawn_blocking(mic::init).await.ok();
 
-    // Set initial lightbar + Player 1 indicator (Default profile on startup)
-    send_output(
-        &handle,
-        ct,
-        conn,
-        &lightbar_cfg,
-        current_state,
-        0,
-        PLAYER1_LEDS,
-        0,
-        0,
-        &mut bt_seq,
-    );
+    // Set initial lightbar + player indicator (the profile the mapper started in)
+    let frame = Frame {
+        color_override: restore_color,
+        state: current_state,
+        elapsed_ms: 0,
+        player_leds: player_leds.load(Ordering::Relaxed),
+        rumble: (0, 0),
+    };
+    send_output(&handle, ct, conn, &live, frame, &mut bt_seq);
 
     let mut ticker = tokio::time::interval(Duration::from_millis(33)); // ~30fps for smooth pulse
-    let mut idle_rx = idle_reminder_rx.lock().await;
-    let mut done_rx = done_rumble_rx.lock().await;
+    // Token meter: player LEDs show the active session's context fill,
+    // refreshed once a second (None = no active session → profile LEDs)
+    let mut meter_ticker = tokio::time::interval(Duration::from_secs(1));
+    let mut meter_leds: Option<u8> = None;
+    // Lightbar sleep: while asleep, a frame is only re-sent when the player
+    // LEDs or rumble change (Some = the last dark frame's leds/rumble)
+    let mut sleep = lightbar::SleepTimer::new(live.lightbar.sleep_after_s);
+    let mut dark_frame: Option<(u8, u8, u8)> = None;
+    // Any controller's button activity (a counter, shared by every session) wakes it
+    let mut seen_activity = shared.input_activity.load(Ordering::Relaxed);
 
     loop {
         tokio::select! {
             _ = ticker.tick() => {
+                if restore_color.is_some() && state_start.elapsed() >= RESTORE_HOLD {
+                    restore_color = None;
+                }
                 let elapsed = state_start.elapsed().as_millis() as u64;
-                let leds = player_leds.load(Ordering::Relaxed);
+                let leds = meter_leds.unwrap_or_else(|| player_leds.load(Ordering::Relaxed));
                 let rl = rumble_left.load(Ordering::Relaxed);
                 let rr = rumble_right.load(Ordering::Relaxed);
-                send_output(&handle, ct, conn, &lightbar_cfg, current_state, elapsed, leds, rl, rr, &mut bt_seq);
+                let flash = low_battery_since
+                    .and_then(|since| battery::flash_color(since.elapsed().as_millis() as u64));
+                if flash.is_none() {
+                    low_battery_since = None;
+                }
+                let error_flash = error_since
+                    .and_then(|since| lightbar::error_flash_color(since.elapsed().as_millis() as u64));
+                if error_flash.is_none() {
+                    error_since = None;
+                }
+                let overlay = flash.or(error_flash).or(restore_color);
+                let activity = shared.input_activity.load(Ordering::Relaxed);
+                let woke = std::mem::replace(&mut seen_activity, activity) != activity;
+                let asleep = sleep.update(current_state, woke, Instant::now()) && overlay.is_none();
+                let blended = live
+                    .lightbar
+                    .blend
+                    .then(|| lightbar::compute_blended_color(&live.lightbar, &current_counts, elapsed));
+                let color = overlay.or(blended);
+                if asleep != dark_frame.is_some() {
+                    log::debug!("Lightbar {}", if asleep { "sleeping" } else { "awake" });
+                }
+                let frame = |color_override| Frame {
+                    color_override,
+                    state: current_state,
+                    elapsed_ms: elapsed,
+                    player_leds: leds,
+                    rumble: (rl, rr),
+                };
+                if !asleep {
+                    dark_frame = None;
+                    send_output(&handle, ct, conn, &live, frame(color), &mut bt_seq);
+                } else if dark_frame != Some((leds, rl, rr)) {
+                    dark_frame = Some((leds, rl, rr));
+                    send_output(&handle, ct, conn, &live, frame(Some((0, 0, 0))), &mut bt_seq);
+                }
+                // A controller that keeps rejecting writes is half-disconnected:
+                // end the session (as a read error would) so it's reopened
+                let failures = handle.consecutive_write_failures();
+                if failures >= WRITE_FAILURE_LIMIT {
+                    log::warn!("{failures} output writes failed in a row — reconnecting the controller");
+                    return true;
+                }
             }
-            _ = idle_rx.recv() => {
-                // Per-agent idle reminder — fire rumble
-                log::info!("Per-agent idle reminder rumble triggered");
-                fire_rumble(&rumble::idle_reminder_pattern(), Arc::clone(&rumble_left), Arc::clone(&rumble_right));
+            _ = meter_ticker.tick(), if token_meter.is_some() => {
+                if let Some((state_dir, context_window)) = &token_meter {
+                    meter_leds = codex_poll::latest_token_total(state_dir)
+                        .map(|tokens| codex_poll::token_meter_leds(tokens, *context_window));
+                }
             }
-            _ = done_rx.recv() => {
-                // Per-agent Working → Done — fire celebratory rumble
-                log::info!("Per-agent done rumble triggered");
-                if let Some(pattern) = rumble::pattern_for_transition(AgentState::Working, AgentState::Done) {
-                    fire_rumble(&pattern, Arc::clone(&rumble_left), Arc::clone(&rumble_right));
+            event = events.recv() => match event {
+                Ok(OutputEvent::IdleReminder(level)) => {
+                    // Per-agent idle reminder — stronger each time it's ignored
+                    log::info!("Per-agent idle reminder rumble triggered (level {level})");
+                    fire_rumble(live.rumble_level, &live.rumble_patterns.idle_reminder(level), Arc::clone(&rumble_left), Arc::clone(&rumble_right));
                 }
+                Ok(OutputEvent::Done) => {
+                    // Per-agent Working → Done — fire celebratory rumble
+                    log::info!("Per-agent done rumble triggered");
+                    if let Some(pattern) = live.rumble_patterns.pattern_for_transition(AgentState::Working, AgentState::Done) {
+                        fire_rumble(live.rumble_level, &pattern, Arc::clone(&rumble_left), Arc::clone(&rumble_right));
+                    }
+                }
+                Ok(OutputEvent::Error) => {
+                    // An agent entered Error — short buzz and a red flash
+                    log::info!("Per-agent error alert triggered");
+                    fire_rumble(live.rumble_level, &live.rumble_patterns.error(), Arc::clone(&rumble_left), Arc::clone(&rumble_right));
+                    error_since = Some(Instant::now());
+                }
+                Ok(OutputEvent::AllClear) => {
+                    // Every agent of a parallel run settled — distinct all-clear rumble
+                    log::info!("All-clear rumble triggered");
+                    fire_rumble(live.rumble_level, &live.rumble_patterns.all_clear(), Arc::clone(&rumble_left), Arc::clone(&rumble_right));
+                }
+                Ok(OutputEvent::LowBattery) => {
+                    // Battery crossed the warning threshold — rumble once and flash red
+                    fire_rumble(live.rumble_level, &live.rumble_patterns.low_battery(), Arc::clone(&rumble_left), Arc::clone(&rumble_right));
+                    low_battery_since = Some(Instant::now());
+                }
+                // Missed a burst of events — the rumble would be stale anyway
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            Some(()) = profile_rumble_rx.recv() => {
+                // PS switched profile — brief haptic confirmation
+                fire_rumble(live.rumble_level, &live.rumble_patterns.profile_switch(), Arc::clone(&rumble_left), Arc::clone(&rumble_right));
+            }
+            Ok(()) = shutdown_rx.changed() => {
+                if *shutdown_rx.borrow_and_update() {
+                    // Exiting: don't leave the controller glowing
+                    let off = output::build_report(ct, conn, &OutputState::all_off(), &mut bt_seq);
+                    handle.write(&off);
+                    log::info!("Shutdown: controller lights off");
+                    break;
+                }
+            }
+            Ok(()) = config_rx.changed() => {
+                // "Reload config": colors, mute LED, triggers and rumble follow at once
+                live = LiveOutput::from_config(&config_rx.borrow_and_update());
+                sleep = lightbar::SleepTimer::new(live.lightbar.sleep_after_s);
+                log::info!("Output settings reloaded");
             }
             result = state_rx.changed() => {
                 if result.is_err() {
                     log::error!("State channel closed");
                     break;
                 }
-                let new_state = *state_rx.borrow();
+                let state::AgentSummary { state: new_state, counts } = *state_rx.borrow();
+                current_counts = counts;
+                restore_color = None;
                 if new_state != current_state {
                     log::debug!("Lightbar transition {:?} → {:?}", current_state, new_state);
                     current_state = new_state;
@@ -509,17 +1328,21 @@ async fn run_output_loop(
             }
         }
     }
+    false
 }
 
-/// Spawn a rumble pattern (non-blocking).
+/// Spawn a rumble pattern (non-blocking), scaled by `level` (no-op if rumble is off).
 /// Updates shared atomics that the output ticker reads each frame, so the
 /// 33ms ticker doesn't overwrite active rumble with zeros mid-pattern.
 fn fire_rumble(
+    level: rumble::RumbleLevel,
     pattern: &[rumble::RumbleStep],
     rumble_left: Arc<AtomicU8>,
     rumble_right: Arc<AtomicU8>,
 ) {
-    let pattern = pattern.to_vec();
+    let Some(pattern) = level.apply(pattern) else {
+        return;
+    };
     tokio::spawn(async move {
         rumble::play_pattern(&pattern, |left, right| {
             rumble_left.store(left, Ordering::Relaxed);
@@ -528,19 +1351,30 @@ fn fire_rumble(
     });
 }
 
+/// What one output report shows, besides the live settings.
+struct Frame {
+    /// Lightbar color instead of the state's (flashes, restore, sleep), before `brightness`.
+    color_override: Option<(u8, u8, u8)>,
+    state: AgentState,
+    elapsed_ms: u64,
+    player_leds: u8,
+    rumble: (u8, u8),
+}
+
 fn send_output(
     handle: &hid::HidHandle,
     ct: controller::ControllerType,
     conn: controller::ConnectionType,
-    lightbar_cfg: &config::LightbarConfig,
-    state: AgentState,
-    elapsed_ms: u64,
-    player_leds: u8,
-    rumble_left: u8,
-    rumble_right: u8,
+    live: &LiveOutput,
+    frame: Frame,
     bt_seq: &mut u8,
 ) {
-    let (r, g, b) = lightbar::compute_color(lightbar_cfg, state, elapsed_ms);
+    let Frame { color_override, state, elapsed_ms, player_leds, rumble: (rumble_left, rumble_right) } = frame;
+    let lightbar_cfg = &live.lightbar;
+    let (trigger_left, trigger_right) = live.triggers;
+    let color = color_override.unwrap_or_else(|| lightbar::base_color(lightbar_cfg, state, elapsed_ms));
+    lightbar::remember_color(color);
+    let (r, g, b) = lightbar::apply_brightness(lightbar_cfg, color);
     let out = OutputState {
         lightbar_r: r,
         lightbar_g: g,
@@ -548,7 +1382,14 @@ fn send_output(
         rumble_left,
         rumble_right,
         player_leds,
-        mute_led: mic::MIC_MUTED.load(std::sync::atomic::Ordering::Relaxed) as u8,
+        mute_led: output::mute_led_value(
+            live.mute_led_mode,
+            state,
+            mic::MIC_MUTED.load(std::sync::atomic::Ordering::Relaxed),
+        ),
+        led_brightness: output::led_brightness_byte(lightbar_cfg.hardware_brightness),
+        trigger_left,
+        trigger_right,
     };
     let report = output::build_report(ct, conn, &out, bt_seq);
     handle.write(&report);
@@ -1,13 +1,20 @@
 /// Button mapper: translates UnifiedInput → keyboard/mouse events via SendInput.
 ///
-/// Always active (both profiles):
+/// Always active (all profiles):
 ///   D-pad Up/Down/Left/Right → Arrow keys (two-frame confirm + repeat)
-///   Cross    → Enter
-///   Circle   → Escape
-///   Triangle → Tab
+///   Cross    → Enter  ([buttons] cross)
+///   Circle   → Escape ([buttons] circle)
+///   Triangle → Tab    ([buttons] triangle; not in the Agent profile)
+///   Double-tap Cross/Circle/Triangle → [tap] combo, if set (replaces the second press)
+///   [turbo] buttons (held) → repeat [turbo] key at rate_ms, replacing their normal action
 ///   Left stick  → Mouse cursor (velocity-based, configurable sensitivity)
+///   [gyro] button (held) → Mouse cursor from controller yaw/pitch (DualSense)
 ///   Right stick → Mouse scroll wheel (vertical + horizontal)
-///   PS       → Cycle profiles (Default ↔ Tmux)
+///   ([stick_mouse] swap_sticks trades the two sticks' roles)
+///   Touchpad    → Mouse cursor (one finger) / scroll wheel (two fingers) / right click (two-finger tap)
+///   PS       → Cycle profiles (Default → Tmux → Agent; remembered across restarts)
+///   Create + PS → Screenshot (Win+Shift+S, configurable)
+///   [layer] button (held) → every other button sends its [layer] binding instead
 ///
 /// Default profile (Windows Terminal shortcuts, auto-detected from settings.json):
 ///   Square   → new tab / profile 1   (newTab,  default ctrl+shift+1)
@@ -17,6 +24,7 @@
 ///   R2       → Ctrl+C
 ///   L3       → Ctrl+T
 ///   R3       → Ctrl+P
+///   Options  → tap: Escape, hold 500ms: Ctrl+Shift+P (sent on release, configurable)
 ///
 /// Tmux profile (auto-detected from tmux config):
 ///   L1       → tmux prefix + previous-window key
@@ -27,28 +35,40 @@
 ///   Square   → tmux prefix + new-window key
 ///   L3       → Ctrl+T
 ///
+/// Agent profile (OpenCode sessions + tmux windows, needs `opencode.enabled`):
+///   L1 / R1  → OpenCode previous / next session
+///   R2 + L1 / R2 + R1 → tmux L1 / R1 bindings (previous / next window)
+///   Square   → OpenCode new session
+///   Triangle → tmux Square binding (new window)
+///   L3       → Ctrl+T
+///   R3       → Ctrl+P
+///   Create / Options → OpenCode bindings, if mapped
+///
 /// Combos are sent atomically in a single SendInput call.
 
-use crate::config::{OpenCodeConfig, ScrollConfig, StickMouseConfig, TouchpadConfig, TmuxConfig, WtConfig};
-use crate::input::{ButtonState, DPad, UnifiedInput};
+use crate::config::{CalibrationConfig, Config, DeadZoneShape, GyroConfig, LayerConfig, MacroConfig, OpenCodeConfig, ScrollConfig, ScrollMode, StickMouseConfig, TouchpadConfig, TmuxConfig, TurboConfig, WtConfig};
+use crate::input::{Button, ButtonState, DPad, UnifiedInput};
 use crate::opencode_detect::{ActionBinding, OpenCodeDetected};
 use crate::tmux_detect::TmuxDetected;
 use crate::wt_detect::WtDetected;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 #[cfg(windows)]
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
     SendInput, INPUT, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, MOUSEINPUT,
-    KEYEVENTF_KEYUP,
+    KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
     MOUSEEVENTF_WHEEL, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_MOVE,
-    MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+    MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP,
     VK_RETURN, VK_ESCAPE, VK_TAB, VK_UP, VK_DOWN, VK_LEFT, VK_RIGHT,
     VK_MENU, VK_SHIFT, VK_CONTROL,
 };
 
 /// Virtual key codes we use.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum VKey {
     Return,
     Escape,
@@ -78,8 +98,24 @@ pub enum VKey {
     Period,       // VK_OEM_PERIOD (.>)
     Backtick,     // VK_OEM_3 (`~)
     Space,        // VK_SPACE
+    // Navigation / editing keys
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Delete,
+    Insert,
     // Function keys
     F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    // Media / volume keys
+    VolumeUp,
+    VolumeDown,
+    VolumeMute,
+    MediaPlayPause,
+    MediaNext,
+    MediaPrev,
+    // Numpad digits
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
 }
 
 #[cfg(windows)]
@@ -119,9 +155,25 @@ impl VKey {
             VKey::Period => 0xBE,         // VK_OEM_PERIOD
             VKey::Backtick => 0xC0,       // VK_OEM_3
             VKey::Space => 0x20,          // VK_SPACE
+            VKey::Home => 0x24,           // VK_HOME
+            VKey::End => 0x23,            // VK_END
+            VKey::PageUp => 0x21,         // VK_PRIOR
+            VKey::PageDown => 0x22,       // VK_NEXT
+            VKey::Delete => 0x2E,         // VK_DELETE
+            VKey::Insert => 0x2D,         // VK_INSERT
             VKey::F1  => 0x70, VKey::F2  => 0x71, VKey::F3  => 0x72, VKey::F4  => 0x73,
             VKey::F5  => 0x74, VKey::F6  => 0x75, VKey::F7  => 0x76, VKey::F8  => 0x77,
             VKey::F9  => 0x78, VKey::F10 => 0x79, VKey::F11 => 0x7A, VKey::F12 => 0x7B,
+            VKey::VolumeUp => 0xAF,       // VK_VOLUME_UP
+            VKey::VolumeDown => 0xAE,     // VK_VOLUME_DOWN
+            VKey::VolumeMute => 0xAD,     // VK_VOLUME_MUTE
+            VKey::MediaPlayPause => 0xB3, // VK_MEDIA_PLAY_PAUSE
+            VKey::MediaNext => 0xB0,      // VK_MEDIA_NEXT_TRACK
+            VKey::MediaPrev => 0xB1,      // VK_MEDIA_PREV_TRACK
+            VKey::Numpad0 => 0x60, VKey::Numpad1 => 0x61, VKey::Numpad2 => 0x62,
+            VKey::Numpad3 => 0x63, VKey::Numpad4 => 0x64, VKey::Numpad5 => 0x65,
+            VKey::Numpad6 => 0x66, VKey::Numpad7 => 0x67, VKey::Numpad8 => 0x68,
+            VKey::Numpad9 => 0x69,
         }
     }
 }
@@ -166,35 +218,132 @@ impl VKey {
             "." | "period" => Some(VKey::Period),
             "`" | "backtick" => Some(VKey::Backtick),
             "space" => Some(VKey::Space),
+            "home" => Some(VKey::Home),
+            "end" => Some(VKey::End),
+            "pageup" | "pgup" | "prior" => Some(VKey::PageUp),
+            "pagedown" | "pgdn" | "next" => Some(VKey::PageDown),
+            "delete" | "del" => Some(VKey::Delete),
+            "insert" | "ins" => Some(VKey::Insert),
             "f1"  => Some(VKey::F1),  "f2"  => Some(VKey::F2),  "f3"  => Some(VKey::F3),
             "f4"  => Some(VKey::F4),  "f5"  => Some(VKey::F5),  "f6"  => Some(VKey::F6),
             "f7"  => Some(VKey::F7),  "f8"  => Some(VKey::F8),  "f9"  => Some(VKey::F9),
             "f10" => Some(VKey::F10), "f11" => Some(VKey::F11), "f12" => Some(VKey::F12),
+            "volumeup" | "volup" => Some(VKey::VolumeUp),
+            "volumedown" | "voldown" => Some(VKey::VolumeDown),
+            "volumemute" | "volmute" => Some(VKey::VolumeMute),
+            "playpause" | "mediaplaypause" => Some(VKey::MediaPlayPause),
+            "nexttrack" | "medianext" => Some(VKey::MediaNext),
+            "prevtrack" | "mediaprev" => Some(VKey::MediaPrev),
+            "numpad0" | "num0" => Some(VKey::Numpad0), "numpad1" | "num1" => Some(VKey::Numpad1),
+            "numpad2" | "num2" => Some(VKey::Numpad2), "numpad3" | "num3" => Some(VKey::Numpad3),
+            "numpad4" | "num4" => Some(VKey::Numpad4), "numpad5" | "num5" => Some(VKey::Numpad5),
+            "numpad6" | "num6" => Some(VKey::Numpad6), "numpad7" | "num7" => Some(VKey::Numpad7),
+            "numpad8" | "num8" => Some(VKey::Numpad8), "numpad9" | "num9" => Some(VKey::Numpad9),
             _ => None,
         }
     }
 }
 
+impl VKey {
+    /// Short human-readable key name (e.g. "Ctrl", "Enter", "A", "[").
+    pub fn label(self) -> &'static str {
+        match self {
+            VKey::Return => "Enter",
+            VKey::Escape => "Esc",
+            VKey::Tab => "Tab",
+            VKey::Up => "Up",
+            VKey::Down => "Down",
+            VKey::Left => "Left",
+            VKey::Right => "Right",
+            VKey::Alt => "Alt",
+            VKey::Shift => "Shift",
+            VKey::Control => "Ctrl",
+            VKey::Win => "Win",
+            VKey::A => "A", VKey::B => "B", VKey::C => "C", VKey::D => "D",
+            VKey::E => "E", VKey::F => "F", VKey::G => "G", VKey::H => "H",
+            VKey::I => "I", VKey::J => "J", VKey::K => "K", VKey::L => "L",
+            VKey::M => "M", VKey::N => "N", VKey::O => "O", VKey::P => "P",
+            VKey::Q => "Q", VKey::R => "R", VKey::S => "S", VKey::T => "T",
+            VKey::U => "U", VKey::V => "V", VKey::W => "W", VKey::X => "X",
+            VKey::Y => "Y", VKey::Z => "Z",
+            VKey::D0 => "0", VKey::D1 => "1", VKey::D2 => "2", VKey::D3 => "3",
+            VKey::D4 => "4", VKey::D5 => "5", VKey::D6 => "6", VKey::D7 => "7",
+            VKey::D8 => "8", VKey::D9 => "9",
+            VKey::Semicolon => ";",
+            VKey::LeftBracket => "[",
+            VKey::RightBracket => "]",
+            VKey::Backslash => "\\",
+            VKey::Quote => "'",
+            VKey::Slash => "/",
+            VKey::Minus => "-",
+            VKey::Equals => "=",
+            VKey::Comma => ",",
+            VKey::Period => ".",
+            VKey::Backtick => "`",
+            VKey::Space => "Space",
+            VKey::Home => "Home",
+            VKey::End => "End",
+            VKey::PageUp => "PageUp",
+            VKey::PageDown => "PageDown",
+            VKey::Delete => "Delete",
+            VKey::Insert => "Insert",
+            VKey::F1  => "F1",  VKey::F2  => "F2",  VKey::F3  => "F3",  VKey::F4  => "F4",
+            VKey::F5  => "F5",  VKey::F6  => "F6",  VKey::F7  => "F7",  VKey::F8  => "F8",
+            VKey::F9  => "F9",  VKey::F10 => "F10", VKey::F11 => "F11", VKey::F12 => "F12",
+            VKey::VolumeUp => "VolumeUp",
+            VKey::VolumeDown => "VolumeDown",
+            VKey::VolumeMute => "VolumeMute",
+            VKey::MediaPlayPause => "PlayPause",
+            VKey::MediaNext => "NextTrack",
+            VKey::MediaPrev => "PrevTrack",
+            VKey::Numpad0 => "Num0", VKey::Numpad1 => "Num1", VKey::Numpad2 => "Num2",
+            VKey::Numpad3 => "Num3", VKey::Numpad4 => "Num4", VKey::Numpad5 => "Num5",
+            VKey::Numpad6 => "Num6", VKey::Numpad7 => "Num7", VKey::Numpad8 => "Num8",
+            VKey::Numpad9 => "Num9",
+        }
+    }
+}
+
+/// Format a key combo for display, e.g. [Control, Shift, D1] → "Ctrl+Shift+1".
+pub fn format_key_combo(keys: &[VKey]) -> String {
+    keys.iter().map(|k| k.label()).collect::<Vec<_>>().join("+")
+}
+
+/// Format a key sequence for display, e.g. prefix then key → "Ctrl+B, P".
+fn format_key_sequence(seq: &[Vec<VKey>]) -> String {
+    seq.iter().map(|c| format_key_combo(c)).collect::<Vec<_>>().join(", ")
+}
+
+/// Format a detected action map as an aligned two-column table sorted by
+/// action name, one `  <action>  <keys>` row per line (`--dump-detect`).
+pub fn format_action_table<V>(actions: &HashMap<String, V>, describe: impl Fn(&V) -> String) -> String {
+    if actions.is_empty() {
+        return "  (none)\n".to_string();
+    }
+    let mut names: Vec<&String> = actions.keys().collect();
+    names.sort();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    names
+        .into_iter()
+        .map(|name| format!("  {name:<width$}  {}\n", describe(&actions[name])))
+        .collect()
+}
+
 /// Parse a key combo string like "Ctrl+B" or "p" into a Vec<VKey>.
 pub fn parse_key_combo(s: &str) -> Option<Vec<VKey>> {
     s.split('+').map(|part| VKey::from_name(part.trim())).collect()
 }
 
-/// Active input profile. PS button cycles between Default and Tmux.
-///
-/// TODO: Add a third "Agent" profile that merges OpenCode + tmux shortcuts onto
-/// buttons that make sense for AI-assisted coding sessions — e.g. session nav on
-/// L1/R1 (OpenCode sessions), window nav on shoulder combos (tmux windows), new
-/// session/window on Square (OpenCode) / Triangle (tmux), etc. The resolved
-/// `OpenCodeState` and `TmuxState` are both already computed inside `MapperState`
-/// and ready to dispatch; only the profile variant, PS cycling, LED slot, and
-/// tray color need to be wired up.
+/// Active input profile. PS button cycles Default → Tmux → Agent; Tmux is
+/// skipped unless `tmux.enabled`, Agent unless `opencode.enabled`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Profile {
     /// L2-Touchpad unmapped.
     Default,
     /// L2-Touchpad send tmux prefix + key sequences.
     Tmux,
+    /// OpenCode session shortcuts merged with tmux window shortcuts.
+    Agent,
 }
 
 impl std::fmt::Display for Profile {
@@ -202,10 +351,67 @@ impl std::fmt::Display for Profile {
         match self {
             Profile::Default => f.write_str("default"),
             Profile::Tmux    => f.write_str("tmux"),
+            Profile::Agent   => f.write_str("agent"),
+        }
+    }
+}
+
+impl Profile {
+    /// Parse a profile name as written by `Display` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "default" => Some(Profile::Default),
+            "tmux" => Some(Profile::Tmux),
+            "agent" => Some(Profile::Agent),
+            _ => None,
+        }
+    }
+}
+
+/// File (in the state dir) holding the last profile selected with PS.
+pub const PROFILE_FILE: &str = "ds4cc_profile";
+
+/// Read a saved profile. None if the file is missing or doesn't name a profile.
+fn load_profile(path: &Path) -> Option<Profile> {
+    Profile::from_name(&std::fs::read_to_string(path).ok()?)
+}
+
+fn save_profile(path: &Path, profile: Profile) -> std::io::Result<()> {
+    std::fs::write(path, profile.to_string())
+}
+
+/// Where a resolved binding applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingScope {
+    /// Active in every profile.
+    Always,
+    /// Active only in this profile.
+    Profile(Profile),
+    /// Active while the `[layer]` button is held.
+    Layer,
+}
+
+impl std::fmt::Display for BindingScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingScope::Always => f.write_str("always"),
+            BindingScope::Profile(p) => write!(f, "{p}"),
+            BindingScope::Layer => f.write_str("layer"),
         }
     }
 }
 
+/// What a button ended up doing after config + auto-detection were resolved.
+/// Produced by `MapperState::resolved_bindings` for diagnostics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedBinding {
+    pub scope: BindingScope,
+    /// Button (or chord) in config naming, e.g. "square" or "share+ps".
+    pub button: String,
+    /// Human-readable action, e.g. "Ctrl+Shift+1" or "Ctrl+B, C".
+    pub action: String,
+}
+
 /// An action the mapper can produce.
 #[derive(Debug, Clone)]
 pub enum Action {
@@ -217,16 +423,124 @@ pub enum Action {
     KeyUp(Vec<VKey>),
     /// Sequence of key combos with a delay between each (for tmux prefix+key).
     KeySequence(Vec<Vec<VKey>>),
+    /// Press `modifiers` once, tap each of `keys` while they're held, then
+    /// release them (e.g. Ctrl held across several arrow presses).
+    KeySequenceHeld { modifiers: Vec<VKey>, keys: Vec<Vec<VKey>> },
     /// Mouse scroll event. Values in wheel-delta units (positive = up/right).
     Scroll { horizontal: i32, vertical: i32 },
     /// Relative mouse cursor movement (screen pixels). Emitted by touchpad touch.
     MouseMove { dx: i32, dy: i32 },
-    /// Left mouse button click (press + release). Emitted by touchpad physical click.
-    MouseClick,
+    /// Left mouse button press. Emitted when the touchpad is pressed down.
+    MouseButtonDown,
+    /// Left mouse button release. Emitted when the touchpad press ends, so
+    /// a tap is a plain click and press-and-move drags.
+    MouseButtonUp,
+    /// Right mouse button click (press + release). Emitted by a two-finger touchpad tap.
+    MouseRightClick,
+    /// Configured `[macros]` sequence: combos and pauses, played in order.
+    Macro(Vec<MacroStep>),
+    /// Type literal text (e.g. a Windows Terminal `sendInput` string) as
+    /// Unicode key events, independent of the keyboard layout.
+    TypeText(String),
     /// Custom action identifier (e.g., "new_session").
     Custom(String),
 }
 
+/// One step of a `[macros]` sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MacroStep {
+    /// Press and release a key combo.
+    Keys(Vec<VKey>),
+    /// Pause before the next step, in milliseconds.
+    Delay(u64),
+    /// Type literal text ("text:<string>"), like [`Action::TypeText`].
+    Text(String),
+    /// Keys tapped under held modifiers ("hold:<modifiers> <key> <key> ..."),
+    /// like [`Action::KeySequenceHeld`].
+    Held { modifiers: Vec<VKey>, keys: Vec<Vec<VKey>> },
+}
+
+/// Longest accepted pause of a macro, all its delays together. Macros play on
+/// the input thread, so a long pause would freeze every other button until it
+/// finishes.
+const MAX_MACRO_DELAY_MS: u64 = 2000;
+
+/// Parse one macro step: "delay:<ms>", "text:<string>", "hold:<modifiers> <keys>"
+/// or a key combo. The text after "text:" is kept verbatim, spaces included.
+fn parse_macro_step(s: &str) -> Option<MacroStep> {
+    let untrimmed = s.trim_start();
+    if let Some(prefix) = untrimmed.get(..5)
+        && prefix.eq_ignore_ascii_case("text:")
+    {
+        let text = &untrimmed[5..];
+        return (!text.is_empty()).then(|| MacroStep::Text(text.to_string()));
+    }
+    let s = s.trim();
+    if let Some(prefix) = s.get(..5)
+        && prefix.eq_ignore_ascii_case("hold:")
+    {
+        // "hold:ctrl left left" → Ctrl held across two Left taps
+        let mut parts = s[5..].split_whitespace();
+        let modifiers = parse_key_combo(parts.next()?)?;
+        let keys = parts.map(parse_key_combo).collect::<Option<Vec<_>>>()?;
+        return (!keys.is_empty()).then_some(MacroStep::Held { modifiers, keys });
+    }
+    match s.get(..6) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("delay:") => {
+            let ms: u64 = s[6..].trim().parse().ok()?;
+            Some(MacroStep::Delay(ms.min(MAX_MACRO_DELAY_MS)))
+        }
+        _ => parse_key_combo(s).map(MacroStep::Keys),
+    }
+}
+
+/// Parse a macro's steps, skipping (and warning about) invalid ones. Delays
+/// past `MAX_MACRO_DELAY_MS` in total are shortened to fit.
+fn parse_macro(button: &str, steps: &[String]) -> Vec<MacroStep> {
+    let mut delay_left = MAX_MACRO_DELAY_MS;
+    let mut capped = false;
+    let steps = steps
+        .iter()
+        .filter_map(|step| {
+            let parsed = parse_macro_step(step);
+            if parsed.is_none() {
+                log::warn!("[macros] {button}: invalid step '{step}' skipped");
+            }
+            match parsed {
+                Some(MacroStep::Delay(ms)) => {
+                    capped |= ms > delay_left;
+                    let ms = ms.min(delay_left);
+                    delay_left -= ms;
+                    Some(MacroStep::Delay(ms))
+                }
+                parsed => parsed,
+            }
+        })
+        .collect();
+    if capped {
+        log::warn!("[macros] {button}: delays add up to more than {MAX_MACRO_DELAY_MS}ms — shortened");
+    }
+    steps
+}
+
+/// Format a macro for display, e.g. "Ctrl+A, wait 50ms, Ctrl+C".
+fn format_macro(steps: &[MacroStep]) -> String {
+    steps
+        .iter()
+        .map(|step| match step {
+            MacroStep::Keys(keys) => format_key_combo(keys),
+            MacroStep::Delay(ms) => format!("wait {ms}ms"),
+            MacroStep::Text(text) => format!("type {text:?}"),
+            MacroStep::Held { modifiers, keys } => format!(
+                "hold {}: {}",
+                format_key_combo(modifiers),
+                keys.iter().map(|k| format_key_combo(k)).collect::<Vec<_>>().join(" ")
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Key repeat timing.
 const REPEAT_DELAY_MS: u64 = 300;  // hold before repeating
 const REPEAT_RATE_MS: u64 = 100;   // interval between repeats
@@ -235,6 +549,11 @@ const REPEAT_RATE_MS: u64 = 100;   // interval between repeats
 const SCROLL_MIN_INTERVAL_MS: u64 = 30;  // fastest scroll at full deflection
 const SCROLL_MAX_INTERVAL_MS: u64 = 200; // slowest scroll near dead zone edge
 const WHEEL_DELTA: i32 = 120;            // Windows standard per notch
+const WHEEL_LINES_PER_NOTCH: f32 = 3.0;  // Windows default lines per notch
+const SMOOTH_SCROLL_STEPS: u64 = 4;      // smooth scrolling splits each event into this many
+const TOUCH_SCROLL_STEP: i32 = 60;       // two-finger travel (touchpad units) per notch
+const TWO_FINGER_TAP_MS: u64 = 250;      // longest touch that still counts as a two-finger tap
+const TWO_FINGER_TAP_TRAVEL: i32 = 40;   // most a tap's fingers may drift (touchpad units)
 
 /// Per-button repeat tracking with two-frame confirmation.
 /// First frame of a new press is "pending" — only fires if still held next frame.
@@ -252,6 +571,11 @@ impl RepeatTimer {
     }
 
     fn on_hold(&mut self, now: Instant) -> bool {
+        self.on_hold_with(now, REPEAT_DELAY_MS, REPEAT_RATE_MS)
+    }
+
+    /// `on_hold` with explicit timing (turbo repeats with no initial delay).
+    fn on_hold_with(&mut self, now: Instant, delay_ms: u64, rate_ms: u64) -> bool {
         if let Some(pending) = self.pending_since.take() {
             self.pressed_at = Some(pending);
             self.last_fired = Some(now);
@@ -262,11 +586,11 @@ impl RepeatTimer {
             None => return false,
         };
         let held_ms = now.duration_since(pressed_at).as_millis() as u64;
-        if held_ms < REPEAT_DELAY_MS {
+        if held_ms < delay_ms {
             return false;
         }
         let last = self.last_fired.unwrap_or(pressed_at);
-        if now.duration_since(last).as_millis() as u64 >= REPEAT_RATE_MS {
+        if now.duration_since(last).as_millis() as u64 >= rate_ms {
             self.last_fired = Some(now);
             return true;
         }
@@ -279,35 +603,237 @@ impl RepeatTimer {
     }
 }
 
+/// One rapid-fire button and its repeat state.
+#[derive(Clone)]
+struct TurboButton {
+    button: Button,
+    timer: RepeatTimer,
+    held: bool,
+}
+
+/// Resolved `[turbo]` settings. Empty `buttons` = turbo off.
+#[derive(Clone, Default)]
+struct TurboState {
+    buttons: Vec<TurboButton>,
+    key: Vec<VKey>,
+    rate_ms: u64,
+}
+
+impl TurboState {
+    fn from_config(cfg: &TurboConfig) -> Self {
+        if !cfg.enabled {
+            return Self::default();
+        }
+        let Some(key) = parse_key_combo(&cfg.key) else {
+            log::warn!("Invalid [turbo] key '{}' — turbo disabled", cfg.key);
+            return Self::default();
+        };
+        let mut buttons: Vec<TurboButton> = Vec::new();
+        for name in &cfg.buttons {
+            match Button::from_name(name) {
+                Some(Button::DpadUp | Button::DpadDown | Button::DpadLeft | Button::DpadRight) => {
+                    log::warn!("[turbo] button '{name}' ignored — the d-pad already repeats");
+                }
+                Some(button) if !buttons.iter().any(|t| t.button == button) => {
+                    buttons.push(TurboButton { button, timer: RepeatTimer::default(), held: false });
+                }
+                Some(_) => {}
+                None => log::warn!("Unknown [turbo] button '{name}'"),
+            }
+        }
+        Self { buttons, key, rate_ms: cfg.rate_ms.max(1) }
+    }
+
+    /// Fire `key` for every turbo button due a repeat. The first repeat waits
+    /// for the two-frame confirm, like the d-pad; there is no initial delay.
+    fn process(&mut self, buttons: &crate::input::ButtonState, now: Instant, actions: &mut Vec<Action>) {
+        for turbo in &mut self.buttons {
+            let held = buttons.pressed(turbo.button);
+            if held && !turbo.held {
+                turbo.timer.on_press(now);
+            } else if held {
+                if turbo.timer.on_hold_with(now, 0, self.rate_ms) {
+                    actions.push(Action::KeyCombo(self.key.clone()));
+                }
+            } else {
+                turbo.timer.on_release();
+            }
+            turbo.held = held;
+        }
+    }
+
+    fn release_all(&mut self) {
+        for turbo in &mut self.buttons {
+            turbo.timer.on_release();
+            turbo.held = false;
+        }
+    }
+}
+
+/// The action that plays `steps`. A macro that is a single held sequence is
+/// sent as one [`Action::KeySequenceHeld`] (a single SendInput call).
+fn macro_action(steps: &[MacroStep]) -> Action {
+    match steps {
+        [MacroStep::Held { modifiers, keys }] => {
+            Action::KeySequenceHeld { modifiers: modifiers.clone(), keys: keys.clone() }
+        }
+        _ => Action::Macro(steps.to_vec()),
+    }
+}
+
+/// One button bound to a macro and its last seen state.
+#[derive(Clone)]
+struct MacroBinding {
+    button: Button,
+    steps: Vec<MacroStep>,
+    held: bool,
+}
+
+/// Resolved `[macros]` bindings.
+#[derive(Clone, Default)]
+struct MacroState {
+    bindings: Vec<MacroBinding>,
+}
+
+impl MacroState {
+    fn from_config(cfg: &MacroConfig) -> Self {
+        let mut bindings: Vec<MacroBinding> = Vec::new();
+        for (name, steps) in &cfg.0 {
+            let Some(button) = Button::from_name(name) else {
+                log::warn!("Unknown [macros] button '{name}'");
+                continue;
+            };
+            if bindings.iter().any(|m| m.button == button) {
+                log::warn!("[macros] button '{name}' bound twice — keeping the first");
+                continue;
+            }
+            let steps = parse_macro(name, steps);
+            if steps.is_empty() {
+                log::warn!("[macros] {name}: no valid steps — macro ignored");
+                continue;
+            }
+            bindings.push(MacroBinding { button, steps, held: false });
+        }
+        Self { bindings }
+    }
+
+    /// Play the macro of every button pressed this frame.
+    fn process(&mut self, buttons: &crate::input::ButtonState, actions: &mut Vec<Action>) {
+        for binding in &mut self.bindings {
+            let held = buttons.pressed(binding.button);
+            if held && !binding.held {
+                actions.push(macro_action(&binding.steps));
+            }
+            binding.held = held;
+        }
+    }
+
+    /// Follow button state without playing anything (passthrough), so a
+    /// button held across the switch doesn't fire on resume.
+    fn track(&mut self, buttons: &crate::input::ButtonState) {
+        for binding in &mut self.bindings {
+            binding.held = buttons.pressed(binding.button);
+        }
+    }
+}
+
+/// Short vs long press, decided on release. Uses the d-pad's two-frame
+/// confirm: a press that is released on its first frame is a glitch.
+#[derive(Clone, Default)]
+struct HoldTimer {
+    pending_since: Option<Instant>,
+    pressed_at: Option<Instant>,
+}
+
+impl HoldTimer {
+    fn on_press(&mut self, now: Instant) {
+        self.pending_since = Some(now);
+        self.pressed_at = None;
+    }
+
+    fn on_hold(&mut self) {
+        if let Some(pending) = self.pending_since.take() {
+            self.pressed_at = Some(pending);
+        }
+    }
+
+    /// How long the confirmed press lasted. None for an unconfirmed
+    /// (single-frame) press.
+    fn on_release(&mut self, now: Instant) -> Option<Duration> {
+        self.pending_since = None;
+        self.pressed_at.take().map(|t| now.duration_since(t))
+    }
+
+    fn reset(&mut self) {
+        self.pending_since = None;
+        self.pressed_at = None;
+    }
+}
+
+/// Double-tap detection for one button: remembers its last rising edge.
+#[derive(Clone, Default)]
+struct TapTracker {
+    last_press: Option<Instant>,
+}
+
+impl TapTracker {
+    /// Record a rising edge. Returns true if it completes a double tap (the
+    /// previous press was within `window`). A completed double tap is not the
+    /// first press of the next one, so a triple tap is double + single.
+    fn on_press(&mut self, now: Instant, window: Duration) -> bool {
+        match self.last_press.take() {
+            Some(prev) if now.duration_since(prev) < window => true,
+            _ => {
+                self.last_press = Some(now);
+                false
+            }
+        }
+    }
+}
+
+/// A resolved tmux binding and the key table it lives in.
+#[derive(Clone, Debug, PartialEq)]
+enum TmuxKey {
+    /// Prefix table: sends prefix, then the key.
+    Prefix(Vec<VKey>),
+    /// Custom key table (`tmux.key_table`): enters the table, then sends the key.
+    Table(Vec<VKey>),
+    /// Root table (`bind -n`, with `tmux.root_bindings`): sends the key alone.
+    Root(Vec<VKey>),
+}
+
 /// Resolved tmux button mappings (parsed once from config strings).
-/// None = unmapped in tmux profile; Some = sends prefix + keys.
+/// None = unmapped in tmux profile; Some = sends prefix (or table switch) + keys.
 #[derive(Clone)]
 struct TmuxState {
     prefix: Vec<VKey>,
-    l1: Option<Vec<VKey>>,
-    r1: Option<Vec<VKey>>,
-    l2: Option<Vec<VKey>>,
-    r2: Option<Vec<VKey>>,
-    l3: Option<Vec<VKey>>,
-    r3: Option<Vec<VKey>>,
-    square: Option<Vec<VKey>>,
-    share: Option<Vec<VKey>>,
-    options: Option<Vec<VKey>>,
-    touchpad: Option<Vec<VKey>>,
+    /// Key sequence that switches into the custom key table (empty = none).
+    table_enter: Vec<Vec<VKey>>,
+    l1: Option<TmuxKey>,
+    r1: Option<TmuxKey>,
+    l2: Option<TmuxKey>,
+    r2: Option<TmuxKey>,
+    l3: Option<TmuxKey>,
+    r3: Option<TmuxKey>,
+    square: Option<TmuxKey>,
+    share: Option<TmuxKey>,
+    options: Option<TmuxKey>,
+    touchpad: Option<TmuxKey>,
 }
 
 impl Default for TmuxState {
     fn default() -> Self {
         Self {
             prefix: vec![VKey::Control, VKey::B],
-            l1: Some(vec![VKey::P]),                   // prev window
-            r1: Some(vec![VKey::N]),                   // next window
+            table_enter: Vec::new(),
+            l1: Some(TmuxKey::Prefix(vec![VKey::P])),               // prev window
+            r1: Some(TmuxKey::Prefix(vec![VKey::N])),               // next window
             l2: None,
-            r2: Some(vec![VKey::Shift, VKey::D7]),     // kill window (&)
+            r2: Some(TmuxKey::Prefix(vec![VKey::Shift, VKey::D7])), // kill window (&)
             l3: None,
             r3: None,
-            square: Some(vec![VKey::C]),               // new window
-            share: None,
+            square: Some(TmuxKey::Prefix(vec![VKey::C])),           // new window
+            share: Some(TmuxKey::Prefix(vec![VKey::LeftBracket])),  // copy-mode
             options: None,
             touchpad: None,
         }
@@ -334,34 +860,49 @@ fn default_key_for_action(action: &str) -> Option<Vec<VKey>> {
     }
 }
 
-/// Resolve a button config value to VKey combo.
+/// Resolve a button config value to a tmux binding.
 ///
 /// Resolution order:
 /// 1. If empty → None (unmapped)
-/// 2. Look up in auto-detected tmux bindings (action name → key)
-/// 3. Look up in hardcoded tmux defaults (action name → key)
-/// 4. Parse as direct key combo string (backward compatible)
-fn resolve_button(value: &str, detected: Option<&TmuxDetected>) -> Option<Vec<VKey>> {
+/// 2. Look up in the detected custom key table (`tmux.key_table`)
+/// 3. With `root`, look up in the detected root table (sent without prefix)
+/// 4. Look up in auto-detected tmux bindings (action name → key)
+/// 5. Look up in hardcoded tmux defaults (action name → key)
+/// 6. Parse as direct key combo string (backward compatible)
+fn resolve_button(value: &str, detected: Option<&TmuxDetected>, root: bool) -> Option<TmuxKey> {
     if value.is_empty() {
         return None;
     }
 
+    // A custom key table is opt-in, so its bindings win over the prefix table
+    let table = detected.and_then(|d| d.key_table.as_ref());
+    if let Some((table, keys)) = table.and_then(|t| t.key_for_action(value).map(|k| (t, k))) {
+        log::debug!("Resolved tmux action '{value}' from key table '{}'", table.name);
+        return Some(TmuxKey::Table(keys.clone()));
+    }
+
+    // So is the root table
+    if let Some(keys) = detected.filter(|_| root).and_then(|d| d.root_key_for_action(value)) {
+        log::debug!("Resolved tmux action '{value}' from the root table");
+        return Some(TmuxKey::Root(keys.clone()));
+    }
+
     // Try auto-detected bindings first
     if let Some(det) = detected {
         if let Some(keys) = det.key_for_action(value) {
             log::debug!("Resolved tmux action '{value}' from detected bindings");
-            return Some(keys.clone());
+            return Some(TmuxKey::Prefix(keys.clone()));
         }
     }
 
     // Try hardcoded defaults for well-known tmux actions
     if let Some(keys) = default_key_for_action(value) {
         log::debug!("Resolved tmux action '{value}' from hardcoded defaults");
-        return Some(keys);
+        return Some(TmuxKey::Prefix(keys));
     }
 
     // Try parsing as direct key combo (backward compatible with manual config)
-    parse_key_combo(value)
+    parse_key_combo(value).map(TmuxKey::Prefix)
 }
 
 impl TmuxState {
@@ -369,7 +910,7 @@ impl TmuxState {
         // Prefix: prefer detected, fall back to config, then hardcoded default
         let prefix = if cfg.auto_detect {
             detected
-                .and_then(|d| d.prefix.clone())
+                .and_then(|d| d.bound_prefix().cloned())
                 .unwrap_or_else(|| {
                     parse_key_combo(&cfg.prefix).unwrap_or_else(|| vec![VKey::Control, VKey::B])
                 })
@@ -379,12 +920,20 @@ impl TmuxState {
 
         // Resolve buttons: action name → detected key → default key → direct key combo
         let det = if cfg.auto_detect { detected } else { None };
-        let resolve = |s: &str| -> Option<Vec<VKey>> { resolve_button(s, det) };
+        let resolve = |s: &str| -> Option<TmuxKey> { resolve_button(s, det, cfg.root_bindings) };
+
+        // Sequence that enters the custom key table (prefix + switch key, or a root key)
+        let table_enter = match det.and_then(|d| d.key_table.as_ref()) {
+            Some(table) if table.enter_via_prefix => vec![prefix.clone(), table.enter_key.clone()],
+            Some(table) => vec![table.enter_key.clone()],
+            None => Vec::new(),
+        };
 
         log::info!("Tmux prefix resolved to: {:?}", prefix);
 
         Self {
             prefix,
+            table_enter,
             l1: resolve(&cfg.l1),
             r1: resolve(&cfg.r1),
             l2: resolve(&cfg.l2),
@@ -397,6 +946,19 @@ impl TmuxState {
             touchpad: resolve(&cfg.touchpad),
         }
     }
+
+    /// Full key sequence to send for a resolved binding.
+    fn sequence(&self, key: &TmuxKey) -> Vec<Vec<VKey>> {
+        match key {
+            TmuxKey::Prefix(keys) => vec![self.prefix.clone(), keys.clone()],
+            TmuxKey::Table(keys) => {
+                let mut seq = self.table_enter.clone();
+                seq.push(keys.clone());
+                seq
+            }
+            TmuxKey::Root(keys) => vec![keys.clone()],
+        }
+    }
 }
 
 // ── OpenCode hardcoded defaults ───────────────────────────────────────
@@ -459,20 +1021,16 @@ fn resolve_opencode_button(
 }
 
 /// Resolved OpenCode button mappings (parsed once from config strings).
-/// None = unmapped; Combo = direct keypress; LeaderKey = leader then key.
+/// None = unmapped; Combo = direct keypress; LeaderKey = leader then key;
+/// LeaderSequence = leader then each key in turn.
 #[derive(Clone)]
 struct OpenCodeState {
     leader: Vec<VKey>,
     l1: Option<ActionBinding>,
     r1: Option<ActionBinding>,
-    l2: Option<ActionBinding>,
-    r2: Option<ActionBinding>,
-    l3: Option<ActionBinding>,
-    r3: Option<ActionBinding>,
     square: Option<ActionBinding>,
     share: Option<ActionBinding>,
     options: Option<ActionBinding>,
-    touchpad: Option<ActionBinding>,
 }
 
 impl Default for OpenCodeState {
@@ -482,19 +1040,37 @@ impl Default for OpenCodeState {
             leader: vec![VKey::Control, VKey::X],
             l1: Some(ActionBinding::Combo(vec![VKey::Control, VKey::LeftBracket])),
             r1: Some(ActionBinding::Combo(vec![VKey::Control, VKey::RightBracket])),
-            l2: None,
-            r2: None,
-            l3: None,
-            r3: None,
             square: Some(ActionBinding::LeaderKey(vec![VKey::N])),
             share: None,
             options: None,
-            touchpad: None,
         }
     }
 }
 
 impl OpenCodeState {
+    /// Action for a resolved binding: a direct combo, or leader then key(s).
+    fn action(&self, binding: &ActionBinding) -> Action {
+        match binding {
+            ActionBinding::Combo(keys) => Action::KeyCombo(keys.clone()),
+            ActionBinding::LeaderKey(keys) => Action::KeySequence(vec![self.leader.clone(), keys.clone()]),
+            ActionBinding::LeaderSequence(steps) => Action::KeySequence(self.leader_sequence(steps)),
+        }
+    }
+
+    /// Human-readable form of a resolved binding (for diagnostics).
+    fn describe(&self, binding: &ActionBinding) -> String {
+        match binding {
+            ActionBinding::Combo(keys) => format_key_combo(keys),
+            ActionBinding::LeaderKey(keys) => format_key_sequence(&[self.leader.clone(), keys.clone()]),
+            ActionBinding::LeaderSequence(steps) => format_key_sequence(&self.leader_sequence(steps)),
+        }
+    }
+
+    /// The leader followed by every step of a chord.
+    fn leader_sequence(&self, steps: &[Vec<VKey>]) -> Vec<Vec<VKey>> {
+        std::iter::once(self.leader.clone()).chain(steps.iter().cloned()).collect()
+    }
+
     fn from_config(cfg: &OpenCodeConfig, detected: Option<&OpenCodeDetected>) -> Self {
         // Leader: prefer detected, fall back to config string, then ctrl+x
         let leader = if cfg.auto_detect {
@@ -517,46 +1093,72 @@ impl OpenCodeState {
             leader,
             l1: resolve(&cfg.l1),
             r1: resolve(&cfg.r1),
-            l2: resolve(&cfg.l2),
-            r2: resolve(&cfg.r2),
-            l3: resolve(&cfg.l3),
-            r3: resolve(&cfg.r3),
             square: resolve(&cfg.square),
             share: resolve(&cfg.share),
             options: resolve(&cfg.options),
-            touchpad: resolve(&cfg.touchpad),
         }
     }
 }
 
 // ── Windows Terminal shortcut state ──────────────────────────────────
 
-/// Resolved key combos for the Windows Terminal shortcut dictionary.
+/// A resolved Windows Terminal button: a key combo, or literal text from a
+/// `sendInput` action, typed out.
+#[derive(Debug, Clone)]
+enum WtBinding {
+    Keys(Vec<VKey>),
+    Text(String),
+}
+
+impl WtBinding {
+    fn action(&self) -> Action {
+        match self {
+            WtBinding::Keys(keys) => Action::KeyCombo(keys.clone()),
+            WtBinding::Text(text) => Action::TypeText(text.clone()),
+        }
+    }
+
+    /// Human-readable form (for diagnostics).
+    fn describe(&self) -> String {
+        match self {
+            WtBinding::Keys(keys) => format_key_combo(keys),
+            WtBinding::Text(text) => format!("type {text:?}"),
+        }
+    }
+}
+
+/// Resolved bindings for the Windows Terminal shortcut dictionary.
 #[derive(Clone)]
 struct WtState {
-    square:  Option<Vec<VKey>>,   // newTab  (profile 1)
-    l1:      Option<Vec<VKey>>,   // prevTab
-    r1:      Option<Vec<VKey>>,   // nextTab
-    l2:      Option<Vec<VKey>>,
-    r2:      Option<Vec<VKey>>,
-    l3:      Option<Vec<VKey>>,
-    r3:      Option<Vec<VKey>>,
-    share:   Option<Vec<VKey>>,
-    options: Option<Vec<VKey>>,
+    square:  Option<WtBinding>,   // newTab  (profile 1)
+    l1:      Option<WtBinding>,   // prevTab
+    r1:      Option<WtBinding>,   // nextTab
+    l2:      Option<WtBinding>,
+    r2:      Option<WtBinding>,
+    l3:      Option<WtBinding>,
+    r3:      Option<WtBinding>,
+    share:   Option<WtBinding>,
+    options: Option<WtBinding>,
+    /// L2 + d-pad pane focus moves, in up/down/left/right order.
+    move_focus: [Vec<VKey>; 4],
 }
 
+/// WT actions behind L2 + d-pad, in up/down/left/right order.
+const WT_MOVE_FOCUS: [&str; 4] = ["moveFocusUp", "moveFocusDown", "moveFocusLeft", "moveFocusRight"];
+
 impl Default for WtState {
     fn default() -> Self {
         Self {
-            square:  default_key_for_wt_action("newTab"),
-            l1:      default_key_for_wt_action("prevTab"),
-            r1:      default_key_for_wt_action("nextTab"),
+            square:  default_key_for_wt_action("newTab").map(WtBinding::Keys),
+            l1:      default_key_for_wt_action("prevTab").map(WtBinding::Keys),
+            r1:      default_key_for_wt_action("nextTab").map(WtBinding::Keys),
             l2:      None,
             r2:      None,
             l3:      None,
             r3:      None,
             share:   None,
             options: None,
+            move_focus: WT_MOVE_FOCUS.map(|action| default_key_for_wt_action(action).unwrap_or_default()),
         }
     }
 }
@@ -573,6 +1175,10 @@ fn default_key_for_wt_action(action: &str) -> Option<Vec<VKey>> {
         "find"         => parse_key_combo("ctrl+shift+f"),
         "splitDown"    => parse_key_combo("alt+shift+minus"),
         "splitRight"   => parse_key_combo("alt+shift+plus"),
+        "moveFocusUp"    => parse_key_combo("alt+up"),
+        "moveFocusDown"  => parse_key_combo("alt+down"),
+        "moveFocusLeft"  => parse_key_combo("alt+left"),
+        "moveFocusRight" => parse_key_combo("alt+right"),
         _ => None,
     }
 }
@@ -596,10 +1202,20 @@ fn resolve_wt_button(value: &str, detected: Option<&WtDetected>) -> Option<Vec<V
     parse_key_combo(value)
 }
 
+/// Resolve a button's value for the Windows Terminal profile, including the
+/// names of detected `sendInput` actions (typed as text).
+fn resolve_wt_binding(value: &str, detected: Option<&WtDetected>) -> Option<WtBinding> {
+    if let Some(text) = detected.and_then(|det| det.text_for_action(value)) {
+        log::debug!("Resolved WT action '{value}' to detected sendInput text");
+        return Some(WtBinding::Text(text.to_string()));
+    }
+    resolve_wt_button(value, detected).map(WtBinding::Keys)
+}
+
 impl WtState {
     fn from_config(cfg: &WtConfig, detected: Option<&WtDetected>) -> Self {
         let det = if cfg.auto_detect { detected } else { None };
-        let resolve = |s: &str| -> Option<Vec<VKey>> { resolve_wt_button(s, det) };
+        let resolve = |s: &str| -> Option<WtBinding> { resolve_wt_binding(s, det) };
         Self {
             square:  resolve(&cfg.square),
             l1:      resolve(&cfg.l1),
@@ -610,10 +1226,135 @@ impl WtState {
             r3:      resolve(&cfg.r3),
             share:   resolve(&cfg.share),
             options: resolve(&cfg.options),
+            move_focus: WT_MOVE_FOCUS.map(|action| resolve_wt_button(action, det).unwrap_or_default()),
+        }
+    }
+}
+
+/// Resolve a `[buttons]` face-button combo. Empty = unmapped; an unparseable
+/// value logs a warning and keeps the built-in default.
+fn resolve_face_button(name: &str, value: &str, default: VKey) -> Option<Vec<VKey>> {
+    if value.trim().is_empty() {
+        return None;
+    }
+    parse_key_combo(value).or_else(|| {
+        log::warn!("Invalid [buttons] {name} = '{value}' — using {}", default.label());
+        Some(vec![default])
+    })
+}
+
+/// Parse a `[tap]` double-tap combo. None = no double-tap action.
+fn double_tap_combo(name: &str, value: &str) -> Option<Vec<VKey>> {
+    if value.trim().is_empty() {
+        return None;
+    }
+    let combo = parse_key_combo(value);
+    if combo.is_none() {
+        log::warn!("Invalid [tap] {name} = '{value}' — no double-tap action");
+    }
+    combo
+}
+
+/// Resolve `[gyro]`: None = disabled, Some(None) = always active,
+/// Some(Some(b)) = active while `b` is held.
+fn gyro_activation(cfg: &GyroConfig) -> Option<Option<Button>> {
+    if !cfg.enabled {
+        return None;
+    }
+    if cfg.button.trim().is_empty() {
+        return Some(None);
+    }
+    match Button::from_name(&cfg.button) {
+        Some(button) => Some(Some(button)),
+        None => {
+            log::warn!("Unknown [gyro] button '{}' — gyro mouse disabled", cfg.button);
+            None
+        }
+    }
+}
+
+/// A touch that may still turn out to be a two-finger tap (right click).
+#[derive(Clone, Copy)]
+struct TouchTap {
+    /// When the first finger landed.
+    started: Instant,
+    /// Where the first finger landed.
+    origin: (u16, u16),
+    /// Midpoint of the two fingers when the second one landed (None = one finger so far).
+    midpoint: Option<(u16, u16)>,
+}
+
+/// Distance between two touchpad points along the axis they differ most on.
+fn touch_travel(a: (u16, u16), b: (u16, u16)) -> i32 {
+    (a.0 as i32 - b.0 as i32).abs().max((a.1 as i32 - b.1 as i32).abs())
+}
+
+/// Resolve `[stick_mouse] momentary_button`: None = the tray toggle decides.
+fn momentary_stick_button(cfg: &StickMouseConfig) -> Option<Button> {
+    if cfg.momentary_button.trim().is_empty() {
+        return None;
+    }
+    let button = Button::from_name(&cfg.momentary_button);
+    if button.is_none() {
+        log::warn!("Unknown [stick_mouse] momentary_button '{}' — using the tray toggle", cfg.momentary_button);
+    }
+    button
+}
+
+/// Resolved hold-layer bindings (parsed once from `[layer]`).
+#[derive(Clone, Default)]
+struct LayerState {
+    /// Button that activates the layer while held. None = layer disabled.
+    button: Option<Button>,
+    /// Per-button combo while the layer is held. Buttons not listed do nothing.
+    bindings: Vec<(Button, Vec<VKey>)>,
+}
+
+impl LayerState {
+    fn from_config(cfg: &LayerConfig) -> Self {
+        let button = Button::from_name(&cfg.button);
+        if button.is_none() && !cfg.button.trim().is_empty() {
+            log::warn!("Unknown [layer] button '{}' — layer disabled", cfg.button);
         }
+        let Some(layer_button) = button else {
+            return Self::default();
+        };
+        let entries = [
+            (Button::Cross, &cfg.cross),
+            (Button::Circle, &cfg.circle),
+            (Button::Square, &cfg.square),
+            (Button::Triangle, &cfg.triangle),
+            (Button::L1, &cfg.l1),
+            (Button::R1, &cfg.r1),
+            (Button::L2, &cfg.l2),
+            (Button::R2, &cfg.r2),
+            (Button::L3, &cfg.l3),
+            (Button::R3, &cfg.r3),
+            (Button::Share, &cfg.share),
+            (Button::Options, &cfg.options),
+            (Button::DpadUp, &cfg.dpad_up),
+            (Button::DpadDown, &cfg.dpad_down),
+            (Button::DpadLeft, &cfg.dpad_left),
+            (Button::DpadRight, &cfg.dpad_right),
+        ];
+        let bindings = entries
+            .into_iter()
+            .filter(|(b, _)| *b != layer_button)
+            .filter_map(|(b, value)| parse_key_combo(value).map(|keys| (b, keys)))
+            .collect();
+        Self { button, bindings }
     }
 }
 
+/// Key bindings found by tmux / OpenCode / Windows Terminal auto-detection
+/// (None = not detected, use the config's defaults).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Detected<'a> {
+    pub tmux: Option<&'a TmuxDetected>,
+    pub opencode: Option<&'a OpenCodeDetected>,
+    pub wt: Option<&'a WtDetected>,
+}
+
 /// Main mapper state.
 pub struct MapperState {
     prev: ButtonState,
@@ -627,23 +1368,86 @@ pub struct MapperState {
     scroll_dead_zone: i16,
     scroll_sensitivity: f32,
     scroll_horizontal: bool,
+    scroll_invert_vertical: bool,
+    scroll_invert_horizontal: bool,
+    scroll_mode: ScrollMode,
+    scroll_lines_per_event: f32,
+    scroll_smooth: bool,
     // Left stick as mouse cursor state
     stick_mouse_enabled: bool,
     stick_mouse_sensitivity: f32,
     stick_mouse_dead_zone: i16,
+    stick_mouse_dead_zone_shape: DeadZoneShape,
+    stick_mouse_axis_snap: f32,
+    stick_mouse_accel_curve: f32,
+    // Right stick moves the cursor, left stick scrolls
+    swap_sticks: bool,
+    // Stick mouse only while this is held (replaces the mouse_stick_active toggle)
+    stick_mouse_button: Option<Button>,
+    // Resting stick centers (x, y), from [calibration]
+    left_center: (u8, u8),
+    right_center: (u8, u8),
     stick_acc_x: f32,
     stick_acc_y: f32,
+    // Gyro as mouse cursor (None = disabled; Some(None) = no activation button)
+    gyro_button: Option<Option<Button>>,
+    gyro_sensitivity: f32,
+    gyro_dead_zone: i16,
+    gyro_acc_x: f32,
+    gyro_acc_y: f32,
     // Mouse mode toggle: shared with tray thread.
     // false = touchpad touch moves cursor; true = left stick moves cursor.
     // Touchpad click (press) fires regardless of mode.
     mouse_stick_active: Arc<AtomicBool>,
+    // Foreground passthrough: shared with the focus watcher thread.
+    // true = a passthrough app is focused, no actions are emitted.
+    passthrough_active: Arc<AtomicBool>,
+    passthrough: bool, // passthrough state seen on the previous frame
     // Touchpad-as-mouse state
     prev_touch: Option<(u16, u16)>,
+    // Two-finger scroll: previous averaged Y and travel not yet turned into notches
+    prev_two_finger_y: Option<u16>,
+    touch_scroll_acc: i32,
     touchpad_enabled: bool,
     touchpad_sensitivity: f32,
+    // Left button held down by a touchpad press (a click or drag in progress)
+    touch_drag: bool,
+    // A finger was on the touchpad last frame (touchpad mode), and the touch
+    // in progress while it may still be a two-finger tap
+    touching: bool,
+    touch_tap: Option<TouchTap>,
+    // Face buttons (always active). None = unmapped.
+    cross: Option<Vec<VKey>>,
+    circle: Option<Vec<VKey>>,
+    triangle: Option<Vec<VKey>>,
+    // Double-tap combos for the face buttons (None = no double-tap action)
+    double_tap_window: Duration,
+    double_cross: Option<Vec<VKey>>,
+    double_circle: Option<Vec<VKey>>,
+    double_triangle: Option<Vec<VKey>>,
+    tap_cross: TapTracker,
+    tap_circle: TapTracker,
+    tap_triangle: TapTracker,
+    // Options short/long press in the Default profile (None = nothing)
+    options_tap: Option<Vec<VKey>>,
+    options_hold: Option<Vec<VKey>>,
+    options_hold_threshold: Duration,
+    options_timer: HoldTimer,
+    // Create + PS chord → screenshot combo (None = chord disabled)
+    screenshot: Option<Vec<VKey>>,
+    // Rapid-fire buttons (their normal actions are suppressed)
+    turbo: TurboState,
+    // Macro buttons (their normal actions are suppressed)
+    macros: MacroState,
+    // Hold layer (Fn-style alternate bindings)
+    layer: LayerState,
+    // Whether L2's Ctrl+Win KeyDown has been sent and still needs its KeyUp
+    l2_hold_active: bool,
     // Profile system
     active_profile: Profile,
-    tmux_available: bool, // false = only Default profile, PS does nothing
+    tmux_available: bool, // false = PS skips the Tmux profile
+    opencode_available: bool, // false = PS skips the Agent profile
+    profile_file: Option<PathBuf>, // where PS profile switches are saved (None = not persisted)
     tmux: TmuxState,
     opencode: OpenCodeState,
     wt: WtState,
@@ -661,17 +1465,62 @@ impl Default for MapperState {
             scroll_dead_zone: 20,
             scroll_sensitivity: 1.0,
             scroll_horizontal: true,
+            scroll_invert_vertical: false,
+            scroll_invert_horizontal: false,
+            scroll_mode: ScrollMode::Wheel,
+            scroll_lines_per_event: 3.0,
+            scroll_smooth: false,
             stick_mouse_enabled: true,
             stick_mouse_sensitivity: 8.0,
             stick_mouse_dead_zone: 15,
+            stick_mouse_dead_zone_shape: DeadZoneShape::Axial,
+            stick_mouse_axis_snap: 0.0,
+            stick_mouse_accel_curve: 1.0,
+            swap_sticks: false,
+            stick_mouse_button: None,
+            left_center: (128, 128),
+            right_center: (128, 128),
             stick_acc_x: 0.0,
             stick_acc_y: 0.0,
+            gyro_button: None,
+            gyro_sensitivity: 0.002,
+            gyro_dead_zone: 30,
+            gyro_acc_x: 0.0,
+            gyro_acc_y: 0.0,
             mouse_stick_active: Arc::new(AtomicBool::new(false)),
+            passthrough_active: Arc::new(AtomicBool::new(false)),
+            passthrough: false,
             prev_touch: None,
+            prev_two_finger_y: None,
+            touch_scroll_acc: 0,
             touchpad_enabled: true,
             touchpad_sensitivity: 1.5,
+            touch_drag: false,
+            touching: false,
+            touch_tap: None,
+            cross: Some(vec![VKey::Return]),
+            circle: Some(vec![VKey::Escape]),
+            triangle: Some(vec![VKey::Tab]),
+            double_tap_window: Duration::from_millis(250),
+            double_cross: None,
+            double_circle: None,
+            double_triangle: None,
+            tap_cross: TapTracker::default(),
+            tap_circle: TapTracker::default(),
+            tap_triangle: TapTracker::default(),
+            options_tap: Some(vec![VKey::Escape]),
+            options_hold: Some(vec![VKey::Control, VKey::Shift, VKey::P]),
+            options_hold_threshold: Duration::from_millis(500),
+            options_timer: HoldTimer::default(),
+            screenshot: Some(vec![VKey::Win, VKey::Shift, VKey::S]),
+            turbo: TurboState::default(),
+            macros: MacroState::default(),
+            layer: LayerState::default(),
+            l2_hold_active: false,
             active_profile: Profile::Default,
             tmux_available: true,
+            opencode_available: true,
+            profile_file: None,
             tmux: TmuxState::default(),
             opencode: OpenCodeState::default(),
             wt: WtState::default(),
@@ -683,34 +1532,102 @@ impl MapperState {
     /// Create a mapper with config-driven settings.
     /// Detected configurations are used to resolve action-name → key bindings.
     pub fn new(
-        scroll: &ScrollConfig,
-        stick_mouse: &StickMouseConfig,
-        touchpad: &TouchpadConfig,
-        tmux: &TmuxConfig,
-        tmux_detected: Option<&TmuxDetected>,
-        opencode: &OpenCodeConfig,
-        opencode_detected: Option<&OpenCodeDetected>,
-        wt: &WtConfig,
-        wt_detected: Option<&WtDetected>,
+        cfg: &Config,
+        detected: Detected,
         mouse_stick_active: Arc<AtomicBool>,
+        passthrough_active: Arc<AtomicBool>,
+        state_dir: Option<&Path>,
     ) -> Self {
-        Self {
-            scroll_dead_zone: scroll.dead_zone as i16,
-            scroll_sensitivity: scroll.sensitivity,
-            scroll_horizontal: scroll.horizontal,
-            stick_mouse_enabled: stick_mouse.enabled,
-            stick_mouse_sensitivity: stick_mouse.sensitivity,
-            stick_mouse_dead_zone: stick_mouse.dead_zone as i16,
+        let Config { buttons, tap, turbo, macros, layer, tmux, opencode, wt, .. } = cfg;
+        let mut mapper = Self {
             mouse_stick_active,
-            touchpad_enabled: touchpad.enabled,
-            touchpad_sensitivity: touchpad.sensitivity,
+            passthrough_active,
+            cross: resolve_face_button("cross", &buttons.cross, VKey::Return),
+            circle: resolve_face_button("circle", &buttons.circle, VKey::Escape),
+            triangle: resolve_face_button("triangle", &buttons.triangle, VKey::Tab),
+            double_tap_window: Duration::from_millis(tap.double_tap_ms),
+            double_cross: double_tap_combo("cross", &tap.cross),
+            double_circle: double_tap_combo("circle", &tap.circle),
+            double_triangle: double_tap_combo("triangle", &tap.triangle),
+            options_tap: parse_key_combo(&buttons.options_tap),
+            options_hold: parse_key_combo(&buttons.options_hold),
+            options_hold_threshold: Duration::from_millis(buttons.options_hold_ms),
+            screenshot: parse_key_combo(&buttons.screenshot),
+            turbo: TurboState::from_config(turbo),
+            macros: MacroState::from_config(macros),
+            layer: LayerState::from_config(layer),
             active_profile: Profile::Default,
             tmux_available: tmux.enabled,
-            tmux: TmuxState::from_config(tmux, tmux_detected),
-            opencode: OpenCodeState::from_config(opencode, opencode_detected),
-            wt: WtState::from_config(wt, wt_detected),
+            opencode_available: opencode.enabled,
+            tmux: TmuxState::from_config(tmux, detected.tmux),
+            opencode: OpenCodeState::from_config(opencode, detected.opencode),
+            wt: WtState::from_config(wt, detected.wt),
+            profile_file: state_dir.map(|dir| dir.join(PROFILE_FILE)),
             ..Default::default()
+        };
+        mapper.apply_live_config(&cfg.scroll, &cfg.stick_mouse, &cfg.gyro, &cfg.touchpad);
+        // Resume in the profile selected before the last restart, if it's still enabled
+        let saved = mapper
+            .profile_file
+            .as_deref()
+            .and_then(load_profile)
+            .filter(|&p| mapper.profile_available(p));
+        if let Some(saved) = saved {
+            mapper.active_profile = saved;
+            log::info!("Restored profile: {saved}");
+        }
+        mapper
+    }
+
+    /// Apply the hot-reloadable settings: scroll, stick mouse, gyro and
+    /// touchpad tuning. Bindings and profiles need a reconnect to change.
+    pub fn apply_live_config(
+        &mut self,
+        scroll: &ScrollConfig,
+        stick_mouse: &StickMouseConfig,
+        gyro: &GyroConfig,
+        touchpad: &TouchpadConfig,
+    ) {
+        self.scroll_dead_zone = scroll.dead_zone as i16;
+        self.scroll_sensitivity = scroll.sensitivity;
+        self.scroll_horizontal = scroll.horizontal;
+        self.scroll_invert_vertical = scroll.invert_vertical;
+        self.scroll_invert_horizontal = scroll.invert_horizontal;
+        self.scroll_mode = scroll.mode;
+        self.scroll_lines_per_event = scroll.lines_per_event.max(0.0);
+        self.scroll_smooth = scroll.smooth;
+        self.stick_mouse_enabled = stick_mouse.enabled;
+        self.stick_mouse_sensitivity = stick_mouse.sensitivity;
+        self.stick_mouse_dead_zone = stick_mouse.dead_zone as i16;
+        self.stick_mouse_dead_zone_shape = stick_mouse.dead_zone_shape;
+        self.stick_mouse_axis_snap = stick_mouse.axis_snap_ratio.clamp(0.0, 1.0);
+        self.stick_mouse_accel_curve = stick_mouse.accel_curve;
+        self.swap_sticks = stick_mouse.swap_sticks;
+        self.stick_mouse_button = momentary_stick_button(stick_mouse);
+        self.gyro_button = gyro_activation(gyro);
+        self.gyro_sensitivity = gyro.sensitivity;
+        self.gyro_dead_zone = gyro.dead_zone.max(0);
+        self.touchpad_enabled = touchpad.enabled;
+        self.touchpad_sensitivity = touchpad.sensitivity;
+    }
+
+    /// Releases for the holds in progress (L2's Ctrl+Win, a touchpad drag),
+    /// so nothing stays pressed when mapping pauses or the controller goes away.
+    pub fn release_holds(&mut self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        if std::mem::take(&mut self.l2_hold_active) {
+            actions.push(Action::KeyUp(vec![VKey::Control, VKey::Win]));
+        }
+        if std::mem::take(&mut self.touch_drag) {
+            actions.push(Action::MouseButtonUp);
         }
+        actions
+    }
+
+    /// Measure stick deflection from these resting centers instead of 128.
+    pub fn set_calibration(&mut self, calibration: &CalibrationConfig) {
+        self.left_center = (calibration.left_x, calibration.left_y);
+        self.right_center = (calibration.right_x, calibration.right_y);
     }
 
     /// Returns the currently active profile.
@@ -718,82 +1635,371 @@ impl MapperState {
         self.active_profile
     }
 
-    /// Given current input, return actions for newly pressed buttons and analog input.
-    pub fn update(&mut self, input: &UnifiedInput) -> Vec<Action> {
-        let current = &input.buttons;
-        let mut actions = Vec::new();
-        let now = Instant::now();
+    /// Switch to `profile` from outside the controller (tray menu). Returns
+    /// false, changing nothing, if it's disabled or already active.
+    pub fn set_profile(&mut self, profile: Profile) -> bool {
+        if profile == self.active_profile || !self.profile_available(profile) {
+            return false;
+        }
+        self.active_profile = profile;
+        self.persist_profile();
+        log::info!("Profile set to: {profile}");
+        true
+    }
 
-        // --- Face buttons: rising edge only ---
-        macro_rules! on_press {
-            ($field:ident, $action:expr) => {
-                if current.$field && !self.prev.$field {
-                    actions.push($action);
-                }
-            };
+    /// Profile the PS button switches to from `from`, skipping disabled ones.
+    /// None when no profile besides Default is available.
+    fn next_profile(&self, from: Profile) -> Option<Profile> {
+        let cycle = |p: Profile| match p {
+            Profile::Default => Profile::Tmux,
+            Profile::Tmux => Profile::Agent,
+            Profile::Agent => Profile::Default,
+        };
+        let mut next = cycle(from);
+        while !self.profile_available(next) {
+            next = cycle(next);
         }
+        (next != from).then_some(next)
+    }
+
+    /// Whether `profile` is enabled in config (Default always is).
+    fn profile_available(&self, profile: Profile) -> bool {
+        match profile {
+            Profile::Default => true,
+            Profile::Tmux => self.tmux_available,
+            Profile::Agent => self.opencode_available,
+        }
+    }
+
+    /// Save the active profile so the next start resumes in it.
+    fn persist_profile(&self) {
+        let Some(path) = &self.profile_file else { return };
+        if let Err(e) = save_profile(path, self.active_profile) {
+            log::debug!("Failed to persist profile: {e}");
+        }
+    }
+
+    /// Every button binding as resolved from config and auto-detection, per
+    /// profile. Mirrors the dispatch in `update`; unmapped buttons are omitted.
+    pub fn resolved_bindings(&self) -> Vec<ResolvedBinding> {
+        let mut out = Vec::new();
+        let mut push = |scope: BindingScope, button: &str, action: String| {
+            out.push(ResolvedBinding { scope, button: button.to_string(), action });
+        };
+        let combo = |keys: &[VKey]| format_key_combo(keys);
+
+        // Always active
+        let always = BindingScope::Always;
+        let face = [("cross", &self.cross), ("circle", &self.circle)];
+        for (button, keys) in face {
+            if let Some(keys) = keys {
+                push(always, button, combo(keys));
+            }
+        }
+        if let Some(ref keys) = self.triangle {
+            push(always, "triangle", format!("{} (not in agent)", combo(keys)));
+        }
+        let doubles = [
+            ("cross×2", &self.double_cross),
+            ("circle×2", &self.double_circle),
+            ("triangle×2", &self.double_triangle),
+        ];
+        for (button, keys) in doubles {
+            if let Some(keys) = keys {
+                push(always, button, combo(keys));
+            }
+        }
+        push(always, "l2", format!("hold {}", combo(&[VKey::Control, VKey::Win])));
+        push(always, "dpad_up", combo(&[VKey::Up]));
+        push(always, "dpad_down", combo(&[VKey::Down]));
+        push(always, "dpad_left", combo(&[VKey::Left]));
+        push(always, "dpad_right", combo(&[VKey::Right]));
+        if self.tmux_available || self.opencode_available {
+            push(always, "ps", "cycle profile".into());
+        }
+        if let Some(ref keys) = self.screenshot {
+            push(always, "share+ps", combo(keys));
+        }
+
+        // Default profile (Windows Terminal)
+        let default = BindingScope::Profile(Profile::Default);
+        let wt = [("square", &self.wt.square), ("l1", &self.wt.l1), ("r1", &self.wt.r1)];
+        for (button, binding) in wt {
+            if let Some(binding) = binding {
+                push(default, button, binding.describe());
+            }
+        }
+        let panes = ["l2+dpad_up", "l2+dpad_down", "l2+dpad_left", "l2+dpad_right"];
+        for (button, keys) in panes.into_iter().zip(&self.wt.move_focus) {
+            push(default, button, combo(keys));
+        }
+        push(default, "r2", combo(&[VKey::Control, VKey::C]));
+        push(default, "l3", combo(&[VKey::Control, VKey::T]));
+        push(default, "r3", combo(&[VKey::Control, VKey::P]));
+        if let Some(ref keys) = self.options_tap {
+            push(default, "options", format!("tap {}", combo(keys)));
+        }
+        if let Some(ref keys) = self.options_hold {
+            let ms = self.options_hold_threshold.as_millis();
+            push(default, "options", format!("hold {ms}ms {}", combo(keys)));
+        }
+
+        // Tmux profile
+        if self.tmux_available {
+            let tmux = BindingScope::Profile(Profile::Tmux);
+            let keys = [
+                ("l1", &self.tmux.l1),
+                ("r1", &self.tmux.r1),
+                ("square", &self.tmux.square),
+                ("r2", &self.tmux.r2),
+                ("share", &self.tmux.share),
+                ("options", &self.tmux.options),
+            ];
+            for (button, key) in keys {
+                if let Some(key) = key {
+                    push(tmux, button, format_key_sequence(&self.tmux.sequence(key)));
+                }
+            }
+            push(tmux, "l3", combo(&[VKey::Control, VKey::T]));
+            push(tmux, "r3", combo(&[VKey::Control, VKey::U]));
+        }
+
+        // Agent profile
+        if self.opencode_available {
+            let agent = BindingScope::Profile(Profile::Agent);
+            let opencode = [
+                ("l1", &self.opencode.l1),
+                ("r1", &self.opencode.r1),
+                ("square", &self.opencode.square),
+                ("share", &self.opencode.share),
+                ("options", &self.opencode.options),
+            ];
+            for (button, binding) in opencode {
+                if let Some(binding) = binding {
+                    push(agent, button, self.opencode.describe(binding));
+                }
+            }
+            let tmux = [("r2+l1", &self.tmux.l1), ("r2+r1", &self.tmux.r1), ("triangle", &self.tmux.square)];
+            for (button, key) in tmux {
+                if let Some(key) = key {
+                    push(agent, button, format_key_sequence(&self.tmux.sequence(key)));
+                }
+            }
+            push(agent, "l3", combo(&[VKey::Control, VKey::T]));
+            push(agent, "r3", combo(&[VKey::Control, VKey::P]));
+        }
+
+        if let Some(button) = self.stick_mouse_button {
+            push(always, button.name(), "hold: stick mouse".into());
+        }
+
+        // Gyro
+        match self.gyro_button {
+            Some(Some(button)) => push(always, button.name(), "hold: gyro mouse".into()),
+            Some(None) => push(always, "gyro", "mouse cursor".into()),
+            None => {}
+        }
+
+        // Turbo
+        for turbo in &self.turbo.buttons {
+            let action = format!("repeat {} every {}ms", combo(&self.turbo.key), self.turbo.rate_ms);
+            push(always, turbo.button.name(), action);
+        }
+
+        // Macros
+        for binding in &self.macros.bindings {
+            push(always, binding.button.name(), format!("macro: {}", format_macro(&binding.steps)));
+        }
+
+        // Hold layer
+        if let Some(layer_button) = self.layer.button {
+            for (button, keys) in &self.layer.bindings {
+                push(BindingScope::Layer, &format!("{}+{}", layer_button.name(), button.name()), combo(keys));
+            }
+        }
+
+        out
+    }
+
+    /// Given current input, return actions for newly pressed buttons and analog input.
+    pub fn update(&mut self, input: &UnifiedInput) -> Vec<Action> {
+        self.update_at(input, Instant::now())
+    }
+
+    fn update_at(&mut self, input: &UnifiedInput, now: Instant) -> Vec<Action> {
+        // Turbo buttons only repeat; hide them from every other mapping
+        let mut buttons = input.buttons;
+        for turbo in &self.turbo.buttons {
+            buttons.release(turbo.button);
+        }
+        // Same for macro buttons
+        for binding in &self.macros.bindings {
+            buttons.release(binding.button);
+        }
+        // So do the gyro and momentary stick mouse buttons
+        if let Some(Some(button)) = self.gyro_button {
+            buttons.release(button);
+        }
+        if let Some(button) = self.stick_mouse_button {
+            buttons.release(button);
+        }
+        let current = &buttons;
+        let mut actions = Vec::new();
+
+        // --- Passthrough app focused: emit nothing, just track state ---
+        if self.passthrough_active.load(Ordering::Relaxed) {
+            self.turbo.release_all();
+            self.macros.track(&input.buttons);
+            // Release a hold that was in progress when the app took focus
+            actions.extend(self.release_holds());
+            self.passthrough = true;
+            self.prev_touch = None;
+            self.prev_two_finger_y = None;
+            self.touch_scroll_acc = 0;
+            self.touching = false;
+            self.touch_tap = None;
+            self.last_scroll_at = None;
+            self.stick_acc_x = 0.0;
+            self.stick_acc_y = 0.0;
+            self.gyro_acc_x = 0.0;
+            self.gyro_acc_y = 0.0;
+            self.prev = *current;
+            return actions;
+        }
+        self.passthrough = false;
+
+        // --- Face buttons: rising edge only ---
+        macro_rules! on_press {
+            ($field:ident, $action:expr) => {
+                if current.$field && !self.prev.$field {
+                    actions.push($action);
+                }
+            };
+        }
+        // Configured combo (None = unmapped); the double-tap combo, if set,
+        // replaces the second of two presses inside the window.
+        macro_rules! on_press_combo {
+            ($field:ident, $tap:ident, $double:ident) => {
+                if current.$field && !self.prev.$field {
+                    let double = self.$double.is_some()
+                        && self.$tap.on_press(now, self.double_tap_window);
+                    let keys = if double { &self.$double } else { &self.$field };
+                    if let Some(keys) = keys {
+                        actions.push(Action::KeyCombo(keys.clone()));
+                    }
+                }
+            };
+        }
+
+        // --- Turbo: rapid-fire while held (all profiles) ---
+        self.turbo.process(&input.buttons, now, &mut actions);
+
+        // --- Macros: play the configured sequence on press (all profiles) ---
+        self.macros.process(&input.buttons, &mut actions);
 
         // --- Touchpad: touch → cursor movement, click → left mouse button (always active) ---
-        self.process_touchpad(input, &mut actions);
+        self.process_touchpad(input, now, &mut actions);
 
-        // --- Left stick → mouse cursor (always active) ---
+        // --- Left stick (right with swap_sticks) → mouse cursor (always active) ---
         self.process_stick_mouse(input, &mut actions);
 
-        // --- Always active face buttons ---
-        on_press!(cross, Action::KeyCombo(vec![VKey::Return]));
-        on_press!(circle, Action::KeyCombo(vec![VKey::Escape]));
-        on_press!(triangle, Action::KeyCombo(vec![VKey::Tab]));
+        // --- Gyro → mouse cursor while the activation button is held ---
+        self.process_gyro(input, &mut actions);
 
-        // --- PS button: cycle profiles ---
-        if current.ps && !self.prev.ps && self.tmux_available {
-            self.active_profile = match self.active_profile {
-                Profile::Default => Profile::Tmux,
-                Profile::Tmux    => Profile::Default,
-            };
+        // --- Hold layer: while its button is held, other buttons use the layer table ---
+        let layer_held = self.layer.button.is_some_and(|b| current.pressed(b));
+        if layer_held {
+            for (button, keys) in &self.layer.bindings {
+                if current.pressed(*button) && !self.prev.pressed(*button) {
+                    actions.push(Action::KeyCombo(keys.clone()));
+                }
+            }
+            // A base press before the layer must not pair with one after it
+            self.tap_cross = TapTracker::default();
+            self.tap_circle = TapTracker::default();
+            self.tap_triangle = TapTracker::default();
+        } else {
+            // --- Always active face buttons ---
+            on_press_combo!(cross, tap_cross, double_cross);
+            on_press_combo!(circle, tap_circle, double_circle);
+            // Agent profile uses Triangle for tmux new-window
+            if self.active_profile != Profile::Agent {
+                on_press_combo!(triangle, tap_triangle, double_triangle);
+            }
+        }
+
+        // --- Create + PS: screenshot chord (hold Create, then press PS) ---
+        let screenshot = if current.ps && !self.prev.ps && current.share {
+            self.screenshot.clone()
+        } else {
+            None
+        };
+        let screenshot_chord = screenshot.is_some();
+        if let Some(keys) = screenshot {
+            actions.push(Action::KeyCombo(keys));
+        }
+
+        // --- PS button: cycle profiles (not when it completes the screenshot chord) ---
+        let next_profile = if current.ps && !self.prev.ps && !screenshot_chord {
+            self.next_profile(self.active_profile)
+        } else {
+            None
+        };
+        if let Some(profile) = next_profile {
+            self.active_profile = profile;
+            self.persist_profile();
             actions.push(Action::Custom(format!("profile:{}", self.active_profile)));
             log::info!("Profile switched to: {}", self.active_profile);
         }
 
         // --- Profile-dependent buttons ---
         match self.active_profile {
+            // Layer held: the layer table already handled these buttons
+            _ if layer_held => {}
             Profile::Default => {
                 // Square → Windows Terminal new tab (profile 1, auto-detected or ctrl+shift+1)
                 if current.square && !self.prev.square {
-                    if let Some(ref keys) = self.wt.square {
-                        actions.push(Action::KeyCombo(keys.clone()));
+                    if let Some(ref binding) = self.wt.square {
+                        actions.push(binding.action());
                     }
                 }
                 // L1 → previous tab (auto-detected or ctrl+shift+tab)
                 if current.l1 && !self.prev.l1 {
-                    if let Some(ref keys) = self.wt.l1 {
-                        actions.push(Action::KeyCombo(keys.clone()));
+                    if let Some(ref binding) = self.wt.l1 {
+                        actions.push(binding.action());
                     }
                 }
                 // R1 → next tab (auto-detected or ctrl+tab)
                 if current.r1 && !self.prev.r1 {
-                    if let Some(ref keys) = self.wt.r1 {
-                        actions.push(Action::KeyCombo(keys.clone()));
+                    if let Some(ref binding) = self.wt.r1 {
+                        actions.push(binding.action());
                     }
                 }
-                // L2: hold Ctrl+Win while button is held
-                if current.l2 && !self.prev.l2 {
-                    actions.push(Action::KeyDown(vec![VKey::Control, VKey::Win]));
-                } else if !current.l2 && self.prev.l2 {
-                    actions.push(Action::KeyUp(vec![VKey::Control, VKey::Win]));
-                }
                 on_press!(r2, Action::KeyCombo(vec![VKey::Control, VKey::C]));
                 on_press!(l3, Action::KeyCombo(vec![VKey::Control, VKey::T]));
                 on_press!(r3, Action::KeyCombo(vec![VKey::Control, VKey::P]));
+                // Options → tap / hold combo, decided on release
+                if current.options && !self.prev.options {
+                    self.options_timer.on_press(now);
+                } else if current.options {
+                    self.options_timer.on_hold();
+                } else if let Some(held) = self.options_timer.on_release(now) {
+                    let keys = if held >= self.options_hold_threshold {
+                        self.options_hold.as_ref().or(self.options_tap.as_ref())
+                    } else {
+                        self.options_tap.as_ref()
+                    };
+                    if let Some(keys) = keys {
+                        actions.push(Action::KeyCombo(keys.clone()));
+                    }
+                }
             }
             Profile::Tmux => {
                 macro_rules! on_press_tmux {
                     ($field:ident, $keys_field:ident) => {
                         if current.$field && !self.prev.$field {
-                            if let Some(ref keys) = self.tmux.$keys_field {
-                                actions.push(Action::KeySequence(vec![
-                                    self.tmux.prefix.clone(),
-                                    keys.clone(),
-                                ]));
+                            if let Some(ref key) = self.tmux.$keys_field {
+                                actions.push(Action::KeySequence(self.tmux.sequence(key)));
                             }
                         }
                     };
@@ -802,12 +2008,6 @@ impl MapperState {
                 on_press_tmux!(l1, l1);
                 on_press_tmux!(r1, r1);
                 on_press_tmux!(square, square);
-                // L2: hold Ctrl+Win while button is held
-                if current.l2 && !self.prev.l2 {
-                    actions.push(Action::KeyDown(vec![VKey::Control, VKey::Win]));
-                } else if !current.l2 && self.prev.l2 {
-                    actions.push(Action::KeyUp(vec![VKey::Control, VKey::Win]));
-                }
                 on_press_tmux!(r2, r2);
                 on_press!(l3, Action::KeyCombo(vec![VKey::Control, VKey::T]));
                 on_press!(r3, Action::KeyCombo(vec![VKey::Control, VKey::U]));
@@ -815,7 +2015,69 @@ impl MapperState {
                 on_press_tmux!(options, options);
                 // Note: touchpad button is handled globally by process_touchpad() above.
             }
+            Profile::Agent => {
+                macro_rules! on_press_opencode {
+                    ($field:ident) => {
+                        if current.$field && !self.prev.$field {
+                            if let Some(ref binding) = self.opencode.$field {
+                                actions.push(self.opencode.action(binding));
+                            }
+                        }
+                    };
+                }
+                macro_rules! on_press_tmux {
+                    ($field:ident, $keys_field:ident) => {
+                        if current.$field && !self.prev.$field {
+                            if let Some(ref key) = self.tmux.$keys_field {
+                                actions.push(Action::KeySequence(self.tmux.sequence(key)));
+                            }
+                        }
+                    };
+                }
+
+                // R2 held turns the shoulders into tmux window nav
+                if current.r2 {
+                    on_press_tmux!(l1, l1);
+                    on_press_tmux!(r1, r1);
+                } else {
+                    on_press_opencode!(l1);
+                    on_press_opencode!(r1);
+                }
+                on_press_opencode!(square);
+                on_press_tmux!(triangle, square);
+                on_press!(l3, Action::KeyCombo(vec![VKey::Control, VKey::T]));
+                on_press!(r3, Action::KeyCombo(vec![VKey::Control, VKey::P]));
+                on_press_opencode!(share);
+                on_press_opencode!(options);
+            }
+
+        }
+
+        // Options tap/hold only runs in the Default profile without the layer
+        if layer_held || self.active_profile != Profile::Default {
+            self.options_timer.reset();
+        }
 
+        // --- L2 + d-pad: Windows Terminal pane focus (Default profile) or
+        // tmux copy-mode (Tmux profile, where the right stick scrolls lines too).
+        // Using the layer ends the dictation hold L2 started, so L2 alone
+        // still dictates.
+        let l2_layer = current.l2 && !layer_held;
+        let copy_layer = l2_layer && self.active_profile == Profile::Tmux;
+        let pane_layer = l2_layer && self.active_profile == Profile::Default;
+        let dpad_used = current.dpad != DPad::Neutral;
+        let l2_nav = (copy_layer && (dpad_used || self.scroll_deflected(input)))
+            || (pane_layer && dpad_used);
+
+        // --- L2: hold Ctrl+Win while button is held (both profiles) ---
+        // The release is tracked separately so a hold started before the layer
+        // (or another mode) took over is still released cleanly.
+        if current.l2 && !self.prev.l2 && !layer_held && !l2_nav && !self.l2_hold_active {
+            actions.push(Action::KeyDown(vec![VKey::Control, VKey::Win]));
+            self.l2_hold_active = true;
+        } else if (!current.l2 || l2_nav) && self.l2_hold_active {
+            actions.push(Action::KeyUp(vec![VKey::Control, VKey::Win]));
+            self.l2_hold_active = false;
         }
 
         // --- D-pad with two-frame confirm + repeat ---
@@ -830,12 +2092,12 @@ impl MapperState {
         let prev_right = matches!(self.prev.dpad, DPad::Right | DPad::UpRight | DPad::DownRight);
 
         macro_rules! dpad {
-            ($held:expr, $prev:expr, $timer:expr, $key:expr) => {
+            ($held:expr, $prev:expr, $timer:expr, $keys:expr) => {
                 if $held && !$prev {
                     $timer.on_press(now);
                 } else if $held {
                     if $timer.on_hold(now) {
-                        actions.push(Action::KeyCombo(vec![$key]));
+                        actions.push(Action::KeyCombo($keys));
                     }
                 } else {
                     $timer.on_release();
@@ -843,23 +2105,60 @@ impl MapperState {
             };
         }
 
-        dpad!(up_held, prev_up, self.repeat_up, VKey::Up);
-        dpad!(down_held, prev_down, self.repeat_down, VKey::Down);
-        dpad!(left_held, prev_left, self.repeat_left, VKey::Left);
-        dpad!(right_held, prev_right, self.repeat_right, VKey::Right);
+        if layer_held {
+            // D-pad presses went to the layer table; don't let repeats build up
+            self.repeat_up.on_release();
+            self.repeat_down.on_release();
+            self.repeat_left.on_release();
+            self.repeat_right.on_release();
+        } else {
+            // Copy-mode layer: up/down page by half a screen (vi copy-mode keys).
+            // Pane layer: move focus between Windows Terminal panes.
+            let [up, down, left, right] = if copy_layer {
+                [vec![VKey::Control, VKey::U], vec![VKey::Control, VKey::D], vec![VKey::Left], vec![VKey::Right]]
+            } else if pane_layer {
+                self.wt.move_focus.clone()
+            } else {
+                [vec![VKey::Up], vec![VKey::Down], vec![VKey::Left], vec![VKey::Right]]
+            };
+            dpad!(up_held, prev_up, self.repeat_up, up.clone());
+            dpad!(down_held, prev_down, self.repeat_down, down.clone());
+            dpad!(left_held, prev_left, self.repeat_left, left.clone());
+            dpad!(right_held, prev_right, self.repeat_right, right.clone());
+        }
 
-        // --- Right stick → scroll ---
-        self.process_scroll(input.right_stick, now, &mut actions);
+        // --- Right stick (left with swap_sticks) → scroll (line scroll in the copy-mode layer) ---
+        self.process_scroll(input, now, copy_layer, &mut actions);
 
         self.prev = *current;
         actions
     }
 
-    /// Process right stick into scroll actions with dead zone and rate limiting.
-    fn process_scroll(&mut self, stick: (u8, u8), now: Instant, actions: &mut Vec<Action>) {
-        let (rx, ry) = stick;
-        let dx = rx as i16 - 128;
-        let dy = ry as i16 - 128;
+    /// The scrolling stick's position and resting center: the right stick,
+    /// or the left one with `swap_sticks`.
+    fn scroll_stick(&self, input: &UnifiedInput) -> ((u8, u8), (u8, u8)) {
+        if self.swap_sticks { (input.left_stick, self.left_center) } else { (input.right_stick, self.right_center) }
+    }
+
+    /// The cursor stick's position and resting center (the other one).
+    fn cursor_stick(&self, input: &UnifiedInput) -> ((u8, u8), (u8, u8)) {
+        if self.swap_sticks { (input.right_stick, self.right_center) } else { (input.left_stick, self.left_center) }
+    }
+
+    /// True if the scrolling stick is pushed past the scroll dead zone.
+    fn scroll_deflected(&self, input: &UnifiedInput) -> bool {
+        let ((rx, ry), (cx, cy)) = self.scroll_stick(input);
+        let past = |v: u8, c: u8| (v as i16 - c as i16).abs() >= self.scroll_dead_zone;
+        past(rx, cx) || past(ry, cy)
+    }
+
+    /// Process the scrolling stick into scroll actions with dead zone and rate limiting.
+    /// In arrows mode the same cadence emits arrow keys instead of wheel events;
+    /// with `copy_lines` it emits tmux copy-mode line scrolls (vi Ctrl+Y / Ctrl+E).
+    fn process_scroll(&mut self, input: &UnifiedInput, now: Instant, copy_lines: bool, actions: &mut Vec<Action>) {
+        let ((rx, ry), (cx, cy)) = self.scroll_stick(input);
+        let dx = rx as i16 - cx as i16;
+        let dy = ry as i16 - cy as i16;
 
         // Apply dead zone
         let dx = if dx.abs() < self.scroll_dead_zone { 0 } else { dx };
@@ -868,6 +2167,10 @@ impl MapperState {
         // Ignore horizontal if disabled
         let dx = if self.scroll_horizontal { dx } else { 0 };
 
+        // Natural scrolling: flip the stick axes (both wheel and arrows modes)
+        let dx = if self.scroll_invert_horizontal { -dx } else { dx };
+        let dy = if self.scroll_invert_vertical { -dy } else { dy };
+
         if dx == 0 && dy == 0 {
             self.last_scroll_at = None;
             return;
@@ -876,9 +2179,17 @@ impl MapperState {
         // Deflection magnitude (0.0 to 1.0)
         let max_deflection = (dx.abs().max(dy.abs()) as f32 / 127.0).min(1.0);
 
+        // Smooth wheel scrolling sends smaller deltas more often
+        let steps = if self.scroll_smooth && self.scroll_mode == ScrollMode::Wheel && !copy_lines {
+            SMOOTH_SCROLL_STEPS
+        } else {
+            1
+        };
+
         // Rate limiting: more deflection → shorter interval → faster scrolling
-        let interval_ms = SCROLL_MAX_INTERVAL_MS
-            - ((SCROLL_MAX_INTERVAL_MS - SCROLL_MIN_INTERVAL_MS) as f32 * max_deflection) as u64;
+        let interval_ms = (SCROLL_MAX_INTERVAL_MS
+            - ((SCROLL_MAX_INTERVAL_MS - SCROLL_MIN_INTERVAL_MS) as f32 * max_deflection) as u64)
+            / steps;
 
         if let Some(last) = self.last_scroll_at {
             if now.duration_since(last).as_millis() < interval_ms as u128 {
@@ -886,10 +2197,33 @@ impl MapperState {
             }
         }
 
+        if copy_lines {
+            if dy != 0 {
+                let key = if dy > 0 { VKey::E } else { VKey::Y };
+                actions.push(Action::KeyCombo(vec![VKey::Control, key]));
+            }
+            self.last_scroll_at = Some(now);
+            return;
+        }
+
+        if self.scroll_mode == ScrollMode::Arrows {
+            if dy != 0 {
+                actions.push(Action::KeyCombo(vec![if dy > 0 { VKey::Down } else { VKey::Up }]));
+            }
+            if dx != 0 {
+                actions.push(Action::KeyCombo(vec![if dx > 0 { VKey::Right } else { VKey::Left }]));
+            }
+            self.last_scroll_at = Some(now);
+            return;
+        }
+
+        // Wheel delta per event at full deflection
+        let delta = WHEEL_DELTA as f32 * self.scroll_lines_per_event / WHEEL_LINES_PER_NOTCH / steps as f32;
+
         // Y: stick up (dy < 0) → scroll up (positive vertical wheel delta)
         let vertical = if dy != 0 {
             let norm = (dy as f32 / -127.0).clamp(-1.0, 1.0);
-            (norm * self.scroll_sensitivity * WHEEL_DELTA as f32) as i32
+            (norm * self.scroll_sensitivity * delta) as i32
         } else {
             0
         };
@@ -897,7 +2231,7 @@ impl MapperState {
         // X: stick right (dx > 0) → scroll right (positive horizontal)
         let horizontal = if dx != 0 {
             let norm = (dx as f32 / 127.0).clamp(-1.0, 1.0);
-            (norm * self.scroll_sensitivity * WHEEL_DELTA as f32) as i32
+            (norm * self.scroll_sensitivity * delta) as i32
         } else {
             0
         };
@@ -909,19 +2243,63 @@ impl MapperState {
     }
 
     /// Translate touchpad touch coordinates into relative mouse movement and
-    /// touchpad click into a left mouse button click.
+    /// the touchpad press into the left mouse button (held while pressed, so
+    /// moving a finger meanwhile drags). A short two-finger tap that barely
+    /// moves and isn't pressed down is a right click.
     ///
     /// Called on every frame BEFORE profile-dependent dispatch so that the
     /// touchpad works identically in both Default and Tmux profiles.
-    fn process_touchpad(&mut self, input: &UnifiedInput, actions: &mut Vec<Action>) {
+    fn process_touchpad(&mut self, input: &UnifiedInput, now: Instant, actions: &mut Vec<Action>) {
         if !self.touchpad_enabled {
-            return; // config-level disable: suppresses both movement and click
+            // config-level disable: suppresses both movement and click
+            if std::mem::take(&mut self.touch_drag) {
+                actions.push(Action::MouseButtonUp);
+            }
+            return;
         }
 
         // ── Touch movement: only in touchpad mode (not when left stick drives cursor) ──
-        let stick_active = self.mouse_stick_active.load(Ordering::Relaxed);
-        let tp = &input.touchpad[0];
-        if tp.active && !stick_active {
+        let stick_active = self.stick_mouse_active(input);
+        let [tp, tp2] = &input.touchpad;
+        let touching = tp.active && !stick_active;
+        if touching && !self.touching {
+            self.touch_tap = Some(TouchTap { started: now, origin: (tp.x, tp.y), midpoint: None });
+        }
+        // Pressing the touchpad down makes it a (left) click instead
+        if input.buttons.touchpad {
+            self.touch_tap = None;
+        }
+        self.touching = touching;
+
+        if tp.active && tp2.active && !stick_active {
+            // Two fingers scroll instead of moving the cursor. Content follows
+            // the fingers (drag down = scroll up), like a Windows precision touchpad.
+            self.prev_touch = None;
+            let x = ((tp.x as u32 + tp2.x as u32) / 2) as u16;
+            let y = ((tp.y as u32 + tp2.y as u32) / 2) as u16;
+            if let Some(tap) = &mut self.touch_tap {
+                match tap.midpoint {
+                    None => tap.midpoint = Some((x, y)),
+                    Some(midpoint) if touch_travel(midpoint, (x, y)) > TWO_FINGER_TAP_TRAVEL => self.touch_tap = None,
+                    Some(_) => {}
+                }
+            }
+            if let Some(prev_y) = self.prev_two_finger_y {
+                self.touch_scroll_acc += y as i32 - prev_y as i32;
+                let notches = self.touch_scroll_acc / TOUCH_SCROLL_STEP;
+                if notches != 0 {
+                    self.touch_scroll_acc -= notches * TOUCH_SCROLL_STEP;
+                    actions.push(Action::Scroll { horizontal: 0, vertical: notches * WHEEL_DELTA });
+                }
+            }
+            self.prev_two_finger_y = Some(y);
+        } else if tp.active && !stick_active {
+            // A finger just lifted from a two-finger scroll: restart tracking
+            // so the remaining finger doesn't jump the cursor.
+            if self.prev_two_finger_y.take().is_some() {
+                self.touch_scroll_acc = 0;
+                self.prev_touch = None;
+            }
             if let Some((px, py)) = self.prev_touch {
                 let raw_dx = tp.x as i32 - px as i32;
                 let raw_dy = tp.y as i32 - py as i32;
@@ -930,39 +2308,83 @@ impl MapperState {
                 if dx != 0 || dy != 0 {
                     log::debug!("TouchpadMove raw=({raw_dx},{raw_dy}) scaled=({dx},{dy})");
                     actions.push(Action::MouseMove { dx, dy });
+                    // The finger left behind by a two-finger tap mustn't steer
+                    if self.touch_tap.is_some_and(|tap| tap.midpoint.is_some()) {
+                        self.touch_tap = None;
+                    }
                 }
             }
+            // A single finger that wanders off is a swipe, not the start of a tap
+            if let Some(tap) = self.touch_tap
+                && tap.midpoint.is_none()
+                && touch_travel(tap.origin, (tp.x, tp.y)) > TWO_FINGER_TAP_TRAVEL
+            {
+                self.touch_tap = None;
+            }
             self.prev_touch = Some((tp.x, tp.y));
         } else {
             // Clear prev_touch so switching back to touchpad mode doesn't
             // produce a spurious large jump.
             self.prev_touch = None;
+            self.prev_two_finger_y = None;
+            self.touch_scroll_acc = 0;
+            // All fingers lifted: a short two-finger touch was a tap. Stick
+            // mode taking over mid-touch (fingers still down) just drops it.
+            if let Some(tap) = self.touch_tap.take()
+                && !tp.active
+                && !tp2.active
+                && tap.midpoint.is_some()
+                && now.duration_since(tap.started) <= Duration::from_millis(TWO_FINGER_TAP_MS)
+            {
+                log::debug!("TouchpadTwoFingerTap → MouseRightClick");
+                actions.push(Action::MouseRightClick);
+            }
         }
 
-        // ── Touchpad press → left click (always active regardless of mouse mode) ──
-        if input.buttons.touchpad && !self.prev.touchpad {
-            log::debug!("TouchpadClick → MouseClick");
-            actions.push(Action::MouseClick);
+        // ── Touchpad press → left button held until release (always active regardless of mouse mode) ──
+        if input.buttons.touchpad && !self.touch_drag {
+            log::debug!("TouchpadPress → MouseButtonDown");
+            actions.push(Action::MouseButtonDown);
+            self.touch_drag = true;
+        } else if !input.buttons.touchpad && self.touch_drag {
+            log::debug!("TouchpadRelease → MouseButtonUp");
+            actions.push(Action::MouseButtonUp);
+            self.touch_drag = false;
         }
     }
 
-    /// Translate left analog stick deflection into relative mouse movement.
+    /// Translate left analog stick deflection (right with `swap_sticks`) into
+    /// relative mouse movement.
     ///
     /// Velocity-based: stick position → cursor speed per frame.
     /// A sub-pixel accumulator (`stick_acc_x/y`) carries fractional pixels
     /// across frames so slow, precise movements don't stutter.
     fn process_stick_mouse(&mut self, input: &UnifiedInput, actions: &mut Vec<Action>) {
-        if !self.stick_mouse_enabled || !self.mouse_stick_active.load(Ordering::Relaxed) {
+        if !self.stick_mouse_enabled || !self.stick_mouse_active(input) {
+            // Drop leftover sub-pixels (e.g. the momentary button was released)
+            // so they don't nudge the cursor when it's next active
+            self.stick_acc_x = 0.0;
+            self.stick_acc_y = 0.0;
             return;
         }
 
-        let (lx, ly) = input.left_stick;
-        let dx_raw = lx as i16 - 128;
-        let dy_raw = ly as i16 - 128;
+        let ((lx, ly), (cx, cy)) = self.cursor_stick(input);
+        let dx_raw = lx as i16 - cx as i16;
+        let dy_raw = ly as i16 - cy as i16;
 
-        // Apply dead zone per axis
-        let dx_raw = if dx_raw.abs() < self.stick_mouse_dead_zone { 0 } else { dx_raw };
-        let dy_raw = if dy_raw.abs() < self.stick_mouse_dead_zone { 0 } else { dy_raw };
+        let dead_zone = self.stick_mouse_dead_zone;
+        let (dx_raw, dy_raw) = match self.stick_mouse_dead_zone_shape {
+            DeadZoneShape::Axial => (
+                if dx_raw.abs() < dead_zone { 0 } else { dx_raw },
+                if dy_raw.abs() < dead_zone { 0 } else { dy_raw },
+            ),
+            DeadZoneShape::Radial => radial_dead_zone(dx_raw, dy_raw, dead_zone),
+        };
+
+        // Axis snap: drop the minor axis when the stick is nearly straight, so
+        // horizontal/vertical drags don't drift. The dropped axis' accumulator is
+        // cleared too, otherwise leftover sub-pixels would still nudge the cursor.
+        let (dx_raw, dy_raw) = self.snap_to_axis(dx_raw, dy_raw);
 
         if dx_raw == 0 && dy_raw == 0 {
             // Reset accumulators when stick returns to center so no phantom move
@@ -972,9 +2394,10 @@ impl MapperState {
             return;
         }
 
-        // Normalize to -1.0..1.0 and scale by sensitivity (pixels/frame at full deflection)
-        let vx = (dx_raw as f32 / 127.0).clamp(-1.0, 1.0) * self.stick_mouse_sensitivity;
-        let vy = (dy_raw as f32 / 127.0).clamp(-1.0, 1.0) * self.stick_mouse_sensitivity;
+        // Normalize to -1.0..1.0, apply the response curve, and scale by
+        // sensitivity (pixels/frame at full deflection)
+        let vx = self.stick_curve((dx_raw as f32 / 127.0).clamp(-1.0, 1.0)) * self.stick_mouse_sensitivity;
+        let vy = self.stick_curve((dy_raw as f32 / 127.0).clamp(-1.0, 1.0)) * self.stick_mouse_sensitivity;
 
         // Accumulate; extract whole pixels; keep remainder for next frame
         self.stick_acc_x += vx;
@@ -990,6 +2413,85 @@ impl MapperState {
             actions.push(Action::MouseMove { dx, dy });
         }
     }
+
+    /// Whether the stick drives the cursor: while the momentary button is
+    /// held if one is set, else per the tray toggle.
+    fn stick_mouse_active(&self, input: &UnifiedInput) -> bool {
+        match self.stick_mouse_button {
+            Some(button) => input.buttons.pressed(button),
+            None => self.mouse_stick_active.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Raise a normalized axis value's magnitude to `stick_mouse_accel_curve`,
+    /// keeping its sign. Curves at or below 1.0 are linear.
+    fn stick_curve(&self, v: f32) -> f32 {
+        if self.stick_mouse_accel_curve > 1.0 {
+            v.signum() * v.abs().powf(self.stick_mouse_accel_curve)
+        } else {
+            v
+        }
+    }
+
+    /// Map gyro yaw → horizontal and pitch → vertical cursor movement while
+    /// gyro aiming is active. Turning left / tilting up moves the cursor left / up.
+    fn process_gyro(&mut self, input: &UnifiedInput, actions: &mut Vec<Action>) {
+        let Some(activation) = self.gyro_button else {
+            return;
+        };
+        let active = activation.is_none_or(|b| input.buttons.pressed(b));
+        let Some(motion) = input.motion.filter(|_| active) else {
+            self.gyro_acc_x = 0.0;
+            self.gyro_acc_y = 0.0;
+            return;
+        };
+
+        let [pitch, yaw, _roll] = motion.gyro;
+        let dead = |v: i16| if v.saturating_abs() < self.gyro_dead_zone { 0.0 } else { v as f32 };
+        self.gyro_acc_x -= dead(yaw) * self.gyro_sensitivity;
+        self.gyro_acc_y -= dead(pitch) * self.gyro_sensitivity;
+
+        let dx = self.gyro_acc_x as i32;
+        let dy = self.gyro_acc_y as i32;
+        if dx != 0 || dy != 0 {
+            self.gyro_acc_x -= dx as f32;
+            self.gyro_acc_y -= dy as f32;
+            actions.push(Action::MouseMove { dx, dy });
+        }
+    }
+
+    /// Zero the minor stick axis when its ratio to the major axis is below
+    /// `stick_mouse_axis_snap`. No-op when snapping is disabled (ratio 0.0).
+    fn snap_to_axis(&mut self, dx_raw: i16, dy_raw: i16) -> (i16, i16) {
+        let ratio = self.stick_mouse_axis_snap;
+        if ratio <= 0.0 {
+            return (dx_raw, dy_raw);
+        }
+        let (ax, ay) = (dx_raw.unsigned_abs() as f32, dy_raw.unsigned_abs() as f32);
+        if ay < ax * ratio {
+            self.stick_acc_y = 0.0;
+            (dx_raw, 0)
+        } else if ax < ay * ratio {
+            self.stick_acc_x = 0.0;
+            (0, dy_raw)
+        } else {
+            (dx_raw, dy_raw)
+        }
+    }
+}
+
+/// Apply a circular dead zone of radius `dead_zone` to a stick deflection.
+/// Past it, the distance is rescaled so it grows from 0 at the edge to 127 at
+/// full deflection, keeping the direction. Diagonals reach past 127 from
+/// center (up to ~180 in a corner), so the distance is capped at 127 first.
+fn radial_dead_zone(dx: i16, dy: i16, dead_zone: i16) -> (i16, i16) {
+    let magnitude = f32::from(dx).hypot(f32::from(dy));
+    let dead_zone = f32::from(dead_zone.clamp(0, 126));
+    if magnitude < dead_zone || magnitude == 0.0 {
+        return (0, 0);
+    }
+    let scale = (magnitude.min(127.0) - dead_zone) * 127.0 / (127.0 - dead_zone) / magnitude;
+    ((f32::from(dx) * scale).round() as i16, (f32::from(dy) * scale).round() as i16)
 }
 
 // ── Windows SendInput functions ──────────────────────────────────────
@@ -1065,6 +2567,81 @@ pub fn send_key_sequence(combos: &[Vec<VKey>], delay_ms: u64) {
     }
 }
 
+/// Key events for `keys` tapped under held `modifiers`, as (key, released):
+/// every modifier down, each combo pressed and released in turn, then the
+/// modifiers released in reverse.
+fn held_sequence_events(modifiers: &[VKey], keys: &[Vec<VKey>]) -> Vec<(VKey, bool)> {
+    let mut events: Vec<(VKey, bool)> = modifiers.iter().map(|&m| (m, false)).collect();
+    for combo in keys {
+        events.extend(combo.iter().map(|&k| (k, false)));
+        events.extend(combo.iter().rev().map(|&k| (k, true)));
+    }
+    events.extend(modifiers.iter().rev().map(|&m| (m, true)));
+    events
+}
+
+/// Tap `keys` while `modifiers` stay held, in one SendInput call.
+#[cfg(windows)]
+pub fn send_key_sequence_held(modifiers: &[VKey], keys: &[Vec<VKey>]) {
+    let inputs: Vec<INPUT> = held_sequence_events(modifiers, keys)
+        .into_iter()
+        .map(|(key, up)| make_key_input(key.code(), if up { KEYEVENTF_KEYUP } else { 0 }))
+        .collect();
+    if inputs.is_empty() {
+        return;
+    }
+    unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        );
+    }
+}
+
+/// Play a macro: combos back to back, sleeping at delay steps.
+#[cfg(windows)]
+pub fn send_macro(steps: &[MacroStep]) {
+    for step in steps {
+        match step {
+            MacroStep::Keys(keys) => send_key_combo(keys),
+            MacroStep::Delay(ms) => std::thread::sleep(Duration::from_millis(*ms)),
+            MacroStep::Text(text) => send_text(text),
+            MacroStep::Held { modifiers, keys } => send_key_sequence_held(modifiers, keys),
+        }
+    }
+}
+
+/// Key events that type `text`: a press and release per UTF-16 unit, so
+/// characters outside the BMP go out as their surrogate pair.
+fn text_key_events(text: &str) -> Vec<(u16, bool)> {
+    text.encode_utf16().flat_map(|unit| [(unit, false), (unit, true)]).collect()
+}
+
+/// Type `text` via Windows SendInput (`KEYEVENTF_UNICODE`), in one call.
+#[cfg(windows)]
+pub fn send_text(text: &str) {
+    let inputs: Vec<INPUT> = text_key_events(text)
+        .into_iter()
+        .map(|(unit, up)| {
+            let flags = if up { KEYEVENTF_UNICODE | KEYEVENTF_KEYUP } else { KEYEVENTF_UNICODE };
+            let mut input = make_key_input(0, flags);
+            input.Anonymous.ki.wScan = unit;
+            input
+        })
+        .collect();
+    if inputs.is_empty() {
+        return;
+    }
+    unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        );
+    }
+}
+
 /// Move the mouse cursor by a relative offset via Windows SendInput.
 #[cfg(windows)]
 pub fn send_mouse_move(dx: i32, dy: i32) {
@@ -1074,12 +2651,30 @@ pub fn send_mouse_move(dx: i32, dy: i32) {
     }
 }
 
-/// Send a left mouse button click (down + up) via Windows SendInput.
+/// Press the left mouse button via Windows SendInput.
+#[cfg(windows)]
+pub fn send_mouse_button_down() {
+    let input = make_mouse_flag_input(MOUSEEVENTF_LEFTDOWN);
+    unsafe {
+        SendInput(1, &input, std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+/// Release the left mouse button via Windows SendInput.
+#[cfg(windows)]
+pub fn send_mouse_button_up() {
+    let input = make_mouse_flag_input(MOUSEEVENTF_LEFTUP);
+    unsafe {
+        SendInput(1, &input, std::mem::size_of::<INPUT>() as i32);
+    }
+}
+
+/// Send a right mouse button click (down + up) via Windows SendInput.
 #[cfg(windows)]
-pub fn send_mouse_click() {
+pub fn send_mouse_right_click() {
     let inputs = [
-        make_mouse_flag_input(MOUSEEVENTF_LEFTDOWN),
-        make_mouse_flag_input(MOUSEEVENTF_LEFTUP),
+        make_mouse_flag_input(MOUSEEVENTF_RIGHTDOWN),
+        make_mouse_flag_input(MOUSEEVENTF_RIGHTUP),
     ];
     unsafe {
         SendInput(
@@ -1182,7 +2777,7 @@ fn make_mouse_flag_input(flags: u32) -> INPUT {
     }
 }
 
-/// Execute an action (send keystrokes, scroll, mouse movement/click, or handle custom actions).
+/// Execute an action (send keystrokes, scroll, mouse movement/buttons, or handle custom actions).
 #[cfg(windows)]
 pub fn execute_action(action: &Action) {
     match action {
@@ -1190,9 +2785,14 @@ pub fn execute_action(action: &Action) {
         Action::KeyDown(keys) => send_key_down(keys),
         Action::KeyUp(keys) => send_key_up(keys),
         Action::KeySequence(combos) => send_key_sequence(combos, 10),
+        Action::KeySequenceHeld { modifiers, keys } => send_key_sequence_held(modifiers, keys),
         Action::Scroll { horizontal, vertical } => send_scroll(*horizontal, *vertical),
         Action::MouseMove { dx, dy } => send_mouse_move(*dx, *dy),
-        Action::MouseClick => send_mouse_click(),
+        Action::MouseButtonDown => send_mouse_button_down(),
+        Action::MouseButtonUp => send_mouse_button_up(),
+        Action::MouseRightClick => send_mouse_right_click(),
+        Action::Macro(steps) => send_macro(steps),
+        Action::TypeText(text) => send_text(text),
         Action::Custom(name) => {
             log::info!("Custom action triggered: {name}");
         }
@@ -1204,6 +2804,7 @@ pub fn execute_action(action: &Action) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ButtonConfig;
 
     fn input_with(f: impl FnOnce(&mut UnifiedInput)) -> UnifiedInput {
         let mut input = UnifiedInput::default();
@@ -1318,6 +2919,24 @@ mod tests {
         assert!(!actions.iter().any(|a| matches!(a, Action::Scroll { .. })));
     }
 
+    #[test]
+    fn scroll_dead_zone_follows_calibrated_center() {
+        let mut mapper = MapperState::default();
+        // Right stick resting at y = 100: 28 past 128, still inside the dead zone
+        mapper.set_calibration(&CalibrationConfig { right_y: 100, ..Default::default() });
+        let scrolls = |mapper: &mut MapperState, ry| {
+            let input = input_with(|i| i.right_stick = (128, ry));
+            mapper.update(&input).iter().any(|a| matches!(a, Action::Scroll { .. }))
+        };
+        assert!(!scrolls(&mut mapper, 100));
+        assert!(!scrolls(&mut mapper, 115));
+        // 128 is 28 below the calibrated center → scrolls down
+        let input = input_with(|i| i.right_stick = (128, 128));
+        assert!(
+            mapper.update(&input).iter().any(|a| matches!(a, Action::Scroll { vertical, .. } if *vertical < 0))
+        );
+    }
+
     #[test]
     fn scroll_beyond_dead_zone_fires() {
         let mut mapper = MapperState::default();
@@ -1357,19 +2976,91 @@ mod tests {
         );
     }
 
-    /// Helper: activate tmux profile by pressing PS.
-    fn switch_to_tmux(mapper: &mut MapperState) {
-        let ps_press = input_with(|i| i.buttons.ps = true);
-        let actions = mapper.update(&ps_press);
-        assert!(actions.iter().any(|a| matches!(a, Action::Custom(s) if s == "profile:tmux")));
-        assert_eq!(mapper.profile(), Profile::Tmux);
-        // Release PS
+    #[test]
+    fn scroll_invert_vertical_flips_stick_up() {
+        let mut mapper = MapperState { scroll_invert_vertical: true, ..Default::default() };
+
+        let input = input_with(|i| i.right_stick = (128, 80));
+        let actions = mapper.update(&input);
+        assert!(
+            actions.iter().any(|a| matches!(a, Action::Scroll { vertical, .. } if *vertical < 0)),
+            "Expected negative vertical scroll for stick-up when inverted"
+        );
+    }
+
+    #[test]
+    fn scroll_invert_horizontal_flips_stick_right() {
+        let mut mapper = MapperState { scroll_invert_horizontal: true, ..Default::default() };
+
+        let input = input_with(|i| i.right_stick = (200, 128));
+        let actions = mapper.update(&input);
+        assert!(
+            actions.iter().any(|a| matches!(a, Action::Scroll { horizontal, vertical: 0 } if *horizontal < 0)),
+            "Expected negative horizontal scroll for stick-right when inverted"
+        );
+    }
+
+    #[test]
+    fn scroll_smooth_sends_smaller_deltas_more_often() {
+        let up = input_with(|i| i.right_stick = (128, 0));
+        let t0 = Instant::now();
+        let vertical = |actions: Vec<Action>| {
+            actions.into_iter().find_map(|a| match a {
+                Action::Scroll { vertical, .. } => Some(vertical),
+                _ => None,
+            })
+        };
+
+        // Notches: a full notch every 30 ms at full deflection
+        let mut notch = MapperState::default();
+        assert_eq!(vertical(notch.update_at(&up, t0)), Some(120));
+        assert_eq!(vertical(notch.update_at(&up, t0 + Duration::from_millis(10))), None);
+        assert_eq!(vertical(notch.update_at(&up, t0 + Duration::from_millis(30))), Some(120));
+
+        // Smooth: a quarter notch four times as often
+        let mut smooth = MapperState { scroll_smooth: true, ..Default::default() };
+        assert_eq!(vertical(smooth.update_at(&up, t0)), Some(30));
+        assert_eq!(vertical(smooth.update_at(&up, t0 + Duration::from_millis(10))), Some(30));
+
+        // lines_per_event scales the delta (1 line = a third of a notch)
+        let mut one_line = MapperState { scroll_lines_per_event: 1.0, ..Default::default() };
+        assert_eq!(vertical(one_line.update_at(&up, t0)), Some(40));
+        let mut smooth_line = MapperState { scroll_lines_per_event: 1.0, scroll_smooth: true, ..Default::default() };
+        assert_eq!(vertical(smooth_line.update_at(&up, t0)), Some(10));
+    }
+
+    #[test]
+    fn scroll_arrows_mode_emits_down_keys() {
+        let mut mapper = MapperState { scroll_mode: ScrollMode::Arrows, ..Default::default() };
+
+        let center = input_with(|i| i.right_stick = (128, 128));
+        assert!(mapper.update(&center).is_empty());
+
+        let down = input_with(|i| i.right_stick = (128, 200));
+        let actions = mapper.update(&down);
+        assert!(
+            actions.iter().any(|a| matches!(a, Action::KeyCombo(k) if k == &[VKey::Down])),
+            "Expected Down key for stick-down in arrows mode"
+        );
+        assert!(!actions.iter().any(|a| matches!(a, Action::Scroll { .. })));
+
+        // Same cadence as wheel scrolling: immediate repeat is rate-limited
+        assert!(mapper.update(&down).is_empty());
+    }
+
+    /// Helper: activate tmux profile by pressing PS.
+    fn switch_to_tmux(mapper: &mut MapperState) {
+        let ps_press = input_with(|i| i.buttons.ps = true);
+        let actions = mapper.update(&ps_press);
+        assert!(actions.iter().any(|a| matches!(a, Action::Custom(s) if s == "profile:tmux")));
+        assert_eq!(mapper.profile(), Profile::Tmux);
+        // Release PS
         mapper.update(&UnifiedInput::default());
     }
 
     #[test]
     fn ps_cycles_profiles() {
-        let mut mapper = MapperState::default();
+        let mut mapper = MapperState { opencode_available: false, ..Default::default() };
         assert_eq!(mapper.profile(), Profile::Default);
 
         // Press PS → switch to Tmux
@@ -1382,6 +3073,401 @@ mod tests {
         assert_eq!(mapper.profile(), Profile::Default);
     }
 
+    fn mapper_with_buttons(buttons: ButtonConfig) -> MapperState {
+        let cfg = Config { buttons, ..Default::default() };
+        MapperState::new(&cfg, Detected::default(), Arc::new(AtomicBool::new(false)), Arc::new(AtomicBool::new(false)), None)
+    }
+
+    #[test]
+    fn cross_remapped_to_ctrl_s() {
+        let buttons = ButtonConfig { cross: "ctrl+s".into(), ..Default::default() };
+        let mut mapper = mapper_with_buttons(buttons);
+        let actions = mapper.update(&input_with(|i| i.buttons.cross = true));
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if *k == vec![VKey::Control, VKey::S]));
+    }
+
+    /// Press and release a button, returning the actions from the press.
+    fn tap(mapper: &mut MapperState, f: impl FnOnce(&mut UnifiedInput)) -> Vec<Action> {
+        let actions = mapper.update(&input_with(f));
+        mapper.update(&UnifiedInput::default());
+        actions
+    }
+
+    #[test]
+    fn triangle_double_tap_sends_shift_tab() {
+        let mut mapper = MapperState {
+            double_triangle: Some(vec![VKey::Shift, VKey::Tab]),
+            ..Default::default()
+        };
+        let first = tap(&mut mapper, |i| i.buttons.triangle = true);
+        assert!(matches!(&first[..], [Action::KeyCombo(k)] if *k == vec![VKey::Tab]));
+        let second = tap(&mut mapper, |i| i.buttons.triangle = true);
+        assert!(matches!(&second[..], [Action::KeyCombo(k)] if *k == vec![VKey::Shift, VKey::Tab]));
+        // A third press starts a new tap
+        let third = tap(&mut mapper, |i| i.buttons.triangle = true);
+        assert!(matches!(&third[..], [Action::KeyCombo(k)] if *k == vec![VKey::Tab]));
+    }
+
+    #[test]
+    fn invalid_double_tap_combo_is_dropped() {
+        assert_eq!(double_tap_combo("triangle", "Shift+Tab"), Some(vec![VKey::Shift, VKey::Tab]));
+        assert_eq!(double_tap_combo("cross", "Shift+Nope"), None);
+        assert_eq!(double_tap_combo("circle", " "), None);
+    }
+
+    #[test]
+    fn double_tap_outside_window_is_two_singles() {
+        let mut mapper = MapperState {
+            double_cross: Some(vec![VKey::Control, VKey::S]),
+            double_tap_window: Duration::ZERO,
+            ..Default::default()
+        };
+        for _ in 0..2 {
+            let actions = tap(&mut mapper, |i| i.buttons.cross = true);
+            assert!(matches!(&actions[..], [Action::KeyCombo(k)] if *k == vec![VKey::Return]));
+        }
+    }
+
+    fn input_with_gyro(l1: bool, pitch: i16, yaw: i16) -> UnifiedInput {
+        input_with(|i| {
+            i.buttons.l1 = l1;
+            i.motion = Some(crate::input::MotionData { gyro: [pitch, yaw, 0], ..Default::default() });
+        })
+    }
+
+    #[test]
+    fn gyro_moves_cursor_only_while_button_held() {
+        let cfg = GyroConfig { enabled: true, sensitivity: 0.01, ..Default::default() };
+        let mut mapper = MapperState { gyro_button: gyro_activation(&cfg), gyro_sensitivity: 0.01, ..Default::default() };
+
+        // Not held: no movement
+        assert!(mapper.update(&input_with_gyro(false, 0, 500)).is_empty());
+
+        // Held: yaw 500 → 5px left, and L1's prev-tab combo is suppressed
+        let actions = mapper.update(&input_with_gyro(true, 0, 500));
+        assert!(matches!(&actions[..], [Action::MouseMove { dx: -5, dy: 0 }]));
+
+        // Tilting up (positive pitch) moves up; tremor inside the dead zone is ignored
+        let actions = mapper.update(&input_with_gyro(true, 300, 20));
+        assert!(matches!(&actions[..], [Action::MouseMove { dx: 0, dy: -3 }]));
+    }
+
+    fn turbo_mapper(button: &str, rate_ms: u64) -> MapperState {
+        let cfg = TurboConfig { enabled: true, rate_ms, buttons: vec![button.into()], key: "Enter".into() };
+        MapperState { turbo: TurboState::from_config(&cfg), ..Default::default() }
+    }
+
+    #[test]
+    fn turbo_r2_repeats_enter_at_rate() {
+        let mut mapper = turbo_mapper("r2", 50);
+        let held = input_with(|i| i.buttons.r2 = true);
+        let t0 = Instant::now();
+        let mut fired = Vec::new();
+        // 10ms frames for 500ms: confirm on the 2nd frame, then one every 50ms
+        for frame in 0..=50u64 {
+            fired.extend(mapper.update_at(&held, t0 + Duration::from_millis(frame * 10)));
+        }
+        assert_eq!(fired.len(), 10);
+        assert!(fired.iter().all(|a| matches!(a, Action::KeyCombo(k) if *k == vec![VKey::Return])));
+        // R2's normal Ctrl+C is suppressed
+        assert!(!fired.iter().any(|a| matches!(a, Action::KeyCombo(k) if *k == vec![VKey::Control, VKey::C])));
+
+        // Release stops the repeat; a one-frame tap is a glitch
+        let t1 = t0 + Duration::from_millis(600);
+        assert!(mapper.update_at(&UnifiedInput::default(), t1).is_empty());
+        assert!(mapper.update_at(&held, t1 + Duration::from_millis(10)).is_empty());
+        assert!(mapper.update_at(&UnifiedInput::default(), t1 + Duration::from_millis(20)).is_empty());
+    }
+
+    #[test]
+    fn turbo_l2_never_holds_modifiers() {
+        let mut mapper = turbo_mapper("l2", 50);
+        let held = input_with(|i| i.buttons.l2 = true);
+        let t0 = Instant::now();
+        let mut fired = Vec::new();
+        for frame in 0..10u64 {
+            fired.extend(mapper.update_at(&held, t0 + Duration::from_millis(frame * 10)));
+        }
+        fired.extend(mapper.update_at(&UnifiedInput::default(), t0 + Duration::from_millis(100)));
+        assert!(!fired.iter().any(|a| matches!(a, Action::KeyDown(_) | Action::KeyUp(_))));
+        assert_eq!(fired.len(), 2);
+    }
+
+    fn macro_config(button: &str, steps: &[&str]) -> MacroConfig {
+        let steps = steps.iter().map(|s| s.to_string()).collect();
+        MacroConfig([(button.to_string(), steps)].into_iter().collect())
+    }
+
+    #[test]
+    fn macro_steps_parse_combos_and_delays() {
+        let steps = parse_macro("square", &["ctrl+a".into(), "delay:50".into(), "Ctrl+C".into()]);
+        assert_eq!(
+            steps,
+            vec![
+                MacroStep::Keys(vec![VKey::Control, VKey::A]),
+                MacroStep::Delay(50),
+                MacroStep::Keys(vec![VKey::Control, VKey::C]),
+            ]
+        );
+        // Invalid steps are dropped; oversized delays are capped
+        let steps = parse_macro("square", &["bogus+key".into(), "delay:abc".into(), "DELAY: 99999".into()]);
+        assert_eq!(steps, vec![MacroStep::Delay(MAX_MACRO_DELAY_MS)]);
+
+        // The total pause is capped too
+        let steps = parse_macro("square", &["delay:1500".into(), "a".into(), "delay:1500".into(), "delay:10".into()]);
+        assert_eq!(
+            steps,
+            vec![MacroStep::Delay(1500), MacroStep::Keys(vec![VKey::A]), MacroStep::Delay(500), MacroStep::Delay(0)]
+        );
+    }
+
+    #[test]
+    fn macro_text_steps_keep_their_text() {
+        let steps = parse_macro("square", &["text:feat: ".into(), "Text:héllo 😀".into(), "text:".into()]);
+        assert_eq!(steps, vec![MacroStep::Text("feat: ".into()), MacroStep::Text("héllo 😀".into())]);
+        assert_eq!(format_macro(&steps[..1]), r#"type "feat: ""#);
+
+        // One INPUT per press and per release of each UTF-16 unit:
+        // 6 BMP characters plus a surrogate pair → 8 units → 16 events
+        assert_eq!(text_key_events("héllo 😀").len(), 16);
+    }
+
+    #[test]
+    fn macro_hold_step_keeps_modifiers_down() {
+        let steps = parse_macro("dpad_left", &["hold:ctrl left left shift+up".into()]);
+        let keys = vec![vec![VKey::Left], vec![VKey::Left], vec![VKey::Shift, VKey::Up]];
+        assert_eq!(steps, vec![MacroStep::Held { modifiers: vec![VKey::Control], keys: keys.clone() }]);
+        assert!(matches!(
+            macro_action(&steps),
+            Action::KeySequenceHeld { modifiers, keys: k } if modifiers == [VKey::Control] && k == keys
+        ));
+        // A hold needs at least one valid key
+        assert!(parse_macro("square", &["hold:ctrl".into(), "hold:ctrl left bogus".into()]).is_empty());
+
+        // Modifier down, each key tapped, modifier up
+        let (ctrl, left, shift, up) = (VKey::Control, VKey::Left, VKey::Shift, VKey::Up);
+        assert_eq!(
+            held_sequence_events(&[ctrl], &keys),
+            [
+                (ctrl, false),
+                (left, false), (left, true),
+                (left, false), (left, true),
+                (shift, false), (up, false), (up, true), (shift, true),
+                (ctrl, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn macro_button_plays_sequence_once_per_press() {
+        let cfg = macro_config("square", &["ctrl+a", "delay:50", "ctrl+c"]);
+        let mut mapper = MapperState { macros: MacroState::from_config(&cfg), ..Default::default() };
+
+        let square = input_with(|i| i.buttons.square = true);
+        let actions = mapper.update(&square);
+        // Replaces the Default-profile new-tab action
+        assert_eq!(actions.len(), 1);
+        match &actions[0] {
+            Action::Macro(steps) => assert_eq!(
+                steps,
+                &vec![
+                    MacroStep::Keys(vec![VKey::Control, VKey::A]),
+                    MacroStep::Delay(50),
+                    MacroStep::Keys(vec![VKey::Control, VKey::C]),
+                ]
+            ),
+            other => panic!("Expected Macro, got {other:?}"),
+        }
+        assert!(mapper.update(&square).is_empty());
+        assert!(mapper.update(&UnifiedInput::default()).is_empty());
+    }
+
+    #[test]
+    fn macro_with_no_valid_steps_is_ignored() {
+        let cfg = macro_config("cross", &["not-a-key"]);
+        let mut mapper = MapperState { macros: MacroState::from_config(&cfg), ..Default::default() };
+        // Cross keeps its normal Enter binding
+        let actions = mapper.update(&input_with(|i| i.buttons.cross = true));
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if k == &[VKey::Return]));
+    }
+
+    #[test]
+    fn options_short_press_sends_tap_combo() {
+        let mut mapper = MapperState::default();
+        let options = input_with(|i| i.buttons.options = true);
+        assert!(mapper.update(&options).is_empty());
+        assert!(mapper.update(&options).is_empty()); // confirmed, still held
+        let actions = mapper.update(&UnifiedInput::default());
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if *k == vec![VKey::Escape]));
+    }
+
+    #[test]
+    fn options_long_press_sends_hold_combo() {
+        let mut mapper = MapperState::default();
+        let options = input_with(|i| i.buttons.options = true);
+        mapper.update(&options);
+        mapper.update(&options);
+        // Pretend the press started past the threshold
+        mapper.options_timer.pressed_at = Some(Instant::now() - Duration::from_millis(600));
+        let actions = mapper.update(&UnifiedInput::default());
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if *k == vec![VKey::Control, VKey::Shift, VKey::P]));
+    }
+
+    #[test]
+    fn options_single_frame_glitch_filtered() {
+        let mut mapper = MapperState::default();
+        mapper.update(&input_with(|i| i.buttons.options = true));
+        assert!(mapper.update(&UnifiedInput::default()).is_empty());
+    }
+
+    #[test]
+    fn face_button_empty_unmaps_and_invalid_falls_back() {
+        let buttons = ButtonConfig { circle: "".into(), triangle: "ctrl+nope".into(), ..Default::default() };
+        let mut mapper = mapper_with_buttons(buttons);
+        assert!(mapper.update(&input_with(|i| i.buttons.circle = true)).is_empty());
+        mapper.update(&UnifiedInput::default());
+        let actions = mapper.update(&input_with(|i| i.buttons.triangle = true));
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if *k == vec![VKey::Tab]));
+    }
+
+    /// Helper: press and release PS, returning the actions from the press.
+    fn press_ps(mapper: &mut MapperState) -> Vec<Action> {
+        let actions = mapper.update(&input_with(|i| i.buttons.ps = true));
+        mapper.update(&UnifiedInput::default());
+        actions
+    }
+
+    #[test]
+    fn ps_cycles_three_profiles_with_agent() {
+        let mut mapper = MapperState::default();
+        assert_eq!(mapper.profile(), Profile::Default);
+
+        switch_to_tmux(&mut mapper);
+
+        let actions = press_ps(&mut mapper);
+        assert!(actions.iter().any(|a| matches!(a, Action::Custom(s) if s == "profile:agent")));
+        assert_eq!(mapper.profile(), Profile::Agent);
+
+        let actions = press_ps(&mut mapper);
+        assert!(actions.iter().any(|a| matches!(a, Action::Custom(s) if s == "profile:default")));
+        assert_eq!(mapper.profile(), Profile::Default);
+    }
+
+    #[test]
+    fn ps_skips_tmux_when_disabled() {
+        let mut mapper = MapperState { tmux_available: false, ..Default::default() };
+        press_ps(&mut mapper);
+        assert_eq!(mapper.profile(), Profile::Agent);
+        press_ps(&mut mapper);
+        assert_eq!(mapper.profile(), Profile::Default);
+    }
+
+    #[test]
+    fn set_profile_from_tray_respects_availability() {
+        let mut mapper = MapperState { tmux_available: false, ..Default::default() };
+        assert!(mapper.set_profile(Profile::Agent));
+        assert_eq!(mapper.profile(), Profile::Agent);
+        // Already active / disabled → no change
+        assert!(!mapper.set_profile(Profile::Agent));
+        assert!(!mapper.set_profile(Profile::Tmux));
+        assert_eq!(mapper.profile(), Profile::Agent);
+        // PS keeps cycling from the externally set profile
+        press_ps(&mut mapper);
+        assert_eq!(mapper.profile(), Profile::Default);
+    }
+
+    #[test]
+    fn apply_live_config_retunes_motion_only() {
+        let mut mapper = MapperState { active_profile: Profile::Tmux, ..Default::default() };
+        let scroll = ScrollConfig { sensitivity: 3.0, invert_vertical: true, ..Default::default() };
+        let stick = crate::config::StickMouseConfig { axis_snap_ratio: 5.0, ..Default::default() };
+        let touchpad = crate::config::TouchpadConfig { enabled: false, ..Default::default() };
+        mapper.apply_live_config(&scroll, &stick, &GyroConfig::default(), &touchpad);
+        assert_eq!(mapper.scroll_sensitivity, 3.0);
+        assert!(mapper.scroll_invert_vertical);
+        assert_eq!(mapper.stick_mouse_axis_snap, 1.0);
+        assert!(!mapper.touchpad_enabled);
+        // Profile and bindings are untouched
+        assert_eq!(mapper.profile(), Profile::Tmux);
+        assert_eq!(mapper.cross, MapperState::default().cross);
+    }
+
+    #[test]
+    fn profile_file_round_trip() {
+        let path = std::env::temp_dir().join("ds4cc_test_profile_round_trip");
+        for profile in [Profile::Default, Profile::Tmux, Profile::Agent] {
+            save_profile(&path, profile).unwrap();
+            assert_eq!(load_profile(&path), Some(profile));
+        }
+        std::fs::write(&path, "garbage").unwrap();
+        assert_eq!(load_profile(&path), None);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(load_profile(&path), None);
+    }
+
+    fn mapper_in_state_dir(tmux: TmuxConfig, dir: &Path) -> MapperState {
+        let cfg = Config { tmux, ..Default::default() };
+        MapperState::new(&cfg, Detected::default(), Arc::new(AtomicBool::new(false)), Arc::new(AtomicBool::new(false)), Some(dir))
+    }
+
+    #[test]
+    fn ps_switch_survives_restart() {
+        let dir = std::env::temp_dir().join("ds4cc_test_profile_restore");
+        let _ = std::fs::create_dir_all(&dir);
+        let _ = std::fs::remove_file(dir.join(PROFILE_FILE));
+
+        // Nothing saved yet → Default
+        let mut mapper = mapper_in_state_dir(TmuxConfig::default(), &dir);
+        assert_eq!(mapper.profile(), Profile::Default);
+        switch_to_tmux(&mut mapper);
+
+        let restarted = mapper_in_state_dir(TmuxConfig::default(), &dir);
+        assert_eq!(restarted.profile(), Profile::Tmux);
+
+        // A saved profile that has since been disabled falls back to Default
+        let tmux_off = TmuxConfig { enabled: false, ..Default::default() };
+        assert_eq!(mapper_in_state_dir(tmux_off, &dir).profile(), Profile::Default);
+
+        let _ = std::fs::remove_file(dir.join(PROFILE_FILE));
+    }
+
+    #[test]
+    fn agent_profile_dispatches_opencode_and_tmux() {
+        let mut mapper = MapperState { tmux_available: false, ..Default::default() };
+        press_ps(&mut mapper);
+        assert_eq!(mapper.profile(), Profile::Agent);
+
+        // L1 → OpenCode previous session (ctrl+[)
+        let actions = mapper.update(&input_with(|i| i.buttons.l1 = true));
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if *k == vec![VKey::Control, VKey::LeftBracket]));
+        mapper.update(&UnifiedInput::default());
+
+        // R2 + R1 → tmux next window (prefix, n)
+        mapper.update(&input_with(|i| i.buttons.r2 = true));
+        let actions = mapper.update(&input_with(|i| { i.buttons.r2 = true; i.buttons.r1 = true; }));
+        assert!(matches!(&actions[..], [Action::KeySequence(s)] if *s == vec![vec![VKey::Control, VKey::B], vec![VKey::N]]));
+        mapper.update(&UnifiedInput::default());
+
+        // Square → OpenCode new session (leader, n)
+        let actions = mapper.update(&input_with(|i| i.buttons.square = true));
+        assert!(matches!(&actions[..], [Action::KeySequence(s)] if *s == vec![vec![VKey::Control, VKey::X], vec![VKey::N]]));
+        mapper.update(&UnifiedInput::default());
+
+        // Triangle → tmux new window instead of Tab
+        let actions = mapper.update(&input_with(|i| i.buttons.triangle = true));
+        assert!(matches!(&actions[..], [Action::KeySequence(s)] if *s == vec![vec![VKey::Control, VKey::B], vec![VKey::C]]));
+    }
+
+    #[test]
+    fn opencode_leader_chord_sends_each_step() {
+        let opencode = OpenCodeState::default();
+        let binding = resolve_opencode_button("<leader>s p", None).unwrap();
+        assert!(matches!(
+            opencode.action(&binding),
+            Action::KeySequence(s) if s == vec![vec![VKey::Control, VKey::X], vec![VKey::S], vec![VKey::P]]
+        ));
+    }
+
     #[test]
     fn default_profile_l2_does_nothing() {
         let mut mapper = MapperState::default();
@@ -1417,10 +3503,11 @@ mod tests {
 
     #[test]
     fn tmux_disabled_ps_does_nothing() {
-        let scroll_cfg = ScrollConfig::default();
         let mut tmux_cfg = TmuxConfig::default();
         tmux_cfg.enabled = false;
-        let mut mapper = MapperState::new(&scroll_cfg, &crate::config::StickMouseConfig::default(), &crate::config::TouchpadConfig::default(), &tmux_cfg, None, &crate::config::OpenCodeConfig::default(), None, &crate::config::WtConfig::default(), None, Arc::new(AtomicBool::new(false)));
+        let opencode_cfg = crate::config::OpenCodeConfig { enabled: false, ..Default::default() };
+        let cfg = Config { tmux: tmux_cfg, opencode: opencode_cfg, ..Default::default() };
+        let mut mapper = MapperState::new(&cfg, Detected::default(), Arc::new(AtomicBool::new(false)), Arc::new(AtomicBool::new(false)), None);
 
         // PS press should not switch profiles
         let ps_press = input_with(|i| i.buttons.ps = true);
@@ -1429,6 +3516,206 @@ mod tests {
         assert_eq!(mapper.profile(), Profile::Default);
     }
 
+    #[test]
+    fn create_ps_chord_fires_screenshot_once() {
+        let mut mapper = MapperState::default();
+
+        // Hold Create first
+        mapper.update(&input_with(|i| i.buttons.share = true));
+
+        // Press PS while Create is held → screenshot combo, no profile switch
+        let chord = input_with(|i| {
+            i.buttons.share = true;
+            i.buttons.ps = true;
+        });
+        let actions = mapper.update(&chord);
+        let shots: Vec<_> = actions.iter()
+            .filter(|a| matches!(a, Action::KeyCombo(k) if k == &[VKey::Win, VKey::Shift, VKey::S]))
+            .collect();
+        assert_eq!(shots.len(), 1);
+        assert!(!actions.iter().any(|a| matches!(a, Action::Custom(s) if s.starts_with("profile:"))));
+        assert_eq!(mapper.profile(), Profile::Default);
+
+        // Holding the chord does not repeat
+        let actions = mapper.update(&chord);
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn passthrough_app_suppresses_all_actions() {
+        let mut mapper = MapperState::default();
+        mapper.passthrough_active.store(true, Ordering::Relaxed);
+
+        let input = input_with(|i| {
+            i.buttons.cross = true;
+            i.buttons.square = true;
+            i.buttons.r2 = true;
+            i.buttons.touchpad = true;
+            i.right_stick = (128, 255);
+        });
+        assert!(mapper.update(&input).is_empty());
+        assert!(mapper.update(&input).is_empty());
+    }
+
+    fn layer_mapper() -> MapperState {
+        let layer = LayerConfig {
+            button: "r1".into(),
+            cross: "ctrl+s".into(),
+            ..Default::default()
+        };
+        MapperState { layer: LayerState::from_config(&layer), ..Default::default() }
+    }
+
+    #[test]
+    fn layer_held_cross_sends_layer_binding() {
+        let mut mapper = layer_mapper();
+
+        // Holding the layer button alone does nothing (its next-tab action is suppressed)
+        let actions = mapper.update(&input_with(|i| i.buttons.r1 = true));
+        assert!(actions.is_empty());
+
+        let actions = mapper.update(&input_with(|i| {
+            i.buttons.r1 = true;
+            i.buttons.cross = true;
+        }));
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(&actions[0], Action::KeyCombo(k) if k == &[VKey::Control, VKey::S]));
+
+        // Unbound buttons are silent while the layer is held
+        let actions = mapper.update(&input_with(|i| {
+            i.buttons.r1 = true;
+            i.buttons.circle = true;
+        }));
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn layer_released_cross_sends_enter() {
+        let mut mapper = layer_mapper();
+        let actions = mapper.update(&input_with(|i| i.buttons.cross = true));
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(&actions[0], Action::KeyCombo(k) if k == &[VKey::Return]));
+    }
+
+    fn l2_layer_mapper() -> MapperState {
+        let layer = LayerConfig {
+            button: "l2".into(),
+            cross: "f1".into(),
+            circle: "f2".into(),
+            square: "f3".into(),
+            triangle: "f4".into(),
+            ..Default::default()
+        };
+        MapperState { layer: LayerState::from_config(&layer), ..Default::default() }
+    }
+
+    #[test]
+    fn l2_layer_remaps_face_cluster() {
+        let mut mapper = l2_layer_mapper();
+
+        // Base layer: Cross → Enter
+        let actions = mapper.update(&input_with(|i| i.buttons.cross = true));
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if k == &[VKey::Return]));
+        mapper.update(&UnifiedInput::default());
+
+        // L2 as the layer button doesn't send its Ctrl+Win hold
+        assert!(mapper.update(&input_with(|i| i.buttons.l2 = true)).is_empty());
+
+        let layered = [
+            (Button::Cross, VKey::F1),
+            (Button::Circle, VKey::F2),
+            (Button::Square, VKey::F3),
+            (Button::Triangle, VKey::F4),
+        ];
+        for (button, key) in layered {
+            let actions = mapper.update(&input_with(|i| {
+                i.buttons.l2 = true;
+                match button {
+                    Button::Cross => i.buttons.cross = true,
+                    Button::Circle => i.buttons.circle = true,
+                    Button::Square => i.buttons.square = true,
+                    _ => i.buttons.triangle = true,
+                }
+            }));
+            assert!(matches!(&actions[..], [Action::KeyCombo(k)] if k == &[key]), "{actions:?}");
+            mapper.update(&input_with(|i| i.buttons.l2 = true));
+        }
+    }
+
+    #[test]
+    fn releasing_layer_button_fires_nothing() {
+        let mut mapper = l2_layer_mapper();
+        mapper.update(&input_with(|i| i.buttons.l2 = true));
+        mapper.update(&input_with(|i| {
+            i.buttons.l2 = true;
+            i.buttons.cross = true;
+        }));
+
+        // Layer released while Cross is still down: no base Enter, no KeyUp
+        assert!(mapper.update(&input_with(|i| i.buttons.cross = true)).is_empty());
+        assert!(mapper.update(&UnifiedInput::default()).is_empty());
+    }
+
+    #[test]
+    fn layer_press_does_not_complete_base_double_tap() {
+        let mut mapper = l2_layer_mapper();
+        mapper.double_cross = Some(vec![VKey::Shift, VKey::Tab]);
+
+        mapper.update(&input_with(|i| i.buttons.cross = true));
+        mapper.update(&UnifiedInput::default());
+        mapper.update(&input_with(|i| i.buttons.l2 = true));
+        mapper.update(&UnifiedInput::default());
+
+        // Second base press right after the layer was used: a single press
+        let actions = mapper.update(&input_with(|i| i.buttons.cross = true));
+        assert!(matches!(&actions[..], [Action::KeyCombo(k)] if k == &[VKey::Return]));
+    }
+
+    #[test]
+    fn layer_does_not_strand_l2_hold() {
+        let mut mapper = layer_mapper();
+        let actions = mapper.update(&input_with(|i| i.buttons.l2 = true));
+        assert!(matches!(&actions[0], Action::KeyDown(_)));
+
+        // Layer pressed while L2 is held, then L2 released inside the layer
+        mapper.update(&input_with(|i| {
+            i.buttons.l2 = true;
+            i.buttons.r1 = true;
+        }));
+        let actions = mapper.update(&input_with(|i| i.buttons.r1 = true));
+        assert!(actions.iter().any(|a| matches!(a, Action::KeyUp(k) if k == &[VKey::Control, VKey::Win])));
+    }
+
+    #[test]
+    fn tmux_key_table_binding_enters_table_first() {
+        let mut mapper = MapperState::default();
+        mapper.tmux.table_enter = vec![vec![VKey::Control, VKey::B], vec![VKey::A]];
+        mapper.tmux.square = Some(TmuxKey::Table(vec![VKey::G]));
+        switch_to_tmux(&mut mapper);
+
+        let actions = mapper.update(&input_with(|i| i.buttons.square = true));
+        match &actions[0] {
+            Action::KeySequence(seq) => assert_eq!(
+                seq,
+                &vec![vec![VKey::Control, VKey::B], vec![VKey::A], vec![VKey::G]]
+            ),
+            other => panic!("Expected KeySequence, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn tmux_root_binding_sends_key_without_prefix() {
+        let mut mapper = MapperState::default();
+        mapper.tmux.r1 = Some(TmuxKey::Root(vec![VKey::Alt, VKey::N]));
+        switch_to_tmux(&mut mapper);
+
+        let actions = mapper.update(&input_with(|i| i.buttons.r1 = true));
+        match &actions[0] {
+            Action::KeySequence(seq) => assert_eq!(seq, &vec![vec![VKey::Alt, VKey::N]]),
+            other => panic!("Expected KeySequence, got {other:?}"),
+        }
+    }
+
     #[test]
     fn tmux_mapped_buttons() {
         let mut mapper = MapperState::default();
@@ -1439,6 +3726,7 @@ mod tests {
             (|i| i.buttons.r1 = true, vec![VKey::N]),                   // next window
             (|i| i.buttons.r2 = true, vec![VKey::Shift, VKey::D7]),     // kill window (&)
             (|i| i.buttons.square = true, vec![VKey::C]),               // new window
+            (|i| i.buttons.share = true, vec![VKey::LeftBracket]),      // copy-mode
         ];
 
         for (setup, expected_action) in tests {
@@ -1462,7 +3750,6 @@ mod tests {
 
         // These buttons are unmapped in the default tmux config
         let unmapped: Vec<fn(&mut UnifiedInput)> = vec![
-            |i| i.buttons.share = true,
             |i| i.buttons.options = true,
             |i| i.buttons.touchpad = true,
         ];
@@ -1479,6 +3766,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tmux_copy_layer_dpad_pages_and_ends_dictation() {
+        let mut mapper = MapperState::default();
+        switch_to_tmux(&mut mapper);
+
+        // L2 alone still starts dictation
+        let l2 = input_with(|i| i.buttons.l2 = true);
+        assert!(matches!(mapper.update(&l2).as_slice(), [Action::KeyDown(k)] if k == &[VKey::Control, VKey::Win]));
+
+        // L2 + d-pad up: the hold is released, then Ctrl+U once confirmed
+        let up = input_with(|i| {
+            i.buttons.l2 = true;
+            i.buttons.dpad = DPad::Up;
+        });
+        assert!(matches!(mapper.update(&up).as_slice(), [Action::KeyUp(k)] if k == &[VKey::Control, VKey::Win]));
+        assert!(matches!(mapper.update(&up).as_slice(), [Action::KeyCombo(k)] if k == &[VKey::Control, VKey::U]));
+
+        let down = input_with(|i| {
+            i.buttons.l2 = true;
+            i.buttons.dpad = DPad::Down;
+        });
+        mapper.update(&down);
+        assert!(matches!(mapper.update(&down).as_slice(), [Action::KeyCombo(k)] if k == &[VKey::Control, VKey::D]));
+
+        // Releasing L2 afterwards sends nothing more
+        assert!(mapper.update(&UnifiedInput::default()).is_empty());
+    }
+
+    #[test]
+    fn tmux_copy_layer_stick_scrolls_lines() {
+        let mut mapper = MapperState::default();
+        switch_to_tmux(&mut mapper);
+        let stick_up = input_with(|i| {
+            i.buttons.l2 = true;
+            i.right_stick = (128, 0);
+        });
+        let actions = mapper.update(&stick_up);
+        assert!(actions.iter().any(|a| matches!(a, Action::KeyCombo(k) if k == &[VKey::Control, VKey::Y])));
+        assert!(!actions.iter().any(|a| matches!(a, Action::Scroll { .. } | Action::KeyDown(_))));
+
+        // Without L2 the stick scrolls the wheel as usual
+        let mut mapper = MapperState::default();
+        switch_to_tmux(&mut mapper);
+        let actions = mapper.update(&input_with(|i| i.right_stick = (128, 0)));
+        assert!(actions.iter().any(|a| matches!(a, Action::Scroll { .. })));
+    }
+
+    #[test]
+    fn default_profile_l2_dpad_moves_pane_focus() {
+        let mut mapper = MapperState::default();
+
+        // L2 alone still starts dictation
+        let l2 = input_with(|i| i.buttons.l2 = true);
+        assert!(matches!(mapper.update(&l2).as_slice(), [Action::KeyDown(k)] if k == &[VKey::Control, VKey::Win]));
+
+        // L2 + d-pad left: the hold is released, then Alt+Left once confirmed
+        let left = input_with(|i| {
+            i.buttons.l2 = true;
+            i.buttons.dpad = DPad::Left;
+        });
+        assert!(matches!(mapper.update(&left).as_slice(), [Action::KeyUp(k)] if k == &[VKey::Control, VKey::Win]));
+        assert!(matches!(mapper.update(&left).as_slice(), [Action::KeyCombo(k)] if k == &[VKey::Alt, VKey::Left]));
+        mapper.update(&l2);
+
+        // Detected bindings win over the Alt+arrow defaults
+        let detected: WtDetected =
+            serde_json::from_str(r#"{"actions": {"moveFocusUp": ["Control", "Alt", "K"]}}"#).unwrap();
+        mapper.wt = WtState::from_config(&WtConfig::default(), Some(&detected));
+        let up = input_with(|i| {
+            i.buttons.l2 = true;
+            i.buttons.dpad = DPad::Up;
+        });
+        mapper.update(&up);
+        assert!(matches!(mapper.update(&up).as_slice(), [Action::KeyCombo(k)] if k == &[VKey::Control, VKey::Alt, VKey::K]));
+
+        // Without L2 the d-pad is still arrows
+        let mut mapper = MapperState::default();
+        let up = input_with(|i| i.buttons.dpad = DPad::Up);
+        mapper.update(&up);
+        assert!(matches!(mapper.update(&up).as_slice(), [Action::KeyCombo(k)] if k == &[VKey::Up]));
+    }
+
+    #[test]
+    fn action_table_is_sorted_and_aligned() {
+        let detected: WtDetected = serde_json::from_str(
+            r#"{"actions": {"nextTab": ["Control", "Tab"], "moveFocusUp": ["Alt", "Up"], "find": ["Control", "Shift", "F"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            format_action_table(detected.actions(), |keys| format_key_combo(keys)),
+            "  find         Ctrl+Shift+F\n  moveFocusUp  Alt+Up\n  nextTab      Ctrl+Tab\n"
+        );
+        assert_eq!(format_action_table(&HashMap::<String, Vec<VKey>>::new(), |keys| format_key_combo(keys)), "  (none)\n");
+    }
+
+    #[test]
+    fn wt_send_input_button_types_text() {
+        let detected: WtDetected =
+            serde_json::from_str(r#"{"actions": {}, "texts": {"Clear": "clear\r"}}"#).unwrap();
+        let cfg = WtConfig { square: "Clear".into(), ..WtConfig::default() };
+        let mut mapper = MapperState { wt: WtState::from_config(&cfg, Some(&detected)), ..Default::default() };
+        let actions = mapper.update(&input_with(|i| i.buttons.square = true));
+        assert!(matches!(actions.as_slice(), [Action::TypeText(t)] if t == "clear\r"));
+
+        // One press + release per UTF-16 unit; astral characters as surrogate pairs
+        assert_eq!(text_key_events("a\r"), [(0x61, false), (0x61, true), (0x0D, false), (0x0D, true)]);
+        assert_eq!(text_key_events("😀").len(), 4);
+    }
+
     #[test]
     fn r3_ctrl_p_default_ctrl_u_tmux() {
         // Default profile: R3 → Ctrl+P
@@ -1633,6 +4029,108 @@ mod tests {
         );
     }
 
+    fn input_with_two_touches(y1: u16, y2: u16) -> UnifiedInput {
+        let mut i = UnifiedInput::default();
+        i.touchpad[0] = crate::input::TouchPoint { active: true, x: 400, y: y1 };
+        i.touchpad[1] = crate::input::TouchPoint { active: true, x: 800, y: y2 };
+        i
+    }
+
+    #[test]
+    fn two_finger_drag_scrolls_instead_of_moving() {
+        let mut mapper = MapperState::default();
+        assert!(mapper.update(&input_with_two_touches(300, 320)).is_empty());
+        // Average moves down 60 → one notch up (content follows the fingers)
+        let actions = mapper.update(&input_with_two_touches(360, 380));
+        assert!(matches!(&actions[..], [Action::Scroll { horizontal: 0, vertical: 120 }]));
+        // Drag back up 130 → two notches down, 10 units carried over
+        let actions = mapper.update(&input_with_two_touches(230, 250));
+        assert!(matches!(&actions[..], [Action::Scroll { horizontal: 0, vertical: -240 }]));
+        assert_eq!(mapper.touch_scroll_acc, -10);
+    }
+
+    #[test]
+    fn two_finger_tap_right_clicks() {
+        let mut mapper = MapperState::default();
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        let right_clicked = |actions: &[Action]| actions.iter().any(|a| matches!(a, Action::MouseRightClick));
+
+        // Both fingers land, wobble a little, lift within the tap window
+        assert!(mapper.update_at(&input_with_two_touches(300, 320), at(0)).is_empty());
+        assert!(mapper.update_at(&input_with_two_touches(310, 330), at(60)).is_empty());
+        assert!(matches!(&mapper.update_at(&UnifiedInput::default(), at(120))[..], [Action::MouseRightClick]));
+
+        // The second finger a frame late, lifting one finger at a time
+        mapper.update_at(&input_with_touch(500, 300, false), at(1000));
+        mapper.update_at(&input_with_two_touches(300, 320), at(1010));
+        assert!(!right_clicked(&mapper.update_at(&input_with_touch(400, 300, false), at(1080))));
+        assert!(right_clicked(&mapper.update_at(&UnifiedInput::default(), at(1090))));
+
+        // Resting two fingers is no tap
+        mapper.update_at(&input_with_two_touches(300, 320), at(2000));
+        assert!(!right_clicked(&mapper.update_at(&UnifiedInput::default(), at(2400))));
+
+        // Nor is pressing the touchpad down with two fingers (that's a left click)
+        let pressed = input_with(|i| {
+            i.touchpad = input_with_two_touches(300, 320).touchpad;
+            i.buttons.touchpad = true;
+        });
+        assert!(matches!(&mapper.update_at(&pressed, at(3000))[..], [Action::MouseButtonDown]));
+        assert!(matches!(&mapper.update_at(&UnifiedInput::default(), at(3050))[..], [Action::MouseButtonUp]));
+    }
+
+    #[test]
+    fn stick_mode_engaging_mid_touch_is_not_a_tap() {
+        let stick = StickMouseConfig { momentary_button: "l3".into(), ..Default::default() };
+        let mut mapper = MapperState { stick_mouse_button: momentary_stick_button(&stick), ..Default::default() };
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        let right_clicked = |actions: &[Action]| actions.iter().any(|a| matches!(a, Action::MouseRightClick));
+
+        // Two fingers land, then L3 turns stick mode on while they're still down
+        mapper.update_at(&input_with_two_touches(300, 320), at(0));
+        let held = input_with(|i| {
+            i.touchpad = input_with_two_touches(300, 320).touchpad;
+            i.buttons.l3 = true;
+        });
+        assert!(!right_clicked(&mapper.update_at(&held, at(60))));
+        assert!(!right_clicked(&mapper.update_at(&UnifiedInput::default(), at(120))));
+    }
+
+    #[test]
+    fn two_finger_scroll_is_not_a_tap() {
+        let mut mapper = MapperState::default();
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        mapper.update_at(&input_with_two_touches(300, 320), at(0));
+        let actions = mapper.update_at(&input_with_two_touches(360, 380), at(50));
+        assert!(matches!(&actions[..], [Action::Scroll { horizontal: 0, vertical: 120 }]));
+        // Quick, but the fingers travelled: lifting them doesn't right-click
+        assert!(mapper.update_at(&UnifiedInput::default(), at(100)).is_empty());
+
+        // A one-finger swipe that picks up a second finger isn't either
+        mapper.update_at(&input_with_touch(500, 300, false), at(200));
+        mapper.update_at(&input_with_touch(600, 300, false), at(220));
+        mapper.update_at(&input_with_two_touches(300, 320), at(240));
+        assert!(mapper.update_at(&UnifiedInput::default(), at(260)).is_empty());
+    }
+
+    #[test]
+    fn second_finger_mid_drag_does_not_jump() {
+        let mut mapper = MapperState::default();
+        mapper.update(&input_with_touch(500, 300, false));
+        mapper.update(&input_with_touch(510, 300, false));
+        // Second finger lands: no MouseMove, tracking restarts
+        let actions = mapper.update(&input_with_two_touches(300, 700));
+        assert!(actions.is_empty());
+        // Second finger lifts: first finger restarts cursor tracking without a jump
+        let actions = mapper.update(&input_with_touch(900, 900, false));
+        assert!(actions.is_empty());
+        let actions = mapper.update(&input_with_touch(910, 900, false));
+        assert!(matches!(&actions[..], [Action::MouseMove { dx: 15, dy: 0 }]));
+    }
+
     #[test]
     fn touchpad_no_move_when_stationary() {
         let mut mapper = MapperState::default();
@@ -1648,15 +4146,53 @@ mod tests {
         let input = input_with_touch(500, 300, true);
         let actions = mapper.update(&input);
         assert!(
-            actions.iter().any(|a| matches!(a, Action::MouseClick)),
-            "MouseClick on first press frame"
+            actions.iter().any(|a| matches!(a, Action::MouseButtonDown)),
+            "MouseButtonDown on first press frame"
         );
-        // Hold: no second click
+        // Hold: no second press
         let actions = mapper.update(&input);
         assert!(
-            !actions.iter().any(|a| matches!(a, Action::MouseClick)),
-            "No click on hold"
+            !actions.iter().any(|a| matches!(a, Action::MouseButtonDown | Action::MouseButtonUp)),
+            "No button change on hold"
         );
+        // A quick tap is a plain click: the release follows right away
+        let actions = mapper.update(&input_with_touch(500, 300, false));
+        assert!(matches!(&actions[..], [Action::MouseButtonUp]));
+    }
+
+    #[test]
+    fn touchpad_press_and_move_drags() {
+        let mut mapper = MapperState::default();
+        assert!(matches!(&mapper.update(&input_with_touch(500, 300, true))[..], [Action::MouseButtonDown]));
+        // Moving while pressed moves the cursor with the button still down
+        let actions = mapper.update(&input_with_touch(520, 310, true));
+        assert!(matches!(&actions[..], [Action::MouseMove { dx: 30, dy: 15 }]));
+        assert!(mapper.touch_drag);
+        // Lifting the press ends the drag where the finger stopped
+        let actions = mapper.update(&input_with_touch(520, 310, false));
+        assert!(matches!(&actions[..], [Action::MouseButtonUp]));
+        assert!(!mapper.touch_drag);
+
+        // A passthrough app taking focus mid-drag gets the button released
+        mapper.update(&input_with_touch(520, 310, true));
+        mapper.passthrough_active.store(true, Ordering::Relaxed);
+        assert!(matches!(&mapper.update(&input_with_touch(520, 310, true))[..], [Action::MouseButtonUp]));
+        assert!(mapper.update(&input_with_touch(520, 310, true)).is_empty());
+    }
+
+    #[test]
+    fn release_holds_lets_go_of_drag_and_l2() {
+        let mut mapper = MapperState::default();
+        mapper.update(&input_with_touch(500, 300, true));
+        let mut l2 = UnifiedInput::default();
+        l2.buttons.l2 = true;
+        l2.buttons.touchpad = true;
+        mapper.update(&l2);
+        assert!(mapper.touch_drag && mapper.l2_hold_active);
+        // The controller disconnects mid-drag with L2 held
+        let actions = mapper.release_holds();
+        assert!(matches!(&actions[..], [Action::KeyUp(k), Action::MouseButtonUp] if k == &[VKey::Control, VKey::Win]));
+        assert!(mapper.release_holds().is_empty());
     }
 
     #[test]
@@ -1667,7 +4203,7 @@ mod tests {
         mapper.update(&input_with_touch(400, 200, false)); // set prev_touch (should be skipped)
         let actions = mapper.update(&input);
         assert!(!actions.iter().any(|a| matches!(a, Action::MouseMove { .. })));
-        assert!(!actions.iter().any(|a| matches!(a, Action::MouseClick)));
+        assert!(!actions.iter().any(|a| matches!(a, Action::MouseButtonDown)));
     }
 
     // ── Left stick mouse tests ────────────────────────────────────────
@@ -1694,6 +4230,21 @@ mod tests {
         assert!(!actions.iter().any(|a| matches!(a, Action::MouseMove { .. })));
     }
 
+    #[test]
+    fn stick_mouse_dead_zone_follows_calibrated_center() {
+        let mut mapper = MapperState::default();
+        enable_stick_mode(&mapper);
+        // A pad resting at (145, 128): 17 right of 128 but inside the dead zone
+        mapper.set_calibration(&CalibrationConfig { left_x: 145, ..Default::default() });
+        let moved = |mapper: &mut MapperState, lx| {
+            mapper.update(&input_with_left_stick(lx, 128)).iter().any(|a| matches!(a, Action::MouseMove { .. }))
+        };
+        assert!(!moved(&mut mapper, 145));
+        assert!(!moved(&mut mapper, 155));
+        // 128 is now 17 left of center → past the dead zone (15)
+        assert!(moved(&mut mapper, 128));
+    }
+
     /// Helper: activate stick mouse mode for tests.
     fn enable_stick_mode(mapper: &MapperState) {
         mapper.mouse_stick_active.store(true, Ordering::Relaxed);
@@ -1738,6 +4289,184 @@ mod tests {
         assert!(fired, "Sub-pixel accumulator should emit move after enough frames");
     }
 
+    /// Total horizontal pixels emitted over `frames` frames at a fixed stick position.
+    fn stick_mouse_total_dx(mapper: &mut MapperState, lx: u8, frames: usize) -> i32 {
+        let input = input_with_left_stick(lx, 128);
+        (0..frames)
+            .flat_map(|_| mapper.update(&input))
+            .map(|a| match a {
+                Action::MouseMove { dx, .. } => dx,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn momentary_button_enables_stick_mouse_while_held() {
+        let stick = StickMouseConfig { momentary_button: "l3".into(), ..Default::default() };
+        let mut mapper = MapperState {
+            stick_mouse_button: momentary_stick_button(&stick),
+            stick_mouse_sensitivity: 2.3,
+            ..Default::default()
+        };
+        let moved = |actions: &[Action]| actions.iter().any(|a| matches!(a, Action::MouseMove { dx, .. } if *dx > 0));
+
+        // The tray toggle is ignored either way
+        enable_stick_mode(&mapper);
+        assert!(!moved(&mapper.update(&input_with_left_stick(255, 128))));
+
+        let held = input_with(|i| {
+            i.left_stick = (255, 128);
+            i.buttons.l3 = true;
+        });
+        let mut fired = Vec::new();
+        for _ in 0..3 {
+            fired.extend(mapper.update(&held));
+        }
+        assert!(moved(&fired));
+        // L3's own Ctrl+T is suppressed
+        assert!(!fired.iter().any(|a| matches!(a, Action::KeyCombo(_))));
+
+        mapper.mouse_stick_active.store(false, Ordering::Relaxed);
+        assert!(moved(&mapper.update(&held)));
+        assert!(!moved(&mapper.update(&input_with_left_stick(255, 128))));
+        // Releasing the button drops the leftover sub-pixels
+        assert_eq!((mapper.stick_acc_x, mapper.stick_acc_y), (0.0, 0.0));
+
+        let unknown = StickMouseConfig { momentary_button: "l4".into(), ..Default::default() };
+        assert_eq!(momentary_stick_button(&unknown), None);
+    }
+
+    #[test]
+    fn swap_sticks_moves_cursor_with_right_and_scrolls_with_left() {
+        let mut mapper = MapperState { swap_sticks: true, ..Default::default() };
+        let right = input_with(|i| i.right_stick = (255, 128));
+        let left = input_with_left_stick(128, 0);
+
+        // Stick mode off: the right stick neither moves the cursor nor scrolls
+        assert!(mapper.update(&right).is_empty());
+
+        enable_stick_mode(&mapper);
+        let actions = mapper.update(&right);
+        assert!(actions.iter().any(|a| matches!(a, Action::MouseMove { dx, .. } if *dx > 0)));
+        assert!(!actions.iter().any(|a| matches!(a, Action::Scroll { .. })));
+
+        let actions = mapper.update(&left);
+        assert!(actions.iter().any(|a| matches!(a, Action::Scroll { vertical, .. } if *vertical > 0)));
+        assert!(!actions.iter().any(|a| matches!(a, Action::MouseMove { .. })));
+
+        // Dead zones follow the swapped sticks' calibrated centers
+        mapper.set_calibration(&CalibrationConfig { left_y: 100, right_x: 240, ..Default::default() });
+        assert!(mapper.update(&input_with(|i| {
+            i.left_stick = (128, 110);
+            i.right_stick = (250, 128);
+        })).is_empty());
+    }
+
+    #[test]
+    fn stick_mouse_radial_dead_zone_passes_small_diagonals() {
+        // (12, 12) from center: each axis is inside the dead zone (15), but the
+        // stick is ≈17 from center
+        let input = input_with_left_stick(140, 140);
+        let moved = |mapper: &mut MapperState| {
+            (0..100)
+                .flat_map(|_| mapper.update(&input))
+                .any(|a| matches!(a, Action::MouseMove { dx, dy } if dx > 0 && dy > 0))
+        };
+
+        let mut axial = MapperState::default();
+        enable_stick_mode(&axial);
+        assert!(!moved(&mut axial));
+
+        let mut radial = MapperState { stick_mouse_dead_zone_shape: DeadZoneShape::Radial, ..Default::default() };
+        enable_stick_mode(&radial);
+        assert!(moved(&mut radial));
+
+        // (10, 10) is ≈14 from center → inside both
+        let mut radial = MapperState { stick_mouse_dead_zone_shape: DeadZoneShape::Radial, ..Default::default() };
+        enable_stick_mode(&radial);
+        assert!(radial.update(&input_with_left_stick(138, 138)).iter().all(|a| !matches!(a, Action::MouseMove { .. })));
+    }
+
+    #[test]
+    fn stick_mouse_radial_dead_zone_ramps_from_its_edge() {
+        let mut radial = MapperState { stick_mouse_dead_zone_shape: DeadZoneShape::Radial, ..Default::default() };
+        enable_stick_mode(&radial);
+        // Just past the edge (16 vs 15) barely moves; axial already runs at 16/127 speed
+        assert_eq!(stick_mouse_total_dx(&mut radial, 144, 10), 0);
+        // Full deflection still reaches the configured sensitivity
+        assert_eq!(stick_mouse_total_dx(&mut radial, 255, 10), 80);
+
+        let mut axial = MapperState::default();
+        enable_stick_mode(&axial);
+        assert_eq!(stick_mouse_total_dx(&mut axial, 144, 10), 10);
+
+        assert_eq!(radial_dead_zone(0, 0, 15), (0, 0));
+        assert_eq!(radial_dead_zone(-127, 0, 15), (-127, 0));
+        assert_eq!(radial_dead_zone(5, 5, 0), (5, 5));
+        // A full diagonal stays on the 127 circle instead of overshooting it
+        assert_eq!(radial_dead_zone(127, 127, 15), (90, 90));
+        assert_eq!(radial_dead_zone(-128, -128, 0), (-90, -90));
+        assert_eq!(radial_dead_zone(-128, 0, 15), (-127, 0));
+    }
+
+    #[test]
+    fn stick_mouse_accel_curve_squares_half_deflection() {
+        let mut curved = MapperState { stick_mouse_accel_curve: 2.0, ..Default::default() };
+        enable_stick_mode(&curved);
+        // dx_raw=64 → 0.504² × 8 ≈ 2.03 px/frame; full deflection stays 8 px/frame
+        assert_eq!(stick_mouse_total_dx(&mut curved, 192, 10), 20);
+        assert_eq!(stick_mouse_total_dx(&mut curved, 255, 10), 80);
+        // Sign is preserved on the negative side
+        assert_eq!(stick_mouse_total_dx(&mut curved, 64, 10), -20);
+
+        let mut linear = MapperState::default();
+        enable_stick_mode(&linear);
+        assert_eq!(stick_mouse_total_dx(&mut linear, 192, 10), 40);
+        assert_eq!(stick_mouse_total_dx(&mut linear, 255, 10), 80);
+    }
+
+    #[test]
+    fn resolved_bindings_reports_square_in_default_profile() {
+        let mapper = MapperState::default();
+        let bindings = mapper.resolved_bindings();
+        let square = bindings
+            .iter()
+            .find(|b| b.scope == BindingScope::Profile(Profile::Default) && b.button == "square")
+            .expect("square bound in default profile");
+        assert_eq!(square.action, "Ctrl+Shift+1");
+
+        let tmux_square = bindings
+            .iter()
+            .find(|b| b.scope == BindingScope::Profile(Profile::Tmux) && b.button == "square")
+            .expect("square bound in tmux profile");
+        assert_eq!(tmux_square.action, "Ctrl+B, C");
+    }
+
+    #[test]
+    fn stick_mouse_axis_snap_zeroes_minor_axis() {
+        let mut mapper = MapperState::default();
+        enable_stick_mode(&mapper);
+        mapper.stick_mouse_axis_snap = 0.25;
+        // Near-horizontal: dx_raw=127, dy_raw=20 (ratio ≈ 0.16 < 0.25)
+        let input = input_with_left_stick(255, 148);
+        for _ in 0..10 {
+            for a in mapper.update(&input) {
+                if let Action::MouseMove { dx, dy } = a {
+                    assert!(dx > 0);
+                    assert_eq!(dy, 0, "Minor axis should be snapped away");
+                }
+            }
+        }
+
+        // Same deflection without snapping drifts vertically
+        mapper.stick_mouse_axis_snap = 0.0;
+        let drifted = (0..10).any(|_| {
+            mapper.update(&input).iter().any(|a| matches!(a, Action::MouseMove { dy, .. } if *dy > 0))
+        });
+        assert!(drifted);
+    }
+
     #[test]
     fn stick_mouse_acc_resets_at_center() {
         let mut mapper = MapperState::default();
@@ -1793,7 +4522,7 @@ mod tests {
         // Touchpad press → click must fire even in stick mode
         let actions = mapper.update(&input_with_touch(500, 300, true));
         assert!(
-            actions.iter().any(|a| matches!(a, Action::MouseClick)),
+            actions.iter().any(|a| matches!(a, Action::MouseButtonDown)),
             "Touchpad click must fire regardless of mouse mode"
         );
         // But no touch movement
@@ -1824,4 +4553,37 @@ mod tests {
         assert_eq!(VKey::from_name("z"), Some(VKey::Z));
         assert_eq!(VKey::from_name("unknown"), None);
     }
+
+    #[test]
+    fn vkey_from_name_navigation_keys() {
+        assert_eq!(VKey::from_name("Home"), Some(VKey::Home));
+        assert_eq!(VKey::from_name("end"), Some(VKey::End));
+        assert_eq!(VKey::from_name("pageup"), Some(VKey::PageUp));
+        assert_eq!(VKey::from_name("pgup"), Some(VKey::PageUp));
+        assert_eq!(VKey::from_name("PageDown"), Some(VKey::PageDown));
+        assert_eq!(VKey::from_name("pgdn"), Some(VKey::PageDown));
+        assert_eq!(VKey::from_name("delete"), Some(VKey::Delete));
+        assert_eq!(VKey::from_name("del"), Some(VKey::Delete));
+        assert_eq!(VKey::from_name("insert"), Some(VKey::Insert));
+        assert_eq!(VKey::from_name("ins"), Some(VKey::Insert));
+        assert_eq!(parse_key_combo("shift+pageup"), Some(vec![VKey::Shift, VKey::PageUp]));
+    }
+
+    #[test]
+    fn vkey_from_name_media_and_numpad_keys() {
+        assert_eq!(VKey::from_name("volumeup"), Some(VKey::VolumeUp));
+        assert_eq!(VKey::from_name("VolumeDown"), Some(VKey::VolumeDown));
+        assert_eq!(VKey::from_name("volumemute"), Some(VKey::VolumeMute));
+        assert_eq!(VKey::from_name("playpause"), Some(VKey::MediaPlayPause));
+        assert_eq!(VKey::from_name("nexttrack"), Some(VKey::MediaNext));
+        assert_eq!(VKey::from_name("prevtrack"), Some(VKey::MediaPrev));
+        assert_eq!(VKey::from_name("numpad0"), Some(VKey::Numpad0));
+        assert_eq!(VKey::from_name("Numpad9"), Some(VKey::Numpad9));
+        assert_eq!(VKey::from_name("num5"), Some(VKey::Numpad5));
+        assert_eq!(parse_key_combo("ctrl+numpad1"), Some(vec![VKey::Control, VKey::Numpad1]));
+        // Labels round-trip through from_name
+        for key in [VKey::VolumeUp, VKey::MediaPlayPause, VKey::MediaNext, VKey::Numpad7] {
+            assert_eq!(VKey::from_name(key.label()), Some(key));
+        }
+    }
 }
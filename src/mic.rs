@@ -1,43 +1,122 @@
 /// Toggle the default audio capture (microphone) mute state.
 /// Uses the Windows Core Audio API — no third-party dependencies.
 /// Profile-agnostic: called directly from the input loop on any profile.
+///
+/// With `[mic] prefer_controller` and a headset in the DualSense audio jack,
+/// the controller's own capture endpoint is toggled instead of the default.
+/// The mute LED follows whichever endpoint the button currently toggles:
+/// `refresh()` re-reads it when the target changes.
 
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use windows::Win32::Devices::FunctionDiscovery::PKEY_Device_FriendlyName;
 use windows::Win32::Foundation::BOOL;
-use windows::Win32::Media::Audio::{eCapture, eConsole, IMMDeviceEnumerator, MMDeviceEnumerator};
+use windows::Win32::Media::Audio::{
+    eCapture, eConsole, IMMDevice, IMMDeviceEnumerator, MMDeviceEnumerator, DEVICE_STATE_ACTIVE,
+};
 use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
 use windows::Win32::System::Com::{
-    CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_APARTMENTTHREADED,
+    CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_APARTMENTTHREADED, STGM_READ,
 };
 
-/// Cached mute state — written by toggle_mute() and init(), read by the output loop.
+/// Cached mute state of the endpoint the mute button toggles — written by
+/// toggle_mute(), init() and refresh(), read by the output loop.
 pub static MIC_MUTED: AtomicBool = AtomicBool::new(false);
 
+/// Which capture device the mute button toggles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MicTarget {
+    /// The system default microphone.
+    SystemDefault,
+    /// The DualSense's own capture device (its built-in mic or the headset's).
+    Controller,
+}
+
+/// The controller mic only when preferred and a headset is plugged into it.
+pub fn choose_target(prefer_controller: bool, headset: bool) -> MicTarget {
+    if prefer_controller && headset { MicTarget::Controller } else { MicTarget::SystemDefault }
+}
+
+/// Index of the DualSense capture endpoint among the endpoints' friendly
+/// names, e.g. "Headset Microphone (DualSense Wireless Controller)".
+fn find_controller_endpoint<S: AsRef<str>>(names: &[S]) -> Option<usize> {
+    names.iter().position(|name| {
+        let name = name.as_ref().to_ascii_lowercase();
+        name.contains("dualsense") || name.contains("wireless controller")
+    })
+}
+
 /// Query the current system mute state and prime MIC_MUTED.
 /// Call once at startup (on a blocking thread) before the first output frame.
 pub fn init() {
+    refresh(MicTarget::SystemDefault);
+}
+
+/// Re-read the `target` mic's mute state into MIC_MUTED, e.g. after a headset
+/// was plugged into the controller and the button now toggles its mic.
+pub fn refresh(target: MicTarget) {
     unsafe {
         let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
-        if let Some(muted) = query_muted() {
+        if let Some(muted) = query_muted(target) {
             MIC_MUTED.store(muted, Ordering::Relaxed);
-            log::debug!("mic: initial state = {}", if muted { "muted" } else { "unmuted" });
+            log::debug!("mic: {target:?} state = {}", if muted { "muted" } else { "unmuted" });
         }
     }
 }
 
-fn query_muted() -> Option<bool> {
+fn query_muted(target: MicTarget) -> Option<bool> {
     unsafe {
         let enumerator: IMMDeviceEnumerator =
             CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).ok()?;
-        let device = enumerator.GetDefaultAudioEndpoint(eCapture, eConsole).ok()?;
+        let device = target_endpoint(&enumerator, target)?;
         let vol: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None).ok()?;
         Some(vol.GetMute().ok()?.as_bool())
     }
 }
 
-/// Toggle system mic mute and update MIC_MUTED.
-pub fn toggle_mute() {
+/// The capture endpoint for `target`: the controller's if it has one (else
+/// the default, logged), or the system default.
+fn target_endpoint(enumerator: &IMMDeviceEnumerator, target: MicTarget) -> Option<IMMDevice> {
+    if target == MicTarget::Controller {
+        match controller_endpoint(enumerator) {
+            Some(device) => return Some(device),
+            None => log::info!("mic: no controller microphone found, using the default"),
+        }
+    }
+    match unsafe { enumerator.GetDefaultAudioEndpoint(eCapture, eConsole) } {
+        Ok(device) => Some(device),
+        Err(_) => {
+            log::warn!("mic: no default microphone found");
+            None
+        }
+    }
+}
+
+/// The active DualSense capture endpoint, if Windows exposes one (USB only).
+fn controller_endpoint(enumerator: &IMMDeviceEnumerator) -> Option<IMMDevice> {
+    unsafe {
+        let collection = enumerator.EnumAudioEndpoints(eCapture, DEVICE_STATE_ACTIVE).ok()?;
+        let devices: Vec<IMMDevice> =
+            (0..collection.GetCount().ok()?).filter_map(|i| collection.Item(i).ok()).collect();
+        let names: Vec<String> = devices
+            .iter()
+            .map(|device| {
+                device
+                    .OpenPropertyStore(STGM_READ)
+                    .and_then(|store| store.GetValue(&PKEY_Device_FriendlyName))
+                    .map(|name| name.to_string())
+                    .unwrap_or_default()
+            })
+            .collect();
+        let index = find_controller_endpoint(&names)?;
+        log::debug!("mic: controller endpoint = {}", names[index]);
+        devices.into_iter().nth(index)
+    }
+}
+
+/// Toggle the `target` mic's mute and update MIC_MUTED. Falls back to the
+/// system default when the controller has no capture endpoint (e.g. over BT).
+pub fn toggle_mute(target: MicTarget) {
     unsafe {
         let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
 
@@ -48,10 +127,7 @@ pub fn toggle_mute() {
             return;
         };
 
-        let Ok(device) = enumerator.GetDefaultAudioEndpoint(eCapture, eConsole) else {
-            log::warn!("mic: no default microphone found");
-            return;
-        };
+        let Some(device) = target_endpoint(&enumerator, target) else { return };
 
         let Ok(vol): Result<IAudioEndpointVolume, _> = device.Activate(CLSCTX_ALL, None) else {
             log::warn!("mic: Activate(IAudioEndpointVolume) failed");
@@ -69,3 +145,28 @@ pub fn toggle_mute() {
         log::info!("mic: {}", if new_state { "muted" } else { "unmuted" });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn controller_mic_needs_preference_and_headset() {
+        assert_eq!(choose_target(true, true), MicTarget::Controller);
+        assert_eq!(choose_target(true, false), MicTarget::SystemDefault);
+        assert_eq!(choose_target(false, true), MicTarget::SystemDefault);
+        assert_eq!(choose_target(false, false), MicTarget::SystemDefault);
+    }
+
+    #[test]
+    fn finds_controller_endpoint_by_name() {
+        let names = [
+            "Microphone (Realtek(R) Audio)",
+            "Headset Microphone (DualSense Wireless Controller)",
+        ];
+        assert_eq!(find_controller_endpoint(&names), Some(1));
+        assert_eq!(find_controller_endpoint(&["Microphone (Wireless Controller)"]), Some(0));
+        assert_eq!(find_controller_endpoint(&["Microphone (USB Audio)"]), None);
+        assert_eq!(find_controller_endpoint::<&str>(&[]), None);
+    }
+}
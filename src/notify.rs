@@ -0,0 +1,241 @@
+/// Windows toast notifications for per-agent done/error transitions and idle
+/// reminders.
+///
+/// Opt-in via `[notifications] toast = true`; `done`, `error` and
+/// `idle_reminder` pick the kinds, and each kind shows at most one toast per
+/// `debounce_s` so a burst of subagents doesn't stack up. Toasts are shown through a hidden
+/// PowerShell process using the WinRT `ToastNotificationManager`, so no extra
+/// crate or registered AppUserModelID is needed. Spawning is fire-and-forget —
+/// the state poller never waits on it.
+
+use crate::state::AgentState;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// AppUserModelID toasts are attributed to (PowerShell's, always registered).
+const TOAST_APP_ID: &str =
+    r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+
+/// Short, human-friendly label for an agent id (session UUIDs are long).
+pub fn agent_label(agent_id: &str) -> String {
+    let label: String = agent_id.chars().take(8).collect();
+    if label.is_empty() { "agent".into() } else { label }
+}
+
+/// What a toast is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToastKind {
+    Done,
+    Error,
+    IdleReminder,
+}
+
+impl ToastKind {
+    /// Toast for an agent entering `state`, if the state warrants one.
+    pub fn for_state(state: AgentState) -> Option<Self> {
+        match state {
+            AgentState::Done => Some(Self::Done),
+            AgentState::Error => Some(Self::Error),
+            AgentState::Idle | AgentState::Working | AgentState::Thinking => None,
+        }
+    }
+}
+
+/// Which toasts to show (from `[notifications]`; all false = none).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToastSettings {
+    pub done: bool,
+    pub error: bool,
+    pub idle_reminder: bool,
+    /// Minimum seconds between two toasts of the same kind.
+    pub debounce_s: u64,
+}
+
+/// Shows toasts per [`ToastSettings`], debounced per kind.
+pub struct Toaster {
+    settings: ToastSettings,
+    last_shown: HashMap<ToastKind, Instant>,
+}
+
+impl Toaster {
+    pub fn new(settings: ToastSettings) -> Self {
+        Self { settings, last_shown: HashMap::new() }
+    }
+
+    /// True if a `kind` toast may be shown at `now`: it's enabled and none of
+    /// that kind was shown within the debounce window. Records the toast.
+    pub fn allow(&mut self, kind: ToastKind, now: Instant) -> bool {
+        let enabled = match kind {
+            ToastKind::Done => self.settings.done,
+            ToastKind::Error => self.settings.error,
+            ToastKind::IdleReminder => self.settings.idle_reminder,
+        };
+        let debounce = Duration::from_secs(self.settings.debounce_s);
+        let recent = self
+            .last_shown
+            .get(&kind)
+            .is_some_and(|&at| now.duration_since(at) < debounce);
+        if !enabled || recent {
+            return false;
+        }
+        self.last_shown.insert(kind, now);
+        true
+    }
+
+    /// Show a `kind` toast for `agent_id` unless it's disabled or debounced.
+    /// `elapsed` is how long the agent worked (done/error) or waited (idle
+    /// reminder), if known. Non-blocking.
+    pub fn notify(&mut self, kind: ToastKind, agent_id: &str, elapsed: Option<Duration>, now: Instant) {
+        if self.allow(kind, now) {
+            let (title, body) = toast_message(agent_id, kind, elapsed);
+            show_toast(&title, &body);
+        } else {
+            log::debug!("Toast skipped for agent {agent_id}: {kind:?} disabled or debounced");
+        }
+    }
+}
+
+/// Compact duration for toast text: "45s", "12m", "1h 5m".
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Toast title and body for `kind`, with the elapsed time when known.
+pub fn toast_message(agent_id: &str, kind: ToastKind, elapsed: Option<Duration>) -> (String, String) {
+    let label = agent_label(agent_id);
+    let after = elapsed.map(format_elapsed);
+    match kind {
+        ToastKind::Done => (
+            "DS4CC — agent done".into(),
+            match after {
+                Some(t) => format!("Agent {label} done after {t}."),
+                None => format!("Agent {label} finished its task."),
+            },
+        ),
+        ToastKind::Error => (
+            "DS4CC — agent error".into(),
+            match after {
+                Some(t) => format!("Agent {label} hit an error after {t}."),
+                None => format!("Agent {label} hit an error."),
+            },
+        ),
+        ToastKind::IdleReminder => (
+            "DS4CC — agent waiting".into(),
+            match after {
+                Some(t) => format!("Agent {label} has been waiting for {t}."),
+                None => format!("Agent {label} is waiting for input."),
+            },
+        ),
+    }
+}
+
+/// Show a Windows toast with a title and one line of text. Non-blocking.
+#[cfg(windows)]
+pub fn show_toast(title: &str, body: &str) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let script = toast_script(title, body);
+    let result = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-WindowStyle", "Hidden", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn();
+    if let Err(e) = result {
+        log::debug!("Toast notification failed to spawn: {e}");
+    }
+}
+
+#[cfg(not(windows))]
+pub fn show_toast(_title: &str, _body: &str) {}
+
+/// PowerShell script that builds and shows the toast XML.
+fn toast_script(title: &str, body: &str) -> String {
+    let xml = format!(
+        "<toast><visual><binding template='ToastGeneric'><text>{}</text><text>{}</text></binding></visual></toast>",
+        xml_escape(title),
+        xml_escape(body),
+    );
+    format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+         [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom, ContentType = WindowsRuntime] > $null; \
+         $x = New-Object Windows.Data.Xml.Dom.XmlDocument; $x.LoadXml('{}'); \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}').Show([Windows.UI.Notifications.ToastNotification]::new($x))",
+        ps_quote(&xml),
+        TOAST_APP_ID,
+    )
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Escape for a PowerShell single-quoted string literal.
+fn ps_quote(s: &str) -> String {
+    s.replace('\'', "''")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toast_message_includes_agent_label() {
+        let (title, body) =
+            toast_message("019c846c-3bd5-7593-bdef-de03296a30b1", ToastKind::Done, None);
+        assert_eq!(title, "DS4CC — agent done");
+        assert_eq!(body, "Agent 019c846c finished its task.");
+
+        let (title, body) = toast_message("abc", ToastKind::Error, None);
+        assert_eq!(title, "DS4CC — agent error");
+        assert_eq!(body, "Agent abc hit an error.");
+
+        assert_eq!(ToastKind::for_state(AgentState::Done), Some(ToastKind::Done));
+        assert_eq!(ToastKind::for_state(AgentState::Error), Some(ToastKind::Error));
+        assert!(ToastKind::for_state(AgentState::Working).is_none());
+        assert!(ToastKind::for_state(AgentState::Idle).is_none());
+    }
+
+    #[test]
+    fn toast_message_formats_elapsed_time() {
+        let minutes = |m: u64| Some(Duration::from_secs(m * 60));
+        assert_eq!(toast_message("abc", ToastKind::Done, minutes(12)).1, "Agent abc done after 12m.");
+        assert_eq!(
+            toast_message("abc", ToastKind::Error, Some(Duration::from_secs(45))).1,
+            "Agent abc hit an error after 45s."
+        );
+        let (title, body) = toast_message("abc", ToastKind::IdleReminder, minutes(65));
+        assert_eq!(title, "DS4CC — agent waiting");
+        assert_eq!(body, "Agent abc has been waiting for 1h 5m.");
+    }
+
+    #[test]
+    fn toaster_filters_kinds_and_debounces() {
+        let settings = ToastSettings { done: true, error: true, idle_reminder: false, debounce_s: 10 };
+        let mut toaster = Toaster::new(settings);
+        let t0 = Instant::now();
+        assert!(toaster.allow(ToastKind::Done, t0));
+        // A second done within the window is dropped; other kinds are independent
+        assert!(!toaster.allow(ToastKind::Done, t0 + Duration::from_secs(3)));
+        assert!(toaster.allow(ToastKind::Error, t0 + Duration::from_secs(3)));
+        assert!(toaster.allow(ToastKind::Done, t0 + Duration::from_secs(10)));
+        // Disabled kinds never show
+        assert!(!toaster.allow(ToastKind::IdleReminder, t0));
+    }
+
+    #[test]
+    fn toast_script_escapes_markup_and_quotes() {
+        let script = toast_script("a<b", "it's");
+        assert!(script.contains("a&lt;b"));
+        // XML apostrophe entity, never a raw quote that would end the PS literal
+        assert!(script.contains("it&apos;s"));
+    }
+}
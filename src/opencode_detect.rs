@@ -9,19 +9,23 @@
 ///   1. Auto-detected binding  →  2. Hardcoded default  →  3. Direct combo parse
 
 use crate::mapper::{parse_key_combo, VKey};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// A resolved OpenCode key binding.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActionBinding {
     /// Direct key combo (e.g., ctrl+s → [Control, S]).
     Combo(Vec<VKey>),
     /// Leader key followed by a single key (e.g., <leader>n → leader then [N]).
     LeaderKey(Vec<VKey>),
+    /// Leader key followed by several keys in turn
+    /// (e.g., <leader>s p → leader, then [S], then [P]).
+    LeaderSequence(Vec<Vec<VKey>>),
 }
 
-/// Auto-detected OpenCode configuration.
-#[derive(Debug, Clone)]
+/// Auto-detected OpenCode configuration (serializable for the detection cache).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenCodeDetected {
     /// Detected leader key combo (e.g., [Control, X] for ctrl+x).
     pub leader: Option<Vec<VKey>>,
@@ -34,6 +38,11 @@ impl OpenCodeDetected {
     pub fn binding_for_action(&self, action: &str) -> Option<&ActionBinding> {
         self.actions.get(action)
     }
+
+    /// Every detected action → binding (for `--dump-detect`).
+    pub fn actions(&self) -> &HashMap<String, ActionBinding> {
+        &self.actions
+    }
 }
 
 /// Detect OpenCode configuration by reading `~/.config/opencode/opencode.json` via WSL.
@@ -88,19 +97,16 @@ fn parse_config(json: &str) -> (Option<Vec<VKey>>, HashMap<String, ActionBinding
     };
 
     for (action, key_value) in keybinds {
-        let key_str = match key_value.as_str() {
-            Some(s) => s,
-            None => continue,
-        };
+        let alternatives = binding_strings(key_value);
 
         // The leader definition is stored as its own keybind entry.
         // OpenCode may use "leader" or "app:leader" as the key name.
         if action == "leader" || action == "app:leader" {
-            leader = parse_key_combo(key_str);
+            leader = alternatives.into_iter().find_map(parse_key_combo);
             continue;
         }
 
-        if let Some(binding) = parse_opencode_binding(key_str) {
+        if let Some(binding) = alternatives.into_iter().find_map(parse_opencode_binding) {
             actions.insert(action.clone(), binding);
         }
     }
@@ -110,6 +116,16 @@ fn parse_config(json: &str) -> (Option<Vec<VKey>>, HashMap<String, ActionBinding
 
 // ── Key binding parsers ───────────────────────────────────────────────
 
+/// The binding strings of a keybind value: a single string, or an array of
+/// alternatives (newer configs). Anything else has none.
+fn binding_strings(value: &serde_json::Value) -> Vec<&str> {
+    match value {
+        serde_json::Value::String(s) => vec![s.as_str()],
+        serde_json::Value::Array(items) => items.iter().filter_map(|item| item.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
 /// Parse an OpenCode key binding string to an `ActionBinding`.
 ///
 /// Handles comma-separated alternatives — takes the first valid one.
@@ -118,6 +134,7 @@ fn parse_config(json: &str) -> (Option<Vec<VKey>>, HashMap<String, ActionBinding
 /// - `"ctrl+s"`           → `Combo([Control, S])`
 /// - `"ctrl+shift+["`     → `Combo([Control, Shift, LeftBracket])`
 /// - `"<leader>n"`        → `LeaderKey([N])`
+/// - `"<leader>s p"`      → `LeaderSequence([[S], [P]])`
 /// - `"f1"`               → `Combo([F1])`
 /// - `"ctrl+[,<leader>p"` → `Combo([Control, LeftBracket])` (first valid)
 pub fn parse_opencode_binding(s: &str) -> Option<ActionBinding> {
@@ -136,8 +153,16 @@ pub fn parse_opencode_binding(s: &str) -> Option<ActionBinding> {
 /// Parse a single (non-comma) OpenCode binding string.
 fn parse_single_binding(s: &str) -> Option<ActionBinding> {
     if let Some(rest) = s.strip_prefix("<leader>") {
-        let keys = parse_key_combo(rest.trim())?;
-        return Some(ActionBinding::LeaderKey(keys));
+        // Whitespace separates the steps of a multi-key chord
+        let mut steps = rest
+            .split_whitespace()
+            .map(parse_key_combo)
+            .collect::<Option<Vec<_>>>()?;
+        return match steps.len() {
+            0 => None,
+            1 => steps.pop().map(ActionBinding::LeaderKey),
+            _ => Some(ActionBinding::LeaderSequence(steps)),
+        };
     }
     let keys = parse_key_combo(s)?;
     Some(ActionBinding::Combo(keys))
@@ -167,6 +192,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_leader_sequence() {
+        let b = parse_opencode_binding("<leader>s p").unwrap();
+        match b {
+            ActionBinding::LeaderSequence(steps) => assert_eq!(steps, vec![vec![VKey::S], vec![VKey::P]]),
+            _ => panic!("Expected LeaderSequence"),
+        }
+        // A step that doesn't parse rejects the whole alternative
+        assert!(parse_opencode_binding("<leader>s nope").is_none());
+        assert!(parse_opencode_binding("<leader>").is_none());
+    }
+
     #[test]
     fn parse_multi_modifier() {
         let b = parse_opencode_binding("ctrl+shift+[").unwrap();
@@ -216,6 +253,26 @@ mod tests {
         assert!(actions.contains_key("app:new-session"));
     }
 
+    #[test]
+    fn parse_config_array_takes_first_parseable() {
+        let json = r#"{
+            "keybinds": {
+                "leader": ["nope", "ctrl+x"],
+                "session:next": ["ctrl+]", "ctrl+pgdn"],
+                "session:prev": ["not a key", "<leader>p"],
+                "session:list": [42]
+            }
+        }"#;
+        let (leader, actions) = parse_config(json);
+        assert_eq!(leader, Some(vec![VKey::Control, VKey::X]));
+        match actions.get("session:next") {
+            Some(ActionBinding::Combo(keys)) => assert_eq!(*keys, vec![VKey::Control, VKey::RightBracket]),
+            other => panic!("Expected Combo, got {other:?}"),
+        }
+        assert!(matches!(actions.get("session:prev"), Some(ActionBinding::LeaderKey(keys)) if *keys == vec![VKey::P]));
+        assert!(!actions.contains_key("session:list"));
+    }
+
     #[test]
     fn parse_config_no_keybinds_section() {
         let json = r#"{"theme": "dark"}"#;
@@ -8,6 +8,9 @@
 ///   Byte 2:  valid flag 1 (0x04 = lightbar, 0x10 = player LEDs)
 ///   Byte 3:  right rumble motor
 ///   Byte 4:  left rumble motor
+///   Byte 11: right trigger effect (11 bytes: mode, then parameters)
+///   Byte 22: left trigger effect (11 bytes)
+///   Byte 43: LED brightness (0x00 = high, 0x01 = medium, 0x02 = low)
 ///   Byte 44: player indicator LEDs bitmask
 ///   Byte 45: lightbar red
 ///   Byte 46: lightbar green
@@ -42,9 +45,18 @@
 ///   Byte 9:  lightbar green
 ///   Byte 10: lightbar blue
 ///   Last 4 bytes: CRC-32 (seed 0xA2)
+///
+/// Switch Pro (USB and BT): Report ID 0x10 (rumble only), 10 bytes
+///   Byte 0:  report ID (0x10)
+///   Byte 1:  packet counter (low nibble)
+///   Bytes 2–9: rumble data, left then right (neutral = no vibration)
+///   The Switch Pro has no lightbar; its player/HOME LEDs and rumble are not
+///   driven yet, so every report is neutral.
 
+use crate::config::{HardwareBrightness, MuteLedMode, TriggerConfig};
 use crate::controller::{ConnectionType, ControllerType};
 use crate::crc32;
+use crate::state::AgentState;
 
 /// Desired output state to send to the controller.
 #[derive(Debug, Clone, Copy, Default)]
@@ -60,6 +72,81 @@ pub struct OutputState {
     pub player_leds: u8,
     /// Mute button LED (DualSense only). 0x00=off, 0x01=on, 0x02=pulse.
     pub mute_led: u8,
+    /// LED brightness byte (DualSense only). See `led_brightness_byte`.
+    pub led_brightness: u8,
+    /// Adaptive trigger effects (DualSense only).
+    pub trigger_left: TriggerEffect,
+    pub trigger_right: TriggerEffect,
+}
+
+impl OutputState {
+    /// Final report on exit: lightbar black, player and mute LEDs off, no
+    /// rumble, triggers released.
+    pub fn all_off() -> Self {
+        Self::default()
+    }
+}
+
+/// Adaptive trigger effect: constant resistance from `start` (0-255 along the
+/// travel) with strength `force`. A force of 0 means no effect (free trigger).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TriggerEffect {
+    pub start: u8,
+    pub force: u8,
+}
+
+/// Trigger effect mode: no resistance.
+const TRIGGER_MODE_OFF: u8 = 0x00;
+/// Trigger effect mode: constant resistance (params: start position, force).
+const TRIGGER_MODE_CONSTANT: u8 = 0x01;
+
+impl TriggerEffect {
+    /// The 11-byte effect block written to the report: mode, then parameters.
+    fn bytes(self) -> [u8; 11] {
+        let mut block = [0u8; 11];
+        if self.force > 0 {
+            block[0] = TRIGGER_MODE_CONSTANT;
+            block[1] = self.start;
+            block[2] = self.force;
+        } else {
+            block[0] = TRIGGER_MODE_OFF;
+        }
+        block
+    }
+}
+
+/// (left, right) trigger effects for the config. Disabled = both free.
+pub fn trigger_effects(cfg: &TriggerConfig) -> (TriggerEffect, TriggerEffect) {
+    if !cfg.enabled {
+        return Default::default();
+    }
+    (
+        TriggerEffect { start: cfg.left_start, force: cfg.left_force },
+        TriggerEffect { start: cfg.right_start, force: cfg.right_force },
+    )
+}
+
+/// DualSense LED brightness byte for a hardware brightness level.
+pub fn led_brightness_byte(level: HardwareBrightness) -> u8 {
+    match level {
+        HardwareBrightness::High => 0x00,
+        HardwareBrightness::Medium => 0x01,
+        HardwareBrightness::Low => 0x02,
+    }
+}
+
+/// Mute LED byte for the configured mode.
+/// Status mode: on while an agent is active (working/error), pulse on done, off when idle.
+pub fn mute_led_value(mode: MuteLedMode, state: AgentState, mic_muted: bool) -> u8 {
+    match mode {
+        MuteLedMode::Mic => mic_muted as u8,
+        MuteLedMode::Status => match state {
+            AgentState::Working | AgentState::Thinking | AgentState::Error => 0x01,
+            AgentState::Done => 0x02,
+            AgentState::Idle => 0x00,
+        },
+        MuteLedMode::Off => 0x00,
+    }
 }
 
 /// Build an output report. Returns the report as a Vec<u8> ready to write via HID.
@@ -82,6 +169,7 @@ pub fn build_report(
         (ControllerType::Ds4V1 | ControllerType::Ds4V2, ConnectionType::Bluetooth) => {
             build_ds4_bt(state)
         }
+        (ControllerType::SwitchPro, _) => build_switch_pro(bt_seq),
     }
 }
 
@@ -95,9 +183,11 @@ fn build_dualsense_usb(state: &OutputState) -> Vec<u8> {
     buf[3] = state.rumble_right;
     buf[4] = state.rumble_left;
     buf[9] = state.mute_led;    // mute button LED: 0x00=off, 0x01=on, 0x02=pulse
+    buf[11..22].copy_from_slice(&state.trigger_right.bytes());
+    buf[22..33].copy_from_slice(&state.trigger_left.bytes());
     buf[39] = 0x02; // valid_flag2: bit 1 = lightbar setup control enable
     buf[42] = 0x02; // lightbar_setup: fade out default blue LED
-    buf[43] = 0x00; // led_brightness: 0x00=High
+    buf[43] = state.led_brightness; // 0x00=high, 0x01=medium, 0x02=low
     buf[44] = state.player_leds;
     buf[45] = state.lightbar_r;
     buf[46] = state.lightbar_g;
@@ -116,9 +206,11 @@ fn build_dualsense_bt(state: &OutputState, _seq: &mut u8) -> Vec<u8> {
     buf[4] = state.rumble_right;
     buf[5] = state.rumble_left;
     buf[10] = state.mute_led;   // mute button LED (BT offset +1 vs USB)
+    buf[12..23].copy_from_slice(&state.trigger_right.bytes());
+    buf[23..34].copy_from_slice(&state.trigger_left.bytes());
     buf[40] = 0x02; // valid_flag2: bit 1 = lightbar setup control enable
     buf[43] = 0x02; // lightbar_setup: fade out default blue LED
-    buf[44] = 0x00; // led_brightness: 0x00=High
+    buf[44] = state.led_brightness; // BT offset +1 vs USB
     buf[45] = state.player_leds;
     buf[46] = state.lightbar_r;
     buf[47] = state.lightbar_g;
@@ -159,6 +251,27 @@ fn build_ds4_bt(state: &OutputState) -> Vec<u8> {
     buf
 }
 
+/// Switch Pro rumble data that leaves both motors still.
+const SWITCH_NEUTRAL_RUMBLE: [u8; 8] = [0x00, 0x01, 0x40, 0x40, 0x00, 0x01, 0x40, 0x40];
+
+/// Switch Pro output report: a neutral rumble-only packet (see module docs).
+fn build_switch_pro(counter: &mut u8) -> Vec<u8> {
+    let mut buf = vec![0x10, *counter & 0x0F];
+    buf.extend_from_slice(&SWITCH_NEUTRAL_RUMBLE);
+    *counter = counter.wrapping_add(1);
+    buf
+}
+
+/// Switch Pro subcommand report (0x01): neutral rumble, then subcommand `id`
+/// and its arguments.
+pub fn switch_pro_subcommand(counter: u8, id: u8, args: &[u8]) -> Vec<u8> {
+    let mut buf = vec![0x01, counter & 0x0F];
+    buf.extend_from_slice(&SWITCH_NEUTRAL_RUMBLE);
+    buf.push(id);
+    buf.extend_from_slice(args);
+    buf
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -173,6 +286,7 @@ mod tests {
             rumble_right: 0,
             player_leds: 0,
             mute_led: 0,
+            ..Default::default()
         };
         let mut seq = 0u8;
         let report = build_report(ControllerType::DualSense, ConnectionType::Usb, &state, &mut seq);
@@ -183,6 +297,61 @@ mod tests {
         assert_eq!(report[47], 0);   // blue
     }
 
+    #[test]
+    fn mute_led_status_mode_follows_working_state() {
+        // Status mode ignores the mic entirely
+        assert_eq!(mute_led_value(MuteLedMode::Status, AgentState::Working, false), 0x01);
+        assert_eq!(mute_led_value(MuteLedMode::Status, AgentState::Idle, true), 0x00);
+        assert_eq!(mute_led_value(MuteLedMode::Status, AgentState::Done, false), 0x02);
+        assert_eq!(mute_led_value(MuteLedMode::Mic, AgentState::Working, true), 0x01);
+        assert_eq!(mute_led_value(MuteLedMode::Mic, AgentState::Working, false), 0x00);
+        assert_eq!(mute_led_value(MuteLedMode::Off, AgentState::Working, true), 0x00);
+
+        // The value lands in the mute LED byte (USB 9, BT 10)
+        let led = mute_led_value(MuteLedMode::Status, AgentState::Working, false);
+        let state = OutputState { mute_led: led, ..Default::default() };
+        let mut seq = 0u8;
+        let usb = build_report(ControllerType::DualSense, ConnectionType::Usb, &state, &mut seq);
+        assert_eq!(usb[9], 0x01);
+        let bt = build_report(ControllerType::DualSense, ConnectionType::Bluetooth, &state, &mut seq);
+        assert_eq!(bt[10], 0x01);
+    }
+
+    #[test]
+    fn hardware_brightness_byte_mapping() {
+        assert_eq!(led_brightness_byte(HardwareBrightness::High), 0x00);
+        assert_eq!(led_brightness_byte(HardwareBrightness::Medium), 0x01);
+        assert_eq!(led_brightness_byte(HardwareBrightness::Low), 0x02);
+
+        // Lands at buf[43] (USB) and buf[44] (BT)
+        let state = OutputState {
+            led_brightness: led_brightness_byte(HardwareBrightness::Low),
+            ..Default::default()
+        };
+        let mut seq = 0u8;
+        let usb = build_report(ControllerType::DualSense, ConnectionType::Usb, &state, &mut seq);
+        assert_eq!(usb[43], 0x02);
+        let bt = build_report(ControllerType::DualSense, ConnectionType::Bluetooth, &state, &mut seq);
+        assert_eq!(bt[44], 0x02);
+    }
+
+    #[test]
+    fn all_off_report_darkens_everything() {
+        let off = OutputState::all_off();
+        let mut seq = 0u8;
+        let usb = build_report(ControllerType::DualSense, ConnectionType::Usb, &off, &mut seq);
+        // The valid flags still apply the lightbar, player and mute LEDs
+        assert_eq!((usb[1], usb[2]), (0x0F, 0x15));
+        assert_eq!(&usb[44..48], &[0, 0, 0, 0]);
+        assert_eq!((usb[3], usb[4], usb[9]), (0, 0, 0));
+        assert_eq!(usb[11], TRIGGER_MODE_OFF);
+        assert_eq!(usb[22], TRIGGER_MODE_OFF);
+        let bt = build_report(ControllerType::DualSense, ConnectionType::Bluetooth, &off, &mut seq);
+        assert_eq!(&bt[45..49], &[0, 0, 0, 0]);
+        let ds4 = build_report(ControllerType::Ds4V2, ConnectionType::Usb, &off, &mut seq);
+        assert_eq!(&ds4[4..9], &[0, 0, 0, 0, 0]);
+    }
+
     #[test]
     fn dualsense_player_leds_byte_position() {
         // Center dot + instant mode (0x24) must land at buf[44] (USB) and buf[45] (BT).
@@ -194,6 +363,41 @@ mod tests {
         assert_eq!(bt[45], 0x24);
     }
 
+    #[test]
+    fn dualsense_trigger_effect_bytes_and_flags() {
+        let state = OutputState {
+            trigger_left: TriggerEffect { start: 0x40, force: 0xA0 },
+            trigger_right: TriggerEffect { start: 0x20, force: 0xFF },
+            ..Default::default()
+        };
+        let mut seq = 0u8;
+        let usb = build_report(ControllerType::DualSense, ConnectionType::Usb, &state, &mut seq);
+        assert_eq!(usb[1] & 0x0C, 0x0C); // right + left trigger valid
+        assert_eq!(&usb[11..14], &[0x01, 0x20, 0xFF]); // right: constant, start, force
+        assert_eq!(&usb[22..25], &[0x01, 0x40, 0xA0]); // left
+        assert!(usb[14..22].iter().all(|&b| b == 0));
+
+        let bt = build_report(ControllerType::DualSense, ConnectionType::Bluetooth, &state, &mut seq);
+        assert_eq!(bt[2] & 0x0C, 0x0C);
+        assert_eq!(&bt[12..15], &[0x01, 0x20, 0xFF]);
+        assert_eq!(&bt[23..26], &[0x01, 0x40, 0xA0]);
+        assert!(crc32::validate(crc32::SEED_OUTPUT, &bt));
+
+        // No force → mode off, trigger free
+        let usb = build_report(ControllerType::DualSense, ConnectionType::Usb, &OutputState::default(), &mut seq);
+        assert!(usb[11..33].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn trigger_effects_follow_enabled_flag() {
+        let cfg = TriggerConfig { enabled: true, left_start: 10, left_force: 20, right_start: 30, right_force: 40 };
+        let (left, right) = trigger_effects(&cfg);
+        assert_eq!(left, TriggerEffect { start: 10, force: 20 });
+        assert_eq!(right, TriggerEffect { start: 30, force: 40 });
+        let off = trigger_effects(&TriggerConfig { enabled: false, ..cfg });
+        assert_eq!(off, (TriggerEffect::default(), TriggerEffect::default()));
+    }
+
     #[test]
     fn dualsense_bt_report_size_and_crc() {
         let state = OutputState::default();
@@ -215,6 +419,7 @@ mod tests {
             rumble_right: 64,
             player_leds: 0,
             mute_led: 0,
+            ..Default::default()
         };
         let mut seq = 0u8;
         let report = build_report(ControllerType::Ds4V2, ConnectionType::Usb, &state, &mut seq);
@@ -245,4 +450,20 @@ mod tests {
         assert_eq!(r1[1], 0x02);
         assert_eq!(r2[1], 0x02);
     }
+    #[test]
+    fn switch_pro_report_is_neutral_rumble_with_counter() {
+        let state = OutputState { rumble_left: 255, lightbar_r: 255, ..Default::default() };
+        let mut seq = 15u8;
+        let r1 = build_report(ControllerType::SwitchPro, ConnectionType::Bluetooth, &state, &mut seq);
+        let r2 = build_report(ControllerType::SwitchPro, ConnectionType::Usb, &state, &mut seq);
+        assert_eq!(r1[..2], [0x10, 0x0F]);
+        assert_eq!(r1[2..], SWITCH_NEUTRAL_RUMBLE);
+        // Counter wraps within the low nibble
+        assert_eq!(r2[1], 0x00);
+
+        let sub = switch_pro_subcommand(3, 0x03, &[0x30]);
+        assert_eq!(sub.len(), 12);
+        assert_eq!(sub[..2], [0x01, 0x03]);
+        assert_eq!(sub[10..], [0x03, 0x30]);
+    }
 }
@@ -0,0 +1,248 @@
+/// Named-pipe state input: hooks push `<agent_id> <state>` lines to
+/// `\\.\pipe\ds4cc` instead of writing one file per agent.
+///
+/// Pushed states live in memory, keyed by agent id, and follow the same rules
+/// as state files: idle drops the agent, "working" goes stale after
+/// `stale_timeout_s`, and done entries are cleared on auto-idle/acknowledge.
+/// The state poller merges them into its file scan (`[pipe] enabled`).
+
+use crate::state::AgentState;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Pipe the server listens on.
+pub const PIPE_NAME: &str = r"\\.\pipe\ds4cc";
+
+/// Parse one `<agent_id> <state>` message. Ids can't contain whitespace.
+pub fn parse_message(line: &str) -> Option<(&str, AgentState)> {
+    let mut parts = line.split_whitespace();
+    let (id, state) = (parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((id, AgentState::parse(state)?))
+}
+
+/// Agent states pushed over the pipe, shared between the server and the poller.
+#[derive(Clone, Default)]
+pub struct PipeStates(Arc<Mutex<HashMap<String, (AgentState, Instant)>>>);
+
+impl PipeStates {
+    /// Apply one message. Returns false (and changes nothing) if it's malformed.
+    pub fn apply(&self, line: &str) -> bool {
+        let Some((id, state)) = parse_message(line) else {
+            return false;
+        };
+        let mut agents = self.0.lock().unwrap();
+        if state == AgentState::Idle {
+            agents.remove(id);
+        } else {
+            agents.insert(id.to_string(), (state, Instant::now()));
+        }
+        true
+    }
+
+    /// Current agent states, dropping working/thinking entries older than `stale_timeout`.
+    pub fn snapshot(&self, stale_timeout: Duration) -> HashMap<String, AgentState> {
+        let mut agents = self.0.lock().unwrap();
+        agents.retain(|id, (state, at)| {
+            let stale = state.is_busy() && at.elapsed() > stale_timeout;
+            if stale {
+                log::debug!("Ignoring stale pipe agent: {id} ({}s old)", at.elapsed().as_secs());
+            }
+            !stale
+        });
+        agents.iter().map(|(id, (state, _))| (id.clone(), *state)).collect()
+    }
+
+    /// Forget all done agents (the in-memory counterpart of `clean_done_files`).
+    pub fn clear_done(&self) {
+        self.0.lock().unwrap().retain(|_, (state, _)| *state != AgentState::Done);
+    }
+}
+
+/// Serve `name` on background threads, applying every received line to
+/// `states` and calling `on_change` after each message that parsed. Each
+/// client gets its own thread, so a hook holding the pipe open blocks no one.
+#[cfg(windows)]
+pub fn spawn_server(
+    name: &str,
+    states: PipeStates,
+    on_change: impl Fn() + Send + Sync + 'static,
+) -> std::io::Result<()> {
+    let name_w: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let first = create_instance(&name_w)?;
+    let on_change = Arc::new(on_change);
+
+    std::thread::spawn(move || {
+        let mut next = Some(first);
+        loop {
+            let instance = match next.take() {
+                Some(h) => h,
+                None => match create_instance(&name_w) {
+                    Ok(h) => h,
+                    Err(e) => {
+                        log::warn!("State pipe stopped ({e})");
+                        return;
+                    }
+                },
+            };
+            if let Err(e) = wait_for_client(instance) {
+                log::debug!("State pipe connect failed: {e}");
+                continue;
+            }
+            let (states, on_change) = (states.clone(), on_change.clone());
+            std::thread::spawn(move || serve_client(instance, &states, &*on_change));
+        }
+    });
+    Ok(())
+}
+
+/// Raw pipe handle as an integer, so it can cross into a client thread.
+#[cfg(windows)]
+type PipeHandle = usize;
+
+/// Create one server instance of the pipe (NUL-terminated UTF-16 name).
+#[cfg(windows)]
+fn create_instance(name_w: &[u16]) -> std::io::Result<PipeHandle> {
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_INBOUND;
+    use windows_sys::Win32::System::Pipes::{
+        CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE,
+        PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    };
+
+    let handle = unsafe {
+        CreateNamedPipeW(
+            name_w.as_ptr(),
+            PIPE_ACCESS_INBOUND,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            0,
+            4096,
+            0,
+            std::ptr::null(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(handle as PipeHandle)
+}
+
+/// Block until a client connects to `instance`. Closes it on failure.
+#[cfg(windows)]
+fn wait_for_client(instance: PipeHandle) -> std::io::Result<()> {
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_PIPE_CONNECTED, HANDLE};
+    use windows_sys::Win32::System::Pipes::ConnectNamedPipe;
+
+    let handle = instance as HANDLE;
+    if unsafe { ConnectNamedPipe(handle, std::ptr::null_mut()) } != 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    // The client connected between create and connect — still fine
+    if err.raw_os_error() == Some(ERROR_PIPE_CONNECTED as i32) {
+        return Ok(());
+    }
+    unsafe { CloseHandle(handle) };
+    Err(err)
+}
+
+/// Read newline-delimited messages until the client disconnects.
+#[cfg(windows)]
+fn serve_client(instance: PipeHandle, states: &PipeStates, on_change: &dyn Fn()) {
+    use std::io::BufRead;
+    use std::os::windows::io::FromRawHandle;
+
+    // The File owns the handle from here and closes it on drop
+    let pipe = unsafe { std::fs::File::from_raw_handle(instance as _) };
+    for line in std::io::BufReader::new(pipe).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if states.apply(&line) {
+            on_change();
+        } else {
+            log::debug!("Ignoring malformed pipe message: {line:?}");
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn spawn_server(
+    _name: &str,
+    _states: PipeStates,
+    _on_change: impl Fn() + Send + Sync + 'static,
+) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "named pipes need Windows"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STALE: Duration = Duration::from_secs(600);
+
+    #[test]
+    fn parse_message_requires_id_and_state() {
+        assert_eq!(parse_message("sess1 working"), Some(("sess1", AgentState::Working)));
+        assert_eq!(parse_message("  sess1   DONE \r"), Some(("sess1", AgentState::Done)));
+        assert_eq!(parse_message("sess1"), None);
+        assert_eq!(parse_message("sess1 sleeping"), None);
+        assert_eq!(parse_message("sess 1 working"), None);
+    }
+
+    #[test]
+    fn idle_drops_agent_and_clear_done_keeps_working() {
+        let states = PipeStates::default();
+        assert!(states.apply("a working"));
+        assert!(states.apply("b done"));
+        assert!(!states.apply("garbage"));
+        assert_eq!(states.snapshot(STALE).len(), 2);
+
+        states.clear_done();
+        assert_eq!(states.snapshot(STALE), HashMap::from([("a".into(), AgentState::Working)]));
+
+        assert!(states.apply("a idle"));
+        assert!(states.snapshot(STALE).is_empty());
+    }
+
+    #[test]
+    fn stale_busy_entries_are_dropped() {
+        let states = PipeStates::default();
+        states.apply("a working");
+        states.apply("b error");
+        states.apply("c thinking");
+        let snapshot = states.snapshot(Duration::ZERO);
+        assert_eq!(snapshot, HashMap::from([("b".into(), AgentState::Error)]));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn pipe_message_reaches_aggregation() {
+        use crate::state::merge_agent_states;
+        use std::io::Write;
+        use std::sync::mpsc;
+
+        let name = format!(r"\\.\pipe\ds4cc_test_{}", std::process::id());
+        let states = PipeStates::default();
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        spawn_server(&name, states.clone(), move || {
+            let _ = tx.lock().unwrap().send(());
+        })
+        .unwrap();
+
+        let mut client = std::fs::OpenOptions::new().write(true).open(&name).unwrap();
+        client.write_all(b"sess1 working\n").unwrap();
+        rx.recv_timeout(Duration::from_secs(2)).unwrap();
+
+        let (aggregated, agents) =
+            merge_agent_states((AgentState::Idle, HashMap::new()), states.snapshot(STALE));
+        assert_eq!(aggregated, AgentState::Working);
+        assert_eq!(agents.get("sess1"), Some(&AgentState::Working));
+    }
+}
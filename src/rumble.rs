@@ -1,10 +1,20 @@
 /// Rumble engine: fires haptic patterns on state transitions and time thresholds.
 ///
 /// Working → Done (>= 5 min):  two short pulses (notification feel)
-/// Idle > 3 min:                single strong pulse (attention reminder)
+/// Idle > 3 min:                single strong pulse (attention reminder); two
+///                              pulses at 2× the threshold, three longer ones at 3×
+/// All clear (parallel run settled): rising triple pulse + long tail
+/// Profile switch (PS):         two brief, light bumps
+/// Low battery:                 three quick medium pulses
 /// Error:                       no rumble — agent keeps resolving, not worth alarming
+///                              (`[rumble] on_error` opts into a short low buzz)
+///
+/// `[rumble] enabled` / `intensity` apply to every pattern (see `RumbleLevel`),
+/// and `[rumble.patterns]` can replace any built-in pattern (see `RumblePatterns`).
 
+use crate::config::RumbleStepConfig;
 use crate::state::AgentState;
+use std::collections::{BTreeMap, HashMap};
 use tokio::time::{sleep, Duration};
 
 /// A rumble command: intensity (0-255) for left and right motors, plus duration.
@@ -15,6 +25,34 @@ pub struct RumbleStep {
     pub duration_ms: u64,
 }
 
+/// Global rumble settings applied to every pattern before it plays.
+#[derive(Debug, Clone, Copy)]
+pub struct RumbleLevel {
+    enabled: bool,
+    intensity: f32,
+}
+
+impl RumbleLevel {
+    pub fn new(enabled: bool, intensity: f32) -> Self {
+        Self { enabled, intensity: intensity.clamp(0.0, 1.0) }
+    }
+
+    /// `pattern` with its motor values scaled by the intensity, or None if
+    /// rumble is off (disabled or zero intensity).
+    pub fn apply(self, pattern: &[RumbleStep]) -> Option<Vec<RumbleStep>> {
+        if !self.enabled || self.intensity == 0.0 {
+            return None;
+        }
+        let scale = |v: u8| (v as f32 * self.intensity).round() as u8;
+        Some(
+            pattern
+                .iter()
+                .map(|step| RumbleStep { left: scale(step.left), right: scale(step.right), ..*step })
+                .collect(),
+        )
+    }
+}
+
 /// Determine the rumble pattern for a state transition.
 /// Returns None if no rumble should fire.
 pub fn pattern_for_transition(from: AgentState, to: AgentState) -> Option<Vec<RumbleStep>> {
@@ -33,6 +71,152 @@ pub fn idle_reminder_pattern() -> Vec<RumbleStep> {
     vec![RumbleStep { left: 255, right: 255, duration_ms: 300 }]
 }
 
+/// Second idle reminder (still idle at 2× the threshold): two strong pulses.
+pub fn idle_reminder_2_pattern() -> Vec<RumbleStep> {
+    vec![
+        RumbleStep { left: 255, right: 255, duration_ms: 300 },
+        RumbleStep { left: 0, right: 0, duration_ms: 150 },
+        RumbleStep { left: 255, right: 255, duration_ms: 300 },
+    ]
+}
+
+/// Last idle reminder (still idle at 3× the threshold): three long pulses.
+pub fn idle_reminder_3_pattern() -> Vec<RumbleStep> {
+    vec![
+        RumbleStep { left: 255, right: 255, duration_ms: 450 },
+        RumbleStep { left: 0, right: 0, duration_ms: 150 },
+        RumbleStep { left: 255, right: 255, duration_ms: 450 },
+        RumbleStep { left: 0, right: 0, duration_ms: 150 },
+        RumbleStep { left: 255, right: 255, duration_ms: 450 },
+    ]
+}
+
+/// Rumble pattern for the all-clear signal (every agent of a parallel run settled).
+/// Rising intensity so it's distinguishable from a single agent's done pulse.
+pub fn all_clear_pattern() -> Vec<RumbleStep> {
+    vec![
+        RumbleStep { left: 90, right: 90, duration_ms: 90 },
+        RumbleStep { left: 0, right: 0, duration_ms: 70 },
+        RumbleStep { left: 150, right: 150, duration_ms: 90 },
+        RumbleStep { left: 0, right: 0, duration_ms: 70 },
+        RumbleStep { left: 220, right: 220, duration_ms: 90 },
+        RumbleStep { left: 0, right: 0, duration_ms: 90 },
+        RumbleStep { left: 255, right: 255, duration_ms: 350 },
+    ]
+}
+
+/// Rumble pattern confirming a profile switch: two light 40ms bumps, well
+/// below the done pulse so it never reads as an agent notification.
+pub fn profile_switch_pattern() -> Vec<RumbleStep> {
+    vec![
+        RumbleStep { left: 70, right: 70, duration_ms: 40 },
+        RumbleStep { left: 0, right: 0, duration_ms: 60 }, // pause
+        RumbleStep { left: 70, right: 70, duration_ms: 40 },
+    ]
+}
+
+/// Rumble pattern for the low-battery warning: three quick, even pulses —
+/// unlike any agent notification.
+pub fn low_battery_pattern() -> Vec<RumbleStep> {
+    vec![
+        RumbleStep { left: 140, right: 140, duration_ms: 80 },
+        RumbleStep { left: 0, right: 0, duration_ms: 80 },
+        RumbleStep { left: 140, right: 140, duration_ms: 80 },
+        RumbleStep { left: 0, right: 0, duration_ms: 80 },
+        RumbleStep { left: 140, right: 140, duration_ms: 80 },
+    ]
+}
+
+/// Pattern names accepted in `[rumble.patterns]`.
+const PATTERN_NAMES: &[&str] = &[
+    "working_done",
+    "idle_reminder",
+    "idle_reminder_2",
+    "idle_reminder_3",
+    "all_clear",
+    "profile_switch",
+    "low_battery",
+    "error",
+];
+
+/// Built-in patterns, each replaceable from `[rumble.patterns]`.
+#[derive(Debug, Clone, Default)]
+pub struct RumblePatterns {
+    overrides: HashMap<String, Vec<RumbleStep>>,
+}
+
+impl RumblePatterns {
+    /// Take the config-defined patterns, warning about (and ignoring) unknown
+    /// names and empty patterns.
+    pub fn from_config(table: &BTreeMap<String, Vec<RumbleStepConfig>>) -> Self {
+        let mut overrides = HashMap::new();
+        for (name, steps) in table {
+            if !PATTERN_NAMES.contains(&name.as_str()) {
+                log::warn!("Unknown rumble pattern \"{name}\" (expected one of {PATTERN_NAMES:?})");
+                continue;
+            }
+            if steps.is_empty() {
+                log::warn!("Rumble pattern \"{name}\" has no steps; using the built-in one");
+                continue;
+            }
+            let steps = steps
+                .iter()
+                .map(|s| RumbleStep { left: s.left, right: s.right, duration_ms: s.ms })
+                .collect();
+            overrides.insert(name.clone(), steps);
+        }
+        Self { overrides }
+    }
+
+    fn get(&self, name: &str, builtin: fn() -> Vec<RumbleStep>) -> Vec<RumbleStep> {
+        self.overrides.get(name).cloned().unwrap_or_else(builtin)
+    }
+
+    /// Like the free `pattern_for_transition`, preferring a configured pattern.
+    pub fn pattern_for_transition(&self, from: AgentState, to: AgentState) -> Option<Vec<RumbleStep>> {
+        match (from, to) {
+            (AgentState::Working, AgentState::Done) => self
+                .overrides
+                .get("working_done")
+                .cloned()
+                .or_else(|| pattern_for_transition(from, to)),
+            _ => pattern_for_transition(from, to),
+        }
+    }
+
+    /// The idle reminder pattern for `level` (1 = first reminder); levels
+    /// past the last use the strongest pattern.
+    pub fn idle_reminder(&self, level: u8) -> Vec<RumbleStep> {
+        match level {
+            0 | 1 => self.get("idle_reminder", idle_reminder_pattern),
+            2 => self.get("idle_reminder_2", idle_reminder_2_pattern),
+            _ => self.get("idle_reminder_3", idle_reminder_3_pattern),
+        }
+    }
+
+    pub fn all_clear(&self) -> Vec<RumbleStep> {
+        self.get("all_clear", all_clear_pattern)
+    }
+
+    pub fn profile_switch(&self) -> Vec<RumbleStep> {
+        self.get("profile_switch", profile_switch_pattern)
+    }
+
+    pub fn low_battery(&self) -> Vec<RumbleStep> {
+        self.get("low_battery", low_battery_pattern)
+    }
+
+    pub fn error(&self) -> Vec<RumbleStep> {
+        self.get("error", error_pattern)
+    }
+}
+
+/// Rumble pattern for an agent entering Error (`[rumble] on_error`): one low,
+/// heavy buzz on the left motor — unlike the done double-tap.
+pub fn error_pattern() -> Vec<RumbleStep> {
+    vec![RumbleStep { left: 255, right: 60, duration_ms: 250 }]
+}
+
 /// Execute a rumble pattern by calling `set_rumble` for each step.
 /// `set_rumble` receives (left_intensity, right_intensity) and should write
 /// the output report to the controller.
@@ -67,6 +251,86 @@ mod tests {
         assert!(pattern_for_transition(AgentState::Working, AgentState::Error).is_none());
     }
 
+    #[test]
+    fn profile_switch_is_two_short_light_bumps() {
+        let steps = profile_switch_pattern();
+        assert_eq!(steps.len(), 3); // bump, pause, bump
+        assert_eq!((steps[1].left, steps[1].right), (0, 0));
+        let done = pattern_for_transition(AgentState::Working, AgentState::Done).unwrap();
+        for bump in [steps[0], steps[2]] {
+            assert_eq!(bump.duration_ms, 40);
+            // Subtler than the done notification
+            assert!(bump.left < done[0].left && bump.right < done[0].right);
+        }
+    }
+
+    #[test]
+    fn error_pattern_is_distinct_from_done() {
+        let error = error_pattern();
+        let done = pattern_for_transition(AgentState::Working, AgentState::Done).unwrap();
+        assert_eq!(error.len(), 1);
+        assert!(error[0].left > error[0].right);
+        assert_ne!(error.len(), done.len());
+    }
+
+    #[test]
+    fn low_battery_is_three_pulses() {
+        let steps = low_battery_pattern();
+        let pulses = steps.iter().filter(|s| s.left > 0).count();
+        assert_eq!(pulses, 3);
+        assert_eq!(steps.len(), 5);
+    }
+
+    #[test]
+    fn idle_reminders_get_stronger() {
+        let patterns = RumblePatterns::default();
+        let buzz = |steps: &[RumbleStep]| -> u64 { steps.iter().filter(|s| s.left > 0).map(|s| s.duration_ms).sum() };
+        let levels: Vec<u64> = (1..=3).map(|level| buzz(&patterns.idle_reminder(level))).collect();
+        assert!(levels[0] < levels[1] && levels[1] < levels[2], "{levels:?}");
+        assert_eq!(buzz(&patterns.idle_reminder(9)), levels[2]);
+    }
+
+    #[test]
+    fn level_scales_or_silences_patterns() {
+        let pattern = idle_reminder_pattern();
+        let half = RumbleLevel::new(true, 0.5).apply(&pattern).unwrap();
+        assert_eq!((half[0].left, half[0].right), (128, 128));
+        assert_eq!(half[0].duration_ms, pattern[0].duration_ms);
+        let done = pattern_for_transition(AgentState::Working, AgentState::Done).unwrap();
+        let halved = RumbleLevel::new(true, 0.5).apply(&done).unwrap();
+        assert_eq!(halved.iter().map(|s| s.left).collect::<Vec<_>>(), vec![90, 0, 90]);
+
+        assert!(RumbleLevel::new(true, 0.0).apply(&pattern).is_none());
+        assert!(RumbleLevel::new(false, 1.0).apply(&pattern).is_none());
+        // Full intensity leaves patterns untouched
+        let full = RumbleLevel::new(true, 1.0).apply(&pattern).unwrap();
+        assert_eq!((full[0].left, full[0].right), (255, 255));
+    }
+
+    #[test]
+    fn configured_patterns_override_builtins() {
+        let table = BTreeMap::from([
+            (
+                "working_done".to_string(),
+                vec![
+                    RumbleStepConfig { left: 50, right: 60, ms: 30 },
+                    RumbleStepConfig { left: 0, right: 0, ms: 20 },
+                ],
+            ),
+            ("bogus".to_string(), vec![RumbleStepConfig { left: 1, right: 1, ms: 1 }]),
+            ("idle_reminder".to_string(), vec![]),
+        ]);
+        let patterns = RumblePatterns::from_config(&table);
+
+        let done = patterns.pattern_for_transition(AgentState::Working, AgentState::Done).unwrap();
+        let steps: Vec<_> = done.iter().map(|s| (s.left, s.right, s.duration_ms)).collect();
+        assert_eq!(steps, vec![(50, 60, 30), (0, 0, 20)]);
+        // Other transitions stay silent; empty or unknown entries fall back
+        assert!(patterns.pattern_for_transition(AgentState::Idle, AgentState::Working).is_none());
+        assert_eq!(patterns.idle_reminder(1)[0].duration_ms, idle_reminder_pattern()[0].duration_ms);
+        assert_eq!(patterns.all_clear().len(), all_clear_pattern().len());
+    }
+
     #[test]
     fn idle_to_working_no_rumble() {
         assert!(pattern_for_transition(AgentState::Idle, AgentState::Working).is_none());
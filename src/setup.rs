@@ -1,4 +1,5 @@
-/// First-run setup: auto-install Claude Code hooks and OpenCode plugin via WSL.
+/// First-run setup: auto-install Claude Code hooks, the OpenCode plugin and
+/// the Aider wrapper via WSL.
 ///
 /// Hook scripts are compiled into the binary with `include_str!`.  On first
 /// run (and after a version bump) they are written to the correct WSL paths
@@ -20,9 +21,12 @@ const HOOK_SH: &str = include_str!("../hooks/ds4cc-state.sh");
 /// OpenCode plugin (JavaScript).
 const OPENCODE_JS: &str = include_str!("../hooks/opencode/ds4cc-opencode.js");
 
+/// Aider wrapper script (bash).
+const AIDER_SH: &str = include_str!("../hooks/aider/ds4cc-aider.sh");
+
 /// Bump this suffix to force a reinstall on the next launch after an update.
 /// In practice this just needs to change whenever the hook content changes.
-const HOOKS_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "-r4");
+const HOOKS_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "-r5");
 
 // ── Python one-liner for merging settings.json ──────────────────────────────
 //
@@ -50,6 +54,7 @@ const MERGE_SETTINGS_PY: &str = concat!(
 pub struct SetupResult {
     pub claude_code: bool,
     pub opencode: bool,
+    pub aider: bool,
 }
 
 /// Run hook setup.
@@ -80,10 +85,11 @@ pub fn run() -> Option<SetupResult> {
 
     let claude_code = install_claude_code_hook();
     let opencode = install_opencode_plugin();
+    let aider = install_aider_hook();
 
     stamp();
 
-    Some(SetupResult { claude_code, opencode })
+    Some(SetupResult { claude_code, opencode, aider })
 }
 
 // ── Claude Code ──────────────────────────────────────────────────────────────
@@ -240,6 +246,30 @@ fn install_opencode_plugin() -> bool {
     true
 }
 
+// ── Aider ────────────────────────────────────────────────────────────────────
+
+fn install_aider_hook() -> bool {
+    // Only install if Aider is on the WSL PATH
+    let detected = wsl::run_wsl("command -v aider >/dev/null 2>&1 && echo yes || echo no")
+        .map(|s| s.trim() == "yes")
+        .unwrap_or(false);
+
+    if !detected {
+        return false;
+    }
+
+    let aider_sh = AIDER_SH.replace("\r\n", "\n");
+    if !wsl::wsl_write("~/.local/bin/ds4cc-aider", &aider_sh) {
+        log::warn!("setup: failed to write ds4cc-aider to WSL");
+        return false;
+    }
+    wsl::run_wsl("chmod +x ~/.local/bin/ds4cc-aider");
+
+    log::info!("setup: Aider wrapper installed → ~/.local/bin/ds4cc-aider");
+    log::info!("setup: run `ds4cc-aider` instead of `aider` to track its state");
+    true
+}
+
 // ── Version stamp ─────────────────────────────────────────────────────────────
 
 fn stamp_path() -> Option<std::path::PathBuf> {
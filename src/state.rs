@@ -1,20 +1,29 @@
 /// State poller: scans per-agent state files and aggregates into a single state.
 ///
 /// Each Claude Code session writes its own file: `ds4cc_agent_<session_id>`
-/// containing a single word: idle | working | done | error
+/// containing a single word: idle | working | thinking | done | error — or a JSON object
+/// `{ "state": "working", "tool": "Bash", "started": 1700000000 }` carrying
+/// optional metadata (logged on per-agent transitions).
 ///
 /// The poller scans all matching files and applies priority:
-///   working > error > done > idle
+///   working > thinking > error > done > idle
 ///
 /// "working" files older than `stale_timeout_s` are ignored (crashed sessions).
 /// After `idle_timeout_s` in done, auto-transitions to idle.
 /// Error mirrors Working visually (same blue pulse, no rumble) — agent is still active,
-/// self-recovering silently. Working still takes priority over Error in aggregation.
+/// self-recovering silently — unless `[rumble] on_error` asks for an alert on each
+/// agent's entry into Error. Working still takes priority over Error in aggregation.
+/// Thinking (extended reasoning, e.g. Codex `agent_reasoning`) is part of the same
+/// working stretch: it has its own lightbar color, ranks just below Working (an
+/// agent running tools is the more useful signal) and above Error.
 
-use std::collections::{HashMap, HashSet};
+use crate::notify::{ToastKind, ToastSettings, Toaster};
+use crate::pipe::PipeStates;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration as StdDuration, Instant, SystemTime};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 use tokio::time::{interval, Duration};
 
 /// Agent states that map to lightbar colors.
@@ -22,16 +31,36 @@ use tokio::time::{interval, Duration};
 pub enum AgentState {
     Idle,
     Working,
+    Thinking,
     Done,
     Error,
 }
 
 impl AgentState {
-    /// Parse from the state file content. Trims whitespace, case-insensitive.
+    /// Parse from the state file content: a bare word or a JSON object (see
+    /// `parse_with_meta`). Trims whitespace, case-insensitive.
     pub fn parse(s: &str) -> Option<Self> {
+        Self::parse_with_meta(s).map(|(state, _)| state)
+    }
+
+    /// Parse state file content that is either a bare word (`working`) or a
+    /// JSON object with optional metadata:
+    /// `{ "state": "working", "tool": "Bash", "started": 1700000000 }`.
+    pub fn parse_with_meta(s: &str) -> Option<(Self, AgentMeta)> {
+        let s = s.trim();
+        if !s.starts_with('{') {
+            return Some((Self::parse_word(s)?, AgentMeta::default()));
+        }
+        let record: StateRecord = serde_json::from_str(s).ok()?;
+        let state = Self::parse_word(&record.state)?;
+        Some((state, AgentMeta { tool: record.tool, started: record.started }))
+    }
+
+    fn parse_word(s: &str) -> Option<Self> {
         match s.trim().to_ascii_lowercase().as_str() {
             "idle" => Some(AgentState::Idle),
             "working" => Some(AgentState::Working),
+            "thinking" => Some(AgentState::Thinking),
             "done" => Some(AgentState::Done),
             "error" => Some(AgentState::Error),
             _ => None,
@@ -44,9 +73,15 @@ impl AgentState {
             AgentState::Idle => 0,
             AgentState::Done => 1,
             AgentState::Error => 2,
-            AgentState::Working => 3,
+            AgentState::Thinking => 3,
+            AgentState::Working => 4,
         }
     }
+
+    /// Working or Thinking: the agent is mid-task.
+    pub fn is_busy(self) -> bool {
+        matches!(self, AgentState::Working | AgentState::Thinking)
+    }
 }
 
 impl std::fmt::Display for AgentState {
@@ -54,35 +89,128 @@ impl std::fmt::Display for AgentState {
         match self {
             AgentState::Idle => f.write_str("idle"),
             AgentState::Working => f.write_str("working"),
+            AgentState::Thinking => f.write_str("thinking"),
             AgentState::Done => f.write_str("done"),
             AgentState::Error => f.write_str("error"),
         }
     }
 }
 
+/// Optional details from a JSON state file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentMeta {
+    /// Tool the agent is running (e.g. "Bash").
+    pub tool: Option<String>,
+    /// Unix time (seconds) the current state began.
+    pub started: Option<u64>,
+}
+
+impl std::fmt::Display for AgentMeta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(tool) = &self.tool {
+            write!(f, " tool={tool}")?;
+        }
+        if let Some(started) = self.started {
+            write!(f, " started={started}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How many agents are in each non-idle state (idle agents drop their files).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCounts {
+    pub working: usize,
+    pub thinking: usize,
+    pub done: usize,
+    pub error: usize,
+}
+
+impl StateCounts {
+    pub fn from_agents(agents: &HashMap<String, AgentState>) -> Self {
+        let mut counts = Self::default();
+        for state in agents.values() {
+            match state {
+                AgentState::Idle => {}
+                AgentState::Working => counts.working += 1,
+                AgentState::Thinking => counts.thinking += 1,
+                AgentState::Done => counts.done += 1,
+                AgentState::Error => counts.error += 1,
+            }
+        }
+        counts
+    }
+
+    /// (state, count) pairs for every counted state.
+    pub fn iter(&self) -> [(AgentState, usize); 4] {
+        [
+            (AgentState::Working, self.working),
+            (AgentState::Thinking, self.thinking),
+            (AgentState::Done, self.done),
+            (AgentState::Error, self.error),
+        ]
+    }
+
+    pub fn total(&self) -> usize {
+        self.working + self.thinking + self.done + self.error
+    }
+}
+
+/// What the poller publishes on its watch channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentSummary {
+    /// Highest-priority state across all agents.
+    pub state: AgentState,
+    pub counts: StateCounts,
+}
+
+impl Default for AgentSummary {
+    fn default() -> Self {
+        Self { state: AgentState::Idle, counts: StateCounts::default() }
+    }
+}
+
+/// JSON form of a state file.
+#[derive(serde::Deserialize)]
+struct StateRecord {
+    state: String,
+    tool: Option<String>,
+    started: Option<u64>,
+}
+
+/// File name prefix of per-agent state files (and their sidecars).
+const AGENT_FILE_PREFIX: &str = "ds4cc_agent_";
+
+/// Sidecar files written next to an agent's state file (not agent states themselves).
+fn is_sidecar(name: &str) -> bool {
+    name.ends_with("_start") || name.ends_with("_tokens")
+}
+
 /// Scan all `ds4cc_agent_*` files in the state directory.
 /// Returns the aggregated state and a map of agent_id → state for per-agent tracking.
 /// Ignores "working" files older than `stale_timeout`.
+/// Also returns metadata for agents whose file is in the JSON form.
 fn scan_agent_states(
     state_dir: &PathBuf,
     stale_timeout: StdDuration,
-) -> (AgentState, HashMap<String, AgentState>) {
+) -> (AgentState, HashMap<String, AgentState>, HashMap<String, AgentMeta>) {
     let pattern = "ds4cc_agent_";
     let now = SystemTime::now();
     let mut best = AgentState::Idle;
     let mut agents = HashMap::new();
+    let mut meta = HashMap::new();
 
     let entries = match std::fs::read_dir(state_dir) {
         Ok(e) => e,
-        Err(_) => return (AgentState::Idle, agents),
+        Err(_) => return (AgentState::Idle, agents, meta),
     };
 
     for entry in entries.flatten() {
         let name = entry.file_name();
         let name_str = name.to_string_lossy();
 
-        // Only match agent files, skip timestamp files (*_start)
-        if !name_str.starts_with(pattern) || name_str.ends_with("_start") {
+        // Only match agent files, skip sidecars (*_start, *_tokens)
+        if !name_str.starts_with(pattern) || is_sidecar(&name_str) {
             continue;
         }
 
@@ -94,13 +222,13 @@ fn scan_agent_states(
             Err(_) => continue,
         };
 
-        let state = match AgentState::parse(&contents) {
+        let (state, agent_meta) = match AgentState::parse_with_meta(&contents) {
             Some(s) => s,
             None => continue,
         };
 
-        // Check staleness for "working" state — ignore crashed sessions
-        if state == AgentState::Working {
+        // Check staleness for "working"/"thinking" state — ignore crashed sessions
+        if state.is_busy() {
             if let Ok(metadata) = std::fs::metadata(&path) {
                 if let Ok(modified) = metadata.modified() {
                     if let Ok(age) = now.duration_since(modified) {
@@ -121,6 +249,9 @@ fn scan_agent_states(
             continue;
         }
 
+        if agent_meta != AgentMeta::default() {
+            meta.insert(agent_id.clone(), agent_meta);
+        }
         agents.insert(agent_id, state);
 
         if state.priority() > best.priority() {
@@ -128,7 +259,7 @@ fn scan_agent_states(
         }
     }
 
-    (best, agents)
+    (best, agents, meta)
 }
 
 /// Remove all "done" agent files from disk so they don't re-trigger after auto-idle.
@@ -140,7 +271,7 @@ fn clean_done_files(state_dir: &PathBuf) {
     for entry in entries.flatten() {
         let name = entry.file_name();
         let name_str = name.to_string_lossy();
-        if !name_str.starts_with("ds4cc_agent_") || name_str.ends_with("_start") {
+        if !name_str.starts_with("ds4cc_agent_") || is_sidecar(&name_str) {
             continue;
         }
         let contents = match std::fs::read_to_string(entry.path()) {
@@ -149,77 +280,312 @@ fn clean_done_files(state_dir: &PathBuf) {
         };
         if AgentState::parse(&contents) == Some(AgentState::Done) {
             let _ = std::fs::remove_file(entry.path());
-            // Also remove its timestamp file
+            // Also remove its timestamp and token sidecars
             let start_path = format!("{}_start", entry.path().display());
             let _ = std::fs::remove_file(start_path);
+            let tokens_path = format!("{}_tokens", entry.path().display());
+            let _ = std::fs::remove_file(tokens_path);
         }
     }
 }
 
+/// Fold agents reported outside the state dir (the named pipe) into a file
+/// scan. An agent present in both keeps the pushed state, and the aggregate
+/// is taken again over the merged agents so its file state no longer counts.
+pub(crate) fn merge_agent_states(
+    scan: (AgentState, HashMap<String, AgentState>),
+    extra: HashMap<String, AgentState>,
+) -> (AgentState, HashMap<String, AgentState>) {
+    let (_, mut agents) = scan;
+    agents.extend(extra);
+    let best = agents
+        .values()
+        .copied()
+        .fold(AgentState::Idle, |best, state| if state.priority() > best.priority() { state } else { best });
+    (best, agents)
+}
+
+/// The aggregated state while the lightbar follows only agent `focus`: that
+/// agent's state, or idle if it has no state (other agents are ignored).
+pub fn focused_state(agents: &HashMap<String, AgentState>, focus: &str) -> AgentState {
+    agents.get(focus).copied().unwrap_or(AgentState::Idle)
+}
+
 /// Backward-compatible wrapper for tests.
 #[cfg(test)]
 fn aggregate_agent_states(state_dir: &PathBuf, stale_timeout: StdDuration) -> AgentState {
     scan_agent_states(state_dir, stale_timeout).0
 }
 
-/// Polls agent state files and sends aggregated state changes to a channel.
+/// Timing and feature knobs for `poll_state_file` (taken from `Config`).
+#[derive(Debug, Clone)]
+pub struct PollSettings {
+    pub poll_ms: u64,
+    /// Seconds in "done" before auto-transitioning to idle (0 = disabled).
+    pub idle_timeout_s: u64,
+    /// Seconds before a "working" file is considered stale.
+    pub stale_timeout_s: u64,
+    /// Seconds an agent must be idle before its reminder fires (0 = disabled).
+    pub idle_reminder_s: u64,
+    /// Scale the reminder threshold by how long the agent last worked.
+    pub idle_reminder_adaptive: bool,
+    /// Working duration at (and above) which the base threshold applies.
+    pub idle_reminder_reference_s: u64,
+    /// Largest multiplier applied to the threshold for very short work.
+    pub idle_reminder_max_scale: f32,
+    /// Minimum working duration for the per-agent done rumble.
+    pub done_threshold_ms: u64,
+    /// Agents that worked less than this are treated as subagents.
+    pub subagent_filter_s: u64,
+    /// Signal the all-clear channel when a parallel run fully settles.
+    pub all_clear: bool,
+    /// Peak number of concurrently active agents required for the all-clear.
+    pub all_clear_min_agents: usize,
+    /// Signal the error channel when an agent enters Error.
+    pub on_error: bool,
+    /// Windows toasts for done/error transitions and idle reminders.
+    pub toast: ToastSettings,
+    /// Also accept agent states pushed over the named pipe.
+    pub pipe: bool,
+}
+
+/// Adaptive idle-reminder threshold: an agent that worked for `reference` or
+/// longer gets the base threshold; shorter work stretches it proportionally,
+/// up to `max_scale` × base. Unknown work duration keeps the base threshold.
+pub fn adaptive_reminder_threshold(
+    base: Duration,
+    worked: Option<Duration>,
+    reference: Duration,
+    max_scale: f32,
+) -> Duration {
+    let Some(worked) = worked else { return base };
+    let max_scale = max_scale.max(1.0);
+    let scale = if worked.is_zero() {
+        max_scale
+    } else {
+        (reference.as_secs_f32() / worked.as_secs_f32()).clamp(1.0, max_scale)
+    };
+    base.mul_f32(scale)
+}
+
+/// Idle reminders per idle stretch: the first at the threshold, a stronger
+/// one at 2× and the strongest at 3× if the agent is still idle.
+pub const IDLE_REMINDER_LEVELS: u8 = 3;
+
+/// The idle reminder level due for an agent idle for `idle_for`, given the
+/// reminder `threshold` and the highest level already `sent` this stretch.
+/// Level n is due from n × threshold; a late check jumps straight to the
+/// highest level reached. None if nothing new is due.
+pub fn idle_reminder_level(idle_for: Duration, threshold: Duration, sent: u8) -> Option<u8> {
+    let reached = idle_for.as_millis() / threshold.as_millis().max(1);
+    let level = reached.min(IDLE_REMINDER_LEVELS as u128) as u8;
+    (level > sent).then_some(level)
+}
+
+/// Detects the end of a parallel run: the number of active (working/error)
+/// agents dropping to zero after at least `min_agents` were active at once.
+struct AllClearTracker {
+    min_agents: usize,
+    /// Highest concurrent active count since the last time it was zero.
+    peak: usize,
+}
+
+impl AllClearTracker {
+    fn new(min_agents: usize) -> Self {
+        Self { min_agents: min_agents.max(1), peak: 0 }
+    }
+
+    /// Feed the current active-agent count. Returns true exactly once per
+    /// qualifying run, on the tick the count reaches zero.
+    fn update(&mut self, active: usize) -> bool {
+        if active > 0 {
+            self.peak = self.peak.max(active);
+            return false;
+        }
+        let settled = self.peak >= self.min_agents;
+        self.peak = 0;
+        settled
+    }
+}
+
+/// Per-agent event channels `poll_state_file` signals (the output loop
+/// turns them into rumble), plus the per-agent states it publishes and the
+/// focused agent it reads.
+pub struct PollSignals {
+    /// Idle reminder level (1 to [`IDLE_REMINDER_LEVELS`]).
+    pub idle_reminder: mpsc::Sender<u8>,
+    pub done_rumble: mpsc::Sender<()>,
+    pub error: mpsc::Sender<()>,
+    pub all_clear: mpsc::Sender<()>,
+    /// Every agent's state after each scan (read by the HTTP endpoint).
+    pub agents: tokio::sync::watch::Sender<HashMap<String, AgentState>>,
+    /// Agent the aggregated state follows (None = all of them).
+    pub focus: tokio::sync::watch::Receiver<Option<String>>,
+}
+
+/// Polls agent state files and publishes the aggregated state (plus per-state
+/// agent counts) to a watch channel.
 /// Tracks per-agent state transitions:
 /// - Idle reminder: fires when any individual agent has been idle >= `idle_reminder_s`
+///   (optionally stretched for agents that only worked briefly), escalating at
+///   2× and 3× that while it stays idle (see [`idle_reminder_level`])
 /// - Done rumble: fires when any individual agent transitions Working → Done
 ///   after working >= `done_threshold_ms`
+/// - Error alert: optional signal when any individual agent transitions into Error
+/// - Toast: optional desktop notification on per-agent done/error transitions
+///   and idle reminders (done only after working >= `subagent_filter_s`)
+/// - Ack: a signal on `ack_rx` (e.g. shake gesture) clears a pending Done at once
+/// - All clear: fires when the active-agent count drops from a parallel run
+///   (>= `all_clear_min_agents`) to zero. Replaces a done rumble on the same tick.
 pub async fn poll_state_file(
     state_dir: PathBuf,
-    poll_ms: u64,
-    idle_timeout_s: u64,
-    stale_timeout_s: u64,
-    idle_reminder_s: u64,
-    done_threshold_ms: u64,
-    subagent_filter_s: u64,
-    tx: tokio::sync::watch::Sender<AgentState>,
-    idle_reminder_tx: mpsc::Sender<()>,
-    done_rumble_tx: mpsc::Sender<()>,
+    settings: PollSettings,
+    tx: tokio::sync::watch::Sender<AgentSummary>,
+    signals: PollSignals,
+    mut ack_rx: mpsc::Receiver<()>,
 ) {
+    let PollSignals {
+        idle_reminder: idle_reminder_tx,
+        done_rumble: done_rumble_tx,
+        error: error_tx,
+        all_clear: all_clear_tx,
+        agents: agents_tx,
+        focus: mut focus_rx,
+    } = signals;
+    let PollSettings {
+        poll_ms,
+        idle_timeout_s,
+        stale_timeout_s,
+        idle_reminder_s,
+        idle_reminder_adaptive,
+        idle_reminder_reference_s,
+        idle_reminder_max_scale,
+        done_threshold_ms,
+        subagent_filter_s,
+        all_clear,
+        all_clear_min_agents,
+        on_error,
+        toast,
+        pipe,
+    } = settings;
     let mut ticker = interval(Duration::from_millis(poll_ms));
     let mut last_state = AgentState::Idle;
     let mut state_changed_at = Instant::now();
     let stale_timeout = StdDuration::from_secs(stale_timeout_s);
     let idle_reminder_dur = Duration::from_secs(idle_reminder_s);
+    let idle_reminder_reference = Duration::from_secs(idle_reminder_reference_s);
     let done_threshold = Duration::from_millis(done_threshold_ms);
     let subagent_filter = Duration::from_secs(subagent_filter_s);
 
     // Per-agent tracking: agent_id → (last known state, timestamp of that state)
     let mut agent_tracker: HashMap<String, (AgentState, Instant)> = HashMap::new();
-    // Agents whose idle reminder has already fired for the current idle stretch
-    let mut reminder_fired: HashSet<String> = HashSet::new();
+    // Highest idle reminder level fired per agent in its current idle stretch
+    let mut reminders_sent: HashMap<String, u8> = HashMap::new();
+    // How long each agent worked in its most recent Working stretch
+    let mut worked_for: HashMap<String, Duration> = HashMap::new();
+    // Latest metadata from each agent's JSON state file (tool, start time)
+    let mut agent_meta: HashMap<String, AgentMeta> = HashMap::new();
     // Cooldown: after firing an idle reminder, skip per-agent checks for 5s
     let mut reminder_cooldown: Option<Instant> = None;
+    let mut all_clear_tracker = AllClearTracker::new(all_clear_min_agents);
+    let mut toaster = Toaster::new(toast);
+
+    // Rescan as soon as a state file changes or a pipe message arrives; the
+    // tick still covers timeouts
+    let file_changed = Arc::new(Notify::new());
+    let notify = file_changed.clone();
+    match crate::dirwatch::spawn(&state_dir, AGENT_FILE_PREFIX, move || notify.notify_one()) {
+        Ok(()) => log::info!("Watching {} for state changes", state_dir.display()),
+        Err(e) => log::info!("State dir watch unavailable ({e}); polling every {poll_ms}ms"),
+    }
+    let pipe_states = pipe.then(PipeStates::default).filter(|states| {
+        let notify = file_changed.clone();
+        match crate::pipe::spawn_server(crate::pipe::PIPE_NAME, states.clone(), move || notify.notify_one()) {
+            Ok(()) => {
+                log::info!("Listening for agent states on {}", crate::pipe::PIPE_NAME);
+                true
+            }
+            Err(e) => {
+                log::warn!("State pipe unavailable: {e}");
+                false
+            }
+        }
+    });
 
     loop {
-        ticker.tick().await;
-
-        // Auto-idle: if we've been in "done" long enough, transition to idle.
-        // Also remove the "done" state files from disk so the next scan doesn't
-        // re-read them and bounce back to Done (which caused an infinite loop).
-        if idle_timeout_s > 0
-            && last_state == AgentState::Done
-            && state_changed_at.elapsed() >= Duration::from_secs(idle_timeout_s)
-        {
-            log::info!("Auto-idle: {last_state} → idle (after {idle_timeout_s}s)");
+        // Wake on the poll tick, on a state file change, or early when the
+        // user acknowledges Done
+        let acked = tokio::select! {
+            _ = ticker.tick() => false,
+            _ = file_changed.notified() => false,
+            Some(()) = ack_rx.recv() => true,
+            Ok(()) = focus_rx.changed() => {
+                match focus_rx.borrow_and_update().as_deref() {
+                    Some(id) => log::info!("Lightbar now follows agent {id} only"),
+                    None => log::info!("Lightbar follows every agent again"),
+                }
+                false
+            }
+        };
+
+        // Auto-idle: if we've been in "done" long enough (or the user acknowledged
+        // it), transition to idle. Also remove the "done" state files from disk so
+        // the next scan doesn't re-read them and bounce back to Done (which caused
+        // an infinite loop).
+        let timed_out = idle_timeout_s > 0
+            && state_changed_at.elapsed() >= Duration::from_secs(idle_timeout_s);
+        if last_state == AgentState::Done && (acked || timed_out) {
+            if acked {
+                log::info!("Done acknowledged → idle");
+            } else {
+                log::info!("Auto-idle: {last_state} → idle (after {idle_timeout_s}s)");
+            }
             clean_done_files(&state_dir);
+            if let Some(states) = &pipe_states {
+                states.clear_done();
+            }
             last_state = AgentState::Idle;
             state_changed_at = Instant::now();
-            let _ = tx.send(AgentState::Idle);
+            let _ = tx.send(AgentSummary::default());
+            agents_tx.send_if_modified(|published| !std::mem::take(published).is_empty());
+            continue;
+        }
+        if acked {
+            // Nothing pending — wait for the next regular tick
             continue;
         }
 
-        let (aggregated, current_agents) = scan_agent_states(&state_dir, stale_timeout);
+        let (aggregated, current_agents, mut meta) = scan_agent_states(&state_dir, stale_timeout);
+        let mut scan = (aggregated, current_agents);
+        if let Some(states) = &pipe_states {
+            scan = merge_agent_states(scan, states.snapshot(stale_timeout));
+        }
+        let (mut aggregated, current_agents) = scan;
+        // A focused agent alone drives the aggregate; the rest are still tracked
+        if let Some(id) = focus_rx.borrow().as_deref() {
+            aggregated = focused_state(&current_agents, id);
+        }
+        for id in current_agents.keys() {
+            match meta.remove(id) {
+                Some(m) => agent_meta.insert(id.clone(), m),
+                None => agent_meta.remove(id),
+            };
+        }
 
         if aggregated != last_state {
             log::info!("State changed: {last_state} → {aggregated}");
             last_state = aggregated;
             state_changed_at = Instant::now();
-            let _ = tx.send(aggregated);
         }
+        let summary = AgentSummary { state: aggregated, counts: StateCounts::from_agents(&current_agents) };
+        tx.send_if_modified(|published| std::mem::replace(published, summary) != summary);
+        agents_tx.send_if_modified(|published| {
+            let changed = *published != current_agents;
+            if changed {
+                published.clone_from(&current_agents);
+            }
+            changed
+        });
 
         let now = Instant::now();
 
@@ -231,19 +597,32 @@ pub async fn poll_state_file(
         };
 
         // 1. Update tracker for agents with active state files
+        let mut done_pending = false;
         for (id, state) in &current_agents {
             match agent_tracker.get(id) {
                 Some((prev, _)) if *prev == *state => { /* unchanged */ }
+                Some((prev, since)) if prev.is_busy() && state.is_busy() => {
+                    // Working ↔ Thinking: same task, keep the stretch's start time
+                    log::debug!("Agent {id}: {prev} → {state}");
+                    agent_tracker.insert(id.clone(), (*state, *since));
+                }
                 Some((prev, since)) => {
+                    log::debug!(
+                        "Agent {id}: {prev} → {state}{}",
+                        agent_meta.get(id).cloned().unwrap_or_default()
+                    );
                     // State changed — check Working → Done
                     let elapsed = now.duration_since(*since);
-                    if *prev == AgentState::Working && *state == AgentState::Done {
+                    if prev.is_busy() {
+                        worked_for.insert(id.clone(), elapsed);
+                    }
+                    if prev.is_busy() && *state == AgentState::Done {
                         if elapsed >= done_threshold {
                             log::info!(
                                 "Per-agent done: agent {id} worked for {}s → rumble",
                                 elapsed.as_secs()
                             );
-                            let _ = done_rumble_tx.try_send(());
+                            done_pending = true;
                         } else {
                             log::debug!(
                                 "Per-agent done: agent {id} worked {}s (< {}s threshold) — skipping rumble",
@@ -252,15 +631,43 @@ pub async fn poll_state_file(
                             );
                         }
                     }
+                    if on_error && *state == AgentState::Error {
+                        log::info!("Agent {id} entered error → alert");
+                        let _ = error_tx.try_send(());
+                    }
+                    // Subagents finishing within the filter window don't toast
+                    let worked = prev.is_busy().then_some(elapsed);
+                    match ToastKind::for_state(*state) {
+                        Some(ToastKind::Done) if worked.is_none_or(|w| w < subagent_filter) => {}
+                        Some(kind) => toaster.notify(kind, id, worked, now),
+                        None => {}
+                    }
                     agent_tracker.insert(id.clone(), (*state, now));
-                    reminder_fired.remove(id);
+                    reminders_sent.remove(id);
                 }
                 None => {
+                    log::debug!(
+                        "Agent {id}: {state}{}",
+                        agent_meta.get(id).cloned().unwrap_or_default()
+                    );
                     agent_tracker.insert(id.clone(), (*state, now));
                 }
             }
         }
 
+        // All clear: a parallel run just settled. Takes the place of a done
+        // rumble on the same tick so the two patterns don't overlap.
+        let active = current_agents
+            .values()
+            .filter(|s| s.is_busy() || **s == AgentState::Error)
+            .count();
+        if all_clear && all_clear_tracker.update(active) {
+            log::info!("All clear: every agent settled → rumble");
+            let _ = all_clear_tx.try_send(());
+        } else if done_pending {
+            let _ = done_rumble_tx.try_send(());
+        }
+
         // 2. Transition disappeared agents to Idle in-memory.
         //    scan_agent_states deletes idle files immediately (keeps the dir lean);
         //    we continue tracking their idle duration here so the reminder can fire.
@@ -271,8 +678,10 @@ pub async fn poll_state_file(
                 if let Some((state, since)) = agent_tracker.get_mut(&id) {
                     if *state != AgentState::Idle {
                         let worked = now.duration_since(*since);
-                        let is_subagent =
-                            *state == AgentState::Working && worked < subagent_filter;
+                        if state.is_busy() {
+                            worked_for.insert(id.clone(), worked);
+                        }
+                        let is_subagent = state.is_busy() && worked < subagent_filter;
                         if is_subagent {
                             log::debug!(
                                 "Subagent filtered: {id} (worked {}s < {}s threshold)",
@@ -283,9 +692,9 @@ pub async fn poll_state_file(
                         *state = AgentState::Idle;
                         *since = now;
                         if is_subagent {
-                            reminder_fired.insert(id.clone());
+                            reminders_sent.insert(id.clone(), IDLE_REMINDER_LEVELS);
                         } else {
-                            reminder_fired.remove(&id);
+                            reminders_sent.remove(&id);
                         }
                     }
                 }
@@ -294,35 +703,51 @@ pub async fn poll_state_file(
 
         // 3. Check idle reminders across all tracked agents (skip during cooldown)
         if !in_cooldown {
-            let mut fired_this_tick = false;
+            // Strongest level fired this tick (one rumble per tick)
+            let mut fired_level = None;
             for (id, (state, since)) in &agent_tracker {
-                if idle_reminder_s > 0
-                    && *state == AgentState::Idle
-                    && !reminder_fired.contains(id)
-                    && now.duration_since(*since) >= idle_reminder_dur
-                {
+                let threshold = if idle_reminder_adaptive {
+                    adaptive_reminder_threshold(
+                        idle_reminder_dur,
+                        worked_for.get(id).copied(),
+                        idle_reminder_reference,
+                        idle_reminder_max_scale,
+                    )
+                } else {
+                    idle_reminder_dur
+                };
+                if idle_reminder_s == 0 || *state != AgentState::Idle {
+                    continue;
+                }
+                let idle_for = now.duration_since(*since);
+                let sent = reminders_sent.get(id).copied().unwrap_or(0);
+                if let Some(level) = idle_reminder_level(idle_for, threshold, sent) {
                     log::info!(
-                        "Per-agent idle reminder: agent {id} idle for {}s",
-                        now.duration_since(*since).as_secs()
+                        "Per-agent idle reminder {level}/{IDLE_REMINDER_LEVELS}: agent {id} idle for {}s",
+                        idle_for.as_secs()
                     );
-                    reminder_fired.insert(id.clone());
-                    fired_this_tick = true;
+                    toaster.notify(ToastKind::IdleReminder, id, Some(idle_for), now);
+                    reminders_sent.insert(id.clone(), level);
+                    fired_level = fired_level.max(Some(level));
                 }
             }
-            if fired_this_tick {
-                let _ = idle_reminder_tx.try_send(());
+            if let Some(level) = fired_level {
+                let _ = idle_reminder_tx.try_send(level);
                 reminder_cooldown = Some(now);
             }
         }
 
-        // 4. Prune idle agents whose reminder has already fired.
+        // 4. Prune idle agents whose last reminder has already fired.
         //    Active agents are always kept. Idle-in-memory agents are kept only
-        //    while their reminder is still pending.
+        //    while a reminder level is still pending.
         agent_tracker.retain(|id, (state, _)| {
             if current_agents.contains_key(id) { return true; }
-            idle_reminder_s > 0 && *state == AgentState::Idle && !reminder_fired.contains(id)
+            idle_reminder_s > 0
+                && *state == AgentState::Idle
+                && reminders_sent.get(id).is_none_or(|&sent| sent < IDLE_REMINDER_LEVELS)
         });
-        reminder_fired.retain(|id| agent_tracker.contains_key(id));
+        reminders_sent.retain(|id, _| agent_tracker.contains_key(id));
+        worked_for.retain(|id, _| agent_tracker.contains_key(id));
     }
 }
 
@@ -330,16 +755,159 @@ pub async fn poll_state_file(
 mod tests {
     use super::*;
 
+    #[test]
+    fn idle_reminder_escalates_at_multiples_of_the_threshold() {
+        let threshold = Duration::from_secs(480);
+        let secs = Duration::from_secs;
+        assert_eq!(idle_reminder_level(secs(479), threshold, 0), None);
+        assert_eq!(idle_reminder_level(secs(480), threshold, 0), Some(1));
+        // Already reminded once: nothing new until 2×
+        assert_eq!(idle_reminder_level(secs(959), threshold, 1), None);
+        assert_eq!(idle_reminder_level(secs(960), threshold, 1), Some(2));
+        assert_eq!(idle_reminder_level(secs(1439), threshold, 2), None);
+        assert_eq!(idle_reminder_level(secs(1440), threshold, 2), Some(3));
+        // The third is the last
+        assert_eq!(idle_reminder_level(secs(10_000), threshold, 3), None);
+        // A late first check goes straight to the level reached
+        assert_eq!(idle_reminder_level(secs(1000), threshold, 0), Some(2));
+        assert_eq!(idle_reminder_level(secs(5000), threshold, 0), Some(IDLE_REMINDER_LEVELS));
+    }
+
     #[test]
     fn parse_states() {
         assert_eq!(AgentState::parse("idle"), Some(AgentState::Idle));
         assert_eq!(AgentState::parse("WORKING"), Some(AgentState::Working));
         assert_eq!(AgentState::parse("  done\n"), Some(AgentState::Done));
         assert_eq!(AgentState::parse("Error"), Some(AgentState::Error));
+        assert_eq!(AgentState::parse("thinking"), Some(AgentState::Thinking));
         assert_eq!(AgentState::parse("unknown"), None);
         assert_eq!(AgentState::parse(""), None);
     }
 
+    #[test]
+    fn parse_json_state_with_meta() {
+        let (state, meta) = AgentState::parse_with_meta(
+            r#"{ "state": "working", "tool": "Bash", "started": 1700000000 }"#,
+        )
+        .unwrap();
+        assert_eq!(state, AgentState::Working);
+        assert_eq!(meta, AgentMeta { tool: Some("Bash".into()), started: Some(1700000000) });
+
+        // Metadata is optional; the plain word form carries none
+        let (state, meta) = AgentState::parse_with_meta("{\"state\": \"Done\"}\n").unwrap();
+        assert_eq!((state, meta), (AgentState::Done, AgentMeta::default()));
+        assert_eq!(
+            AgentState::parse_with_meta("working"),
+            Some((AgentState::Working, AgentMeta::default()))
+        );
+        assert_eq!(AgentState::parse(r#"{"state":"error","tool":"Edit"}"#), Some(AgentState::Error));
+
+        assert_eq!(AgentState::parse(r#"{"state":"sleeping"}"#), None);
+        assert_eq!(AgentState::parse(r#"{"tool":"Bash"}"#), None);
+        assert_eq!(AgentState::parse("{not json"), None);
+    }
+
+    #[test]
+    fn scan_reads_json_state_files() {
+        let dir = std::env::temp_dir().join(format!("ds4cc_test_json_{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(dir.join("ds4cc_agent_aaa"), r#"{"state":"working","tool":"Bash"}"#).unwrap();
+        std::fs::write(dir.join("ds4cc_agent_bbb"), "done").unwrap();
+
+        let (aggregated, agents, meta) = scan_agent_states(&dir, StdDuration::from_secs(600));
+        assert_eq!(aggregated, AgentState::Working);
+        assert_eq!(agents.len(), 2);
+        assert_eq!(meta.get("aaa").and_then(|m| m.tool.as_deref()), Some("Bash"));
+        assert!(!meta.contains_key("bbb"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn state_counts_from_agents() {
+        let agents = HashMap::from([
+            ("a".to_string(), AgentState::Working),
+            ("b".to_string(), AgentState::Working),
+            ("c".to_string(), AgentState::Error),
+            ("d".to_string(), AgentState::Idle),
+        ]);
+        let counts = StateCounts::from_agents(&agents);
+        assert_eq!(counts, StateCounts { working: 2, error: 1, ..Default::default() });
+        assert_eq!(counts.total(), 3);
+    }
+
+    fn test_settings(on_error: bool) -> PollSettings {
+        PollSettings {
+            poll_ms: 20,
+            idle_timeout_s: 0,
+            stale_timeout_s: 600,
+            idle_reminder_s: 0,
+            idle_reminder_adaptive: false,
+            idle_reminder_reference_s: 600,
+            idle_reminder_max_scale: 1.0,
+            done_threshold_ms: 600_000,
+            subagent_filter_s: 0,
+            all_clear: false,
+            all_clear_min_agents: 2,
+            on_error,
+            toast: ToastSettings::default(),
+            pipe: false,
+        }
+    }
+
+    /// Run the poller on `dir` for `on_error`; returns the error channel.
+    fn spawn_poller(dir: &std::path::Path, on_error: bool) -> mpsc::Receiver<()> {
+        let (tx, _) = tokio::sync::watch::channel(AgentSummary::default());
+        let (error_tx, error_rx) = mpsc::channel(4);
+        let signals = PollSignals {
+            idle_reminder: mpsc::channel(4).0,
+            done_rumble: mpsc::channel(4).0,
+            error: error_tx,
+            all_clear: mpsc::channel(4).0,
+            agents: tokio::sync::watch::channel(HashMap::new()).0,
+            focus: tokio::sync::watch::channel(None).1,
+        };
+        let (_, ack_rx) = mpsc::channel(4);
+        tokio::spawn(poll_state_file(dir.to_path_buf(), test_settings(on_error), tx, signals, ack_rx));
+        error_rx
+    }
+
+    #[tokio::test]
+    async fn error_channel_fires_on_working_to_error() {
+        let dir = std::env::temp_dir().join(format!("ds4cc_test_error_alert_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("ds4cc_agent_err");
+        std::fs::write(&file, "working").unwrap();
+
+        let mut error_rx = spawn_poller(&dir, true);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(error_rx.try_recv().is_err());
+
+        std::fs::write(&file, "error").unwrap();
+        let fired = tokio::time::timeout(Duration::from_secs(2), error_rx.recv()).await;
+        assert_eq!(fired.ok().flatten(), Some(()));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn error_channel_silent_when_disabled() {
+        let dir = std::env::temp_dir().join(format!("ds4cc_test_error_quiet_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("ds4cc_agent_err");
+        std::fs::write(&file, "working").unwrap();
+
+        let mut error_rx = spawn_poller(&dir, false);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        std::fs::write(&file, "error").unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(error_rx.try_recv().is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn priority_order() {
         assert!(AgentState::Working.priority() > AgentState::Error.priority());
@@ -347,6 +915,62 @@ mod tests {
         assert!(AgentState::Done.priority() > AgentState::Idle.priority());
     }
 
+    #[test]
+    fn adaptive_reminder_waits_longer_after_brief_work() {
+        let base = Duration::from_secs(480);
+        let reference = Duration::from_secs(600);
+        let brief = adaptive_reminder_threshold(base, Some(Duration::from_secs(60)), reference, 4.0);
+        let long = adaptive_reminder_threshold(base, Some(Duration::from_secs(1800)), reference, 4.0);
+        assert!(brief > long);
+        assert_eq!(long, base);
+        // 60s of work against a 600s reference would be 10×, capped at 4×
+        assert_eq!(brief, base * 4);
+        assert_eq!(adaptive_reminder_threshold(base, None, reference, 4.0), base);
+    }
+
+    #[test]
+    fn all_clear_fires_once_when_active_count_hits_zero() {
+        let mut tracker = AllClearTracker::new(2);
+        assert!(!tracker.update(0));
+        assert!(!tracker.update(1));
+        assert!(!tracker.update(3));
+        assert!(!tracker.update(1));
+        assert!(tracker.update(0));
+        // Staying at zero doesn't re-fire
+        assert!(!tracker.update(0));
+        assert!(!tracker.update(0));
+    }
+
+    #[test]
+    fn all_clear_ignores_single_agent_runs() {
+        let mut tracker = AllClearTracker::new(2);
+        assert!(!tracker.update(1));
+        assert!(!tracker.update(0));
+    }
+
+    #[test]
+    fn focused_agent_alone_drives_the_aggregate() {
+        let dir = std::env::temp_dir().join("ds4cc_test_focus");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // "a" is working, the focused "b" is idle
+        std::fs::write(dir.join("ds4cc_agent_a"), "working").unwrap();
+        std::fs::write(dir.join("ds4cc_agent_b"), "idle").unwrap();
+        let (aggregated, agents, _) = scan_agent_states(&dir, StdDuration::from_secs(600));
+        assert_eq!(aggregated, AgentState::Working);
+        assert_eq!(focused_state(&agents, "b"), AgentState::Idle);
+        // "a" is still tracked
+        assert_eq!(agents.get("a"), Some(&AgentState::Working));
+
+        std::fs::write(dir.join("ds4cc_agent_b"), "done").unwrap();
+        let (_, agents, _) = scan_agent_states(&dir, StdDuration::from_secs(600));
+        assert_eq!(focused_state(&agents, "b"), AgentState::Done);
+        assert_eq!(focused_state(&agents, "a"), AgentState::Working);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn aggregate_empty_dir() {
         let dir = std::env::temp_dir().join("ds4cc_test_empty");
@@ -383,4 +1007,27 @@ mod tests {
         let _ = std::fs::remove_file(dir.join("ds4cc_agent_bbb"));
         let _ = std::fs::remove_dir(&dir);
     }
+
+    #[test]
+    fn merge_pipe_agents_into_scan() {
+        let scan = (AgentState::Done, HashMap::from([("file".to_string(), AgentState::Done)]));
+        let pushed = HashMap::from([("sess1".to_string(), AgentState::Working)]);
+        let (aggregated, agents) = merge_agent_states(scan, pushed);
+        assert_eq!(aggregated, AgentState::Working);
+        assert_eq!(agents.len(), 2);
+
+        // Lower-priority pushes don't downgrade the scan result
+        let scan = (AgentState::Working, HashMap::from([("file".to_string(), AgentState::Working)]));
+        let pushed = HashMap::from([("sess1".to_string(), AgentState::Done)]);
+        assert_eq!(merge_agent_states(scan, pushed).0, AgentState::Working);
+    }
+
+    #[test]
+    fn pushed_state_replaces_the_file_state_of_the_same_agent() {
+        let scan = (AgentState::Working, HashMap::from([("sess1".to_string(), AgentState::Working)]));
+        let pushed = HashMap::from([("sess1".to_string(), AgentState::Done)]);
+        let (aggregated, agents) = merge_agent_states(scan, pushed);
+        assert_eq!(aggregated, AgentState::Done);
+        assert_eq!(agents, HashMap::from([("sess1".to_string(), AgentState::Done)]));
+    }
 }
@@ -1,23 +1,60 @@
 /// Auto-detect tmux configuration from a running tmux server via WSL.
 ///
-/// Queries `tmux show-options -g prefix` for the prefix key and
-/// `tmux list-keys -T prefix` for the full binding table.
+/// Queries `tmux show-options -g prefix` for the prefix key (falling back to
+/// `prefix2` when `prefix` is unset) and `tmux list-keys -T prefix` for the
+/// full binding table. When `tmux.key_table`
+/// names a custom table (entered via `switch-client -T <table>`), that table is
+/// read too, along with the key that switches into it. With `tmux.root_bindings`
+/// the root table (`bind -n`, no prefix) is read as well.
 /// Parses tmux key notation (C-a, M-n, etc.) into VKey combos.
 ///
 /// Falls back gracefully if tmux isn't running or WSL isn't available.
 
 use crate::mapper::VKey;
 use crate::wsl::run_wsl;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Auto-detected tmux configuration.
-#[derive(Debug, Clone)]
+/// Auto-detected tmux configuration (serializable for the detection cache).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TmuxDetected {
     /// Detected prefix as VKey combo (e.g., [Control, A]).
     pub prefix: Option<Vec<VKey>>,
+    /// Detected secondary prefix (`prefix2`), for configs that unset `prefix`.
+    pub prefix2: Option<Vec<VKey>>,
     /// Map of tmux command → VKey combo for the key bound to that command.
     /// e.g., "previous-window" → [P], "kill-window" → [Shift, D7]
     actions: HashMap<String, Vec<VKey>>,
+    /// Root-table bindings (`bind -n`): command → key sent without the prefix.
+    /// Only read when `tmux.root_bindings` is set.
+    root_actions: HashMap<String, Vec<VKey>>,
+    /// Custom key table from `tmux.key_table`, if configured and reachable.
+    pub key_table: Option<KeyTable>,
+}
+
+/// Bindings of a custom tmux key table and how to enter it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyTable {
+    pub name: String,
+    /// Key bound to `switch-client -T <name>`.
+    pub enter_key: Vec<VKey>,
+    /// Whether `enter_key` lives in the prefix table (send prefix first)
+    /// or the root table (`bind -n`, send it directly).
+    pub enter_via_prefix: bool,
+    /// tmux command → key inside the table.
+    actions: HashMap<String, Vec<VKey>>,
+}
+
+impl KeyTable {
+    /// Look up the key bound to a command inside this table.
+    pub fn key_for_action(&self, action: &str) -> Option<&Vec<VKey>> {
+        self.actions.get(action)
+    }
+
+    /// Every command → key in this table (for `--dump-detect`).
+    pub fn actions(&self) -> &HashMap<String, Vec<VKey>> {
+        &self.actions
+    }
 }
 
 impl TmuxDetected {
@@ -25,90 +62,168 @@ impl TmuxDetected {
     pub fn key_for_action(&self, action: &str) -> Option<&Vec<VKey>> {
         self.actions.get(action)
     }
+
+    /// The prefix to send: `prefix`, or `prefix2` when the primary is unset.
+    pub fn bound_prefix(&self) -> Option<&Vec<VKey>> {
+        self.prefix.as_ref().or(self.prefix2.as_ref())
+    }
+
+    /// Look up the root-table key (no prefix) bound to a tmux action/command.
+    pub fn root_key_for_action(&self, action: &str) -> Option<&Vec<VKey>> {
+        self.root_actions.get(action)
+    }
+
+    /// Every prefix-table command → key (for `--dump-detect`).
+    pub fn actions(&self) -> &HashMap<String, Vec<VKey>> {
+        &self.actions
+    }
+
+    /// Every root-table command → key (for `--dump-detect`).
+    pub fn root_actions(&self) -> &HashMap<String, Vec<VKey>> {
+        &self.root_actions
+    }
 }
 
 /// Detect tmux configuration by querying a running tmux server via WSL.
+/// `key_table` names an extra table to read ("" = prefix table only);
+/// `root` also reads the root table.
 /// Returns `None` if detection fails entirely (WSL not available, tmux not running).
-pub fn detect() -> Option<TmuxDetected> {
+pub fn detect(key_table: &str, root: bool) -> Option<TmuxDetected> {
     log::info!("Auto-detecting tmux configuration via WSL...");
     let start = std::time::Instant::now();
 
-    let prefix = detect_prefix();
-    let actions = detect_bindings();
+    let (prefix, prefix2) = detect_prefixes();
+    let actions = detect_bindings("prefix");
+    let root_actions = if root { detect_bindings("root") } else { HashMap::new() };
+    let key_table = match key_table.trim() {
+        "" | "prefix" => None,
+        name if !is_table_name(name) => {
+            log::warn!("Tmux key table '{name}': only letters, digits, '-' and '_' are allowed — table ignored");
+            None
+        }
+        name => detect_key_table(name, &actions, root.then_some(&root_actions)),
+    };
 
     let elapsed = start.elapsed();
 
-    if prefix.is_none() && actions.is_empty() {
+    if prefix.is_none() && prefix2.is_none() && actions.is_empty() && root_actions.is_empty() && key_table.is_none() {
         log::warn!("Tmux auto-detection failed (took {elapsed:?}). Using config defaults.");
         return None;
     }
 
-    if let Some(ref p) = prefix {
-        log::info!("Detected tmux prefix: {p:?}");
-    } else {
-        log::warn!("Could not detect tmux prefix, using config value");
+    match (&prefix, &prefix2) {
+        (Some(p), _) => log::info!("Detected tmux prefix: {p:?}"),
+        (None, Some(p)) => log::info!("Detected tmux prefix2: {p:?} (prefix is unset)"),
+        (None, None) => log::warn!("Could not detect tmux prefix, using config value"),
     }
     log::info!("Detected {} tmux key bindings (took {elapsed:?})", actions.len());
+    if root {
+        log::info!("Detected {} tmux root-table bindings", root_actions.len());
+    }
 
-    Some(TmuxDetected { prefix, actions })
+    Some(TmuxDetected { prefix, prefix2, actions, root_actions, key_table })
+}
+
+/// Read a custom key table and find the key that switches into it
+/// (prefix table first, then the root table — `root_actions` if it was
+/// already read, else read now).
+fn detect_key_table(
+    name: &str,
+    prefix_actions: &HashMap<String, Vec<VKey>>,
+    root_actions: Option<&HashMap<String, Vec<VKey>>>,
+) -> Option<KeyTable> {
+    let switch = format!("switch-client -T {name}");
+    let root_key = || match root_actions {
+        Some(root) => root.get(&switch).cloned(),
+        None => detect_bindings("root").remove(&switch),
+    };
+    let (enter_key, enter_via_prefix) = match prefix_actions.get(&switch) {
+        Some(keys) => (keys.clone(), true),
+        None => match root_key() {
+            Some(keys) => (keys, false),
+            None => {
+                log::warn!("Tmux key table '{name}': no key bound to `{switch}` — table ignored");
+                return None;
+            }
+        },
+    };
+    let actions = detect_bindings(name);
+    log::info!("Detected {} bindings in tmux key table '{name}'", actions.len());
+    Some(KeyTable { name: name.to_string(), enter_key, enter_via_prefix, actions })
 }
 
 // ── Prefix detection ─────────────────────────────────────────────────
 
-fn detect_prefix() -> Option<Vec<VKey>> {
+/// Detect `prefix` and `prefix2` (None when unset or set to `None`).
+fn detect_prefixes() -> (Option<Vec<VKey>>, Option<Vec<VKey>>) {
     // Try running tmux server first
-    if let Some(output) = run_wsl("tmux show-options -g prefix 2>/dev/null") {
-        // Format: "prefix C-a\n"
-        for line in output.lines() {
-            if let Some(key_str) = line.strip_prefix("prefix ") {
-                let key_str = key_str.trim();
-                if !key_str.is_empty() {
-                    log::debug!("Prefix from tmux server: {key_str}");
-                    return parse_tmux_key(key_str);
-                }
-            }
-        }
+    let query = "tmux show-options -g prefix 2>/dev/null; tmux show-options -g prefix2 2>/dev/null";
+    if let Some(output) = run_wsl(query).filter(|o| !o.trim().is_empty()) {
+        log::debug!("Prefixes from tmux server: {}", output.trim().replace('\n', ", "));
+        return parse_server_prefixes(&output);
     }
 
     // Fallback: parse ~/.tmux.conf directly
     log::debug!("Tmux server not running, parsing ~/.tmux.conf...");
-    if let Some(conf) = run_wsl("cat ~/.tmux.conf 2>/dev/null") {
-        for line in conf.lines() {
-            let line = line.trim();
-            // Match: set -g prefix C-a  or  set-option -g prefix C-a
-            if (line.starts_with("set ") || line.starts_with("set-option "))
-                && line.contains("prefix")
-                && !line.starts_with('#')
-            {
-                // Extract the key after "prefix"
-                if let Some(idx) = line.find("prefix") {
-                    let after = line[idx + 6..].trim();
-                    // Skip "prefix2" lines
-                    if after.starts_with('2') {
-                        continue;
-                    }
-                    let key_str = after.split_whitespace().next().unwrap_or("");
-                    if !key_str.is_empty() {
-                        log::debug!("Prefix from tmux.conf: {key_str}");
-                        return parse_tmux_key(key_str);
-                    }
-                }
-            }
+    run_wsl("cat ~/.tmux.conf 2>/dev/null")
+        .map(|conf| parse_conf_prefixes(&conf))
+        .unwrap_or_default()
+}
+
+/// Parse `tmux show-options -g prefix` / `prefix2` output ("prefix C-a\nprefix2 None").
+fn parse_server_prefixes(output: &str) -> (Option<Vec<VKey>>, Option<Vec<VKey>>) {
+    let mut prefix = None;
+    let mut prefix2 = None;
+    for line in output.lines() {
+        let mut parts = line.split_whitespace();
+        match (parts.next(), parts.next()) {
+            (Some("prefix"), Some(key)) => prefix = parse_tmux_key(key),
+            (Some("prefix2"), Some(key)) => prefix2 = parse_tmux_key(key),
+            _ => {}
         }
     }
+    (prefix, prefix2)
+}
 
-    None
+/// Parse `set -g prefix C-a` / `set -g prefix2 C-a` lines of a tmux.conf.
+/// The last setting of each wins, as it does in tmux.
+fn parse_conf_prefixes(conf: &str) -> (Option<Vec<VKey>>, Option<Vec<VKey>>) {
+    let mut prefix = None;
+    let mut prefix2 = None;
+    for line in conf.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if !matches!(tokens.first(), Some(&"set" | &"set-option")) {
+            continue;
+        }
+        // Skip flags (-g, -s, ...) to reach the option name and its value
+        let mut rest = tokens[1..].iter().skip_while(|t| t.starts_with('-'));
+        match (rest.next(), rest.next()) {
+            (Some(&"prefix"), Some(key)) => prefix = parse_tmux_key(key),
+            (Some(&"prefix2"), Some(key)) => prefix2 = parse_tmux_key(key),
+            _ => {}
+        }
+    }
+    (prefix, prefix2)
 }
 
 // ── Binding table detection ──────────────────────────────────────────
 
-fn detect_bindings() -> HashMap<String, Vec<VKey>> {
+/// Whether `name` is safe to put in the `tmux list-keys -T` shell command.
+fn is_table_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Read the bindings of one key table ("prefix", "root", or a custom table).
+fn detect_bindings(table: &str) -> HashMap<String, Vec<VKey>> {
     let mut actions = HashMap::new();
+    if !is_table_name(table) {
+        return actions;
+    }
 
     // Try running tmux server first
-    if let Some(output) = run_wsl("tmux list-keys -T prefix 2>/dev/null") {
+    if let Some(output) = run_wsl(&format!("tmux list-keys -T {table} 2>/dev/null")) {
         for line in output.lines() {
-            if let Some((vkeys, command)) = parse_binding_line(line) {
+            if let Some((vkeys, command)) = parse_binding_line(line, table) {
                 insert_binding(&mut actions, command, vkeys);
             }
         }
@@ -125,7 +240,7 @@ fn detect_bindings() -> HashMap<String, Vec<VKey>> {
             if line.starts_with('#') {
                 continue;
             }
-            if let Some((vkeys, command)) = parse_conf_bind(line) {
+            if let Some((vkeys, command)) = parse_binding_line(line, table) {
                 insert_binding(&mut actions, command, vkeys);
             }
         }
@@ -148,72 +263,53 @@ fn insert_binding(actions: &mut HashMap<String, Vec<VKey>>, command: String, vke
     }
 }
 
-/// Parse a tmux.conf bind/bind-key line.
-/// Format: `bind [-r] <key> <command> [args...]` or `bind-key [-r] <key> <command> [args...]`
-fn parse_conf_bind(line: &str) -> Option<(Vec<VKey>, String)> {
+/// Parse a `tmux list-keys -T <table>` line or a tmux.conf bind/bind-key line
+/// belonging to `table`. Returns (key_vkeys, extracted_command).
+/// Format: `bind-key [-r] [-N <note>] [-T <table> | -n] <key> <command> [args...]`
+/// Lines without `-T` are prefix-table bindings; `-n` means the root table.
+/// The flags are read up to the key, so a word in the command is never taken
+/// for the table or key.
+fn parse_binding_line(line: &str, table: &str) -> Option<(Vec<VKey>, String)> {
     let tokens: Vec<&str> = line.split_whitespace().collect();
-    if tokens.is_empty() {
-        return None;
-    }
-
-    let is_bind = matches!(tokens[0], "bind" | "bind-key");
-    if !is_bind {
+    if !matches!(tokens.first(), Some(&"bind" | &"bind-key")) {
         return None;
     }
 
     let mut i = 1;
-    // Skip flags
-    while i < tokens.len() && tokens[i].starts_with('-') {
-        // Skip -T <table> pair
-        if tokens[i] == "-T" {
-            i += 1; // skip the table name
-            // If it's not prefix table, skip this binding
-            if i < tokens.len() && tokens[i] != "prefix" {
-                return None;
+    let mut line_table = "prefix";
+    while let Some(flag) = tokens.get(i).filter(|t| t.starts_with('-') && t.len() > 1) {
+        match *flag {
+            // -T <table>
+            "-T" => {
+                i += 1;
+                line_table = tokens.get(i)?;
+            }
+            // -N <note>, possibly a quoted note of several words
+            "-N" => {
+                i += 1;
+                if tokens.get(i)?.starts_with('"') {
+                    while !(tokens.get(i)?.len() > 1 && tokens[i].ends_with('"')) {
+                        i += 1;
+                    }
+                }
             }
+            "-n" => line_table = "root",
+            // -r and any other argument-less flags
+            _ => {}
         }
         i += 1;
     }
-
-    if i >= tokens.len() {
-        return None;
-    }
-
-    let key_str = tokens[i];
-    let vkeys = parse_tmux_key(key_str)?;
-
-    let cmd_start = i + 1;
-    if cmd_start >= tokens.len() {
-        return None;
-    }
-
-    let command = extract_command(&tokens[cmd_start..]);
-    Some((vkeys, command))
-}
-
-/// Parse a single `tmux list-keys -T prefix` line.
-/// Returns (key_vkeys, extracted_command).
-fn parse_binding_line(line: &str) -> Option<(Vec<VKey>, String)> {
-    // Format: "bind-key [-r] -T prefix <key> <command> [args...]"
-    let parts: Vec<&str> = line.split_whitespace().collect();
-
-    // Find "prefix" keyword
-    let prefix_idx = parts.iter().position(|&p| p == "prefix")?;
-    let key_idx = prefix_idx + 1;
-    if key_idx >= parts.len() {
+    // Only bindings for the requested table
+    if line_table != table {
         return None;
     }
 
-    let key_str = parts[key_idx];
-    let vkeys = parse_tmux_key(key_str)?;
-
-    let cmd_start = key_idx + 1;
-    if cmd_start >= parts.len() {
+    let vkeys = parse_tmux_key(tokens.get(i)?)?;
+    let cmd_tokens = &tokens[i + 1..];
+    if cmd_tokens.is_empty() {
         return None;
     }
-
-    let command = extract_command(&parts[cmd_start..]);
-    Some((vkeys, command))
+    Some((vkeys, extract_command(cmd_tokens)))
 }
 
 /// Extract the effective tmux command from a binding's command + args.
@@ -264,6 +360,11 @@ fn extract_command(tokens: &[&str]) -> String {
         "if-shell" | "run-shell" | "display-menu" => String::new(),
         // Internal tmux actions we don't map to gamepad buttons
         "send-keys" | "send-prefix" => String::new(),
+        // Key table switch: keep the table name (`switch-client -T apps`)
+        "switch-client" => match tokens.iter().position(|&t| t == "-T") {
+            Some(i) if i + 1 < tokens.len() => format!("switch-client -T {}", tokens[i + 1]),
+            _ => "switch-client".into(),
+        },
         cmd => {
             // Direct command, include single-char flags that change semantics
             // e.g., split-window -h, resize-pane -Z
@@ -288,7 +389,7 @@ fn extract_command(tokens: &[&str]) -> String {
 /// Handles:
 /// - Modifier prefixes: `C-a` (Ctrl+A), `M-n` (Alt+N), `S-Left` (Shift+Left)
 /// - Escaped symbols: `\;`, `\#`, `\{`, etc.
-/// - Named keys: `Space`, `Enter`, `Up`, `Down`, etc.
+/// - Named keys: `Space`, `Enter`, `Up`, `Home`, `PPage`, `DC`, etc.
 /// - Single characters: `p`, `n`, `c`, `&`, `[`, etc.
 pub fn parse_tmux_key(s: &str) -> Option<Vec<VKey>> {
     // Handle tmux escape prefix
@@ -296,6 +397,9 @@ pub fn parse_tmux_key(s: &str) -> Option<Vec<VKey>> {
 
     // Handle modifier prefixes
     if let Some(rest) = s.strip_prefix("C-") {
+        if let Some(named) = named_key_to_vkey(rest) {
+            return Some(vec![VKey::Control, named]);
+        }
         let key = single_char_to_vkey(rest)?;
         return Some(vec![VKey::Control, key]);
     }
@@ -357,6 +461,12 @@ fn named_key_to_vkey(s: &str) -> Option<VKey> {
         "Down" => Some(VKey::Down),
         "Left" => Some(VKey::Left),
         "Right" => Some(VKey::Right),
+        "Home" => Some(VKey::Home),
+        "End" => Some(VKey::End),
+        "PPage" | "PageUp" | "PgUp" => Some(VKey::PageUp),
+        "NPage" | "PageDown" | "PgDn" => Some(VKey::PageDown),
+        "DC" => Some(VKey::Delete),
+        "IC" => Some(VKey::Insert),
         _ => None,
     }
 }
@@ -469,6 +579,15 @@ mod tests {
         assert_eq!(keys, vec![VKey::Space]);
     }
 
+    #[test]
+    fn parse_named_navigation_keys() {
+        assert_eq!(parse_tmux_key("PPage").unwrap(), vec![VKey::PageUp]);
+        assert_eq!(parse_tmux_key("NPage").unwrap(), vec![VKey::PageDown]);
+        assert_eq!(parse_tmux_key("Home").unwrap(), vec![VKey::Home]);
+        assert_eq!(parse_tmux_key("DC").unwrap(), vec![VKey::Delete]);
+        assert_eq!(parse_tmux_key("C-NPage").unwrap(), vec![VKey::Control, VKey::PageDown]);
+    }
+
     #[test]
     fn parse_pipe() {
         let keys = parse_tmux_key("|").unwrap();
@@ -517,7 +636,7 @@ mod tests {
     #[test]
     fn binding_line_simple() {
         let line = "bind-key    -T prefix p       previous-window";
-        let (keys, cmd) = parse_binding_line(line).unwrap();
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
         assert_eq!(keys, vec![VKey::P]);
         assert_eq!(cmd, "previous-window");
     }
@@ -525,7 +644,7 @@ mod tests {
     #[test]
     fn binding_line_with_repeat_flag() {
         let line = "bind-key -r -T prefix Up      select-pane -U";
-        let (keys, cmd) = parse_binding_line(line).unwrap();
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
         assert_eq!(keys, vec![VKey::Up]);
         assert_eq!(cmd, "select-pane -U");
     }
@@ -534,7 +653,7 @@ mod tests {
     fn binding_line_confirm_before() {
         let line =
             "bind-key    -T prefix &       confirm-before -p \"kill-window #W? (y/n)\" kill-window";
-        let (keys, cmd) = parse_binding_line(line).unwrap();
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
         assert_eq!(keys, vec![VKey::Shift, VKey::D7]);
         assert_eq!(cmd, "kill-window");
     }
@@ -542,15 +661,43 @@ mod tests {
     #[test]
     fn binding_line_custom_split() {
         let line = "bind-key    -T prefix |       split-window -h";
-        let (keys, cmd) = parse_binding_line(line).unwrap();
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
         assert_eq!(keys, vec![VKey::Shift, VKey::Backslash]);
         assert_eq!(cmd, "split-window -h");
     }
 
+    #[test]
+    fn bind_flags_are_read_before_the_key() {
+        let line = "bind -r -T prefix Up select-pane -U";
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
+        assert_eq!(keys, vec![VKey::Up]);
+        assert_eq!(cmd, "select-pane -U");
+
+        // The table name inside the command isn't the binding's table
+        let line = "bind-key    -T apps p       switch-client -T prefix";
+        assert!(parse_binding_line(line, "prefix").is_none());
+
+        // A quoted note is skipped as one flag argument
+        let line = "bind-key -N \"Select the next window\" -T prefix n next-window";
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
+        assert_eq!(keys, vec![VKey::N]);
+        assert_eq!(cmd, "next-window");
+    }
+
+    #[test]
+    fn only_plain_table_names_are_queried() {
+        assert!(is_table_name("apps"));
+        assert!(is_table_name("copy-mode_vi2"));
+        assert!(!is_table_name(""));
+        assert!(!is_table_name("apps; rm -rf ~"));
+        assert!(!is_table_name("$(id)"));
+        assert!(detect_bindings("a b").is_empty());
+    }
+
     #[test]
     fn conf_bind_simple() {
         let line = "bind | split-window -h";
-        let (keys, cmd) = parse_conf_bind(line).unwrap();
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
         assert_eq!(keys, vec![VKey::Shift, VKey::Backslash]);
         assert_eq!(cmd, "split-window -h");
     }
@@ -558,7 +705,7 @@ mod tests {
     #[test]
     fn conf_bind_with_flag() {
         let line = "bind -r n next-window";
-        let (keys, cmd) = parse_conf_bind(line).unwrap();
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
         assert_eq!(keys, vec![VKey::N]);
         assert_eq!(cmd, "next-window");
     }
@@ -566,7 +713,7 @@ mod tests {
     #[test]
     fn conf_bind_key_form() {
         let line = "bind-key r source-file ~/.tmux.conf";
-        let (keys, cmd) = parse_conf_bind(line).unwrap();
+        let (keys, cmd) = parse_binding_line(line, "prefix").unwrap();
         assert_eq!(keys, vec![VKey::R]);
         assert_eq!(cmd, "source-file");
     }
@@ -575,6 +722,90 @@ mod tests {
     fn conf_bind_non_prefix_table_skipped() {
         // -T copy-mode-vi should be skipped (not prefix table)
         let line = "bind-key -T copy-mode-vi y send-keys -X copy-pipe-and-cancel";
-        assert!(parse_conf_bind(line).is_none());
+        assert!(parse_binding_line(line, "prefix").is_none());
+    }
+
+    #[test]
+    fn binding_line_custom_table() {
+        let line = "bind-key    -T apps g       new-window";
+        let (keys, cmd) = parse_binding_line(line, "apps").unwrap();
+        assert_eq!(keys, vec![VKey::G]);
+        assert_eq!(cmd, "new-window");
+        // Same line is not part of the prefix table
+        assert!(parse_binding_line(line, "prefix").is_none());
+    }
+
+    #[test]
+    fn prefix2_used_when_prefix_is_unset() {
+        let (prefix, prefix2) = parse_server_prefixes("prefix None\nprefix2 C-a\n");
+        assert_eq!(prefix, None);
+        assert_eq!(prefix2, Some(vec![VKey::Control, VKey::A]));
+        let detected = TmuxDetected {
+            prefix,
+            prefix2,
+            actions: HashMap::new(),
+            root_actions: HashMap::new(),
+            key_table: None,
+        };
+        assert_eq!(detected.bound_prefix(), Some(&vec![VKey::Control, VKey::A]));
+
+        // The primary prefix wins while it's bound
+        let (prefix, prefix2) = parse_server_prefixes("prefix C-b\nprefix2 C-a\n");
+        assert_eq!(prefix, Some(vec![VKey::Control, VKey::B]));
+        assert_eq!(prefix2, Some(vec![VKey::Control, VKey::A]));
+    }
+
+    #[test]
+    fn conf_prefixes_last_setting_wins() {
+        let conf = "set -g prefix C-b\nunbind C-b\nset-option -g prefix None\nset -g prefix2 C-a\n# set -g prefix C-x";
+        assert_eq!(parse_conf_prefixes(conf), (None, Some(vec![VKey::Control, VKey::A])));
+        assert_eq!(parse_conf_prefixes("set -g prefix C-a"), (Some(vec![VKey::Control, VKey::A]), None));
+    }
+
+    #[test]
+    fn root_table_bindings_parse_without_prefix() {
+        let line = "bind -n M-h select-pane -L";
+        assert!(parse_binding_line(line, "prefix").is_none());
+        let (keys, cmd) = parse_binding_line(line, "root").unwrap();
+        assert_eq!(keys, vec![VKey::Alt, VKey::H]);
+        assert_eq!(cmd, "select-pane -L");
+
+        let line = "bind-key -n C-Right next-window";
+        let (keys, cmd) = parse_binding_line(line, "root").unwrap();
+        assert_eq!(keys, vec![VKey::Control, VKey::Right]);
+        assert_eq!(cmd, "next-window");
+
+        // `tmux list-keys -T root` form
+        let line = "bind-key    -T root M-h     select-pane -L";
+        let (keys, cmd) = parse_binding_line(line, "root").unwrap();
+        assert_eq!(keys, vec![VKey::Alt, VKey::H]);
+        assert_eq!(cmd, "select-pane -L");
+        assert!(parse_binding_line(line, "prefix").is_none());
+    }
+
+    #[test]
+    fn root_key_lookup_is_separate_from_prefix() {
+        let mut actions = HashMap::new();
+        insert_binding(&mut actions, "next-window".into(), vec![VKey::N]);
+        let mut root_actions = HashMap::new();
+        insert_binding(&mut root_actions, "next-window".into(), vec![VKey::Alt, VKey::N]);
+        let detected = TmuxDetected { prefix: None, prefix2: None, actions, root_actions, key_table: None };
+        assert_eq!(detected.key_for_action("next-window"), Some(&vec![VKey::N]));
+        assert_eq!(detected.root_key_for_action("next-window"), Some(&vec![VKey::Alt, VKey::N]));
+        assert_eq!(detected.root_key_for_action("new-window"), None);
+    }
+
+    #[test]
+    fn conf_bind_custom_and_root_tables() {
+        let line = "bind -T apps n next-window";
+        assert!(parse_binding_line(line, "prefix").is_none());
+        let (keys, cmd) = parse_binding_line(line, "apps").unwrap();
+        assert_eq!(keys, vec![VKey::N]);
+        assert_eq!(cmd, "next-window");
+
+        let line = "bind -n M-a switch-client -T apps";
+        let (keys, cmd) = parse_binding_line(line, "root").unwrap();
+        assert_eq!(keys, vec![VKey::Alt, VKey::A]);
+        assert_eq!(cmd, "switch-client -T apps");
     }
 }
@@ -1,23 +1,34 @@
 /// System tray icon: DualSense PNG silhouette, luminance-tinted per profile.
 /// White on OLED black = Default profile.
 /// Neon green on OLED black = Tmux profile.
+/// Electric violet on OLED black = Agent profile.
+/// A corner badge shows the agent state (blue working, purple thinking,
+/// green done, red error; none when idle), and the tooltip names it.
 ///
 /// Right-click context menu:
 ///   Open Wispr Flow
 ///   Restart
 ///   Enable auto start-up  [toggle]
+///   Profile ▸ Default / Tmux / Agent  [radio; disabled profiles greyed out]
+///   Open config
+///   Reload config
 ///   ──────────────────────
-///   Exit
+///   Exit  [optionally confirmed; turns the controller lights off]
 ///
 /// Runs on a dedicated OS thread with a Win32 message pump.
-/// The async runtime sends [`TrayCmd`] messages to update the icon.
+/// The async runtime sends [`TrayCmd`] messages to update the icon; menu picks
+/// that change controller behavior go back as [`TrayRequest`]s.
 
+use crate::config::{config_file_path, Config, StickMouseConfig, UpdateConfig};
+use crate::input::{BatteryStatus, Button};
 use crate::mapper::Profile;
+use crate::state::{AgentState, AgentSummary};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}, mpsc};
 
 use tray_icon::{Icon, TrayIconBuilder};
-use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tray_icon::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
 
 use windows_sys::Win32::System::Console::GetConsoleWindow;
 use windows_sys::Win32::UI::WindowsAndMessaging::{
@@ -33,31 +44,162 @@ const REG_RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
 pub enum TrayCmd {
     SetProfile(Profile),
     SetStickMode(bool),
+    /// Show (Some) or clear (None) a warning line in the tooltip.
+    Warning(Option<String>),
 }
 
+/// Requests from the tray menu to the input loops (every connected controller).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayRequest {
+    /// Switch the mapper to this profile (the input loop confirms with
+    /// `TrayCmd::SetProfile` once it's active).
+    SetProfile(Profile),
+    /// Sample the resting sticks and save their centers as `[calibration]`.
+    /// Numbered, so that only one input loop takes each request.
+    CalibrateSticks(u64),
+}
+
+/// Profiles in menu order.
+const PROFILES: [Profile; 3] = [Profile::Default, Profile::Tmux, Profile::Agent];
+
+/// Menu label for a profile entry ("Profile: Tmux").
+fn profile_label(profile: Profile) -> String {
+    let name = match profile {
+        Profile::Default => "Default",
+        Profile::Tmux => "Tmux",
+        Profile::Agent => "Agent",
+    };
+    format!("Profile: {name}")
+}
+
+/// Stick mode entry: its label, and whether it can be toggled. A
+/// `[stick_mouse] momentary_button` replaces the toggle while it's set.
+fn stick_item_state(cfg: &StickMouseConfig) -> (String, bool) {
+    match Button::from_name(&cfg.momentary_button) {
+        Some(button) => (format!("Mouse: Left Stick (hold {})", button.name().to_ascii_uppercase()), false),
+        None => ("Mouse: Left Stick".to_string(), true),
+    }
+}
+
+/// Entries of the Focus submenu: "All agents" (None), then each known agent
+/// by id, plus the focused agent if it isn't among them.
+fn focus_choices(agents: &[String], focus: Option<&str>) -> Vec<Option<String>> {
+    let mut choices: Vec<Option<String>> = std::iter::once(None).chain(agents.iter().cloned().map(Some)).collect();
+    if let Some(focus) = focus
+        && !agents.iter().any(|id| id == focus)
+    {
+        choices.push(Some(focus.to_string()));
+    }
+    choices
+}
+
+/// Replace the Focus submenu's entries, checking the one that's `focus`.
+fn rebuild_focus_menu(
+    menu: &Submenu,
+    items: &mut Vec<(Option<String>, CheckMenuItem)>,
+    agents: &[String],
+    focus: Option<&str>,
+) {
+    for (_, item) in items.drain(..) {
+        let _ = menu.remove(&item);
+    }
+    for choice in focus_choices(agents, focus) {
+        let label = choice.as_deref().unwrap_or("All agents");
+        let item = CheckMenuItem::new(label, true, choice.as_deref() == focus, None);
+        menu.append(&item).expect("menu append");
+        items.push((choice, item));
+    }
+}
+
+/// Sorted ids of the tracked agents.
+fn agent_ids(agents: &HashMap<String, AgentState>) -> Vec<String> {
+    let mut ids: Vec<String> = agents.keys().cloned().collect();
+    ids.sort();
+    ids
+}
+
+/// Channels between the tray thread and the async runtime.
+pub struct TrayLinks {
+    /// Latest controller battery reading (tooltip).
+    pub battery_rx: tokio::sync::watch::Receiver<Option<BatteryStatus>>,
+    /// Aggregated agent state (icon badge and tooltip).
+    pub agent_rx: tokio::sync::watch::Receiver<AgentSummary>,
+    /// Per-agent states (the Focus submenu's entries).
+    pub agents_rx: tokio::sync::watch::Receiver<HashMap<String, AgentState>>,
+    /// The agent the lightbar follows; set from the Focus submenu (and `/focus`).
+    pub focus_tx: Arc<tokio::sync::watch::Sender<Option<String>>>,
+    /// Menu picks that change controller behavior, for every input loop.
+    pub requests: tokio::sync::broadcast::Sender<TrayRequest>,
+    /// Receives the re-read config file on "Reload config".
+    pub config_tx: tokio::sync::watch::Sender<Arc<Config>>,
+    /// Set to true on Exit so the output loop can turn the controller dark.
+    pub shutdown_tx: tokio::sync::watch::Sender<bool>,
+}
+
+/// How long Exit waits for the output loop's final all-off report.
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_millis(250);
+
 /// Spawn the tray icon on a background thread. Returns a channel sender.
-pub fn spawn(initial: Profile, mouse_stick_active: Arc<AtomicBool>) -> mpsc::Sender<TrayCmd> {
+/// `profiles` are the enabled profiles (the others are greyed out in the menu);
+/// `confirm_exit` asks before quitting.
+pub fn spawn(
+    initial: Profile,
+    profiles: Vec<Profile>,
+    mouse_stick_active: Arc<AtomicBool>,
+    update_cfg: UpdateConfig,
+    confirm_exit: bool,
+    links: TrayLinks,
+) -> mpsc::Sender<TrayCmd> {
     let (tx, rx) = mpsc::channel();
     std::thread::Builder::new()
         .name("tray".into())
-        .spawn(move || run(rx, initial, mouse_stick_active))
+        .spawn(move || run(rx, initial, profiles, mouse_stick_active, update_cfg, confirm_exit, links))
         .expect("spawn tray thread");
     tx
 }
 
-fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<AtomicBool>) {
+fn run(
+    rx: mpsc::Receiver<TrayCmd>,
+    initial: Profile,
+    profiles: Vec<Profile>,
+    mouse_stick_active: Arc<AtomicBool>,
+    mut update_cfg: UpdateConfig,
+    mut confirm_exit: bool,
+    links: TrayLinks,
+) {
+    let TrayLinks { mut battery_rx, mut agent_rx, mut agents_rx, focus_tx, requests, config_tx, shutdown_tx } = links;
     let auto_start_enabled = is_auto_start_enabled();
     let stick_initially = mouse_stick_active.load(Ordering::Relaxed);
-    let (r, g, b) = profile_color(initial);
-    let icon = make_icon(r, g, b);
+    let mut agent_state = agent_rx.borrow_and_update().state;
+    let icon = status_icon(initial, agent_state);
 
     // Build context menu
     let wispr_item    = MenuItem::new("Open Wispr Flow", true, None);
     let restart_item  = MenuItem::new("Restart", true, None);
-    let update_item   = MenuItem::new("Check for Update", true, None);
+    let update_item   = MenuItem::new("Check for Updates", update_cfg.enabled, None);
     let startup_item  = CheckMenuItem::new("Enable auto start-up", true, auto_start_enabled, None);
-    let stick_item    = CheckMenuItem::new("Mouse: Left Stick", true, stick_initially, None);
+    let (stick_label, stick_toggle) = stick_item_state(&config_tx.borrow().stick_mouse);
+    let stick_item    = CheckMenuItem::new(stick_label, stick_toggle, stick_initially, None);
+    let calibrate_item = MenuItem::new("Calibrate sticks", true, None);
     let log_item      = CheckMenuItem::new("Show Log Window", true, false, None);
+    // Radio group: exactly one entry checked, kept in sync with `profile`
+    let profile_menu  = Submenu::new("Profile", true);
+    let profile_items: Vec<(Profile, CheckMenuItem)> = PROFILES
+        .iter()
+        .map(|&p| (p, CheckMenuItem::new(profile_label(p), profiles.contains(&p), p == initial, None)))
+        .collect();
+    for (_, item) in &profile_items {
+        profile_menu.append(item).expect("menu append");
+    }
+    // Radio group too, rebuilt as agents come and go
+    let focus_menu    = Submenu::new("Focus", true);
+    let mut focus_rx  = focus_tx.subscribe();
+    let mut agents    = agent_ids(&agents_rx.borrow_and_update());
+    let mut focus_items = Vec::new();
+    rebuild_focus_menu(&focus_menu, &mut focus_items, &agents, focus_rx.borrow_and_update().as_deref());
+    let open_cfg_item = MenuItem::new("Open config", true, None);
+    let reload_item   = MenuItem::new("Reload config", true, None);
+    let redetect_item = MenuItem::new("Re-detect bindings", true, None);
     let exit_item     = MenuItem::new("Exit", true, None);
 
     // Capture IDs for event matching
@@ -66,7 +208,11 @@ fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<At
     let update_id  = update_item.id().clone();
     let startup_id = startup_item.id().clone();
     let stick_id   = stick_item.id().clone();
+    let calibrate_id = calibrate_item.id().clone();
     let log_id     = log_item.id().clone();
+    let open_cfg_id = open_cfg_item.id().clone();
+    let reload_id  = reload_item.id().clone();
+    let redetect_id = redetect_item.id().clone();
     let exit_id    = exit_item.id().clone();
 
     let menu = Menu::new();
@@ -74,13 +220,19 @@ fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<At
     menu.append(&restart_item).expect("menu append");
     menu.append(&update_item).expect("menu append");
     menu.append(&startup_item).expect("menu append");
+    menu.append(&profile_menu).expect("menu append");
+    menu.append(&focus_menu).expect("menu append");
     menu.append(&stick_item).expect("menu append");
+    menu.append(&calibrate_item).expect("menu append");
     menu.append(&log_item).expect("menu append");
+    menu.append(&open_cfg_item).expect("menu append");
+    menu.append(&reload_item).expect("menu append");
+    menu.append(&redetect_item).expect("menu append");
     menu.append(&PredefinedMenuItem::separator()).expect("menu append");
     menu.append(&exit_item).expect("menu append");
 
     let tray = match TrayIconBuilder::new()
-        .with_tooltip(format!("DS4CC — {initial}"))
+        .with_tooltip(tooltip(initial, agent_state, None, None))
         .with_icon(icon)
         .with_menu(Box::new(menu))
         .build()
@@ -94,6 +246,17 @@ fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<At
 
     log::info!("Tray icon created (profile: {initial}, auto-start: {auto_start_enabled})");
 
+    let mut profile = initial;
+    // Show `selected` as the one checked profile entry
+    let check_profile = |selected: Profile| {
+        for (p, item) in &profile_items {
+            item.set_checked(*p == selected);
+        }
+    };
+    let mut warning: Option<String> = None;
+    let mut battery: Option<BatteryStatus> = None;
+    let mut calibrations = 0u64;
+
     loop {
         // Pump Win32 messages so the tray icon stays responsive.
         unsafe {
@@ -107,13 +270,47 @@ fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<At
         // Handle menu events
         while let Ok(event) = MenuEvent::receiver().try_recv() {
             if event.id == exit_id {
-                std::process::exit(0);
+                if !confirm_exit || confirm_quit() {
+                    log::info!("Exiting");
+                    // Give the output loop a moment to leave the controller dark
+                    if shutdown_tx.send(true).is_ok() {
+                        std::thread::sleep(SHUTDOWN_GRACE);
+                    }
+                    std::process::exit(0);
+                }
             } else if event.id == restart_id {
                 restart_app();
             } else if event.id == wispr_id {
                 open_wispr_flow();
             } else if event.id == update_id {
-                std::thread::spawn(|| crate::update::check_for_update());
+                let cfg = update_cfg.clone();
+                std::thread::spawn(move || crate::update::check_for_update(&cfg, false));
+            } else if event.id == open_cfg_id {
+                open_config_file();
+            } else if event.id == reload_id {
+                // Live settings pick this up at once; the rest on reconnect.
+                // A file that doesn't parse leaves the running config in place.
+                match Config::try_load_from(&config_file_path()) {
+                    Ok(cfg) => {
+                        update_cfg = cfg.update.clone();
+                        update_item.set_enabled(update_cfg.enabled);
+                        confirm_exit = cfg.tray.confirm_exit;
+                        let (stick_label, stick_toggle) = stick_item_state(&cfg.stick_mouse);
+                        stick_item.set_text(stick_label);
+                        stick_item.set_enabled(stick_toggle);
+                        log::info!("Tray: config reloaded");
+                        let _ = config_tx.send(Arc::new(cfg));
+                    }
+                    Err(e) => {
+                        log::error!("Tray: config not reloaded, keeping the current one: {e}");
+                        crate::notify::show_toast("DS4CC: config not reloaded", &e);
+                    }
+                }
+            } else if event.id == redetect_id {
+                // Detection runs at startup; drop the cache and start over
+                log::info!("Tray: re-detecting key bindings");
+                crate::detect_cache::clear();
+                restart_app();
             } else if event.id == startup_id {
                 // CheckMenuItem auto-toggles on click; is_checked() reflects new state
                 set_auto_start(startup_item.is_checked());
@@ -122,6 +319,25 @@ fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<At
                 mouse_stick_active.store(stick, Ordering::Relaxed);
                 let mode = if stick { "left stick" } else { "touchpad" };
                 log::info!("Mouse cursor mode: {mode}");
+            } else if event.id == calibrate_id {
+                log::info!("Tray: calibrating sticks — leave them at rest");
+                calibrations += 1;
+                let _ = requests.send(TrayRequest::CalibrateSticks(calibrations));
+            } else if let Some(&(picked, _)) = profile_items.iter().find(|(_, item)| event.id == *item.id()) {
+                // Clicking toggled the item; keep the current profile checked
+                // until the input loop confirms the switch
+                check_profile(profile);
+                if picked != profile {
+                    log::info!("Tray: switch profile to {picked}");
+                    let _ = requests.send(TrayRequest::SetProfile(picked));
+                }
+            } else if let Some((picked, _)) = focus_items.iter().find(|(_, item)| event.id == *item.id()) {
+                // The entries are rebuilt (and re-checked) once the focus changes
+                match picked {
+                    Some(id) => log::info!("Tray: focus agent {id}"),
+                    None => log::info!("Tray: focus cleared"),
+                }
+                focus_tx.send_replace(picked.clone());
             } else if event.id == log_id {
                 let show = log_item.is_checked();
                 unsafe {
@@ -144,10 +360,11 @@ fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<At
         }
 
         match rx.try_recv() {
-            Ok(TrayCmd::SetProfile(profile)) => {
-                let (r, g, b) = profile_color(profile);
-                let _ = tray.set_icon(Some(make_icon(r, g, b)));
-                let _ = tray.set_tooltip(Some(format!("DS4CC — {profile}")));
+            Ok(TrayCmd::SetProfile(p)) => {
+                profile = p;
+                check_profile(profile);
+                let _ = tray.set_icon(Some(status_icon(profile, agent_state)));
+                let _ = tray.set_tooltip(Some(tooltip(profile, agent_state, battery, warning.as_deref())));
             }
             Ok(TrayCmd::SetStickMode(stick)) => {
                 stick_item.set_checked(stick);
@@ -155,14 +372,70 @@ fn run(rx: mpsc::Receiver<TrayCmd>, initial: Profile, mouse_stick_active: Arc<At
                 let mode = if stick { "left stick" } else { "touchpad" };
                 log::info!("Mouse cursor mode auto-set: {mode}");
             }
+            Ok(TrayCmd::Warning(w)) => {
+                warning = w;
+                let _ = tray.set_tooltip(Some(tooltip(profile, agent_state, battery, warning.as_deref())));
+            }
             Err(mpsc::TryRecvError::Disconnected) => break,
             Err(mpsc::TryRecvError::Empty) => {}
         }
 
+        // Battery level changed (or the controller disconnected)
+        if battery_rx.has_changed().unwrap_or(false) {
+            battery = *battery_rx.borrow_and_update();
+            let _ = tray.set_tooltip(Some(tooltip(profile, agent_state, battery, warning.as_deref())));
+        }
+
+        // Agent state changed: redraw only when the aggregated state differs
+        // (count-only updates don't touch the icon)
+        if agent_rx.has_changed().unwrap_or(false) {
+            let state = agent_rx.borrow_and_update().state;
+            if state != agent_state {
+                agent_state = state;
+                let _ = tray.set_icon(Some(status_icon(profile, agent_state)));
+                let _ = tray.set_tooltip(Some(tooltip(profile, agent_state, battery, warning.as_deref())));
+            }
+        }
+
+        // Agents came or went, or the focus changed (here or over HTTP)
+        let mut focus_changed = focus_rx.has_changed().unwrap_or(false);
+        if agents_rx.has_changed().unwrap_or(false) {
+            let ids = agent_ids(&agents_rx.borrow_and_update());
+            if ids != agents {
+                agents = ids;
+                focus_changed = true;
+            }
+        }
+        if focus_changed {
+            let focus = focus_rx.borrow_and_update().clone();
+            rebuild_focus_menu(&focus_menu, &mut focus_items, &agents, focus.as_deref());
+        }
+
         std::thread::sleep(std::time::Duration::from_millis(50));
     }
 }
 
+/// Tray tooltip text: the active profile, agent state and battery level,
+/// plus a warning line if one is set.
+fn tooltip(
+    profile: Profile,
+    state: AgentState,
+    battery: Option<BatteryStatus>,
+    warning: Option<&str>,
+) -> String {
+    let mut text = format!("DS4CC — {profile} — {state}");
+    if let Some(b) = battery {
+        text.push_str(&format!(" — 🔋 {}%", b.percent));
+        if b.charging {
+            text.push_str(" (charging)");
+        }
+    }
+    if let Some(w) = warning {
+        text.push_str(&format!("\n⚠ {w}"));
+    }
+    text
+}
+
 // ── Menu actions ──────────────────────────────────────────────────────
 
 fn open_wispr_flow() {
@@ -242,6 +515,23 @@ fn wispr_flow_from_app_paths() -> Option<PathBuf> {
     None
 }
 
+/// Ask "Quit DS4CC?" in a Yes/No dialog. True if the user picked Yes.
+fn confirm_quit() -> bool {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        MessageBoxW, IDYES, MB_ICONQUESTION, MB_YESNO,
+    };
+
+    let text: Vec<u16> = "Quit DS4CC? The controller stops mapping input and its lights turn off."
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let caption: Vec<u16> = APP_NAME.encode_utf16().chain(std::iter::once(0)).collect();
+    let result = unsafe {
+        MessageBoxW(std::ptr::null_mut(), text.as_ptr(), caption.as_ptr(), MB_YESNO | MB_ICONQUESTION)
+    };
+    result == IDYES
+}
+
 /// Show a Yes/No dialog when Wispr Flow can't be found.
 /// "Yes" opens the download page; "No" closes the dialog.
 fn prompt_download_wispr_flow() {
@@ -275,7 +565,26 @@ fn prompt_download_wispr_flow() {
     }
 }
 
+/// Open the config file in its default editor, first writing the commented
+/// default template if there is no file yet.
+fn open_config_file() {
+    let path = PathBuf::from(crate::config::config_file_path());
+    match crate::config::write_default_if_missing(&path) {
+        Ok(true) => log::info!("Created default config at {}", path.display()),
+        Ok(false) => {}
+        Err(e) => {
+            log::error!("Failed to create config file {}: {e}", path.display());
+            return;
+        }
+    }
+    if let Err(e) = std::process::Command::new("explorer.exe").arg(&path).spawn() {
+        log::error!("Failed to open config file: {e}");
+    }
+}
+
 fn restart_app() {
+    // Saved before spawning so the new instance finds it on startup
+    crate::lightbar::persist_last_color();
     if let Ok(exe) = std::env::current_exe() {
         if let Err(e) = std::process::Command::new(&exe).spawn() {
             log::error!("Failed to restart: {e}");
@@ -335,6 +644,47 @@ fn profile_color(profile: Profile) -> (u8, u8, u8) {
     match profile {
         Profile::Default => (255, 255, 255), // white on OLED black
         Profile::Tmux    => (57, 255, 20),   // neon green (#39FF14)
+        Profile::Agent   => (191, 64, 255),  // electric violet (#BF40FF)
+    }
+}
+
+/// Badge color for an agent state (None = no badge while idle).
+fn state_badge_color(state: AgentState) -> Option<(u8, u8, u8)> {
+    match state {
+        AgentState::Idle => None,
+        AgentState::Working => Some((0, 100, 255)),   // blue
+        AgentState::Thinking => Some((160, 0, 255)),  // purple
+        AgentState::Done => Some((0, 255, 0)),        // green
+        AgentState::Error => Some((255, 40, 40)),     // red
+    }
+}
+
+/// Badge radius in pixels (bottom-right corner of the 32×32 icon).
+const BADGE_RADIUS: i32 = 6;
+
+/// Profile-tinted icon with the agent state badge.
+fn status_icon(profile: Profile, state: AgentState) -> Icon {
+    let (r, g, b) = profile_color(profile);
+    make_icon(r, g, b, state_badge_color(state))
+}
+
+/// Paint a filled circle with a black outline in the bottom-right corner.
+fn draw_badge(rgba: &mut [u8], (r, g, b): (u8, u8, u8)) {
+    let size = ICON_SIZE as i32;
+    let (cx, cy) = (size - BADGE_RADIUS - 1, size - BADGE_RADIUS - 1);
+    for y in 0..size {
+        for x in 0..size {
+            let d2 = (x - cx).pow(2) + (y - cy).pow(2);
+            let color = if d2 <= BADGE_RADIUS.pow(2) {
+                [r, g, b]
+            } else if d2 <= (BADGE_RADIUS + 1).pow(2) {
+                [0, 0, 0]
+            } else {
+                continue;
+            };
+            let i = ((y * size + x) * 4) as usize;
+            rgba[i..i + 3].copy_from_slice(&color);
+        }
     }
 }
 
@@ -344,7 +694,9 @@ fn profile_color(profile: Profile) -> (u8, u8, u8) {
 /// Each output pixel is fully opaque — luminance of the source pixel scales
 /// the tint color, so the white silhouette becomes the tint, edges anti-alias
 /// smoothly, and the OLED-black background stays black.
-fn make_icon(r: u8, g: u8, b: u8) -> Icon {
+///
+/// `badge` paints a state dot in the bottom-right corner.
+fn make_icon(r: u8, g: u8, b: u8, badge: Option<(u8, u8, u8)>) -> Icon {
     let img = image::load_from_memory(ICON_PNG)
         .expect("embedded controller PNG is valid")
         .resize_exact(ICON_SIZE, ICON_SIZE, image::imageops::FilterType::Lanczos3)
@@ -361,6 +713,9 @@ fn make_icon(r: u8, g: u8, b: u8) -> Icon {
         let tb = (b as u32 * lum / 255) as u8;
         rgba.extend_from_slice(&[tr, tg, tb, 255]);
     }
+    if let Some(color) = badge {
+        draw_badge(&mut rgba, color);
+    }
 
     Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).expect("valid icon data")
 }
@@ -372,19 +727,96 @@ mod tests {
     #[test]
     fn default_icon_loads() {
         let (r, g, b) = profile_color(Profile::Default);
-        make_icon(r, g, b); // must not panic
+        make_icon(r, g, b, None); // must not panic
     }
 
     #[test]
     fn tmux_icon_loads() {
         let (r, g, b) = profile_color(Profile::Tmux);
-        make_icon(r, g, b); // must not panic
+        make_icon(r, g, b, None); // must not panic
+    }
+
+    #[test]
+    fn agent_icon_loads() {
+        let (r, g, b) = profile_color(Profile::Agent);
+        make_icon(r, g, b, None); // must not panic
+    }
+
+    #[test]
+    fn tooltip_shows_battery_and_warning() {
+        assert_eq!(tooltip(Profile::Default, AgentState::Idle, None, None), "DS4CC — default — idle");
+        let battery = BatteryStatus { percent: 70, charging: false };
+        assert_eq!(
+            tooltip(Profile::Default, AgentState::Idle, Some(battery), None),
+            "DS4CC — default — idle — 🔋 70%"
+        );
+        let charging = BatteryStatus { percent: 45, charging: true };
+        assert_eq!(
+            tooltip(Profile::Tmux, AgentState::Idle, Some(charging), Some("Controller busy")),
+            "DS4CC — tmux — idle — 🔋 45% (charging)\n⚠ Controller busy"
+        );
+    }
+
+    #[test]
+    fn tooltip_shows_profile_and_agent_state() {
+        assert_eq!(tooltip(Profile::Default, AgentState::Working, None, None), "DS4CC — default — working");
+        assert_eq!(tooltip(Profile::Agent, AgentState::Done, None, None), "DS4CC — agent — done");
+    }
+
+    #[test]
+    fn badge_paints_corner_only() {
+        let mut rgba = vec![0u8; (ICON_SIZE * ICON_SIZE * 4) as usize];
+        draw_badge(&mut rgba, (0, 255, 0));
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * ICON_SIZE + x) * 4) as usize;
+            (rgba[i], rgba[i + 1], rgba[i + 2])
+        };
+        let c = ICON_SIZE - BADGE_RADIUS as u32 - 1;
+        assert_eq!(pixel(c, c), (0, 255, 0));
+        assert_eq!(pixel(0, 0), (0, 0, 0));
+        assert_eq!(pixel(c - BADGE_RADIUS as u32 - 3, c), (0, 0, 0));
+        assert!(state_badge_color(AgentState::Idle).is_none());
+        status_icon(Profile::Tmux, AgentState::Working); // must not panic
+    }
+
+    #[test]
+    fn momentary_button_disables_the_stick_toggle() {
+        let mut cfg = StickMouseConfig::default();
+        assert_eq!(stick_item_state(&cfg), ("Mouse: Left Stick".to_string(), true));
+        cfg.momentary_button = "l3".into();
+        assert_eq!(stick_item_state(&cfg), ("Mouse: Left Stick (hold L3)".to_string(), false));
+        // An unknown button falls back to the toggle, as in the mapper
+        cfg.momentary_button = "l4".into();
+        assert!(stick_item_state(&cfg).1);
+    }
+
+    #[test]
+    fn focus_menu_lists_all_agents_then_each_id() {
+        let agents = ["a1".to_string(), "b2".to_string()];
+        assert_eq!(focus_choices(&agents, None), [None, Some("a1".to_string()), Some("b2".to_string())]);
+        // A focused agent that isn't tracked (yet) still shows, checked
+        assert_eq!(focus_choices(&[], Some("gone")), [None, Some("gone".to_string())]);
+        assert_eq!(focus_choices(&agents, Some("a1")).len(), 3);
+    }
+
+    #[test]
+    fn profile_menu_labels_round_trip() {
+        assert_eq!(profile_label(Profile::Default), "Profile: Default");
+        assert_eq!(profile_label(Profile::Tmux), "Profile: Tmux");
+        assert_eq!(profile_label(Profile::Agent), "Profile: Agent");
+        for p in PROFILES {
+            let name = profile_label(p).trim_start_matches("Profile: ").to_string();
+            assert_eq!(Profile::from_name(&name), Some(p));
+        }
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tx.send(TrayRequest::SetProfile(Profile::Tmux)).unwrap();
+        assert_eq!(rx.try_recv(), Ok(TrayRequest::SetProfile(Profile::Tmux)));
     }
 
     #[test]
     fn rgba_has_correct_size() {
         let (r, g, b) = profile_color(Profile::Default);
-        let icon = make_icon(r, g, b);
+        let icon = make_icon(r, g, b, None);
         drop(icon); // Icon::from_rgba already validates size internally
     }
 }
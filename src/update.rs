@@ -1,20 +1,76 @@
 /// Check for updates via GitHub Releases API.
 /// Downloads and runs the installer if a newer version is available.
+/// The repository and installer asset come from `[update]` (forks, mirrors).
 
+use crate::config::{UpdateChannel, UpdateConfig};
+use std::cmp::Ordering;
 use windows_sys::Win32::UI::WindowsAndMessaging::{
     MessageBoxW, MB_ICONINFORMATION, MB_ICONWARNING, MB_YESNO, IDYES,
 };
 
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
-const API_URL: &str = "https://api.github.com/repos/VeigaPunk/DS4CC/releases/latest";
-const INSTALLER_NAME: &str = "DS4CC-Setup.exe";
+const GITHUB_API: &str = "https://api.github.com/repos";
 
-/// Entry point — called from tray thread via `std::thread::spawn`.
-pub fn check_for_update() {
-    match check_inner() {
+/// Releases API base for a configured repo: "owner/name" on github.com, or a
+/// full repository API URL used as-is. None if the value is malformed.
+fn repo_api_base(repo: &str) -> Option<String> {
+    let repo = repo.trim().trim_end_matches('/');
+    if repo.starts_with("https://") || repo.starts_with("http://") {
+        return Some(repo.to_string());
+    }
+    let (owner, name) = repo.split_once('/')?;
+    let valid = |s: &str| !s.is_empty() && !s.contains(['/', ' ', '?', '#']);
+    if !valid(owner) || !valid(name) {
+        return None;
+    }
+    Some(format!("{GITHUB_API}/{owner}/{name}"))
+}
+
+/// "Latest release" API URL for a configured repo.
+fn api_url(repo: &str) -> Option<String> {
+    repo_api_base(repo).map(|base| format!("{base}/releases/latest"))
+}
+
+/// All-releases API URL (newest first, prereleases included).
+fn releases_url(repo: &str) -> Option<String> {
+    repo_api_base(repo).map(|base| format!("{base}/releases"))
+}
+
+/// Outcome of comparing the latest release against the running version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateDecision {
+    /// Same version as the release (or the versions can't be compared).
+    UpToDate,
+    /// The release is newer — offer to install it.
+    UpdateAvailable,
+    /// The running build is newer than the release (dev or prerelease build).
+    Newer,
+}
+
+/// Decide what to do given the release version and the running version.
+pub fn decide(remote: &str, current: &str) -> UpdateDecision {
+    match compare_versions(remote, current) {
+        Some(Ordering::Greater) => UpdateDecision::UpdateAvailable,
+        Some(Ordering::Less) => UpdateDecision::Newer,
+        Some(Ordering::Equal) | None => UpdateDecision::UpToDate,
+    }
+}
+
+/// Entry point — called on its own thread (tray click or startup check).
+/// `silent`: background check — only speaks up when an update is available;
+/// a manual check (`false`) always reports the outcome, errors included.
+pub fn check_for_update(cfg: &UpdateConfig, silent: bool) {
+    if !cfg.enabled {
+        log::info!("Update checks disabled ([update] enabled = false)");
+        return;
+    }
+    match check_inner(cfg, silent) {
         Ok(()) => {}
         Err(e) => {
             log::error!("Update check failed: {e}");
+            if silent {
+                return;
+            }
             show_msg(
                 "Could not check for updates.\nCheck your internet connection.",
                 "Update Check Failed",
@@ -24,45 +80,65 @@ pub fn check_for_update() {
     }
 }
 
-fn check_inner() -> Result<(), Box<dyn std::error::Error>> {
-    log::info!("Checking for updates...");
+fn check_inner(cfg: &UpdateConfig, silent: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let url = match cfg.channel {
+        UpdateChannel::Stable => api_url(&cfg.repo),
+        UpdateChannel::Prerelease => releases_url(&cfg.repo),
+    };
+    let url = url
+        .ok_or_else(|| format!("invalid [update] repo '{}' (expected owner/name)", cfg.repo))?;
+    let installer_name = cfg.installer_name.as_str();
+    log::info!("Checking for updates ({url})...");
 
-    let body: String = ureq::get(API_URL)
+    let body: String = ureq::get(&url)
         .header("User-Agent", "DS4CC")
         .header("Accept", "application/vnd.github+json")
         .call()?
         .body_mut()
         .read_to_string()?;
 
-    let json: serde_json::Value = serde_json::from_str(&body)?;
+    let response: serde_json::Value = serde_json::from_str(&body)?;
+    let json = match cfg.channel {
+        UpdateChannel::Stable => &response,
+        UpdateChannel::Prerelease => response
+            .as_array()
+            .and_then(|releases| newest_release(releases))
+            .ok_or("no releases found")?,
+    };
 
     let tag = json["tag_name"].as_str().ok_or("missing tag_name")?;
     let remote_version = tag.strip_prefix('v').unwrap_or(tag);
 
-    if !is_newer(remote_version, CURRENT_VERSION) {
-        log::info!("Already on latest version (v{CURRENT_VERSION})");
-        show_msg(
-            &format!("You're on the latest version (v{CURRENT_VERSION})."),
-            "DS4CC Update",
-            MB_ICONINFORMATION,
-        );
-        return Ok(());
+    match decide(remote_version, CURRENT_VERSION) {
+        UpdateDecision::UpdateAvailable => {}
+        UpdateDecision::UpToDate => {
+            log::info!("Already on latest version (v{CURRENT_VERSION})");
+            if !silent {
+                show_msg(
+                    &format!("You're up to date (v{CURRENT_VERSION})."),
+                    "DS4CC Update",
+                    MB_ICONINFORMATION,
+                );
+            }
+            return Ok(());
+        }
+        UpdateDecision::Newer => {
+            log::info!("Running v{CURRENT_VERSION}, newer than the latest release (v{remote_version})");
+            if !silent {
+                show_msg(
+                    &format!(
+                        "You're up to date (v{CURRENT_VERSION}).\n\nThe latest release is v{remote_version}."
+                    ),
+                    "DS4CC Update",
+                    MB_ICONINFORMATION,
+                );
+            }
+            return Ok(());
+        }
     }
 
     // Find installer download URL
-    let download_url = json["assets"]
-        .as_array()
-        .and_then(|assets| {
-            assets.iter().find_map(|a| {
-                let name = a["name"].as_str()?;
-                if name == INSTALLER_NAME {
-                    a["browser_download_url"].as_str().map(String::from)
-                } else {
-                    None
-                }
-            })
-        })
-        .ok_or("installer asset not found in release")?;
+    let download_url = asset_url(json, installer_name).ok_or("installer asset not found in release")?;
 
     log::info!("New version available: v{remote_version} (current: v{CURRENT_VERSION})");
 
@@ -75,7 +151,7 @@ fn check_inner() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Download installer to %TEMP%
-    let temp = std::env::temp_dir().join(INSTALLER_NAME);
+    let temp = std::env::temp_dir().join(installer_name);
     log::info!("Downloading installer to {}", temp.display());
 
     let bytes = ureq::get(&download_url)
@@ -86,33 +162,202 @@ fn check_inner() -> Result<(), Box<dyn std::error::Error>> {
         .limit(50 * 1024 * 1024)
         .read_to_vec()?;
 
-    std::fs::write(&temp, &bytes)?;
     log::info!("Installer downloaded ({} bytes)", bytes.len());
 
+    if cfg.verify_checksum {
+        let expected = expected_checksum(json, installer_name);
+        let actual = crate::sha256::digest(&bytes);
+        match checksum_verdict(expected.as_deref(), &actual) {
+            ChecksumVerdict::Verified => log::info!("Installer checksum verified ({})", crate::sha256::to_hex(&actual)),
+            ChecksumVerdict::Unverified => log::warn!(
+                "No SHA-256 published for {installer_name}; running it unverified (SHA-256 {})",
+                crate::sha256::to_hex(&actual)
+            ),
+            ChecksumVerdict::Mismatch => {
+                log::error!(
+                    "Installer checksum mismatch: expected {}, got {}",
+                    expected.as_deref().unwrap_or_default(),
+                    crate::sha256::to_hex(&actual)
+                );
+                show_msg(
+                    "The downloaded installer's SHA-256 checksum doesn't match the one published \
+                     with the release.\n\nThe installer was not run.",
+                    "DS4CC Update",
+                    MB_ICONWARNING,
+                );
+                return Ok(());
+            }
+        }
+    }
+
+    std::fs::write(&temp, &bytes)?;
+
     // Run installer and exit
     log::info!("Launching installer...");
+    crate::lightbar::persist_last_color();
     std::process::Command::new(&temp).spawn()?;
     std::process::exit(0);
 }
 
-/// Returns true if `remote` is newer than `current` (semver comparison).
-fn is_newer(remote: &str, current: &str) -> bool {
-    let parse = |v: &str| -> Option<(u32, u32, u32)> {
-        let parts: Vec<&str> = v.split('.').collect();
-        if parts.len() != 3 {
-            return None;
+/// What the installer's published checksum says about the download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChecksumVerdict {
+    /// The published digest matches.
+    Verified,
+    /// The release publishes no digest (older releases): run it, with a warning.
+    Unverified,
+    /// The published digest differs: don't run it.
+    Mismatch,
+}
+
+/// Compare the download's digest against the one the release publishes, if any.
+fn checksum_verdict(expected: Option<&str>, actual: &[u8; 32]) -> ChecksumVerdict {
+    match expected {
+        None => ChecksumVerdict::Unverified,
+        Some(expected) if crate::sha256::matches_hex(expected, actual) => ChecksumVerdict::Verified,
+        Some(_) => ChecksumVerdict::Mismatch,
+    }
+}
+
+/// Download URL of the release asset named `name`.
+fn asset_url(release: &serde_json::Value, name: &str) -> Option<String> {
+    release["assets"].as_array()?.iter().find_map(|a| {
+        if a["name"].as_str()? == name {
+            a["browser_download_url"].as_str().map(String::from)
+        } else {
+            None
+        }
+    })
+}
+
+/// Expected SHA-256 of the installer: from a "<installer>.sha256" asset if the
+/// release has one, otherwise from the release notes.
+fn expected_checksum(release: &serde_json::Value, installer_name: &str) -> Option<String> {
+    if let Some(url) = asset_url(release, &format!("{installer_name}.sha256")) {
+        match fetch_text(&url) {
+            Ok(text) => {
+                if let Some(hex) = find_checksum(&text, installer_name) {
+                    return Some(hex);
+                }
+                log::warn!("{installer_name}.sha256 has no usable digest");
+            }
+            Err(e) => log::warn!("Could not download {installer_name}.sha256: {e}"),
         }
-        Some((
-            parts[0].parse().ok()?,
-            parts[1].parse().ok()?,
-            parts[2].parse().ok()?,
-        ))
+    }
+    find_checksum(release["body"].as_str()?, installer_name)
+}
+
+fn fetch_text(url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(ureq::get(url)
+        .header("User-Agent", "DS4CC")
+        .call()?
+        .body_mut()
+        .read_to_string()?)
+}
+
+/// Find a SHA-256 hex digest for `installer_name` in a checksum listing or
+/// release notes. Prefers a line naming the installer ("<hex>  DS4CC-Setup.exe",
+/// sha256sum style); falls back to the only digest present if there is exactly one.
+fn find_checksum(text: &str, installer_name: &str) -> Option<String> {
+    let is_digest = |w: &str| w.len() == 64 && w.bytes().all(|b| b.is_ascii_hexdigit());
+    let digests_in = |line: &str| -> Vec<String> {
+        line.split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| is_digest(w))
+            .map(str::to_ascii_lowercase)
+            .collect()
     };
 
-    match (parse(remote), parse(current)) {
-        (Some(r), Some(c)) => r > c,
-        _ => false,
+    let installer = installer_name.to_ascii_lowercase();
+    if let Some(hex) = text
+        .lines()
+        .filter(|line| line.to_ascii_lowercase().contains(&installer))
+        .find_map(|line| digests_in(line).into_iter().next())
+    {
+        return Some(hex);
     }
+
+    let mut all: Vec<String> = text.lines().flat_map(digests_in).collect();
+    all.dedup();
+    if all.len() == 1 { all.pop() } else { None }
+}
+
+/// The newest non-draft release by version (prereleases included).
+fn newest_release(releases: &[serde_json::Value]) -> Option<&serde_json::Value> {
+    releases
+        .iter()
+        .filter(|r| !r["draft"].as_bool().unwrap_or(false))
+        .filter_map(|r| {
+            let tag = r["tag_name"].as_str()?;
+            Some((Version::parse(tag)?, r))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, r)| r)
+}
+
+/// Prerelease identifier: numeric ones sort below alphanumeric ones.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum PreId {
+    Num(u64),
+    Alpha(String),
+}
+
+/// Minimal semantic version: MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version {
+    core: (u64, u64, u64),
+    /// Empty for a normal release.
+    pre: Vec<PreId>,
+}
+
+impl Version {
+    fn parse(v: &str) -> Option<Self> {
+        let v = v.trim();
+        let v = v.strip_prefix('v').unwrap_or(v);
+        let v = v.split_once('+').map_or(v, |(v, _build)| v);
+        let (core, pre) = match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (v, None),
+        };
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let core = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) => (major, minor, patch),
+            _ => return None,
+        };
+        let pre = match pre {
+            Some(pre) => pre
+                .split('.')
+                .map(|id| match id {
+                    "" => None,
+                    _ => Some(id.parse().map_or_else(|_| PreId::Alpha(id.to_string()), PreId::Num)),
+                })
+                .collect::<Option<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        Some(Self { core, pre })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.core.cmp(&other.core).then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+            // A release outranks any prerelease of the same core version
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.pre.cmp(&other.pre),
+        })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compare two version strings. None if either isn't valid semver.
+fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    Some(Version::parse(a)?.cmp(&Version::parse(b)?))
 }
 
 fn show_msg(text: &str, caption: &str, flags: u32) {
@@ -141,6 +386,29 @@ fn ask_yes_no(text: &str, caption: &str) -> bool {
 mod tests {
     use super::*;
 
+    fn is_newer(remote: &str, current: &str) -> bool {
+        decide(remote, current) == UpdateDecision::UpdateAvailable
+    }
+
+    #[test]
+    fn api_url_from_repo() {
+        assert_eq!(
+            api_url("VeigaPunk/DS4CC").as_deref(),
+            Some("https://api.github.com/repos/VeigaPunk/DS4CC/releases/latest")
+        );
+        assert_eq!(
+            api_url(" someone/ds4cc-fork/ ").as_deref(),
+            Some("https://api.github.com/repos/someone/ds4cc-fork/releases/latest")
+        );
+        assert_eq!(
+            api_url("https://ghe.example.com/api/v3/repos/tools/ds4cc").as_deref(),
+            Some("https://ghe.example.com/api/v3/repos/tools/ds4cc/releases/latest")
+        );
+        assert_eq!(api_url("DS4CC"), None);
+        assert_eq!(api_url("a/b/c"), None);
+        assert_eq!(api_url("/DS4CC"), None);
+    }
+
     #[test]
     fn version_newer() {
         assert!(is_newer("2.7.0", "2.6.0"));
@@ -154,4 +422,80 @@ mod tests {
         assert!(!is_newer("2.5.0", "2.6.0"));
         assert!(!is_newer("1.0.0", "2.6.0"));
     }
+
+    #[test]
+    fn update_decision_from_version_pairs() {
+        assert_eq!(decide("2.6.5", "2.6.5"), UpdateDecision::UpToDate);
+        assert_eq!(decide("v2.6.5", "2.6.5"), UpdateDecision::UpToDate);
+        assert_eq!(decide("2.7.0", "2.6.5"), UpdateDecision::UpdateAvailable);
+        assert_eq!(decide("2.7.0", "2.7.0-beta.3"), UpdateDecision::UpdateAvailable);
+        assert_eq!(decide("2.6.5", "2.7.0"), UpdateDecision::Newer);
+        assert_eq!(decide("2.7.0-beta.1", "2.7.0"), UpdateDecision::Newer);
+        assert_eq!(decide("nightly", "2.6.5"), UpdateDecision::UpToDate);
+    }
+
+    #[test]
+    fn version_compare_numeric_and_prerelease() {
+        assert_eq!(compare_versions("2.1.0", "2.0.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("2.10.0", "2.9.0"), Some(Ordering::Greater));
+        // Prerelease sorts below its release, above the previous release
+        assert_eq!(compare_versions("2.7.0-beta.1", "2.7.0"), Some(Ordering::Less));
+        assert_eq!(compare_versions("2.7.0-beta.1", "2.6.5"), Some(Ordering::Greater));
+        // Identifiers: numeric by value, numeric < alphanumeric, longer set wins a tie
+        assert_eq!(compare_versions("2.7.0-beta.10", "2.7.0-beta.2"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("2.7.0-alpha", "2.7.0-beta"), Some(Ordering::Less));
+        assert_eq!(compare_versions("2.7.0-1", "2.7.0-rc"), Some(Ordering::Less));
+        assert_eq!(compare_versions("2.7.0-rc", "2.7.0-rc.1"), Some(Ordering::Less));
+        // Build metadata is ignored
+        assert_eq!(compare_versions("2.7.0+abc", "2.7.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("2.7", "2.6.0"), None);
+        assert!(!is_newer("garbage", "2.6.0"));
+    }
+
+    #[test]
+    fn find_checksum_in_listing_and_notes() {
+        let hex = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        // sha256sum output
+        assert_eq!(find_checksum(&format!("{hex}  DS4CC-Setup.exe\n"), "DS4CC-Setup.exe").as_deref(), Some(hex));
+        // Bare digest file
+        assert_eq!(find_checksum(&hex.to_uppercase(), "DS4CC-Setup.exe").as_deref(), Some(hex));
+        // Release notes listing several files: pick the installer's line
+        let other = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        let notes = format!("## Checksums\n- `ds4cc.zip`: `{other}`\n- `DS4CC-Setup.exe`: `{hex}`\n");
+        assert_eq!(find_checksum(&notes, "DS4CC-Setup.exe").as_deref(), Some(hex));
+        // Several digests, none naming the installer: ambiguous
+        let notes = format!("{other}\n{hex}\n");
+        assert_eq!(find_checksum(&notes, "DS4CC-Setup.exe"), None);
+        assert_eq!(find_checksum("no checksums here", "DS4CC-Setup.exe"), None);
+    }
+
+    #[test]
+    fn missing_checksum_is_unverified_not_refused() {
+        let actual = crate::sha256::digest(b"installer");
+        let hex = crate::sha256::to_hex(&actual);
+        assert_eq!(checksum_verdict(Some(&hex), &actual), ChecksumVerdict::Verified);
+        assert_eq!(checksum_verdict(Some(&"0".repeat(64)), &actual), ChecksumVerdict::Mismatch);
+
+        // A release without a .sha256 asset or a digest in its notes
+        let release = serde_json::json!({
+            "body": "Bug fixes",
+            "assets": [{ "name": "DS4CC-Setup.exe", "browser_download_url": "https://example.com/DS4CC-Setup.exe" }],
+        });
+        let expected = expected_checksum(&release, "DS4CC-Setup.exe");
+        assert_eq!(expected, None);
+        assert_eq!(checksum_verdict(expected.as_deref(), &actual), ChecksumVerdict::Unverified);
+    }
+
+    #[test]
+    fn newest_release_includes_prereleases_and_skips_drafts() {
+        let releases: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[
+                {"tag_name": "v2.8.0", "draft": true},
+                {"tag_name": "v2.7.0-beta.2", "prerelease": true},
+                {"tag_name": "v2.6.5"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(newest_release(&releases).unwrap()["tag_name"], "v2.7.0-beta.2");
+    }
 }
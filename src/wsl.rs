@@ -1,20 +1,96 @@
 /// Shared WSL command execution utility.
 ///
 /// Used by `tmux_detect`, `codex_poll`, and `setup` to run commands in WSL.
+/// Commands run in `[wsl] distro` when set ([`configure`]), else the default
+/// distro, and are killed after `[wsl] command_timeout_ms` — a distro that
+/// hangs while starting up fails the command instead of stalling startup.
+
+use crate::config::WslConfig;
+use std::io::Read;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// `[wsl]` settings, set once at startup.
+static SETTINGS: OnceLock<WslConfig> = OnceLock::new();
+
+/// How often a running command is checked for exit.
+const EXIT_POLL: Duration = Duration::from_millis(10);
+
+/// Run every later WSL command with `cfg`'s distro and timeout.
+/// Only the first call counts; the settings are fixed for the process.
+pub fn configure(cfg: &WslConfig) {
+    let _ = SETTINGS.set(WslConfig { distro: cfg.distro.trim().to_string(), ..cfg.clone() });
+}
+
+fn settings() -> &'static WslConfig {
+    SETTINGS.get_or_init(WslConfig::default)
+}
+
+/// Arguments for `wsl.exe`: `-d <distro>` when one is set, then `-e` and `command`.
+fn wsl_args(distro: &str, command: &[&str]) -> Vec<String> {
+    let mut args = Vec::new();
+    if !distro.is_empty() {
+        args.extend(["-d".to_string(), distro.to_string()]);
+    }
+    args.push("-e".to_string());
+    args.extend(command.iter().map(|arg| arg.to_string()));
+    args
+}
+
+/// `wsl.exe` running `command` in the configured distro.
+fn wsl_command(command: &[&str]) -> Command {
+    let mut wsl = Command::new("wsl");
+    wsl.args(wsl_args(&settings().distro, command));
+    wsl
+}
+
+/// Wait for `child` to exit, collecting its stdout (if piped). Past
+/// `timeout` the child is killed and None is returned.
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> Option<(ExitStatus, Vec<u8>)> {
+    // Drain stdout on its own thread so a chatty command can't block on a full pipe
+    let stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut out = Vec::new();
+        if let Some(mut stdout) = stdout {
+            let _ = stdout.read_to_end(&mut out);
+        }
+        out
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(EXIT_POLL),
+            Ok(None) => {
+                log::warn!("WSL command timed out after {}ms", timeout.as_millis());
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Err(_) => return None,
+        }
+    };
+    Some((status, reader.join().unwrap_or_default()))
+}
 
 /// Run a command in WSL via `wsl -e bash -lc` (login shell, PATH-aware).
-/// Returns stdout on success, None on failure.
+/// Returns stdout on success, None on failure or timeout.
 pub fn run_wsl(cmd: &str) -> Option<String> {
-    let output = std::process::Command::new("wsl")
-        .args(["-e", "bash", "-lc", cmd])
-        .output()
+    let child = wsl_command(&["bash", "-lc", cmd])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
         .ok()?;
 
-    if !output.status.success() {
+    let (status, stdout) = wait_with_timeout(child, settings().command_timeout())?;
+    if !status.success() {
         return None;
     }
 
-    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    Some(String::from_utf8_lossy(&stdout).into_owned())
 }
 
 /// Write `content` to a WSL path (e.g. `~/.claude/hooks/ds4cc-state.sh`).
@@ -24,7 +100,6 @@ pub fn run_wsl(cmd: &str) -> Option<String> {
 /// Returns true if the file was written successfully.
 pub fn wsl_write(wsl_path: &str, content: &str) -> bool {
     use std::io::Write;
-    use std::process::{Command, Stdio};
 
     // Bash expands $HOME but not ~ inside double-quoted command substitutions,
     // so we convert ~ → $HOME for reliable expansion.
@@ -33,8 +108,7 @@ pub fn wsl_write(wsl_path: &str, content: &str) -> bool {
         r#"mkdir -p "$(dirname "{expanded}")" && cat > "{expanded}""#
     );
 
-    let mut child = match Command::new("wsl")
-        .args(["-e", "bash", "-c", &cmd])
+    let mut child = match wsl_command(&["bash", "-c", &cmd])
         .stdin(Stdio::piped())
         .spawn()
     {
@@ -47,5 +121,43 @@ pub fn wsl_write(wsl_path: &str, content: &str) -> bool {
         drop(stdin); // close pipe → EOF → cat finishes
     }
 
-    child.wait().map(|s| s.success()).unwrap_or(false)
+    wait_with_timeout(child, settings().command_timeout()).is_some_and(|(status, _)| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distro_is_passed_only_when_set() {
+        assert_eq!(wsl_args("", &["bash", "-lc", "echo ok"]), ["-e", "bash", "-lc", "echo ok"]);
+        assert_eq!(
+            wsl_args("Ubuntu-24.04", &["bash", "-lc", "echo ok"]),
+            ["-d", "Ubuntu-24.04", "-e", "bash", "-lc", "echo ok"]
+        );
+    }
+
+    /// `windows` or `unix` (program + arguments) for the current platform.
+    fn command(windows: &[&str], unix: &[&str]) -> Command {
+        let argv = if cfg!(windows) { windows } else { unix };
+        let mut command = Command::new(argv[0]);
+        command.args(&argv[1..]).stdout(Stdio::piped());
+        command
+    }
+
+    #[test]
+    fn command_past_the_timeout_is_killed() {
+        let Ok(child) = command(&["ping", "-n", "6", "127.0.0.1"], &["sleep", "5"]).spawn() else {
+            eprintln!("Skipping timeout test: no sleep/ping command");
+            return;
+        };
+        let started = Instant::now();
+        assert!(wait_with_timeout(child, Duration::from_millis(100)).is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let child = command(&["cmd", "/C", "echo ok"], &["echo", "ok"]).spawn().unwrap();
+        let (status, stdout) = wait_with_timeout(child, Duration::from_secs(10)).unwrap();
+        assert!(status.success());
+        assert_eq!(String::from_utf8_lossy(&stdout).trim(), "ok");
+    }
 }
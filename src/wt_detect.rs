@@ -11,13 +11,17 @@
 ///   1. Auto-detected binding  →  2. Hardcoded default  →  3. Direct combo parse
 
 use crate::mapper::{parse_key_combo, VKey};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Auto-detected Windows Terminal keybindings.
-#[derive(Debug, Clone)]
+/// Auto-detected Windows Terminal keybindings (serializable for the detection cache).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WtDetected {
     /// Map of WT action name → resolved key combo.
     actions: HashMap<String, Vec<VKey>>,
+    /// Map of `sendInput` action name / id → the literal text it sends.
+    #[serde(default)]
+    texts: HashMap<String, String>,
 }
 
 impl WtDetected {
@@ -25,6 +29,16 @@ impl WtDetected {
     pub fn key_for_action(&self, action: &str) -> Option<&Vec<VKey>> {
         self.actions.get(action)
     }
+
+    /// Every detected action → key combo (for `--dump-detect`).
+    pub fn actions(&self) -> &HashMap<String, Vec<VKey>> {
+        &self.actions
+    }
+
+    /// Look up the text of a `sendInput` action by its `name` or `id`.
+    pub fn text_for_action(&self, action: &str) -> Option<&str> {
+        self.texts.get(action).map(String::as_str)
+    }
 }
 
 /// Detect Windows Terminal keybindings from settings.json.
@@ -35,28 +49,29 @@ pub fn detect() -> Option<WtDetected> {
 
     let json_str = read_settings_json()?;
     let actions = parse_settings(&json_str);
+    let texts = parse_send_inputs(&json_str);
     let elapsed = start.elapsed();
 
     log::info!(
-        "Detected {} Windows Terminal keybinds (took {elapsed:?})",
-        actions.len()
+        "Detected {} Windows Terminal keybinds and {} sendInput actions (took {elapsed:?})",
+        actions.len(),
+        texts.len()
     );
 
-    Some(WtDetected { actions })
+    Some(WtDetected { actions, texts })
 }
 
 // ── Settings file discovery ───────────────────────────────────────────
 
-/// Try to read settings.json from known Windows Terminal installation paths.
-///
-/// Checks (in order):
+/// Known Windows Terminal settings.json locations, in lookup order:
 ///   1. Stable release (Microsoft Store)
 ///   2. Preview release (Microsoft Store)
 ///   3. Unpackaged / winget install
-fn read_settings_json() -> Option<String> {
-    let local_app_data = std::env::var("LOCALAPPDATA").ok()?;
-
-    let candidates = [
+fn settings_candidates() -> Vec<String> {
+    let Ok(local_app_data) = std::env::var("LOCALAPPDATA") else {
+        return Vec::new();
+    };
+    vec![
         format!(
             r"{local_app_data}\Packages\Microsoft.WindowsTerminal_8wekyb3d8bbwe\LocalState\settings.json"
         ),
@@ -64,9 +79,21 @@ fn read_settings_json() -> Option<String> {
             r"{local_app_data}\Packages\Microsoft.WindowsTerminalPreview_8wekyb3d8bbwe\LocalState\settings.json"
         ),
         format!(r"{local_app_data}\Microsoft\Windows Terminal\settings.json"),
-    ];
+    ]
+}
 
-    for path in &candidates {
+/// Modification time (Unix seconds) of the settings.json `detect` would read,
+/// used to key the detection cache. None if there is none.
+pub fn settings_mtime() -> Option<u64> {
+    settings_candidates().iter().find_map(|path| {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        modified.duration_since(std::time::UNIX_EPOCH).ok().map(|d| d.as_secs())
+    })
+}
+
+/// Read settings.json from the first known location that has one.
+fn read_settings_json() -> Option<String> {
+    for path in &settings_candidates() {
         match std::fs::read_to_string(path) {
             Ok(content) => {
                 log::debug!("Found Windows Terminal settings at: {path}");
@@ -102,13 +129,7 @@ fn parse_settings(json: &str) -> HashMap<String, Vec<VKey>> {
 
     // Merge both "actions" and "keybindings" — not either/or.
     // Modern WT may have "actions": [] (empty) with all customizations in "keybindings".
-    let mut all_entries: Vec<&serde_json::Value> = Vec::new();
-    if let Some(arr) = value.get("actions").and_then(|v| v.as_array()) {
-        all_entries.extend(arr.iter());
-    }
-    if let Some(arr) = value.get("keybindings").and_then(|v| v.as_array()) {
-        all_entries.extend(arr.iter());
-    }
+    let all_entries = all_entries(&value);
 
     if all_entries.is_empty() {
         log::debug!("No 'actions' or 'keybindings' entries in Windows Terminal settings.json");
@@ -140,6 +161,44 @@ fn parse_settings(json: &str) -> HashMap<String, Vec<VKey>> {
     actions
 }
 
+/// Every entry of the `"actions"` and `"keybindings"` arrays, in that order.
+fn all_entries(value: &serde_json::Value) -> Vec<&serde_json::Value> {
+    ["actions", "keybindings"]
+        .iter()
+        .filter_map(|key| value.get(key).and_then(|v| v.as_array()))
+        .flatten()
+        .collect()
+}
+
+/// Extract the literal text of `sendInput` actions, keyed by the entry's
+/// `name` and `id` (whichever it has), so a button can be set to either.
+///
+/// `{"command": {"action": "sendInput", "input": "clear\r"}, "name": "Clear"}`
+/// → `"Clear"` → `"clear\r"`. Entries need no `keys`: DS4CC types the text.
+fn parse_send_inputs(json: &str) -> HashMap<String, String> {
+    let mut texts = HashMap::new();
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return texts;
+    };
+    for entry in all_entries(&value) {
+        let Some(command) = entry.get("command").and_then(|c| c.as_object()) else {
+            continue;
+        };
+        if command.get("action").and_then(|a| a.as_str()) != Some("sendInput") {
+            continue;
+        }
+        let Some(input) = command.get("input").and_then(|i| i.as_str()) else {
+            continue;
+        };
+        for key in ["name", "id"] {
+            if let Some(label) = entry.get(key).and_then(|v| v.as_str()) {
+                texts.entry(label.to_string()).or_insert_with(|| input.to_string());
+            }
+        }
+    }
+    texts
+}
+
 /// Extract the first usable key string from an action entry.
 ///
 /// `"keys"` can be:
@@ -173,7 +232,8 @@ fn get_keys_str(entry: &serde_json::Value) -> Option<String> {
 ///   3. `"id": "Terminal.PrevTab"`          (modern ≥1.18, normalized via [`normalize_wt_id`])
 ///
 /// Object-style commands with extra fields (index, profile, etc.) are stored
-/// under the bare action name.
+/// under the bare action name, except `moveFocus`, which is stored per
+/// direction (`"direction": "up"` → `moveFocusUp`).
 fn get_action_name(entry: &serde_json::Value) -> Option<String> {
     // Try "command" field first (works in both old and new WT formats)
     if let Some(cmd) = entry.get("command") {
@@ -182,6 +242,11 @@ fn get_action_name(entry: &serde_json::Value) -> Option<String> {
         }
         if let Some(obj) = cmd.as_object() {
             if let Some(action) = obj.get("action").and_then(|v| v.as_str()) {
+                // moveFocus is one action per direction: "moveFocusUp", ...
+                if action == "moveFocus" {
+                    let direction = obj.get("direction").and_then(|v| v.as_str())?;
+                    return Some(format!("{action}{}", capitalize(direction)));
+                }
                 return Some(action.to_string());
             }
         }
@@ -199,6 +264,12 @@ fn get_action_name(entry: &serde_json::Value) -> Option<String> {
     None
 }
 
+/// `"up"` → `"Up"`.
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+}
+
 /// Map well-known Windows Terminal internal action IDs to the short camelCase
 /// names used in DS4CC config and `default_key_for_wt_action()`.
 fn normalize_wt_id(id: &str) -> Option<&'static str> {
@@ -213,6 +284,10 @@ fn normalize_wt_id(id: &str) -> Option<&'static str> {
         "Terminal.OpenNewWindow"                     => Some("newWindow"),
         "Terminal.DuplicatePaneAuto"                 => Some("duplicatePane"),
         "Terminal.SplitPane"                         => Some("splitDown"),
+        "Terminal.MoveFocusUp"                       => Some("moveFocusUp"),
+        "Terminal.MoveFocusDown"                     => Some("moveFocusDown"),
+        "Terminal.MoveFocusLeft"                     => Some("moveFocusLeft"),
+        "Terminal.MoveFocusRight"                    => Some("moveFocusRight"),
         // Search & misc
         "Terminal.FindText"                          => Some("find"),
         "Terminal.ToggleFullscreen"                  => Some("toggleFullscreen"),
@@ -272,8 +347,7 @@ mod tests {
                {"command": "nextTab", "keys": "ctrl+tab"}"#,
         );
         let map = parse_settings(&json);
-        // ctrl+pgdn fails to parse (pgdn not in VKey), ctrl+tab succeeds
-        assert_eq!(map.get("nextTab").map(|k| k.len()), Some(2));
+        assert_eq!(map.get("nextTab"), Some(&vec![VKey::Control, VKey::PageDown]));
     }
 
     #[test]
@@ -335,6 +409,45 @@ mod tests {
         assert!(map.contains_key("duplicatePane"));
     }
 
+    #[test]
+    fn detects_move_focus_ids() {
+        let json = r#"{"keybindings": [
+            {"id": "Terminal.MoveFocusUp", "keys": "alt+up"},
+            {"id": "Terminal.MoveFocusRight", "keys": "alt+right"}
+        ]}"#;
+        let map = parse_settings(json);
+        assert_eq!(map.get("moveFocusUp"), Some(&vec![VKey::Alt, VKey::Up]));
+        assert_eq!(map.get("moveFocusRight"), Some(&vec![VKey::Alt, VKey::Right]));
+    }
+
+    #[test]
+    fn detects_move_focus_command_per_direction() {
+        let json = make_json(
+            r#"{"command": {"action": "moveFocus", "direction": "left"}, "keys": "ctrl+alt+h"},
+               {"command": {"action": "moveFocus", "direction": "down"}, "keys": "ctrl+alt+j"},
+               {"command": {"action": "moveFocus"}, "keys": "ctrl+alt+x"}"#,
+        );
+        let map = parse_settings(&json);
+        assert_eq!(map.get("moveFocusLeft"), Some(&vec![VKey::Control, VKey::Alt, VKey::H]));
+        assert_eq!(map.get("moveFocusDown"), Some(&vec![VKey::Control, VKey::Alt, VKey::J]));
+        assert!(!map.contains_key("moveFocus"));
+    }
+
+    #[test]
+    fn detects_send_input_text_by_name_and_id() {
+        let json = make_json(
+            r#"{"command": {"action": "sendInput", "input": "clear\r"}, "name": "Clear", "id": "User.sendInput.A1"},
+               {"command": {"action": "sendInput", "input": "git status\r"}, "keys": "ctrl+alt+g", "id": "User.sendInput.B2"},
+               {"command": {"action": "sendInput"}, "name": "Empty"},
+               {"command": "nextTab", "keys": "ctrl+tab", "name": "Next"}"#,
+        );
+        let texts = parse_send_inputs(&json);
+        assert_eq!(texts.get("Clear").map(String::as_str), Some("clear\r"));
+        assert_eq!(texts.get("User.sendInput.A1").map(String::as_str), Some("clear\r"));
+        assert_eq!(texts.get("User.sendInput.B2").map(String::as_str), Some("git status\r"));
+        assert_eq!(texts.len(), 3);
+    }
+
     #[test]
     fn unknown_id_stored_as_is() {
         let json = r#"{"keybindings": [{"id": "Terminal.SomeFutureAction", "keys": "ctrl+f12"}]}"#;
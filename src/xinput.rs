@@ -0,0 +1,176 @@
+/// Xbox / XInput controllers as an input source.
+///
+/// XInput pads are polled (`XInputGetState`) rather than read as HID reports,
+/// and each snapshot is converted to the same `UnifiedInput` the Sony parser
+/// produces, so the mapper works unchanged:
+///
+///   A / B / X / Y        → cross / circle / square / triangle
+///   LB / RB              → L1 / R1
+///   LT / RT              → L2 / R2 (analog, plus the digital button past
+///                          XInput's trigger threshold)
+///   Back / Start         → share / options
+///   LS / RS click        → L3 / R3
+///   D-pad                → d-pad
+///
+/// Xbox pads have no lightbar, player LEDs, touchpad, motion sensors or mic
+/// button, so those inputs stay at rest and no output reports are sent.
+/// The Guide button isn't exposed by XInput, so there's no PS button either.
+
+use crate::input::{ButtonState, DPad, UnifiedInput};
+
+/// XInput user slots (`XUSER_MAX_COUNT`).
+pub const MAX_PADS: u32 = 4;
+
+/// `XINPUT_GAMEPAD.wButtons` bits.
+const DPAD_UP: u16 = 0x0001;
+const DPAD_DOWN: u16 = 0x0002;
+const DPAD_LEFT: u16 = 0x0004;
+const DPAD_RIGHT: u16 = 0x0008;
+const START: u16 = 0x0010;
+const BACK: u16 = 0x0020;
+const LEFT_THUMB: u16 = 0x0040;
+const RIGHT_THUMB: u16 = 0x0080;
+const LEFT_SHOULDER: u16 = 0x0100;
+const RIGHT_SHOULDER: u16 = 0x0200;
+const A: u16 = 0x1000;
+const B: u16 = 0x2000;
+const X: u16 = 0x4000;
+const Y: u16 = 0x8000;
+
+/// `XINPUT_GAMEPAD_TRIGGER_THRESHOLD`: analog trigger value that counts as pressed.
+const TRIGGER_THRESHOLD: u8 = 30;
+
+/// One XInput gamepad reading (the fields of `XINPUT_GAMEPAD`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GamepadSnapshot {
+    pub buttons: u16,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+    /// Thumbstick axes, -32768..=32767 with +Y pointing up.
+    pub thumb_lx: i16,
+    pub thumb_ly: i16,
+    pub thumb_rx: i16,
+    pub thumb_ry: i16,
+}
+
+/// Pseudo device path for XInput slot `index`, used to key its session.
+pub fn pad_path(index: u32) -> String {
+    format!("xinput#{index}")
+}
+
+/// Convert a gamepad snapshot to `UnifiedInput` (see the module docs for the mapping).
+pub fn to_unified(pad: &GamepadSnapshot) -> UnifiedInput {
+    let held = |bit: u16| pad.buttons & bit != 0;
+    let buttons = ButtonState {
+        cross: held(A),
+        circle: held(B),
+        square: held(X),
+        triangle: held(Y),
+        l1: held(LEFT_SHOULDER),
+        r1: held(RIGHT_SHOULDER),
+        l2: pad.left_trigger > TRIGGER_THRESHOLD,
+        r2: pad.right_trigger > TRIGGER_THRESHOLD,
+        share: held(BACK),
+        options: held(START),
+        l3: held(LEFT_THUMB),
+        r3: held(RIGHT_THUMB),
+        dpad: DPad::from_directions(held(DPAD_UP), held(DPAD_DOWN), held(DPAD_LEFT), held(DPAD_RIGHT)),
+        ..ButtonState::default()
+    };
+    UnifiedInput {
+        left_stick: (axis(pad.thumb_lx), inverted_axis(pad.thumb_ly)),
+        right_stick: (axis(pad.thumb_rx), inverted_axis(pad.thumb_ry)),
+        l2_analog: pad.left_trigger,
+        r2_analog: pad.right_trigger,
+        buttons,
+        ..UnifiedInput::default()
+    }
+}
+
+/// Signed XInput axis → Sony's 0..=255 (128 = center).
+fn axis(value: i16) -> u8 {
+    ((value as i32 + 32768) >> 8) as u8
+}
+
+/// XInput Y points up, Sony Y points down. Negating before the mapping keeps
+/// the center at 128 (flipping the mapped value would put it at 127).
+fn inverted_axis(value: i16) -> u8 {
+    axis(value.saturating_neg())
+}
+
+/// Read slot `index`. None if no pad is connected there.
+#[cfg(windows)]
+pub fn read(index: u32) -> Option<GamepadSnapshot> {
+    use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+    use windows_sys::Win32::UI::Input::XboxController::{XInputGetState, XINPUT_STATE};
+
+    let mut state: XINPUT_STATE = unsafe { std::mem::zeroed() };
+    if unsafe { XInputGetState(index, &mut state) } != ERROR_SUCCESS {
+        return None;
+    }
+    let pad = state.Gamepad;
+    Some(GamepadSnapshot {
+        buttons: pad.wButtons,
+        left_trigger: pad.bLeftTrigger,
+        right_trigger: pad.bRightTrigger,
+        thumb_lx: pad.sThumbLX,
+        thumb_ly: pad.sThumbLY,
+        thumb_rx: pad.sThumbRX,
+        thumb_ry: pad.sThumbRY,
+    })
+}
+
+#[cfg(not(windows))]
+pub fn read(_index: u32) -> Option<GamepadSnapshot> {
+    None
+}
+
+/// Slots that currently have a pad connected.
+pub fn connected_pads() -> Vec<u32> {
+    (0..MAX_PADS).filter(|&i| read(i).is_some()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn face_buttons_map_to_sony_names() {
+        let pad = GamepadSnapshot { buttons: A | Y | LEFT_SHOULDER | START | RIGHT_THUMB, ..Default::default() };
+        let b = to_unified(&pad).buttons;
+        assert!(b.cross && b.triangle && b.l1 && b.options && b.r3);
+        assert!(!b.circle && !b.square && !b.r1 && !b.share && !b.l3 && !b.ps && !b.touchpad && !b.mute);
+        assert_eq!(b.dpad, DPad::Neutral);
+    }
+
+    #[test]
+    fn sticks_center_and_flip_y() {
+        let rest = to_unified(&GamepadSnapshot::default());
+        assert_eq!(rest.left_stick, (128, 128));
+        assert_eq!(rest.right_stick, (128, 128));
+
+        // Full left + full up (XInput) → 0, 0 (Sony)
+        let pad = GamepadSnapshot { thumb_lx: i16::MIN, thumb_ly: i16::MAX, thumb_rx: i16::MAX, thumb_ry: i16::MIN, ..Default::default() };
+        let unified = to_unified(&pad);
+        assert_eq!(unified.left_stick, (0, 0));
+        assert_eq!(unified.right_stick, (255, 255));
+    }
+
+    #[test]
+    fn triggers_are_analog_with_threshold_button() {
+        let pad = GamepadSnapshot { left_trigger: TRIGGER_THRESHOLD, right_trigger: 200, ..Default::default() };
+        let unified = to_unified(&pad);
+        assert_eq!((unified.l2_analog, unified.r2_analog), (TRIGGER_THRESHOLD, 200));
+        assert!(!unified.buttons.l2);
+        assert!(unified.buttons.r2);
+    }
+
+    #[test]
+    fn dpad_bits_combine_into_diagonals() {
+        let dir = |buttons| to_unified(&GamepadSnapshot { buttons, ..Default::default() }).buttons.dpad;
+        assert_eq!(dir(DPAD_UP), DPad::Up);
+        assert_eq!(dir(DPAD_DOWN | DPAD_RIGHT), DPad::DownRight);
+        assert_eq!(dir(DPAD_UP | DPAD_LEFT), DPad::UpLeft);
+        assert_eq!(dir(DPAD_LEFT | DPAD_RIGHT), DPad::Neutral);
+    }
+}